@@ -3,12 +3,21 @@
 
 pub mod proto;
 
-use crate::proto::admission_control::AdmissionControlStatus as ProtoAdmissionControlStatus;
+use crate::proto::admission_control::{
+    AdmissionControlStatus as ProtoAdmissionControlStatus,
+    GetTransactionStatusResponse_Status as ProtoSubmittedTransactionStatus,
+};
 use failure::prelude::*;
 use logger::prelude::*;
-use mempool::MempoolAddTransactionStatus;
+use mempool::{MempoolAddTransactionStatus, MempoolTransactionStatus, TimelineState};
 use proto_conv::{FromProto, IntoProto};
-use types::vm_error::VMStatus;
+use types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{SignedTransactionWithProof, TransactionOutput, TransactionStatus, Version},
+    vm_error::VMStatus,
+    write_set::WriteSet,
+};
 
 /// AC response status of submit_transaction to clients.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -17,7 +26,8 @@ pub enum AdmissionControlStatus {
     Accepted = 0,
     /// The sender is blacklisted.
     Blacklisted = 1,
-    /// The transaction is rejected, e.g. due to incorrect signature.
+    /// The transaction is rejected, e.g. due to incorrect signature or because the sender submits
+    /// too often.
     Rejected = 2,
 }
 
@@ -51,6 +61,8 @@ impl FromProto for AdmissionControlStatus {
 pub struct SubmitTransactionResponse {
     /// AC status returned to client if any, it includes can be either error or accepted status.
     pub ac_status: Option<AdmissionControlStatus>,
+    /// Why AC refused the transaction, if it did.
+    pub ac_status_reason: Option<String>,
     /// Mempool error status if any.
     pub mempool_error: Option<MempoolAddTransactionStatus>,
     /// VM error status if any.
@@ -66,6 +78,9 @@ impl IntoProto for SubmitTransactionResponse {
         let mut proto = Self::ProtoType::new();
         if let Some(ac_st) = self.ac_status {
             proto.set_ac_status(ac_st.into_proto());
+            if let Some(reason) = self.ac_status_reason {
+                proto.set_ac_status_reason(reason);
+            }
         } else if let Some(mem_err) = self.mempool_error {
             proto.set_mempool_status(mem_err.into_proto());
         } else if let Some(vm_st) = self.vm_error {
@@ -87,6 +102,11 @@ impl FromProto for SubmitTransactionResponse {
         } else {
             None
         };
+        let ac_status_reason = if object.get_ac_status_reason().is_empty() {
+            None
+        } else {
+            Some(object.take_ac_status_reason())
+        };
         let mempool_error = if object.has_mempool_status() {
             Some(MempoolAddTransactionStatus::from_proto(
                 object.get_mempool_status(),
@@ -102,9 +122,180 @@ impl FromProto for SubmitTransactionResponse {
 
         Ok(SubmitTransactionResponse {
             ac_status,
+            ac_status_reason,
             mempool_error,
             vm_error,
             validator_id: object.take_validator_id(),
         })
     }
 }
+
+/// Rust structure for SimulateTransactionResponse protobuf definition.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SimulateTransactionResponse {
+    /// Whether the transaction would be kept or discarded, with its VM status.
+    pub status: TransactionStatus,
+    /// Gas units the transaction used.
+    pub gas_used: u64,
+    /// Writes the transaction would make.
+    pub write_set: WriteSet,
+    /// Events the transaction would emit.
+    pub events: Vec<ContractEvent>,
+    /// Version of the committed state the transaction ran against.
+    pub version: Version,
+}
+
+impl SimulateTransactionResponse {
+    /// Response for a transaction which ran against the state at version with output.
+    pub fn new(output: TransactionOutput, version: Version) -> Self {
+        SimulateTransactionResponse {
+            status: output.status().clone(),
+            gas_used: output.gas_used(),
+            write_set: output.write_set().clone(),
+            events: output.events().to_vec(),
+            version,
+        }
+    }
+}
+
+impl IntoProto for SimulateTransactionResponse {
+    type ProtoType = crate::proto::admission_control::SimulateTransactionResponse;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        let (vm_status, discarded) = match self.status {
+            TransactionStatus::Keep(vm_status) => (vm_status, false),
+            TransactionStatus::Discard(vm_status) => (vm_status, true),
+        };
+        proto.set_vm_status(vm_status.into_proto());
+        proto.set_discarded(discarded);
+        proto.set_gas_used(self.gas_used);
+        proto.set_write_set(self.write_set.into_proto());
+        proto.set_events(protobuf::RepeatedField::from_vec(
+            self.events
+                .into_iter()
+                .map(ContractEvent::into_proto)
+                .collect(),
+        ));
+        proto.set_version(self.version);
+        proto
+    }
+}
+
+impl FromProto for SimulateTransactionResponse {
+    type ProtoType = crate::proto::admission_control::SimulateTransactionResponse;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        let vm_status = VMStatus::from_proto(object.take_vm_status())?;
+        let status = if object.get_discarded() {
+            TransactionStatus::Discard(vm_status)
+        } else {
+            TransactionStatus::Keep(vm_status)
+        };
+        Ok(SimulateTransactionResponse {
+            status,
+            gas_used: object.get_gas_used(),
+            write_set: WriteSet::from_proto(object.take_write_set())?,
+            events: object
+                .take_events()
+                .into_iter()
+                .map(ContractEvent::from_proto)
+                .collect::<Result<Vec<_>>>()?,
+            version: object.get_version(),
+        })
+    }
+}
+
+/// Where a submitted transaction stands.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SubmittedTransactionStatus {
+    /// Neither the ledger nor Mempool knows the transaction.
+    Unknown,
+    /// The transaction is in Mempool, waiting to be included in a block.
+    Pending(TimelineState),
+    /// The transaction is in the ledger, with the proof it is.
+    Committed(SignedTransactionWithProof),
+    /// The transaction was removed from Mempool because it expired.
+    Expired,
+    /// The transaction was removed from Mempool because it was discarded when it was executed.
+    Discarded,
+}
+
+impl SubmittedTransactionStatus {
+    /// Status of a transaction which is not committed, from where it stands in Mempool.
+    pub fn from_mempool_status(status: MempoolTransactionStatus) -> Self {
+        match status {
+            MempoolTransactionStatus::Unknown => SubmittedTransactionStatus::Unknown,
+            MempoolTransactionStatus::Pending(timeline_state) => {
+                SubmittedTransactionStatus::Pending(timeline_state)
+            }
+            MempoolTransactionStatus::Expired => SubmittedTransactionStatus::Expired,
+            MempoolTransactionStatus::Discarded => SubmittedTransactionStatus::Discarded,
+        }
+    }
+}
+
+/// Rust structure for GetTransactionStatusResponse protobuf definition.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GetTransactionStatusResponse {
+    /// Where the transaction stands.
+    pub status: SubmittedTransactionStatus,
+    /// The latest ledger info, which the proof of a committed transaction is against.
+    pub ledger_info_with_sigs: LedgerInfoWithSignatures,
+}
+
+impl IntoProto for GetTransactionStatusResponse {
+    type ProtoType = crate::proto::admission_control::GetTransactionStatusResponse;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        match self.status {
+            SubmittedTransactionStatus::Unknown => {
+                proto.set_status(ProtoSubmittedTransactionStatus::Unknown)
+            }
+            SubmittedTransactionStatus::Pending(timeline_state) => {
+                proto.set_status(ProtoSubmittedTransactionStatus::Pending);
+                proto.set_timeline_state(timeline_state.into_proto());
+            }
+            SubmittedTransactionStatus::Committed(signed_transaction_with_proof) => {
+                proto.set_status(ProtoSubmittedTransactionStatus::Committed);
+                proto.set_signed_transaction_with_proof(signed_transaction_with_proof.into_proto());
+            }
+            SubmittedTransactionStatus::Expired => {
+                proto.set_status(ProtoSubmittedTransactionStatus::Expired)
+            }
+            SubmittedTransactionStatus::Discarded => {
+                proto.set_status(ProtoSubmittedTransactionStatus::Discarded)
+            }
+        }
+        proto.set_ledger_info_with_sigs(self.ledger_info_with_sigs.into_proto());
+        proto
+    }
+}
+
+impl FromProto for GetTransactionStatusResponse {
+    type ProtoType = crate::proto::admission_control::GetTransactionStatusResponse;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        let status = match object.get_status() {
+            ProtoSubmittedTransactionStatus::Unknown => SubmittedTransactionStatus::Unknown,
+            ProtoSubmittedTransactionStatus::Pending => SubmittedTransactionStatus::Pending(
+                TimelineState::from_proto(object.take_timeline_state())?,
+            ),
+            ProtoSubmittedTransactionStatus::Committed => {
+                SubmittedTransactionStatus::Committed(SignedTransactionWithProof::from_proto(
+                    object.take_signed_transaction_with_proof(),
+                )?)
+            }
+            ProtoSubmittedTransactionStatus::Expired => SubmittedTransactionStatus::Expired,
+            ProtoSubmittedTransactionStatus::Discarded => SubmittedTransactionStatus::Discarded,
+        };
+        Ok(GetTransactionStatusResponse {
+            status,
+            ledger_info_with_sigs: LedgerInfoWithSignatures::from_proto(
+                object.take_ledger_info_with_sigs(),
+            )?,
+        })
+    }
+}
@@ -1,7 +1,9 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::admission_control_service::AdmissionControlService;
+use crate::{
+    admission_control_service::AdmissionControlService, admission_policy::AdmissionPolicy,
+};
 use admission_control_proto::proto::admission_control_grpc;
 use config::config::NodeConfig;
 use debug_interface::{node_debug_service::NodeDebugService, proto::node_debug_interface_grpc};
@@ -88,6 +90,9 @@ impl AdmissionControlNode {
             Arc::clone(&storage_client),
         ));
 
+        let admission_policy = Arc::new(AdmissionPolicy::from_config(
+            &self.node_config.admission_control,
+        )?);
         let handle = AdmissionControlService::new(
             mp_client,
             storage_client,
@@ -95,6 +100,7 @@ impl AdmissionControlNode {
             self.node_config
                 .admission_control
                 .need_to_check_mempool_before_validation,
+            Arc::clone(&admission_policy),
         );
         let service = admission_control_grpc::create_admission_control(handle);
 
@@ -108,8 +114,9 @@ impl AdmissionControlNode {
         );
 
         // Start Debug interface
-        let debug_service =
-            node_debug_interface_grpc::create_node_debug_interface(NodeDebugService::new());
+        let debug_service = node_debug_interface_grpc::create_node_debug_interface(
+            NodeDebugService::with_sender_blacklist(admission_policy.sender_blacklist()),
+        );
         let _debug_handle = spawn_service_thread(
             debug_service,
             self.node_config.admission_control.address.clone(),
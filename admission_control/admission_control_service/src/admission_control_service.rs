@@ -5,37 +5,72 @@
 //! from external clients (such as wallets) and performs necessary processing before sending them to
 //! next step.
 
-use crate::OP_COUNTERS;
-use admission_control_proto::proto::{
-    admission_control::{
-        AdmissionControlStatus, SubmitTransactionRequest, SubmitTransactionResponse,
+use crate::{
+    admission_policy::{parse_peer_ip, AdmissionPolicy},
+    subscription::{EventSubscription, LedgerInfoSubscription, Subscription, Subscriptions},
+    OP_COUNTERS,
+};
+use admission_control_proto::{
+    proto::{
+        admission_control::{
+            AdmissionControlStatus, GetTransactionStatusRequest, GetTransactionStatusResponse,
+            SimulateTransactionRequest, SimulateTransactionResponse, SubmitTransactionRequest,
+            SubmitTransactionResponse, SubmitTransactionsRequest, SubmitTransactionsResponse,
+            SubscribeToEventsRequest, SubscribeToEventsResponse, SubscribeToLedgerInfosRequest,
+            SubscribeToLedgerInfosResponse,
+        },
+        admission_control_grpc::AdmissionControl,
     },
-    admission_control_grpc::AdmissionControl,
+    SubmittedTransactionStatus,
 };
+use crypto::HashValue;
 use failure::prelude::*;
-use futures::future::Future;
+use futures::{future::Future, sink::Sink, stream::Stream};
 use futures03::executor::block_on;
-use grpc_helpers::provide_grpc_response;
+use grpc_helpers::{
+    create_grpc_invalid_arg_status, default_reply_error_logger, provide_grpc_response,
+};
+use grpcio::{RpcStatus, RpcStatusCode, ServerStreamingSink, WriteFlags};
 use logger::prelude::*;
-use mempool::proto::{
-    mempool::{AddTransactionWithValidationRequest, HealthCheckRequest},
-    mempool_client::MempoolClientTrait,
-    shared::mempool_status::MempoolAddTransactionStatus::{self, MempoolIsFull},
+use mempool::{
+    proto::{
+        mempool::{
+            AddTransactionWithValidationRequest, AddTransactionsWithValidationRequest,
+            GetTransactionStatusRequest as MempoolStatusRequest, HealthCheckRequest,
+        },
+        mempool_client::MempoolClientTrait,
+        shared::mempool_status::MempoolAddTransactionStatus::{self, MempoolIsFull},
+    },
+    MempoolTransactionStatus,
 };
 use metrics::counters::SVC_COUNTERS;
 use proto_conv::{FromProto, IntoProto};
-use std::sync::Arc;
+use std::{collections::HashMap, convert::TryFrom, net::IpAddr, sync::Arc};
 use storage_client::StorageRead;
 use types::{
-    proto::get_with_proof::{UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse},
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    get_with_proof::RequestItem,
+    proto::{
+        get_with_proof::{UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse},
+        transaction::SignedTransaction as ProtoSignedTransaction,
+    },
     transaction::SignedTransaction,
+    vm_error::VMStatus,
 };
-use vm_validator::vm_validator::{get_account_state, TransactionValidation};
+use vm_validator::vm_validator::{get_account_state, TransactionSimulation, TransactionValidation};
 
 #[cfg(test)]
 #[path = "unit_tests/admission_control_service_test.rs"]
 mod admission_control_service_test;
 
+/// Number of transactions a batch submission can hold at most.
+const MAX_SUBMIT_TRANSACTIONS_BATCH_SIZE: usize = 1000;
+
+/// The largest max_gas_amount a simulated transaction can set, which bounds the work a
+/// simulation costs the node.
+const MAX_SIMULATED_GAS_AMOUNT: u64 = 100_000;
+
 /// Struct implementing trait (service handle) AdmissionControlService.
 #[derive(Clone)]
 pub struct AdmissionControlService<M, V> {
@@ -48,12 +83,16 @@ pub struct AdmissionControlService<M, V> {
     /// Flag indicating whether we need to check mempool before validation, drop txn if check
     /// fails.
     need_to_check_mempool_before_validation: bool,
+    /// Blacklist and rate limits submissions are checked against before validation.
+    admission_policy: Arc<AdmissionPolicy>,
+    /// Ledger info and event subscriptions being served.
+    subscriptions: Subscriptions,
 }
 
 impl<M: 'static, V> AdmissionControlService<M, V>
 where
     M: MempoolClientTrait,
-    V: TransactionValidation,
+    V: TransactionValidation + TransactionSimulation,
 {
     /// Constructs a new AdmissionControlService instance.
     pub fn new(
@@ -61,13 +100,30 @@ where
         storage_read_client: Arc<dyn StorageRead>,
         vm_validator: Arc<V>,
         need_to_check_mempool_before_validation: bool,
+        admission_policy: Arc<AdmissionPolicy>,
     ) -> Self {
+        let subscriptions = Subscriptions::new(Arc::clone(&storage_read_client));
         AdmissionControlService {
             mempool_client,
             storage_read_client,
             vm_validator,
             need_to_check_mempool_before_validation,
+            admission_policy,
+            subscriptions,
+        }
+    }
+
+    /// Refuse the submission if the peer submitting it is over its rate limit, process it with
+    /// submit_transaction_inner otherwise.
+    pub(crate) fn submit_transaction_from_peer(
+        &self,
+        req: SubmitTransactionRequest,
+        peer_ip: Option<IpAddr>,
+    ) -> Result<SubmitTransactionResponse> {
+        if let Err(response) = self.admit_peer(peer_ip) {
+            return Ok(response);
         }
+        self.submit_transaction_inner(req)
     }
 
     /// Validate transaction signature, then via VM, and add it to Mempool if it passes VM check.
@@ -85,20 +141,9 @@ where
             return Ok(response);
         }
 
-        let signed_txn_proto = req.get_signed_txn();
-
-        let signed_txn = match SignedTransaction::from_proto(signed_txn_proto.clone()) {
-            Ok(t) => t,
-            Err(e) => {
-                security_log(SecurityEvent::InvalidTransactionAC)
-                    .error(&e)
-                    .data(&signed_txn_proto)
-                    .log();
-                let mut response = SubmitTransactionResponse::new();
-                response.set_ac_status(AdmissionControlStatus::Rejected);
-                OP_COUNTERS.inc_by("submit_txn.rejected.invalid_txn", 1);
-                return Ok(response);
-            }
+        let signed_txn = match self.admit_transaction(req.get_signed_txn()) {
+            Ok(signed_txn) => signed_txn,
+            Err(response) => return Ok(response),
         };
 
         let gas_cost = signed_txn.max_gas_amount();
@@ -114,27 +159,318 @@ where
                 e
             })?;
         if let Some(validation_status) = validation_status {
+            return Ok(vm_refused_response(validation_status, &signed_txn));
+        }
+        let account_state = block_on(get_account_state(
+            self.storage_read_client.clone(),
+            signed_txn.sender(),
+        ));
+        let add_transaction_request =
+            add_transaction_request(req.get_signed_txn().clone(), gas_cost, account_state.ok());
+
+        self.add_txn_to_mempool(add_transaction_request)
+    }
+
+    /// Validate many transactions together, against the same state, reading the account state
+    /// of each sender once, and add the ones which pass to Mempool at once. Each transaction gets
+    /// the response its own submission would, so a sender can submit transactions with
+    /// consecutive sequence numbers in one batch.
+    pub(crate) fn submit_transactions_inner(
+        &self,
+        mut req: SubmitTransactionsRequest,
+        peer_ip: Option<IpAddr>,
+    ) -> Result<SubmitTransactionsResponse> {
+        let signed_txn_protos = req.take_signed_txns().into_vec();
+        ensure!(
+            signed_txn_protos.len() <= MAX_SUBMIT_TRANSACTIONS_BATCH_SIZE,
+            "Too many transactions in a batch: {}, at most {}",
+            signed_txn_protos.len(),
+            MAX_SUBMIT_TRANSACTIONS_BATCH_SIZE
+        );
+        OP_COUNTERS.inc_by("submit_txns.requested", signed_txn_protos.len());
+        if !self.can_send_txn_to_mempool()? {
+            debug!("Mempool is full");
+            OP_COUNTERS.inc_by("submit_txn.rejected.mempool_full", signed_txn_protos.len());
             let mut response = SubmitTransactionResponse::new();
-            OP_COUNTERS.inc_by("submit_txn.vm_validation.failure", 1);
-            debug!(
-                "txn failed in vm validation, status: {:?}, txn: {:?}",
-                validation_status, signed_txn
+            response.set_mempool_status(MempoolIsFull);
+            return Ok(batch_response(vec![response; signed_txn_protos.len()]));
+        }
+
+        let mut responses = vec![None; signed_txn_protos.len()];
+        let mut admitted = vec![];
+        for (index, signed_txn_proto) in signed_txn_protos.into_iter().enumerate() {
+            let admission = self
+                .admit_peer(peer_ip)
+                .and_then(|()| self.admit_transaction(&signed_txn_proto));
+            match admission {
+                Ok(signed_txn) => admitted.push((index, signed_txn_proto, signed_txn)),
+                Err(response) => responses[index] = Some(response),
+            }
+        }
+
+        let validation_statuses = self
+            .vm_validator
+            .validate_transactions(
+                admitted
+                    .iter()
+                    .map(|(_, _, signed_txn)| signed_txn.clone())
+                    .collect(),
+            )
+            .wait()
+            .map_err(|e| {
+                security_log(SecurityEvent::InvalidTransactionAC)
+                    .error(&e)
+                    .log();
+                e
+            })?;
+        ensure!(
+            validation_statuses.len() == admitted.len(),
+            "VM validator returned {} statuses for {} transactions",
+            validation_statuses.len(),
+            admitted.len()
+        );
+
+        let mut account_states = HashMap::new();
+        let mut add_transaction_requests = vec![];
+        let mut indexes_in_mempool_req = vec![];
+        for ((index, signed_txn_proto, signed_txn), validation_status) in
+            admitted.into_iter().zip(validation_statuses)
+        {
+            if let Some(validation_status) = validation_status {
+                responses[index] = Some(vm_refused_response(validation_status, &signed_txn));
+                continue;
+            }
+            let sender = signed_txn.sender();
+            let account_state = *account_states.entry(sender).or_insert_with(|| {
+                block_on(get_account_state(self.storage_read_client.clone(), sender)).ok()
+            });
+            add_transaction_requests.push(add_transaction_request(
+                signed_txn_proto,
+                signed_txn.max_gas_amount(),
+                account_state,
+            ));
+            indexes_in_mempool_req.push(index);
+        }
+
+        if !add_transaction_requests.is_empty() {
+            let mut mempool_req = AddTransactionsWithValidationRequest::new();
+            mempool_req.set_transactions(::protobuf::RepeatedField::from_vec(
+                add_transaction_requests,
+            ));
+            let mempool_result = self
+                .mempool_client
+                .add_transactions_with_validation(&mempool_req)?;
+            ensure!(
+                mempool_result.get_statuses().len() == indexes_in_mempool_req.len(),
+                "Mempool returned {} statuses for {} transactions",
+                mempool_result.get_statuses().len(),
+                indexes_in_mempool_req.len()
             );
-            response.set_vm_status(validation_status.into_proto());
-            return Ok(response);
+            debug!("[GRPC] Done with transactions submission request");
+            for ((index, add_transaction_request), status) in indexes_in_mempool_req
+                .into_iter()
+                .zip(mempool_req.get_transactions())
+                .zip(mempool_result.get_statuses())
+            {
+                responses[index] = Some(mempool_response(
+                    *status,
+                    add_transaction_request.get_signed_txn(),
+                ));
+            }
+        }
+
+        Ok(batch_response(
+            responses
+                .into_iter()
+                .map(|response| response.expect("Every transaction has a response"))
+                .collect(),
+        ))
+    }
+
+    /// Refuse the submission if the peer submitting it is over its rate limit.
+    fn admit_peer(
+        &self,
+        peer_ip: Option<IpAddr>,
+    ) -> std::result::Result<(), SubmitTransactionResponse> {
+        if let Some(peer_ip) = peer_ip {
+            if !self.admission_policy.admit_peer(peer_ip) {
+                debug!("Peer {} is over its rate limit", peer_ip);
+                OP_COUNTERS.inc_by("submit_txn.rejected.peer_rate_limited", 1);
+                return Err(refused_response(
+                    AdmissionControlStatus::Rejected,
+                    format!("Too many submissions from {}", peer_ip),
+                ));
+            }
         }
+        Ok(())
+    }
+
+    /// Check the transaction signature, then that the sender is neither blacklisted nor over its
+    /// rate limit. Returns the response to the submission if it is refused.
+    fn admit_transaction(
+        &self,
+        signed_txn_proto: &ProtoSignedTransaction,
+    ) -> std::result::Result<SignedTransaction, SubmitTransactionResponse> {
+        let signed_txn = match SignedTransaction::from_proto(signed_txn_proto.clone()) {
+            Ok(t) => t,
+            Err(e) => {
+                security_log(SecurityEvent::InvalidTransactionAC)
+                    .error(&e)
+                    .data(&signed_txn_proto)
+                    .log();
+                OP_COUNTERS.inc_by("submit_txn.rejected.invalid_txn", 1);
+                return Err(refused_response(
+                    AdmissionControlStatus::Rejected,
+                    format!("Invalid transaction: {}", e),
+                ));
+            }
+        };
+
         let sender = signed_txn.sender();
-        let account_state = block_on(get_account_state(self.storage_read_client.clone(), sender));
-        let mut add_transaction_request = AddTransactionWithValidationRequest::new();
-        add_transaction_request.signed_txn = req.signed_txn.clone();
-        add_transaction_request.set_max_gas_cost(gas_cost);
+        if self.admission_policy.is_blacklisted(&sender) {
+            debug!("Sender {} is blacklisted", sender);
+            OP_COUNTERS.inc_by("submit_txn.rejected.blacklisted", 1);
+            return Err(refused_response(
+                AdmissionControlStatus::Blacklisted,
+                format!("Sender {} is blacklisted", sender),
+            ));
+        }
+        if !self.admission_policy.admit_sender(sender) {
+            debug!("Sender {} is over its rate limit", sender);
+            OP_COUNTERS.inc_by("submit_txn.rejected.sender_rate_limited", 1);
+            return Err(refused_response(
+                AdmissionControlStatus::Rejected,
+                format!("Too many submissions from sender {}", sender),
+            ));
+        }
+        Ok(signed_txn)
+    }
+
+    /// Run the transaction through the VM against the latest committed state. Nothing is applied
+    /// and the transaction does not go to Mempool. Simulations count against the rate limit of
+    /// the peer, as submissions do.
+    pub(crate) fn simulate_transaction_inner(
+        &self,
+        mut req: SimulateTransactionRequest,
+        peer_ip: Option<IpAddr>,
+    ) -> Result<SimulateTransactionResponse> {
+        if let Some(peer_ip) = peer_ip {
+            if !self.admission_policy.admit_peer(peer_ip) {
+                debug!("Peer {} is over its rate limit", peer_ip);
+                OP_COUNTERS.inc_by("simulate_txn.rejected.peer_rate_limited", 1);
+                bail!("Too many requests from {}", peer_ip);
+            }
+        }
+        // The signature, if checked, is checked by the VM, which reports it as any other
+        // validation failure.
+        let check_signature = !req.get_skip_signature_check();
+        let signed_txn =
+            SignedTransaction::from_proto_unverified(req.take_signed_txn()).map_err(|e| {
+                OP_COUNTERS.inc_by("simulate_txn.rejected.invalid_txn", 1);
+                e
+            })?;
+        if signed_txn.max_gas_amount() > MAX_SIMULATED_GAS_AMOUNT {
+            OP_COUNTERS.inc_by("simulate_txn.rejected.max_gas_amount", 1);
+            bail!(
+                "Max gas amount {} is above {}, the most a simulated transaction can set",
+                signed_txn.max_gas_amount(),
+                MAX_SIMULATED_GAS_AMOUNT
+            );
+        }
+        let (output, version) = self
+            .vm_validator
+            .simulate_transaction(signed_txn, check_signature)
+            .wait()?;
+        OP_COUNTERS.inc_by("simulate_txn.simulated", 1);
+        Ok(admission_control_proto::SimulateTransactionResponse::new(output, version).into_proto())
+    }
+
+    /// Look the transaction up in Mempool, then in Storage. It is committed if Storage has it,
+    /// otherwise it stands where Mempool says.
+    pub(crate) fn get_transaction_status_inner(
+        &self,
+        req: GetTransactionStatusRequest,
+    ) -> Result<GetTransactionStatusResponse> {
+        let fetch_events = req.get_fetch_events();
+        let by_hash = !req.get_hash().is_empty();
+        let request_item = if by_hash {
+            RequestItem::GetTransactionByHash {
+                hash: HashValue::from_slice(req.get_hash())?,
+                fetch_events,
+            }
+        } else {
+            RequestItem::GetAccountTransactionBySequenceNumber {
+                account: AccountAddress::try_from(req.get_sender())?,
+                sequence_number: req.get_sequence_number(),
+                fetch_events,
+            }
+        };
+
+        // Mempool is asked first: a transaction committed in between is then found in Storage,
+        // whereas it would be in neither if Storage were asked first.
+        let mut mempool_req = MempoolStatusRequest::new();
+        mempool_req.set_hash(req.get_hash().to_vec());
+        mempool_req.set_sender(req.get_sender().to_vec());
+        mempool_req.set_sequence_number(req.get_sequence_number());
+        let mempool_status = MempoolTransactionStatus::from_proto(
+            self.mempool_client
+                .get_transaction_status(&mempool_req)?
+                .take_status(),
+        )?;
+
+        let (mut response_items, ledger_info_with_sigs, _) = self
+            .storage_read_client
+            .update_to_latest_ledger(0, vec![request_item])?;
+        let response_item = response_items
+            .pop()
+            .ok_or_else(|| format_err!("Storage returned no response item"))?;
+        let signed_transaction_with_proof = if by_hash {
+            response_item.into_get_transaction_by_hash_response()?
+        } else {
+            response_item.into_get_account_txn_by_seq_num_response()?.0
+        };
 
-        if let Ok((sequence_number, balance)) = account_state {
-            add_transaction_request.set_account_balance(balance);
-            add_transaction_request.set_latest_sequence_number(sequence_number);
+        let status = match signed_transaction_with_proof {
+            Some(signed_transaction_with_proof) => {
+                SubmittedTransactionStatus::Committed(signed_transaction_with_proof)
+            }
+            None => SubmittedTransactionStatus::from_mempool_status(mempool_status),
+        };
+        OP_COUNTERS.inc_by("get_txn_status.requested", 1);
+        Ok(admission_control_proto::GetTransactionStatusResponse {
+            status,
+            ledger_info_with_sigs,
         }
+        .into_proto())
+    }
 
-        self.add_txn_to_mempool(add_transaction_request)
+    /// Stream the responses of the subscription to the client until it goes away.
+    fn serve_subscription<S: Subscription>(
+        &self,
+        ctx: ::grpcio::RpcContext<'_>,
+        subscription: S,
+        sink: ServerStreamingSink<S::Response>,
+    ) {
+        match self
+            .subscriptions
+            .start(subscription, parse_peer_ip(&ctx.peer()))
+        {
+            Ok(responses) => {
+                let responses = responses
+                    .map(|response| (response, WriteFlags::default()))
+                    .map_err(|()| -> grpcio::Error { unreachable!("Receivers never fail") });
+                ctx.spawn(
+                    sink.send_all(responses)
+                        .map(|_| ())
+                        .map_err(|e| debug!("Subscription ended: {:?}", e)),
+                );
+            }
+            Err(e) => {
+                OP_COUNTERS.inc_by("subscribe.rejected.too_many_subscriptions", 1);
+                let status = RpcStatus::new(RpcStatusCode::ResourceExhausted, Some(e.to_string()));
+                ctx.spawn(sink.fail(status).map_err(default_reply_error_logger));
+            }
+        }
     }
 
     fn can_send_txn_to_mempool(&self) -> Result<bool> {
@@ -156,20 +492,10 @@ where
             .add_transaction_with_validation(&add_transaction_request)?;
 
         debug!("[GRPC] Done with transaction submission request");
-        let mut response = SubmitTransactionResponse::new();
-        if mempool_result.get_status() == MempoolAddTransactionStatus::Valid {
-            OP_COUNTERS.inc_by("submit_txn.txn_accepted", 1);
-            response.set_ac_status(AdmissionControlStatus::Accepted);
-        } else {
-            debug!(
-                "txn failed in mempool, status: {:?}, txn: {:?}",
-                mempool_result,
-                add_transaction_request.get_signed_txn()
-            );
-            OP_COUNTERS.inc_by("submit_txn.mempool.failure", 1);
-            response.set_mempool_status(mempool_result.get_status());
-        }
-        Ok(response)
+        Ok(mempool_response(
+            mempool_result.get_status(),
+            add_transaction_request.get_signed_txn(),
+        ))
     }
 
     /// Pass the UpdateToLatestLedgerRequest to Storage for read query.
@@ -193,7 +519,7 @@ where
 impl<M: 'static, V> AdmissionControl for AdmissionControlService<M, V>
 where
     M: MempoolClientTrait,
-    V: TransactionValidation,
+    V: TransactionValidation + TransactionSimulation,
 {
     /// Submit a transaction to the validator this AC instance connecting to.
     /// The specific transaction will be first validated by VM and then passed
@@ -206,7 +532,21 @@ where
     ) {
         debug!("[GRPC] AdmissionControl::submit_transaction");
         let _timer = SVC_COUNTERS.req(&ctx);
-        let resp = self.submit_transaction_inner(req);
+        let resp = self.submit_transaction_from_peer(req, parse_peer_ip(&ctx.peer()));
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    /// Submit many transactions at once. They are validated together and passed to Mempool
+    /// together, and each gets the response its own submission would.
+    fn submit_transactions(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: SubmitTransactionsRequest,
+        sink: ::grpcio::UnarySink<SubmitTransactionsResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::submit_transactions");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let resp = self.submit_transactions_inner(req, parse_peer_ip(&ctx.peer()));
         provide_grpc_response(resp, ctx, sink);
     }
 
@@ -227,4 +567,135 @@ where
         let resp = self.update_to_latest_ledger_inner(req);
         provide_grpc_response(resp, ctx, sink);
     }
+
+    /// Run a transaction through the VM against the latest committed state without submitting
+    /// it, to learn its status, the gas it uses, its writes and its events. The signature is only
+    /// checked if the request asks for it.
+    fn simulate_transaction(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: SimulateTransactionRequest,
+        sink: ::grpcio::UnarySink<SimulateTransactionResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::simulate_transaction");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let resp = self.simulate_transaction_inner(req, parse_peer_ip(&ctx.peer()));
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    /// Where a submitted transaction stands: unknown, pending in Mempool, committed (with the
+    /// proof it is), expired or discarded.
+    fn get_transaction_status(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: GetTransactionStatusRequest,
+        sink: ::grpcio::UnarySink<GetTransactionStatusResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::get_transaction_status");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let resp = self.get_transaction_status_inner(req);
+        provide_grpc_response(resp, ctx, sink);
+    }
+
+    /// Stream the ledger info of each version after the one the client knows, as it is
+    /// committed.
+    fn subscribe_to_ledger_infos(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: SubscribeToLedgerInfosRequest,
+        sink: ServerStreamingSink<SubscribeToLedgerInfosResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::subscribe_to_ledger_infos");
+        let subscription = LedgerInfoSubscription::new(req.get_client_known_version());
+        self.serve_subscription(ctx, subscription, sink);
+    }
+
+    /// Stream the events of an event access path, from the requested sequence number on, each
+    /// with the proof it is in the ledger.
+    fn subscribe_to_events(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        mut req: SubscribeToEventsRequest,
+        sink: ServerStreamingSink<SubscribeToEventsResponse>,
+    ) {
+        debug!("[GRPC] AdmissionControl::subscribe_to_events");
+        match AccessPath::from_proto(req.take_access_path()) {
+            Ok(access_path) => {
+                let subscription =
+                    EventSubscription::new(access_path, req.get_start_event_seq_num());
+                self.serve_subscription(ctx, subscription, sink);
+            }
+            Err(e) => ctx.spawn(
+                sink.fail(create_grpc_invalid_arg_status("subscribe_to_events", e))
+                    .map_err(default_reply_error_logger),
+            ),
+        }
+    }
+}
+
+/// Response to a submission AC refuses, with the reason why.
+fn refused_response(status: AdmissionControlStatus, reason: String) -> SubmitTransactionResponse {
+    let mut response = SubmitTransactionResponse::new();
+    response.set_ac_status(status);
+    response.set_ac_status_reason(reason);
+    response
+}
+
+/// Response to a submission the VM refuses.
+fn vm_refused_response(
+    validation_status: VMStatus,
+    signed_txn: &SignedTransaction,
+) -> SubmitTransactionResponse {
+    OP_COUNTERS.inc_by("submit_txn.vm_validation.failure", 1);
+    debug!(
+        "txn failed in vm validation, status: {:?}, txn: {:?}",
+        validation_status, signed_txn
+    );
+    let mut response = SubmitTransactionResponse::new();
+    response.set_vm_status(validation_status.into_proto());
+    response
+}
+
+/// Request to add a transaction which passed validation to Mempool, along with the latest
+/// sequence number and balance of its sender if they could be read.
+fn add_transaction_request(
+    signed_txn: ProtoSignedTransaction,
+    gas_cost: u64,
+    account_state: Option<(u64, u64)>,
+) -> AddTransactionWithValidationRequest {
+    let mut add_transaction_request = AddTransactionWithValidationRequest::new();
+    add_transaction_request.set_signed_txn(signed_txn);
+    add_transaction_request.set_max_gas_cost(gas_cost);
+    if let Some((sequence_number, balance)) = account_state {
+        add_transaction_request.set_account_balance(balance);
+        add_transaction_request.set_latest_sequence_number(sequence_number);
+    }
+    add_transaction_request
+}
+
+/// Response to a submission Mempool added the transaction with the given status for.
+fn mempool_response(
+    status: MempoolAddTransactionStatus,
+    signed_txn: &ProtoSignedTransaction,
+) -> SubmitTransactionResponse {
+    let mut response = SubmitTransactionResponse::new();
+    if status == MempoolAddTransactionStatus::Valid {
+        OP_COUNTERS.inc_by("submit_txn.txn_accepted", 1);
+        response.set_ac_status(AdmissionControlStatus::Accepted);
+    } else {
+        debug!(
+            "txn failed in mempool, status: {:?}, txn: {:?}",
+            status, signed_txn
+        );
+        OP_COUNTERS.inc_by("submit_txn.mempool.failure", 1);
+        response.set_mempool_status(status);
+    }
+    response
+}
+
+/// Response to a batch submission, with the response to each transaction in order.
+fn batch_response(responses: Vec<SubmitTransactionResponse>) -> SubmitTransactionsResponse {
+    let mut response = SubmitTransactionsResponse::new();
+    response.set_responses(::protobuf::RepeatedField::from_vec(responses));
+    response
 }
@@ -0,0 +1,264 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission policy: the checks AC runs on a submission before it validates the transaction. A
+//! submission is refused if its sender is blacklisted, or if its sender or the peer submitting it
+//! is over its rate limit.
+
+use config::config::{AdmissionControlConfig, RateLimitConfig};
+use debug_interface::node_debug_service::SenderBlacklistManager;
+use failure::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    hash::Hash,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use types::account_address::AccountAddress;
+
+#[cfg(test)]
+#[path = "unit_tests/admission_policy_test.rs"]
+mod admission_policy_test;
+
+/// Number of buckets a rate limiter tracks at most. Past it, it forgets the buckets which are full
+/// again, then, if that is not enough, the ones used least recently.
+const MAX_TRACKED_BUCKETS: usize = 100_000;
+
+/// When a rate limiter tracks too many buckets, it forgets at least one in this many of them at
+/// once, so that the cost of finding them is amortized over the new ones.
+const EVICTED_FRACTION: usize = 10;
+
+/// Senders AC refuses transactions from. It is loaded from the config and can be changed at
+/// runtime through the debug interface.
+#[derive(Default)]
+pub struct SenderBlacklist {
+    senders: RwLock<HashSet<AccountAddress>>,
+}
+
+impl SenderBlacklist {
+    /// Constructs a blacklist of the given senders.
+    pub fn new<I: IntoIterator<Item = AccountAddress>>(senders: I) -> Self {
+        SenderBlacklist {
+            senders: RwLock::new(senders.into_iter().collect()),
+        }
+    }
+
+    /// Whether the sender is blacklisted.
+    pub fn contains(&self, sender: &AccountAddress) -> bool {
+        self.senders.read().unwrap().contains(sender)
+    }
+
+    /// Blacklists the sender. Returns false if it already was.
+    pub fn insert(&self, sender: AccountAddress) -> bool {
+        self.senders.write().unwrap().insert(sender)
+    }
+
+    /// Removes the sender from the blacklist. Returns false if it was not in it.
+    pub fn remove(&self, sender: &AccountAddress) -> bool {
+        self.senders.write().unwrap().remove(sender)
+    }
+}
+
+impl SenderBlacklistManager for SenderBlacklist {
+    fn update(&self, address: &[u8], blacklisted: bool) -> Result<bool> {
+        let sender = AccountAddress::try_from(address)?;
+        Ok(if blacklisted {
+            self.insert(sender)
+        } else {
+            self.remove(&sender)
+        })
+    }
+
+    fn senders(&self) -> Vec<Vec<u8>> {
+        let mut senders: Vec<_> = self
+            .senders
+            .read()
+            .unwrap()
+            .iter()
+            .map(|sender| sender.as_ref().to_vec())
+            .collect();
+        senders.sort();
+        senders
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// The tokens the bucket holds at `now`, counting the ones accrued since the last refill, up
+    /// to `burst`.
+    fn tokens_at(&self, now: Instant, burst: f64, per_second: f64) -> f64 {
+        if now > self.last_refill {
+            let elapsed = seconds(now.duration_since(self.last_refill));
+            (self.tokens + elapsed * per_second).min(burst)
+        } else {
+            self.tokens
+        }
+    }
+
+    /// Adds the tokens accrued since the last refill, up to `burst`, and returns the tokens in the
+    /// bucket.
+    fn refill(&mut self, now: Instant, burst: f64, per_second: f64) -> f64 {
+        if now > self.last_refill {
+            self.tokens = self.tokens_at(now, burst, per_second);
+            self.last_refill = now;
+        }
+        self.tokens
+    }
+}
+
+/// One token bucket per key. A bucket holds up to `burst` tokens, gains `per_second` tokens per
+/// second and each admitted request takes a token from it.
+pub struct RateLimiter<K> {
+    burst: f64,
+    per_second: f64,
+    buckets: Mutex<HashMap<K, TokenBucket>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    /// Constructs a rate limiter whose buckets start full.
+    pub fn new(config: &RateLimitConfig) -> Self {
+        RateLimiter {
+            burst: f64::from(config.burst),
+            per_second: config.per_second,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token from the bucket of `key`. Returns false, taking nothing, if the bucket is
+    /// empty.
+    pub fn try_acquire(&self, key: K) -> bool {
+        self.try_acquire_at(key, Instant::now())
+    }
+
+    fn try_acquire_at(&self, key: K, now: Instant) -> bool {
+        let (burst, per_second) = (self.burst, self.per_second);
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_TRACKED_BUCKETS && !buckets.contains_key(&key) {
+            // A full bucket is no different from a new one.
+            buckets.retain(|_, bucket| bucket.tokens_at(now, burst, per_second) < burst);
+            let kept = MAX_TRACKED_BUCKETS - MAX_TRACKED_BUCKETS / EVICTED_FRACTION;
+            if buckets.len() > kept {
+                // Every bucket is refilled when it is used, so the ones refilled first are the
+                // ones used least recently.
+                let mut last_refills: Vec<_> =
+                    buckets.values().map(|bucket| bucket.last_refill).collect();
+                last_refills.sort();
+                let newest_evicted = last_refills[buckets.len() - kept - 1];
+                buckets.retain(|_, bucket| bucket.last_refill > newest_evicted);
+            }
+        }
+        let bucket = buckets.entry(key).or_insert_with(|| TokenBucket {
+            tokens: burst,
+            last_refill: now,
+        });
+        if bucket.refill(now, burst, per_second) < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+/// What AC checks submissions against before validating them. By default, nothing is refused.
+#[derive(Default)]
+pub struct AdmissionPolicy {
+    sender_blacklist: Arc<SenderBlacklist>,
+    sender_rate_limiter: Option<RateLimiter<AccountAddress>>,
+    peer_ip_rate_limiter: Option<RateLimiter<IpAddr>>,
+}
+
+impl AdmissionPolicy {
+    /// Constructs a policy, without rate limits for which no config is given.
+    pub fn new(
+        sender_blacklist: Arc<SenderBlacklist>,
+        sender_rate_limit: Option<&RateLimitConfig>,
+        peer_ip_rate_limit: Option<&RateLimitConfig>,
+    ) -> Self {
+        AdmissionPolicy {
+            sender_blacklist,
+            sender_rate_limiter: sender_rate_limit.map(RateLimiter::new),
+            peer_ip_rate_limiter: peer_ip_rate_limit.map(RateLimiter::new),
+        }
+    }
+
+    /// Constructs the policy the AC config asks for.
+    pub fn from_config(config: &AdmissionControlConfig) -> Result<Self> {
+        let senders = config
+            .blacklisted_senders
+            .iter()
+            .map(|sender| {
+                AccountAddress::try_from(sender.clone())
+                    .map_err(|e| format_err!("Invalid blacklisted sender {}: {}", sender, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(rate_limit) = &config.sender_rate_limit {
+            check_rate_limit("sender_rate_limit", rate_limit)?;
+        }
+        if let Some(rate_limit) = &config.peer_ip_rate_limit {
+            check_rate_limit("peer_ip_rate_limit", rate_limit)?;
+        }
+        Ok(Self::new(
+            Arc::new(SenderBlacklist::new(senders)),
+            config.sender_rate_limit.as_ref(),
+            config.peer_ip_rate_limit.as_ref(),
+        ))
+    }
+
+    /// The sender blacklist, to manage it through the debug interface.
+    pub fn sender_blacklist(&self) -> Arc<SenderBlacklist> {
+        Arc::clone(&self.sender_blacklist)
+    }
+
+    /// Whether transactions from the sender are refused.
+    pub fn is_blacklisted(&self, sender: &AccountAddress) -> bool {
+        self.sender_blacklist.contains(sender)
+    }
+
+    /// Counts a submission from the sender against its rate limit. Returns false if it is over
+    /// the limit.
+    pub fn admit_sender(&self, sender: AccountAddress) -> bool {
+        self.sender_rate_limiter
+            .as_ref()
+            .map_or(true, |limiter| limiter.try_acquire(sender))
+    }
+
+    /// Counts a submission from the peer against its rate limit. Returns false if it is over the
+    /// limit.
+    pub fn admit_peer(&self, peer_ip: IpAddr) -> bool {
+        self.peer_ip_rate_limiter
+            .as_ref()
+            .map_or(true, |limiter| limiter.try_acquire(peer_ip))
+    }
+}
+
+/// Refuses a rate limit whose buckets hold no token or never refill.
+fn check_rate_limit(name: &str, config: &RateLimitConfig) -> Result<()> {
+    ensure!(config.burst > 0, "The burst of {} must be positive", name);
+    ensure!(
+        config.per_second.is_finite() && config.per_second > 0.0,
+        "The per_second of {} must be a positive number, not {}",
+        name,
+        config.per_second
+    );
+    Ok(())
+}
+
+/// Parses the IP address out of a gRPC peer, e.g. `ipv4:127.0.0.1:46160` or `ipv6:[::1]:46160`.
+/// Peers which are not on IP, e.g. on a Unix socket, have none.
+pub(crate) fn parse_peer_ip(peer: &str) -> Option<IpAddr> {
+    let address = peer.splitn(2, ':').nth(1)?;
+    address
+        .parse::<SocketAddr>()
+        .ok()
+        .map(|address| address.ip())
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1_000_000_000.0
+}
@@ -6,14 +6,22 @@
 //! Admission Control
 //!
 //! Admission Control (AC) is the public API end point taking public gRPC requests from clients.
-//! AC serves two types of request from clients:
+//! AC serves these types of request from clients:
 //! 1. SubmitTransaction, to submit transaction to associated validator.
 //! 2. UpdateToLatestLedger, to query storage, e.g. account state, transaction log, and proofs.
+//! 3. SimulateTransaction, to run a transaction through the VM without submitting it.
+//! 4. GetTransactionStatus, to learn where a submitted transaction stands.
+//! 5. SubscribeToLedgerInfos and SubscribeToEvents, to be streamed new ledger infos and events
+//!    instead of polling UpdateToLatestLedger for them.
 
 /// Wrapper to run AC in a separate process.
 pub mod admission_control_node;
 /// AC gRPC service.
 pub mod admission_control_service;
+/// Sender blacklist and rate limits AC checks submissions against.
+pub mod admission_policy;
+/// Ledger info and event subscriptions.
+mod subscription;
 use lazy_static::lazy_static;
 use metrics::OpMetrics;
 
@@ -0,0 +1,443 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ledger info and event subscriptions. Storage does not tell AC about new versions, so a single
+//! poller thread polls Storage for all the subscriptions at once and sends each client what is new
+//! to the stream it reads. A subscription is dropped once a send fails because the client is gone.
+
+use crate::OP_COUNTERS;
+use admission_control_proto::proto::admission_control::{
+    SubscribeToEventsResponse, SubscribeToLedgerInfosResponse,
+};
+use failure::prelude::*;
+use futures::sync::mpsc;
+use logger::prelude::*;
+use proto_conv::IntoProto;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+use storage_client::StorageRead;
+use types::{
+    access_path::AccessPath,
+    contract_event::EventWithProof,
+    get_with_proof::{RequestItem, ResponseItem},
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::Version,
+};
+
+#[cfg(test)]
+#[path = "unit_tests/subscription_test.rs"]
+mod subscription_test;
+
+/// How long the poller waits between two polls of Storage.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long a subscription goes without sending anything before it sends a keepalive response: the
+/// latest ledger info again, or a response without events. This is also how long it takes to notice
+/// a client which is gone.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+/// Number of events sent in a response at most.
+const MAX_EVENTS_PER_RESPONSE: u64 = 100;
+/// Number of responses buffered for a client which reads them slower than they come.
+const BUFFER_SIZE: usize = 16;
+/// Number of subscriptions served at once at most.
+const MAX_ACTIVE_SUBSCRIPTIONS: usize = 1000;
+/// Number of subscriptions served at once at most for a single peer.
+const MAX_SUBSCRIPTIONS_PER_PEER: usize = 16;
+/// Number of request items the poller sends Storage in a single request at most.
+const MAX_REQUEST_ITEMS: usize = 100;
+
+/// Something a client subscribes to.
+pub(crate) trait Subscription: Send + 'static {
+    /// Response streamed to the client.
+    type Response: Send + 'static;
+
+    /// What to ask Storage for when the ledger is at `version`, if anything.
+    fn request_item(&self, version: Version) -> Option<RequestItem>;
+
+    /// The response to send given the latest ledger info and, if it was asked, the answer of
+    /// Storage to the request item. Returns None if there is nothing new to send, unless a
+    /// keepalive response is due.
+    fn respond(
+        &mut self,
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+        response_item: Option<ResponseItem>,
+        keepalive: bool,
+    ) -> Result<Option<Self::Response>>;
+}
+
+/// Subscription to the ledger infos of new versions.
+pub(crate) struct LedgerInfoSubscription {
+    known_version: Version,
+}
+
+impl LedgerInfoSubscription {
+    /// Subscription to the ledger infos of the versions after `client_known_version`.
+    pub(crate) fn new(client_known_version: Version) -> Self {
+        LedgerInfoSubscription {
+            known_version: client_known_version,
+        }
+    }
+}
+
+impl Subscription for LedgerInfoSubscription {
+    type Response = SubscribeToLedgerInfosResponse;
+
+    fn request_item(&self, _version: Version) -> Option<RequestItem> {
+        // Every poll comes with the latest ledger info.
+        None
+    }
+
+    fn respond(
+        &mut self,
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+        _response_item: Option<ResponseItem>,
+        keepalive: bool,
+    ) -> Result<Option<Self::Response>> {
+        let version = ledger_info_with_sigs.ledger_info().version();
+        // The keepalive is the ledger info of the version the client knows, which it accepts.
+        let keepalive = keepalive && version == self.known_version;
+        if version <= self.known_version && !keepalive {
+            return Ok(None);
+        }
+        self.known_version = version;
+        let mut response = SubscribeToLedgerInfosResponse::new();
+        response.set_ledger_info_with_sigs(ledger_info_with_sigs.clone().into_proto());
+        Ok(Some(response))
+    }
+}
+
+/// Subscription to the events of an event access path.
+pub(crate) struct EventSubscription {
+    access_path: AccessPath,
+    next_event_seq_num: u64,
+    /// Version of the ledger the events were last asked for at.
+    polled_version: Option<Version>,
+    /// Whether all the events at `polled_version` were sent.
+    caught_up: bool,
+}
+
+impl EventSubscription {
+    /// Subscription to the events of `access_path` from `start_event_seq_num` on.
+    pub(crate) fn new(access_path: AccessPath, start_event_seq_num: u64) -> Self {
+        EventSubscription {
+            access_path,
+            next_event_seq_num: start_event_seq_num,
+            polled_version: None,
+            caught_up: false,
+        }
+    }
+}
+
+impl Subscription for EventSubscription {
+    type Response = SubscribeToEventsResponse;
+
+    fn request_item(&self, version: Version) -> Option<RequestItem> {
+        // No event is emitted without a new version.
+        if self.caught_up && self.polled_version >= Some(version) {
+            return None;
+        }
+        Some(RequestItem::GetEventsByEventAccessPath {
+            access_path: self.access_path.clone(),
+            start_event_seq_num: self.next_event_seq_num,
+            ascending: true,
+            limit: MAX_EVENTS_PER_RESPONSE,
+        })
+    }
+
+    fn respond(
+        &mut self,
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+        response_item: Option<ResponseItem>,
+        keepalive: bool,
+    ) -> Result<Option<Self::Response>> {
+        let events_with_proof = match response_item {
+            Some(response_item) => {
+                let (events_with_proof, _) =
+                    response_item.into_get_events_by_access_path_response()?;
+                self.polled_version = Some(ledger_info_with_sigs.ledger_info().version());
+                self.caught_up = (events_with_proof.len() as u64) < MAX_EVENTS_PER_RESPONSE;
+                events_with_proof
+            }
+            None => vec![],
+        };
+        if events_with_proof.is_empty() && !keepalive {
+            return Ok(None);
+        }
+
+        self.next_event_seq_num += events_with_proof.len() as u64;
+        let mut response = SubscribeToEventsResponse::new();
+        response.set_events_with_proof(::protobuf::RepeatedField::from_vec(
+            events_with_proof
+                .into_iter()
+                .map(EventWithProof::into_proto)
+                .collect(),
+        ));
+        response.set_ledger_info_with_sigs(ledger_info_with_sigs.clone().into_proto());
+        Ok(Some(response))
+    }
+}
+
+/// A subscription along with the stream of its client.
+struct Subscriber<S: Subscription> {
+    subscription: S,
+    sender: mpsc::Sender<S::Response>,
+    /// Response the client could not take yet because its stream was full.
+    pending: Option<S::Response>,
+    last_sent: Instant,
+}
+
+/// A subscriber, whatever its subscription, for the poller to serve them all.
+trait Serve: Send {
+    /// What to ask Storage for when the ledger is at `version`, if anything.
+    fn request_item(&self, version: Version) -> Option<RequestItem>;
+
+    /// Sends the client what is new. Returns false once the client is gone.
+    fn serve(
+        &mut self,
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+        response_item: Option<ResponseItem>,
+    ) -> bool;
+}
+
+impl<S: Subscription> Serve for Subscriber<S> {
+    fn request_item(&self, version: Version) -> Option<RequestItem> {
+        // Nothing new is sent before the pending response.
+        if self.pending.is_some() {
+            return None;
+        }
+        self.subscription.request_item(version)
+    }
+
+    fn serve(
+        &mut self,
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+        response_item: Option<ResponseItem>,
+    ) -> bool {
+        let response = match self.pending.take() {
+            Some(response) => response,
+            None => {
+                let keepalive = self.last_sent.elapsed() >= KEEPALIVE_INTERVAL;
+                match self
+                    .subscription
+                    .respond(ledger_info_with_sigs, response_item, keepalive)
+                {
+                    Ok(Some(response)) => response,
+                    Ok(None) => return true,
+                    Err(e) => {
+                        warn!("Subscription got an invalid response from storage: {}", e);
+                        return true;
+                    }
+                }
+            }
+        };
+        match self.sender.try_send(response) {
+            Ok(()) => {
+                self.last_sent = Instant::now();
+                true
+            }
+            Err(e) => {
+                if e.is_disconnected() {
+                    return false;
+                }
+                self.pending = Some(e.into_inner());
+                true
+            }
+        }
+    }
+}
+
+/// A subscriber along with the peer it serves.
+struct ActiveSubscriber {
+    subscriber: Box<dyn Serve>,
+    peer_ip: Option<IpAddr>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Subscribers the poller has not picked up yet.
+    new: Vec<ActiveSubscriber>,
+    /// Number of subscriptions served, overall and per peer.
+    active: usize,
+    active_per_peer: HashMap<IpAddr, usize>,
+    /// Whether the poller thread runs.
+    polling: bool,
+}
+
+impl State {
+    fn remove(&mut self, peer_ip: Option<IpAddr>) {
+        self.active -= 1;
+        if let Some(peer_ip) = peer_ip {
+            let remaining = {
+                let active = self
+                    .active_per_peer
+                    .get_mut(&peer_ip)
+                    .expect("Every peer served has a count");
+                *active -= 1;
+                *active
+            };
+            if remaining == 0 {
+                self.active_per_peer.remove(&peer_ip);
+            }
+        }
+        OP_COUNTERS.sub("subscriptions.active");
+    }
+}
+
+/// The subscriptions AC serves, up to `MAX_ACTIVE_SUBSCRIPTIONS` at once and
+/// `MAX_SUBSCRIPTIONS_PER_PEER` for a single peer.
+#[derive(Clone)]
+pub(crate) struct Subscriptions {
+    storage_read_client: Arc<dyn StorageRead>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Subscriptions {
+    pub(crate) fn new(storage_read_client: Arc<dyn StorageRead>) -> Self {
+        Subscriptions {
+            storage_read_client,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    /// Adds the subscription of the peer to the ones the poller serves, starting the poller if it
+    /// does not run. Returns the stream of its responses. Refuses it if too many subscriptions are
+    /// active, overall or for the peer.
+    pub(crate) fn start<S: Subscription>(
+        &self,
+        subscription: S,
+        peer_ip: Option<IpAddr>,
+    ) -> Result<mpsc::Receiver<S::Response>> {
+        let mut state = lock_state(&self.state);
+        ensure!(
+            state.active < MAX_ACTIVE_SUBSCRIPTIONS,
+            "Too many subscriptions"
+        );
+        if let Some(peer_ip) = peer_ip {
+            ensure!(
+                state.active_per_peer.get(&peer_ip).cloned().unwrap_or(0)
+                    < MAX_SUBSCRIPTIONS_PER_PEER,
+                "Too many subscriptions from {}",
+                peer_ip
+            );
+        }
+        if !state.polling {
+            let storage_read_client = Arc::clone(&self.storage_read_client);
+            let shared_state = Arc::clone(&self.state);
+            thread::Builder::new()
+                .name("ac-subscriptions".to_string())
+                .spawn(move || poll(storage_read_client, shared_state))
+                .map_err(|e| format_err!("Failed to start the subscription poller: {}", e))?;
+            state.polling = true;
+        }
+
+        let (sender, receiver) = mpsc::channel(BUFFER_SIZE);
+        state.new.push(ActiveSubscriber {
+            subscriber: Box::new(Subscriber {
+                subscription,
+                sender,
+                pending: None,
+                last_sent: Instant::now(),
+            }),
+            peer_ip,
+        });
+        state.active += 1;
+        if let Some(peer_ip) = peer_ip {
+            *state.active_per_peer.entry(peer_ip).or_insert(0) += 1;
+        }
+        OP_COUNTERS.add("subscriptions.active");
+        Ok(receiver)
+    }
+}
+
+fn lock_state(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Serves the subscribers until none is left.
+fn poll(storage_read_client: Arc<dyn StorageRead>, state: Arc<Mutex<State>>) {
+    let mut subscribers = vec![];
+    let mut gone = vec![];
+    loop {
+        {
+            let mut state = lock_state(&state);
+            for peer_ip in gone.drain(..) {
+                state.remove(peer_ip);
+            }
+            subscribers.append(&mut state.new);
+            if subscribers.is_empty() {
+                state.polling = false;
+                return;
+            }
+        }
+        match serve(&*storage_read_client, &mut subscribers) {
+            Ok(peer_ips) => gone = peer_ips,
+            // Storage may be back by the next poll.
+            Err(e) => warn!("Subscriptions failed to poll storage: {}", e),
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Polls Storage once for all the subscribers and sends each client what is new. The subscribers
+/// whose client is gone are dropped and the peers they served are returned.
+fn serve(
+    storage_read_client: &dyn StorageRead,
+    subscribers: &mut Vec<ActiveSubscriber>,
+) -> Result<Vec<Option<IpAddr>>> {
+    let (_, ledger_info_with_sigs, _) = storage_read_client.update_to_latest_ledger(0, vec![])?;
+    let version = ledger_info_with_sigs.ledger_info().version();
+    let requests: Vec<_> = subscribers
+        .iter()
+        .enumerate()
+        .filter_map(|(index, active)| {
+            active
+                .subscriber
+                .request_item(version)
+                .map(|request_item| (index, request_item))
+        })
+        .collect();
+    // The answers of Storage, each with the ledger info it is proven against.
+    let mut responses: Vec<Option<(ResponseItem, LedgerInfoWithSignatures)>> =
+        subscribers.iter().map(|_| None).collect();
+    for chunk in requests.chunks(MAX_REQUEST_ITEMS) {
+        let (response_items, chunk_ledger_info_with_sigs, _) = storage_read_client
+            .update_to_latest_ledger(
+                0,
+                chunk
+                    .iter()
+                    .map(|(_, request_item)| request_item.clone())
+                    .collect(),
+            )?;
+        ensure!(
+            response_items.len() == chunk.len(),
+            "Storage returned {} response items for {} request items",
+            response_items.len(),
+            chunk.len()
+        );
+        for ((index, _), response_item) in chunk.iter().zip(response_items) {
+            responses[*index] = Some((response_item, chunk_ledger_info_with_sigs.clone()));
+        }
+    }
+
+    let mut gone = vec![];
+    let mut served = Vec::with_capacity(subscribers.len());
+    for (mut active, response) in subscribers.drain(..).zip(responses) {
+        let alive = match response {
+            Some((response_item, ledger_info_with_sigs)) => active
+                .subscriber
+                .serve(&ledger_info_with_sigs, Some(response_item)),
+            None => active.subscriber.serve(&ledger_info_with_sigs, None),
+        };
+        if alive {
+            served.push(active);
+        } else {
+            gone.push(active.peer_ip);
+        }
+    }
+    *subscribers = served;
+    Ok(gone)
+}
@@ -3,35 +3,71 @@
 
 use crate::{
     admission_control_service::{
-        AdmissionControlService, SubmitTransactionRequest,
-        SubmitTransactionResponse as ProtoSubmitTransactionResponse,
+        AdmissionControlService, GetTransactionStatusRequest, SimulateTransactionRequest,
+        SubmitTransactionRequest, SubmitTransactionResponse as ProtoSubmitTransactionResponse,
+        SubmitTransactionsRequest, MAX_SIMULATED_GAS_AMOUNT, MAX_SUBMIT_TRANSACTIONS_BATCH_SIZE,
     },
+    admission_policy::{AdmissionPolicy, SenderBlacklist},
     unit_tests::LocalMockMempool,
 };
-use admission_control_proto::{AdmissionControlStatus, SubmitTransactionResponse};
+use admission_control_proto::{
+    AdmissionControlStatus, GetTransactionStatusResponse, SimulateTransactionResponse,
+    SubmitTransactionResponse, SubmittedTransactionStatus,
+};
+use config::config::RateLimitConfig;
 use crypto::{
     hash::CryptoHash,
     signing::{generate_keypair, sign_message},
+    HashValue,
+};
+use mempool::{
+    proto::{mempool as mempool_proto, mempool_client::MempoolClientTrait},
+    MempoolAddTransactionStatus, TimelineState,
 };
-use mempool::MempoolAddTransactionStatus;
 use proto_conv::FromProto;
 use protobuf::{Message, UnknownFields};
-use std::sync::Arc;
+use std::{
+    future::Future,
+    net::{IpAddr, Ipv4Addr},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use storage_client::StorageRead;
+use storage_proto::ExecutorStartupInfo;
 use storage_service::mocks::mock_storage_client::MockStorageReadClient;
 use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
-    test_helpers::transaction_test_helpers::get_test_signed_txn,
-    transaction::RawTransactionBytes,
+    account_state_blob::AccountStateBlob,
+    get_with_proof::{RequestItem, ResponseItem},
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{AccumulatorProof, SignedTransactionProof, SparseMerkleProof},
+    proto::transaction::SignedTransaction as ProtoSignedTransaction,
+    test_helpers::transaction_test_helpers::{get_test_signed_transaction, get_test_signed_txn},
+    transaction::{
+        RawTransactionBytes, SignedTransaction, SignedTransactionWithProof, TransactionInfo,
+        TransactionListWithProof, TransactionStatus, Version,
+    },
+    validator_change::ValidatorChangeEventWithProof,
     vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
 };
-use vm_validator::mocks::mock_vm_validator::MockVMValidator;
+use vm_validator::mocks::mock_vm_validator::{MockVMValidator, MOCK_GAS_USED};
 
 fn create_ac_service_for_ut() -> AdmissionControlService<LocalMockMempool, MockVMValidator> {
+    create_ac_service_with_policy_for_ut(AdmissionPolicy::default())
+}
+
+fn create_ac_service_with_policy_for_ut(
+    admission_policy: AdmissionPolicy,
+) -> AdmissionControlService<LocalMockMempool, MockVMValidator> {
     AdmissionControlService::new(
         Arc::new(LocalMockMempool::new()),
         Arc::new(MockStorageReadClient),
         Arc::new(MockVMValidator),
         false,
+        Arc::new(admission_policy),
     )
 }
 
@@ -278,3 +314,487 @@ fn test_submit_txn_inner_mempool() {
         AdmissionControlStatus::Accepted,
     );
 }
+
+#[test]
+fn test_submit_txns_inner() {
+    let ac_service = create_ac_service_for_ut();
+    let keypair = generate_keypair();
+    let accepted_add = AccountAddress::new([103; ADDRESS_LENGTH]);
+    let invalid_sig_add = AccountAddress::new([1; ADDRESS_LENGTH]);
+    let insufficient_balance_add = AccountAddress::new([100; ADDRESS_LENGTH]);
+    let mut req = SubmitTransactionsRequest::new();
+    // Consecutive sequence numbers from the same sender.
+    for (sender, sequence_number) in &[
+        (accepted_add, 0),
+        (accepted_add, 1),
+        (invalid_sig_add, 0),
+        (insufficient_balance_add, 0),
+    ] {
+        req.mut_signed_txns().push(get_test_signed_txn(
+            *sender,
+            *sequence_number,
+            keypair.0.clone(),
+            keypair.1,
+            None,
+        ));
+    }
+    req.mut_signed_txns().push(ProtoSignedTransaction::new());
+
+    let responses: Vec<_> = ac_service
+        .submit_transactions_inner(req.clone(), None)
+        .unwrap()
+        .take_responses()
+        .into_iter()
+        .map(|response| SubmitTransactionResponse::from_proto(response).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 5);
+    assert_eq!(
+        responses[0].ac_status,
+        Some(AdmissionControlStatus::Accepted)
+    );
+    assert_eq!(
+        responses[1].ac_status,
+        Some(AdmissionControlStatus::Accepted)
+    );
+    assert_eq!(
+        responses[2].vm_error,
+        Some(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+    );
+    assert_eq!(
+        responses[3].mempool_error,
+        Some(MempoolAddTransactionStatus::InsufficientBalance)
+    );
+    assert_eq!(
+        responses[4].ac_status,
+        Some(AdmissionControlStatus::Rejected)
+    );
+
+    // Batches are bounded.
+    let signed_txn = req.get_signed_txns()[0].clone();
+    req.set_signed_txns(vec![signed_txn; MAX_SUBMIT_TRANSACTIONS_BATCH_SIZE + 1].into());
+    assert!(ac_service.submit_transactions_inner(req, None).is_err());
+}
+
+#[test]
+fn test_simulate_txn_inner() {
+    let ac_service = create_ac_service_for_ut();
+    let mut req = SimulateTransactionRequest::new();
+    let keypair = generate_keypair();
+    let mut signed_txn = get_test_signed_txn(
+        AccountAddress::new([103; ADDRESS_LENGTH]),
+        0,
+        keypair.0.clone(),
+        keypair.1,
+        None,
+    );
+    req.set_signed_txn(signed_txn.clone());
+    let response = SimulateTransactionResponse::from_proto(
+        ac_service
+            .simulate_transaction_inner(req.clone(), None)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.status,
+        TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+    );
+    assert_eq!(response.gas_used, MOCK_GAS_USED);
+
+    // Without a signature.
+    signed_txn.clear_sender_signature();
+    req.set_signed_txn(signed_txn);
+    req.set_skip_signature_check(true);
+    let response = SimulateTransactionResponse::from_proto(
+        ac_service
+            .simulate_transaction_inner(req.clone(), None)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(response.gas_used, MOCK_GAS_USED);
+
+    req.set_signed_txn(get_test_signed_txn(
+        AccountAddress::new([2; ADDRESS_LENGTH]),
+        0,
+        keypair.0.clone(),
+        keypair.1,
+        None,
+    ));
+    let response = SimulateTransactionResponse::from_proto(
+        ac_service
+            .simulate_transaction_inner(req.clone(), None)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.status,
+        TransactionStatus::Discard(VMStatus::Validation(
+            VMValidationStatus::InsufficientBalanceForTransactionFee
+        ))
+    );
+    assert_eq!(response.gas_used, 0);
+
+    // The max gas amount of a simulated transaction is bounded.
+    req.set_signed_txn(get_test_signed_transaction(
+        AccountAddress::new([103; ADDRESS_LENGTH]),
+        0,
+        keypair.0.clone(),
+        keypair.1,
+        None,
+        u64::max_value(),
+        1,
+        Some(MAX_SIMULATED_GAS_AMOUNT + 1),
+    ));
+    assert!(ac_service.simulate_transaction_inner(req, None).is_err());
+}
+
+#[test]
+fn test_simulate_txn_rate_limited() {
+    let rate_limit = RateLimitConfig {
+        burst: 1,
+        per_second: 0.001,
+    };
+    let ac_service = create_ac_service_with_policy_for_ut(AdmissionPolicy::new(
+        Arc::new(SenderBlacklist::default()),
+        None,
+        Some(&rate_limit),
+    ));
+    let keypair = generate_keypair();
+    let mut req = SimulateTransactionRequest::new();
+    req.set_signed_txn(get_test_signed_txn(
+        AccountAddress::new([103; ADDRESS_LENGTH]),
+        0,
+        keypair.0.clone(),
+        keypair.1,
+        None,
+    ));
+    let first_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let second_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    assert!(ac_service
+        .simulate_transaction_inner(req.clone(), Some(first_peer))
+        .is_ok());
+    assert!(ac_service
+        .simulate_transaction_inner(req.clone(), Some(first_peer))
+        .is_err());
+    // Simulations and submissions share the rate limit of a peer.
+    let mut submit_req = SubmitTransactionRequest::new();
+    submit_req.set_signed_txn(req.get_signed_txn().clone());
+    let response = SubmitTransactionResponse::from_proto(
+        ac_service
+            .submit_transaction_from_peer(submit_req, Some(first_peer))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.ac_status.unwrap(),
+        AdmissionControlStatus::Rejected,
+    );
+    assert!(ac_service
+        .simulate_transaction_inner(req, Some(second_peer))
+        .is_ok());
+}
+
+#[test]
+fn test_submit_txn_inner_blacklisted() {
+    let blacklisted = AccountAddress::new([103; ADDRESS_LENGTH]);
+    let policy = AdmissionPolicy::new(
+        Arc::new(SenderBlacklist::new(vec![blacklisted])),
+        None,
+        None,
+    );
+    let sender_blacklist = policy.sender_blacklist();
+    let ac_service = create_ac_service_with_policy_for_ut(policy);
+    let mut req: SubmitTransactionRequest = SubmitTransactionRequest::new();
+    let keypair = generate_keypair();
+    req.set_signed_txn(get_test_signed_txn(
+        blacklisted,
+        0,
+        keypair.0.clone(),
+        keypair.1,
+        None,
+    ));
+    let response = SubmitTransactionResponse::from_proto(
+        ac_service.submit_transaction_inner(req.clone()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.ac_status.unwrap(),
+        AdmissionControlStatus::Blacklisted,
+    );
+    assert!(response.ac_status_reason.is_some());
+
+    // Removed from the blacklist at runtime.
+    assert!(sender_blacklist.remove(&blacklisted));
+    let response = SubmitTransactionResponse::from_proto(
+        ac_service.submit_transaction_inner(req.clone()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.ac_status.unwrap(),
+        AdmissionControlStatus::Accepted,
+    );
+    assert_eq!(response.ac_status_reason, None);
+}
+
+#[test]
+fn test_submit_txn_rate_limited() {
+    let rate_limit = RateLimitConfig {
+        burst: 1,
+        per_second: 0.001,
+    };
+    let ac_service = create_ac_service_with_policy_for_ut(AdmissionPolicy::new(
+        Arc::new(SenderBlacklist::default()),
+        Some(&rate_limit),
+        Some(&rate_limit),
+    ));
+    let keypair = generate_keypair();
+    let submit = |sender: AccountAddress, peer_ip: IpAddr| {
+        let mut req: SubmitTransactionRequest = SubmitTransactionRequest::new();
+        req.set_signed_txn(get_test_signed_txn(
+            sender,
+            0,
+            keypair.0.clone(),
+            keypair.1,
+            None,
+        ));
+        SubmitTransactionResponse::from_proto(
+            ac_service
+                .submit_transaction_from_peer(req, Some(peer_ip))
+                .unwrap(),
+        )
+        .unwrap()
+    };
+    let first_sender = AccountAddress::new([103; ADDRESS_LENGTH]);
+    let second_sender = AccountAddress::new([8; ADDRESS_LENGTH]);
+    let first_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let second_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+    assert_eq!(
+        submit(first_sender, first_peer).ac_status.unwrap(),
+        AdmissionControlStatus::Accepted,
+    );
+    // Same peer, other sender.
+    let response = submit(second_sender, first_peer);
+    assert_eq!(
+        response.ac_status.unwrap(),
+        AdmissionControlStatus::Rejected,
+    );
+    assert!(response.ac_status_reason.is_some());
+    // Same sender, other peer.
+    let response = submit(first_sender, second_peer);
+    assert_eq!(
+        response.ac_status.unwrap(),
+        AdmissionControlStatus::Rejected,
+    );
+    assert!(response.ac_status_reason.is_some());
+}
+
+#[test]
+fn test_get_transaction_status_inner() {
+    let ac_service = create_ac_service_for_ut();
+    let sender = AccountAddress::new([103; ADDRESS_LENGTH]);
+    let keypair = generate_keypair();
+    let signed_txn =
+        SignedTransaction::from_proto(get_test_signed_txn(sender, 0, keypair.0, keypair.1, None))
+            .unwrap();
+
+    // The mock ledger has no transaction, so the status comes from the mock mempool.
+    let mut req = GetTransactionStatusRequest::new();
+    req.set_hash(signed_txn.hash().to_vec());
+    let response = GetTransactionStatusResponse::from_proto(
+        ac_service.get_transaction_status_inner(req).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.status,
+        SubmittedTransactionStatus::Pending(TimelineState::NotReady)
+    );
+    assert_eq!(response.ledger_info_with_sigs.ledger_info().version(), 7);
+
+    let mut req = GetTransactionStatusRequest::new();
+    req.set_hash(vec![1, 2, 3]);
+    assert!(ac_service.get_transaction_status_inner(req).is_err());
+}
+
+/// Mempool which knows none of the transactions it is asked about, as when they were committed and
+/// removed from it.
+struct ForgetfulMempool {
+    asked: Arc<AtomicBool>,
+}
+
+impl MempoolClientTrait for ForgetfulMempool {
+    fn add_transaction_with_validation(
+        &self,
+        req: &mempool_proto::AddTransactionWithValidationRequest,
+    ) -> ::grpcio::Result<mempool_proto::AddTransactionWithValidationResponse> {
+        LocalMockMempool::new().add_transaction_with_validation(req)
+    }
+    fn add_transactions_with_validation(
+        &self,
+        req: &mempool_proto::AddTransactionsWithValidationRequest,
+    ) -> ::grpcio::Result<mempool_proto::AddTransactionsWithValidationResponse> {
+        LocalMockMempool::new().add_transactions_with_validation(req)
+    }
+    fn health_check(
+        &self,
+        req: &mempool_proto::HealthCheckRequest,
+    ) -> ::grpcio::Result<mempool_proto::HealthCheckResponse> {
+        LocalMockMempool::new().health_check(req)
+    }
+    fn get_transaction_status(
+        &self,
+        _req: &mempool_proto::GetTransactionStatusRequest,
+    ) -> ::grpcio::Result<mempool_proto::GetTransactionStatusResponse> {
+        self.asked.store(true, Ordering::SeqCst);
+        // The state defaults to unknown.
+        Ok(mempool_proto::GetTransactionStatusResponse::new())
+    }
+}
+
+/// Storage in which a transaction is committed once Mempool was asked about it, as when it is
+/// committed between the two lookups.
+struct CommittingStorage {
+    mempool_asked: Arc<AtomicBool>,
+    committed: SignedTransactionWithProof,
+}
+
+impl StorageRead for CommittingStorage {
+    fn update_to_latest_ledger(
+        &self,
+        client_known_version: Version,
+        request_items: Vec<RequestItem>,
+    ) -> failure::Result<(
+        Vec<ResponseItem>,
+        LedgerInfoWithSignatures,
+        Vec<ValidatorChangeEventWithProof>,
+    )> {
+        let (mut response_items, ledger_info_with_sigs, validator_change_events) =
+            MockStorageReadClient.update_to_latest_ledger(client_known_version, request_items)?;
+        if self.mempool_asked.load(Ordering::SeqCst) {
+            response_items = vec![ResponseItem::GetTransactionByHash {
+                signed_transaction_with_proof: Some(self.committed.clone()),
+            }];
+        }
+        Ok((
+            response_items,
+            ledger_info_with_sigs,
+            validator_change_events,
+        ))
+    }
+
+    fn update_to_latest_ledger_async(
+        &self,
+        _client_known_version: Version,
+        _request_items: Vec<RequestItem>,
+    ) -> Pin<
+        Box<
+            dyn Future<
+                    Output = failure::Result<(
+                        Vec<ResponseItem>,
+                        LedgerInfoWithSignatures,
+                        Vec<ValidatorChangeEventWithProof>,
+                    )>,
+                > + Send,
+        >,
+    > {
+        unimplemented!()
+    }
+
+    fn get_transactions(
+        &self,
+        _start_version: Version,
+        _batch_size: u64,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> failure::Result<TransactionListWithProof> {
+        unimplemented!()
+    }
+
+    fn get_transactions_async(
+        &self,
+        _start_version: Version,
+        _batch_size: u64,
+        _ledger_version: Version,
+        _fetch_events: bool,
+    ) -> Pin<Box<dyn Future<Output = failure::Result<TransactionListWithProof>> + Send>> {
+        unimplemented!()
+    }
+
+    fn get_account_state_with_proof_by_state_root(
+        &self,
+        _address: AccountAddress,
+        _state_root_hash: HashValue,
+    ) -> failure::Result<(Option<AccountStateBlob>, SparseMerkleProof)> {
+        unimplemented!()
+    }
+
+    fn get_account_state_with_proof_by_state_root_async(
+        &self,
+        _address: AccountAddress,
+        _state_root_hash: HashValue,
+    ) -> Pin<
+        Box<
+            dyn Future<Output = failure::Result<(Option<AccountStateBlob>, SparseMerkleProof)>>
+                + Send,
+        >,
+    > {
+        unimplemented!()
+    }
+
+    fn get_executor_startup_info(&self) -> failure::Result<Option<ExecutorStartupInfo>> {
+        unimplemented!()
+    }
+
+    fn get_executor_startup_info_async(
+        &self,
+    ) -> Pin<Box<dyn Future<Output = failure::Result<Option<ExecutorStartupInfo>>> + Send>> {
+        unimplemented!()
+    }
+}
+
+#[test]
+fn test_get_transaction_status_committed_in_between() {
+    let keypair = generate_keypair();
+    let signed_txn = SignedTransaction::from_proto(get_test_signed_txn(
+        AccountAddress::new([103; ADDRESS_LENGTH]),
+        0,
+        keypair.0,
+        keypair.1,
+        None,
+    ))
+    .unwrap();
+    let committed = SignedTransactionWithProof {
+        version: 0,
+        signed_transaction: signed_txn.clone(),
+        events: None,
+        proof: SignedTransactionProof::new(
+            AccumulatorProof::new(vec![]),
+            TransactionInfo::new(signed_txn.hash(), HashValue::zero(), HashValue::zero(), 0),
+        ),
+    };
+    let mempool_asked = Arc::new(AtomicBool::new(false));
+    let ac_service = AdmissionControlService::new(
+        Arc::new(ForgetfulMempool {
+            asked: Arc::clone(&mempool_asked),
+        }),
+        Arc::new(CommittingStorage {
+            mempool_asked,
+            committed: committed.clone(),
+        }),
+        Arc::new(MockVMValidator),
+        false,
+        Arc::new(AdmissionPolicy::default()),
+    );
+
+    // Mempool no longer knows the transaction, but Storage, asked after it, has it.
+    let mut req = GetTransactionStatusRequest::new();
+    req.set_hash(signed_txn.hash().to_vec());
+    let response = GetTransactionStatusResponse::from_proto(
+        ac_service.get_transaction_status_inner(req).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        response.status,
+        SubmittedTransactionStatus::Committed(committed)
+    );
+}
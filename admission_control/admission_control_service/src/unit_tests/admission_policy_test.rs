@@ -0,0 +1,136 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::admission_policy::{
+    parse_peer_ip, AdmissionPolicy, RateLimiter, SenderBlacklist, MAX_TRACKED_BUCKETS,
+};
+use config::config::{AdmissionControlConfig, RateLimitConfig};
+use debug_interface::node_debug_service::SenderBlacklistManager;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::{Duration, Instant},
+};
+use types::account_address::{AccountAddress, ADDRESS_LENGTH};
+
+#[test]
+fn test_rate_limiter() {
+    let limiter = RateLimiter::new(&RateLimitConfig {
+        burst: 2,
+        per_second: 0.5,
+    });
+    let start = Instant::now();
+    assert!(limiter.try_acquire_at(1, start));
+    assert!(limiter.try_acquire_at(1, start));
+    assert!(!limiter.try_acquire_at(1, start));
+    // Buckets are per key.
+    assert!(limiter.try_acquire_at(2, start));
+
+    assert!(!limiter.try_acquire_at(1, start + Duration::from_secs(1)));
+    assert!(limiter.try_acquire_at(1, start + Duration::from_secs(2)));
+    assert!(!limiter.try_acquire_at(1, start + Duration::from_secs(2)));
+
+    // A bucket holds no more than the burst.
+    let later = start + Duration::from_secs(60);
+    assert!(limiter.try_acquire_at(1, later));
+    assert!(limiter.try_acquire_at(1, later));
+    assert!(!limiter.try_acquire_at(1, later));
+}
+
+#[test]
+fn test_rate_limiter_bounded() {
+    let limiter = RateLimiter::new(&RateLimitConfig {
+        burst: 1,
+        per_second: 0.001,
+    });
+    let start = Instant::now();
+    let at = |key: usize| start + Duration::from_millis(key as u64);
+    for key in 0..MAX_TRACKED_BUCKETS {
+        assert!(limiter.try_acquire_at(key, at(key)));
+    }
+    // The bucket of the first key is still empty, so it is still tracked.
+    assert!(!limiter.try_acquire_at(0, at(MAX_TRACKED_BUCKETS)));
+    assert_eq!(limiter.buckets.lock().unwrap().len(), MAX_TRACKED_BUCKETS);
+
+    // None of the buckets is full again, so a new key makes the limiter forget the buckets used
+    // least recently.
+    assert!(limiter.try_acquire_at(MAX_TRACKED_BUCKETS, at(MAX_TRACKED_BUCKETS)));
+    let buckets = limiter.buckets.lock().unwrap();
+    assert!(buckets.len() < MAX_TRACKED_BUCKETS);
+    assert!(buckets.contains_key(&0));
+    assert!(!buckets.contains_key(&1));
+    assert!(buckets.contains_key(&(MAX_TRACKED_BUCKETS - 1)));
+    assert!(buckets.contains_key(&MAX_TRACKED_BUCKETS));
+}
+
+#[test]
+fn test_sender_blacklist() {
+    let first = AccountAddress::new([1; ADDRESS_LENGTH]);
+    let second = AccountAddress::new([2; ADDRESS_LENGTH]);
+    let blacklist = SenderBlacklist::new(vec![first]);
+    assert!(blacklist.contains(&first));
+    assert!(!blacklist.contains(&second));
+
+    assert!(blacklist.update(second.as_ref(), true).unwrap());
+    assert!(!blacklist.update(second.as_ref(), true).unwrap());
+    assert!(blacklist.contains(&second));
+    assert_eq!(
+        blacklist.senders(),
+        vec![first.as_ref().to_vec(), second.as_ref().to_vec()]
+    );
+
+    assert!(blacklist.update(first.as_ref(), false).unwrap());
+    assert!(!blacklist.update(first.as_ref(), false).unwrap());
+    assert!(!blacklist.contains(&first));
+
+    assert!(blacklist.update(&[1, 2, 3], true).is_err());
+}
+
+#[test]
+fn test_admission_policy_from_config() {
+    let sender = AccountAddress::new([1; ADDRESS_LENGTH]);
+    let mut config = AdmissionControlConfig {
+        address: "localhost".to_string(),
+        admission_control_service_port: 0,
+        need_to_check_mempool_before_validation: false,
+        blacklisted_senders: vec![String::from(&sender)],
+        sender_rate_limit: Some(RateLimitConfig {
+            burst: 1,
+            per_second: 0.001,
+        }),
+        peer_ip_rate_limit: None,
+    };
+    let policy = AdmissionPolicy::from_config(&config).unwrap();
+    assert!(policy.is_blacklisted(&sender));
+    assert!(policy.admit_sender(sender));
+    assert!(!policy.admit_sender(sender));
+    let peer_ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    for _ in 0..10 {
+        assert!(policy.admit_peer(peer_ip));
+    }
+
+    for (burst, per_second) in &[(0, 1.0), (1, 0.0), (1, -1.0), (1, std::f64::NAN)] {
+        config.peer_ip_rate_limit = Some(RateLimitConfig {
+            burst: *burst,
+            per_second: *per_second,
+        });
+        assert!(AdmissionPolicy::from_config(&config).is_err());
+    }
+    config.peer_ip_rate_limit = None;
+
+    config.blacklisted_senders = vec!["not an address".to_string()];
+    assert!(AdmissionPolicy::from_config(&config).is_err());
+}
+
+#[test]
+fn test_parse_peer_ip() {
+    assert_eq!(
+        parse_peer_ip("ipv4:127.0.0.1:46160"),
+        Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    );
+    assert_eq!(
+        parse_peer_ip("ipv6:[::1]:46160"),
+        Some(IpAddr::V6(Ipv6Addr::LOCALHOST))
+    );
+    assert_eq!(parse_peer_ip("unix:/tmp/ac.sock"), None);
+    assert_eq!(parse_peer_ip(""), None);
+}
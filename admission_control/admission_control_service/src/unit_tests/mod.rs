@@ -4,10 +4,14 @@
 use mempool::proto::{
     mempool::{
         AddTransactionWithValidationRequest, AddTransactionWithValidationResponse,
-        HealthCheckRequest, HealthCheckResponse,
+        AddTransactionsWithValidationRequest, AddTransactionsWithValidationResponse,
+        GetTransactionStatusRequest, GetTransactionStatusResponse, HealthCheckRequest,
+        HealthCheckResponse,
     },
     mempool_client::MempoolClientTrait,
-    shared::mempool_status::MempoolAddTransactionStatus,
+    shared::mempool_status::{
+        MempoolAddTransactionStatus, MempoolTransactionStatus_State, TimelineState_Kind,
+    },
 };
 use proto_conv::FromProto;
 use std::time::SystemTime;
@@ -53,6 +57,17 @@ impl MempoolClientTrait for LocalMockMempool {
         }
         Ok(resp)
     }
+    fn add_transactions_with_validation(
+        &self,
+        req: &AddTransactionsWithValidationRequest,
+    ) -> ::grpcio::Result<AddTransactionsWithValidationResponse> {
+        let mut resp = AddTransactionsWithValidationResponse::new();
+        for txn_req in req.get_transactions() {
+            let status = self.add_transaction_with_validation(txn_req)?.get_status();
+            resp.mut_statuses().push(status);
+        }
+        Ok(resp)
+    }
     fn health_check(&self, _req: &HealthCheckRequest) -> ::grpcio::Result<HealthCheckResponse> {
         let mut ret = HealthCheckResponse::new();
         let duration_ms = SystemTime::now()
@@ -62,4 +77,17 @@ impl MempoolClientTrait for LocalMockMempool {
         ret.set_is_healthy(duration_ms > 500 || duration_ms < 300);
         Ok(ret)
     }
+    fn get_transaction_status(
+        &self,
+        _req: &GetTransactionStatusRequest,
+    ) -> ::grpcio::Result<GetTransactionStatusResponse> {
+        // every transaction asked about is pending
+        let mut ret = GetTransactionStatusResponse::new();
+        ret.mut_status()
+            .set_state(MempoolTransactionStatus_State::Pending);
+        ret.mut_status()
+            .mut_timeline_state()
+            .set_kind(TimelineState_Kind::NotReady);
+        Ok(ret)
+    }
 }
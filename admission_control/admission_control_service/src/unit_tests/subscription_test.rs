@@ -0,0 +1,159 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::subscription::{
+    EventSubscription, LedgerInfoSubscription, Serve, Subscriber, Subscription, Subscriptions,
+    KEEPALIVE_INTERVAL, MAX_SUBSCRIPTIONS_PER_PEER,
+};
+use futures::{sync::mpsc, Stream};
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    sync::Arc,
+    time::Instant,
+};
+use storage_client::StorageRead;
+use storage_service::mocks::mock_storage_client::MockStorageReadClient;
+use types::{
+    access_path::AccessPath, account_address::AccountAddress, ledger_info::LedgerInfoWithSignatures,
+};
+
+/// The latest ledger info of the mock ledger, which is at version 7.
+fn latest_ledger_info() -> LedgerInfoWithSignatures {
+    MockStorageReadClient
+        .update_to_latest_ledger(0, vec![])
+        .unwrap()
+        .1
+}
+
+#[test]
+fn test_ledger_info_subscription() {
+    let ledger_info_with_sigs = latest_ledger_info();
+    let mut subscription = LedgerInfoSubscription::new(0);
+    assert!(subscription.request_item(7).is_none());
+    let response = subscription
+        .respond(&ledger_info_with_sigs, None, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        response
+            .get_ledger_info_with_sigs()
+            .get_ledger_info()
+            .get_version(),
+        7
+    );
+    assert!(subscription
+        .respond(&ledger_info_with_sigs, None, false)
+        .unwrap()
+        .is_none());
+    // The keepalive is the latest ledger info again.
+    assert!(subscription
+        .respond(&ledger_info_with_sigs, None, true)
+        .unwrap()
+        .is_some());
+
+    // No ledger info older than the one the client knows is sent, not even as a keepalive.
+    let mut subscription = LedgerInfoSubscription::new(8);
+    assert!(subscription
+        .respond(&ledger_info_with_sigs, None, true)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_event_subscription_keepalive() {
+    let access_path = AccessPath::new_for_received_event(AccountAddress::random());
+    let mut subscription = EventSubscription::new(access_path, 0);
+    let request_item = subscription.request_item(7).unwrap();
+    // The mock ledger has no events.
+    let (mut response_items, ledger_info_with_sigs, _) = MockStorageReadClient
+        .update_to_latest_ledger(0, vec![request_item])
+        .unwrap();
+    assert!(subscription
+        .respond(&ledger_info_with_sigs, response_items.pop(), false)
+        .unwrap()
+        .is_none());
+    // Storage is only asked again once the ledger has a new version.
+    assert!(subscription.request_item(7).is_none());
+    assert!(subscription.request_item(8).is_some());
+
+    let response = subscription
+        .respond(&ledger_info_with_sigs, None, true)
+        .unwrap()
+        .unwrap();
+    assert!(response.get_events_with_proof().is_empty());
+    assert_eq!(
+        response
+            .get_ledger_info_with_sigs()
+            .get_ledger_info()
+            .get_version(),
+        7
+    );
+}
+
+#[test]
+fn test_subscriber_slow_or_gone() {
+    let ledger_info_with_sigs = latest_ledger_info();
+    let (sender, receiver) = mpsc::channel(0);
+    let mut subscriber = Subscriber {
+        subscription: LedgerInfoSubscription::new(0),
+        sender,
+        pending: None,
+        last_sent: Instant::now(),
+    };
+    assert!(subscriber.serve(&ledger_info_with_sigs, None));
+    assert!(subscriber.pending.is_none());
+
+    // The stream is full, so the keepalive waits.
+    subscriber.last_sent = Instant::now() - KEEPALIVE_INTERVAL;
+    assert!(subscriber.serve(&ledger_info_with_sigs, None));
+    assert!(subscriber.pending.is_some());
+
+    drop(receiver);
+    assert!(!subscriber.serve(&ledger_info_with_sigs, None));
+}
+
+#[test]
+fn test_subscriptions_stream_responses() {
+    let subscriptions = Subscriptions::new(Arc::new(MockStorageReadClient));
+    // A single poller serves both.
+    let receivers: Vec<_> = (0..2)
+        .map(|_| {
+            subscriptions
+                .start(LedgerInfoSubscription::new(0), None)
+                .unwrap()
+        })
+        .collect();
+    for receiver in receivers {
+        let response = receiver.wait().next().unwrap().unwrap();
+        assert_eq!(
+            response
+                .get_ledger_info_with_sigs()
+                .get_ledger_info()
+                .get_version(),
+            7
+        );
+    }
+}
+
+#[test]
+fn test_subscriptions_per_peer() {
+    let subscriptions = Subscriptions::new(Arc::new(MockStorageReadClient));
+    let first_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let second_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+    let mut receivers = vec![];
+    for _ in 0..MAX_SUBSCRIPTIONS_PER_PEER {
+        receivers.push(
+            subscriptions
+                .start(LedgerInfoSubscription::new(0), Some(first_peer))
+                .unwrap(),
+        );
+    }
+    assert!(subscriptions
+        .start(LedgerInfoSubscription::new(0), Some(first_peer))
+        .is_err());
+    receivers.push(
+        subscriptions
+            .start(LedgerInfoSubscription::new(0), Some(second_peer))
+            .unwrap(),
+    );
+}
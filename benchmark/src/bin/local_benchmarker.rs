@@ -4,6 +4,7 @@ use client::{client_proxy::ClientProxy, AccountData};
 use debug_interface::NodeDebugClient;
 use grpcio::{ChannelBuilder, EnvBuilder};
 use libra_swarm::swarm::LibraSwarm;
+use libra_wallet::co_signer::CoSignerConfig;
 use logger::{self, prelude::*};
 use std::sync::Arc;
 
@@ -24,6 +25,8 @@ fn main() {
         &mint_key_file_path,
         None,
         None,
+        None,
+        Some(CoSignerConfig::in_process("local_benchmarker")),
     )
     .unwrap();
     // Create a AdmissionControlClient instance.
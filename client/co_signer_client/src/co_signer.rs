@@ -0,0 +1,807 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Configuration of the remote co-signer holding the server share of two-party keys.
+//!
+//! A CoSignerConfig is stored alongside every KeyShare, so that accounts created against
+//! different co-signers keep talking to the server that holds their share. The config is turned
+//! into a CoSigner: the HTTP ClientShim for a remote `server_exec`, or an InProcessCoSigner for
+//! `in-process://` urls. The in-process co-signer only exists in builds with the `testing`
+//! feature, elsewhere its urls are refused.
+//!
+//! A co-signer may also hold shares of threshold keys (see ThresholdConfig), each taking part in
+//! the key as one named participant among several co-signers, known by its pinned identity key.
+
+use crate::error::{Result, WalletError};
+#[cfg(any(test, feature = "testing"))]
+use crate::in_process::in_process_co_signer;
+use co_signer_protocol::{
+    threshold::{
+        decode_identity_key, ThresholdKeyGenCommitRequest, ThresholdKeyGenCommitResponse,
+        ThresholdKeyGenCompleteRequest, ThresholdKeyGenCompleteResponse,
+        ThresholdKeyGenShareRequest, ThresholdKeyGenShareResponse, ThresholdParameters,
+        ThresholdSignCommitRequest, ThresholdSignCommitResponse, ThresholdSignRequest,
+        ThresholdSignResponse, ThresholdSignRevealRequest, ThresholdSignRevealResponse,
+    },
+    ErrorResponse, KeyGenRequest, KeyGenResponse, RefreshConfirmRequest, RefreshConfirmResponse,
+    RefreshRequest, RefreshResponse, RotationConfirmRequest, RotationConfirmResponse,
+    SignFirstRequest, SignFirstResponse, SignSecondRequest, SignSecondResponse, GE,
+};
+use log::warn;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Certificate, Identity, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    net::Ipv4Addr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+/// Co-signer used when nothing else is configured.
+pub const DEFAULT_CO_SIGNER_URL: &str = "http://localhost:8000";
+
+/// Scheme of the co-signer urls resolved to an InProcessCoSigner.
+pub const IN_PROCESS_SCHEME: &str = "in-process://";
+
+const PEM_CERTIFICATE_END: &str = "-----END CERTIFICATE-----";
+
+/// Number of times a co-signer call is attempted before giving up.
+const MAX_CO_SIGNER_ATTEMPTS: u32 = 4;
+/// Delay before the first retry, doubled after every failed attempt.
+const INITIAL_BACKOFF_MS: u64 = 250;
+
+/// Server side of the two-party protocol, as seen by the wallet. Every call is one round trip of
+/// the protocol, for the key stored under id by the co-signer.
+pub trait CoSigner: fmt::Debug + Send + Sync {
+    /// Generates the server share of a new key
+    fn keygen(&self, request: KeyGenRequest) -> Result<KeyGenResponse>;
+    /// Opens a signing session
+    fn sign_first(&self, id: &str, request: SignFirstRequest) -> Result<SignFirstResponse>;
+    /// Closes a signing session, returning the partial signature of the co-signer
+    fn sign_second(&self, id: &str, request: SignSecondRequest) -> Result<SignSecondResponse>;
+    /// Refreshes the server share of a key
+    fn refresh(&self, id: &str, request: RefreshRequest) -> Result<RefreshResponse>;
+    /// Confirms that the refreshed client share is persisted
+    fn confirm_refresh(
+        &self,
+        id: &str,
+        request: RefreshConfirmRequest,
+    ) -> Result<RefreshConfirmResponse>;
+    /// Confirms that the authentication key of an account was rotated to the key
+    fn confirm_rotation(
+        &self,
+        id: &str,
+        request: RotationConfirmRequest,
+    ) -> Result<RotationConfirmResponse>;
+    /// Opens a threshold key generation session
+    fn threshold_keygen_commit(
+        &self,
+        request: ThresholdKeyGenCommitRequest,
+    ) -> Result<ThresholdKeyGenCommitResponse>;
+    /// Returns the sub-shares of the co-signer for the other participants
+    fn threshold_keygen_share(
+        &self,
+        request: ThresholdKeyGenShareRequest,
+    ) -> Result<ThresholdKeyGenShareResponse>;
+    /// Closes a threshold key generation session, storing the share of the co-signer
+    fn threshold_keygen_complete(
+        &self,
+        request: ThresholdKeyGenCompleteRequest,
+    ) -> Result<ThresholdKeyGenCompleteResponse>;
+    /// Opens a threshold signing session
+    fn threshold_sign_commit(
+        &self,
+        id: &str,
+        request: ThresholdSignCommitRequest,
+    ) -> Result<ThresholdSignCommitResponse>;
+    /// Reveals the ephemeral key of the co-signer, once all the signers committed
+    fn threshold_sign_reveal(
+        &self,
+        id: &str,
+        request: ThresholdSignRevealRequest,
+    ) -> Result<ThresholdSignRevealResponse>;
+    /// Closes a threshold signing session, returning the partial signature of the co-signer
+    fn threshold_sign(
+        &self,
+        id: &str,
+        request: ThresholdSignRequest,
+    ) -> Result<ThresholdSignResponse>;
+}
+
+/// How the wallet authenticates itself to the co-signer.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CoSignerAuth {
+    /// No client authentication
+    None,
+    /// Send `Authorization: Bearer <token>` with every request
+    BearerToken {
+        /// The bearer token
+        token: String,
+    },
+    /// Authenticate with a TLS client certificate
+    ClientCertificate {
+        /// Path to a PKCS#12 archive holding the client certificate and private key
+        pkcs12_path: PathBuf,
+        /// Password protecting the PKCS#12 archive
+        password: String,
+    },
+}
+
+impl Default for CoSignerAuth {
+    fn default() -> Self {
+        CoSignerAuth::None
+    }
+}
+
+/// Secrets are redacted, so that configs can be logged.
+impl fmt::Debug for CoSignerAuth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CoSignerAuth::None => write!(f, "None"),
+            CoSignerAuth::BearerToken { .. } => f
+                .debug_struct("BearerToken")
+                .field("token", &"<redacted>")
+                .finish(),
+            CoSignerAuth::ClientCertificate { pkcs12_path, .. } => f
+                .debug_struct("ClientCertificate")
+                .field("pkcs12_path", pkcs12_path)
+                .field("password", &"<redacted>")
+                .finish(),
+        }
+    }
+}
+
+/// Endpoint, trust anchors and credentials used to reach a co-signer. Its Debug output leaves the
+/// credentials out.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CoSignerConfig {
+    /// Base URL of the co-signer, e.g. https://cosigner.example.com:8000
+    pub url: String,
+    /// PEM file with the CA certificate(s) to trust in addition to the system roots. To pin a
+    /// self-signed co-signer certificate, point this to the certificate itself.
+    #[serde(default)]
+    pub ca_cert_path: Option<PathBuf>,
+    /// Client authentication
+    #[serde(default)]
+    pub auth: CoSignerAuth,
+}
+
+impl Default for CoSignerConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_CO_SIGNER_URL.to_string(),
+            ca_cert_path: None,
+            auth: CoSignerAuth::None,
+        }
+    }
+}
+
+impl CoSignerConfig {
+    /// Reads a CoSignerConfig from a TOML file
+    pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    /// Writes the CoSignerConfig to a TOML file. The file holds the client credentials: only the
+    /// owner can read it, and it is written to a temporary file renamed over output_file, so
+    /// that an interrupted write never leaves a truncated file behind.
+    pub fn save_config<P: AsRef<Path>>(&self, output_file: P) -> Result<()> {
+        let contents = toml::to_vec(&self)?;
+        let path = output_file.as_ref();
+        let mut tmp_file_name = path.file_name().map(OsString::from).ok_or_else(|| {
+            WalletError::LibraWalletGeneric(format!("Invalid file path {}", path.display()))
+        })?;
+        tmp_file_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_file_name);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp_path)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Parses a CoSignerConfig from a TOML string
+    pub fn parse(config_string: &str) -> Result<Self> {
+        Ok(toml::from_str(config_string)?)
+    }
+
+    /// Config of the in-process co-signer registered under name, for tests and local clusters
+    #[cfg(any(test, feature = "testing"))]
+    pub fn in_process(name: &str) -> Self {
+        Self {
+            url: format!("{}{}", IN_PROCESS_SCHEME, name),
+            ca_cert_path: None,
+            auth: CoSignerAuth::None,
+        }
+    }
+
+    /// Builds the CoSigner the two-party protocol runs against
+    pub fn connect(&self) -> Result<Arc<dyn CoSigner>> {
+        if self.is_in_process() {
+            return self.connect_in_process();
+        }
+        Ok(Arc::new(self.client_shim()?))
+    }
+
+    /// Whether this is the config of an in-process co-signer, whose shares are lost when the
+    /// process exits
+    pub fn is_in_process(&self) -> bool {
+        self.url.starts_with(IN_PROCESS_SCHEME)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn connect_in_process(&self) -> Result<Arc<dyn CoSigner>> {
+        Ok(in_process_co_signer(&self.url[IN_PROCESS_SCHEME.len()..]))
+    }
+
+    #[cfg(not(any(test, feature = "testing")))]
+    fn connect_in_process(&self) -> Result<Arc<dyn CoSigner>> {
+        Err(WalletError::LibraWalletGeneric(format!(
+            "In-process co-signer {} is only available in builds with the testing feature of \
+             co_signer_client",
+            self.url
+        )))
+    }
+
+    /// Whether the co-signer runs on this host, where plain HTTP traffic cannot be read on the way
+    fn is_loopback(&self) -> bool {
+        let url = match reqwest::Url::parse(&self.url) {
+            Ok(url) => url,
+            Err(_) => return false,
+        };
+        match url.host_str() {
+            Some("localhost") | Some("[::1]") => true,
+            Some(host) => host
+                .parse::<Ipv4Addr>()
+                .map_or(false, |address| address.is_loopback()),
+            None => false,
+        }
+    }
+
+    /// Builds the HTTP transport to this co-signer
+    pub fn client_shim(&self) -> Result<ClientShim> {
+        let plain_http = self.url.starts_with("http://") && !self.is_loopback();
+        if plain_http && self.auth != CoSignerAuth::None {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Refusing to send co-signer credentials over plain HTTP to {}",
+                self.url
+            )));
+        }
+
+        let mut builder = reqwest::Client::builder();
+        if let Some(ca_cert_path) = &self.ca_cert_path {
+            for certificate in Self::load_certificates(ca_cert_path)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        match &self.auth {
+            CoSignerAuth::None => (),
+            CoSignerAuth::BearerToken { token } => {
+                let mut headers = HeaderMap::new();
+                let value = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|_| {
+                    WalletError::LibraWalletGeneric("Invalid co-signer bearer token".to_string())
+                })?;
+                headers.insert(AUTHORIZATION, value);
+                builder = builder.default_headers(headers);
+            }
+            CoSignerAuth::ClientCertificate {
+                pkcs12_path,
+                password,
+            } => {
+                let der = fs::read(pkcs12_path)?;
+                builder = builder.identity(Identity::from_pkcs12_der(&der, password)?);
+            }
+        }
+
+        Ok(ClientShim {
+            client: builder.build()?,
+            endpoint: self.url.clone(),
+        })
+    }
+
+    /// Loads every certificate of a PEM bundle
+    fn load_certificates(path: &Path) -> Result<Vec<Certificate>> {
+        let bundle = fs::read_to_string(path)?;
+        let mut certificates = vec![];
+        for pem in bundle.split_terminator(PEM_CERTIFICATE_END) {
+            if pem.trim().is_empty() {
+                continue;
+            }
+            let pem = format!("{}{}\n", pem.trim(), PEM_CERTIFICATE_END);
+            certificates.push(Certificate::from_pem(pem.as_bytes())?);
+        }
+        if certificates.is_empty() {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "No certificate found in {:?}",
+                path
+            )));
+        }
+        Ok(certificates)
+    }
+}
+
+/// A co-signer taking part in threshold keys, under the name it is chosen by when signing.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct NamedCoSigner {
+    /// Name of the co-signer, e.g. `server` or `cold`
+    pub name: String,
+    /// Identity key of the co-signer, hex encoded, as logged by the co-signer at startup. Key
+    /// generation fails unless the commitment of the co-signer is signed by this key.
+    pub identity_key: String,
+    /// Endpoint of the co-signer
+    #[serde(flatten)]
+    pub config: CoSignerConfig,
+}
+
+impl NamedCoSigner {
+    /// The in-process co-signer registered under co_signer, named name
+    #[cfg(any(test, feature = "testing"))]
+    pub fn in_process(name: &str, co_signer: &str) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            identity_key: co_signer_protocol::threshold::encode_identity_key(
+                &in_process_co_signer(co_signer).identity_key(),
+            )
+            .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?,
+            config: CoSignerConfig::in_process(co_signer),
+        })
+    }
+}
+
+/// Participants of new threshold keys: the client and the listed co-signers, any threshold of
+/// which sign together.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ThresholdConfig {
+    /// Number of participants needed to sign, the client included
+    pub threshold: u64,
+    /// Co-signers holding a share, in participant order
+    pub co_signers: Vec<NamedCoSigner>,
+}
+
+impl ThresholdConfig {
+    /// Reads a ThresholdConfig from a TOML file
+    pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    /// Parses a ThresholdConfig from a TOML string, checking that co-signer names and identity
+    /// keys are unique
+    pub fn parse(config_string: &str) -> Result<Self> {
+        let config: Self = toml::from_str(config_string)?;
+        let identity_keys = config.identity_keys()?;
+        for (i, co_signer) in config.co_signers.iter().enumerate() {
+            if config.co_signers[..i]
+                .iter()
+                .any(|other| other.name == co_signer.name)
+            {
+                return Err(WalletError::LibraWalletGeneric(format!(
+                    "Co-signer {} is listed twice",
+                    co_signer.name
+                )));
+            }
+            if identity_keys[..i].contains(&identity_keys[i]) {
+                return Err(WalletError::LibraWalletGeneric(format!(
+                    "Co-signer {} has the identity key of another co-signer",
+                    co_signer.name
+                )));
+            }
+        }
+        config
+            .parameters()
+            .validate()
+            .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?;
+        Ok(config)
+    }
+
+    /// Identity keys of the co-signers, in participant order
+    pub fn identity_keys(&self) -> Result<Vec<GE>> {
+        self.co_signers
+            .iter()
+            .map(|co_signer| {
+                decode_identity_key(&co_signer.identity_key).map_err(|e| {
+                    WalletError::LibraWalletGeneric(format!(
+                        "Invalid identity key of co-signer {}: {}",
+                        co_signer.name, e
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Threshold parameters of the keys generated with this config
+    pub fn parameters(&self) -> ThresholdParameters {
+        ThresholdParameters {
+            threshold: self.threshold,
+            participants: self.co_signers.len() as u64 + 1,
+        }
+    }
+}
+
+/// HTTP transport to a co-signer, built from a CoSignerConfig.
+#[derive(Clone, Debug)]
+pub struct ClientShim {
+    client: reqwest::Client,
+    endpoint: String,
+}
+
+impl ClientShim {
+    /// Posts a JSON request to the co-signer and decodes its JSON answer. Transport failures and
+    /// server errors are reported as CoSignerUnreachable, requests refused by the co-signer
+    /// policy as CoSignerRejected and any other failure as CoSignerProtocol.
+    pub(crate) fn post<T, R>(&self, path: &str, body: &T) -> Result<R>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let url = format!("{}/{}", self.endpoint.trim_end_matches('/'), path);
+        let mut response = self
+            .client
+            .post(&url)
+            .json(body)
+            .send()
+            .map_err(|e| WalletError::CoSignerUnreachable(format!("{}: {}", url, e)))?;
+        let status = response.status();
+        if status.is_success() {
+            return response.json().map_err(|e| {
+                WalletError::CoSignerProtocol(format!("Malformed answer from {}: {}", url, e))
+            });
+        }
+
+        let reason = response
+            .json::<ErrorResponse>()
+            .map(|error| error.reason)
+            .unwrap_or_else(|_| status.to_string());
+        Err(error_from_status(&url, status, reason))
+    }
+}
+
+/// Classifies an error answer of the co-signer: server errors are transient, refusals of the
+/// co-signer policy and any other client error are not.
+fn error_from_status(url: &str, status: StatusCode, reason: String) -> WalletError {
+    if status.is_server_error() {
+        WalletError::CoSignerUnreachable(format!("{}: {}", url, reason))
+    } else if status == StatusCode::FORBIDDEN {
+        WalletError::CoSignerRejected(reason)
+    } else {
+        WalletError::CoSignerProtocol(format!("{}: {}", url, reason))
+    }
+}
+
+impl CoSigner for ClientShim {
+    fn keygen(&self, request: KeyGenRequest) -> Result<KeyGenResponse> {
+        self.post("eddsa/keygen", &request)
+    }
+
+    fn sign_first(&self, id: &str, request: SignFirstRequest) -> Result<SignFirstResponse> {
+        self.post(&format!("eddsa/sign/{}/first", id), &request)
+    }
+
+    fn sign_second(&self, id: &str, request: SignSecondRequest) -> Result<SignSecondResponse> {
+        self.post(&format!("eddsa/sign/{}/second", id), &request)
+    }
+
+    fn refresh(&self, id: &str, request: RefreshRequest) -> Result<RefreshResponse> {
+        self.post(&format!("eddsa/refresh/{}", id), &request)
+    }
+
+    fn confirm_refresh(
+        &self,
+        id: &str,
+        request: RefreshConfirmRequest,
+    ) -> Result<RefreshConfirmResponse> {
+        self.post(&format!("eddsa/refresh/{}/confirm", id), &request)
+    }
+
+    fn confirm_rotation(
+        &self,
+        id: &str,
+        request: RotationConfirmRequest,
+    ) -> Result<RotationConfirmResponse> {
+        self.post(&format!("eddsa/rotate/{}/confirm", id), &request)
+    }
+
+    fn threshold_keygen_commit(
+        &self,
+        request: ThresholdKeyGenCommitRequest,
+    ) -> Result<ThresholdKeyGenCommitResponse> {
+        self.post("eddsa/threshold/keygen/commit", &request)
+    }
+
+    fn threshold_keygen_share(
+        &self,
+        request: ThresholdKeyGenShareRequest,
+    ) -> Result<ThresholdKeyGenShareResponse> {
+        self.post("eddsa/threshold/keygen/share", &request)
+    }
+
+    fn threshold_keygen_complete(
+        &self,
+        request: ThresholdKeyGenCompleteRequest,
+    ) -> Result<ThresholdKeyGenCompleteResponse> {
+        self.post("eddsa/threshold/keygen/complete", &request)
+    }
+
+    fn threshold_sign_commit(
+        &self,
+        id: &str,
+        request: ThresholdSignCommitRequest,
+    ) -> Result<ThresholdSignCommitResponse> {
+        self.post(&format!("eddsa/threshold/sign/{}/commit", id), &request)
+    }
+
+    fn threshold_sign_reveal(
+        &self,
+        id: &str,
+        request: ThresholdSignRevealRequest,
+    ) -> Result<ThresholdSignRevealResponse> {
+        self.post(&format!("eddsa/threshold/sign/{}/reveal", id), &request)
+    }
+
+    fn threshold_sign(
+        &self,
+        id: &str,
+        request: ThresholdSignRequest,
+    ) -> Result<ThresholdSignResponse> {
+        self.post(&format!("eddsa/threshold/sign/{}", id), &request)
+    }
+}
+
+/// Runs a co-signer call, retrying with exponential backoff as long as the co-signer is
+/// unreachable. Any other failure is reported right away.
+pub(crate) fn call_with_retries<T, F>(operation: &str, call: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    call_with_backoff(operation, Duration::from_millis(INITIAL_BACKOFF_MS), call)
+}
+
+fn call_with_backoff<T, F>(operation: &str, mut backoff: Duration, mut call: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    let mut attempt = 1;
+    loop {
+        match call() {
+            Ok(value) => return Ok(value),
+            Err(WalletError::CoSignerUnreachable(reason)) if attempt < MAX_CO_SIGNER_ATTEMPTS => {
+                warn!(
+                    "{} failed (attempt {}/{}): {}, retrying in {:?}",
+                    operation, attempt, MAX_CO_SIGNER_ATTEMPTS, reason, backoff
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[test]
+fn test_co_signer_config_roundtrip() {
+    let config = CoSignerConfig {
+        url: "https://cosigner.example.com:8000".to_string(),
+        ca_cert_path: Some(PathBuf::from("/etc/libra/cosigner-ca.pem")),
+        auth: CoSignerAuth::BearerToken {
+            token: "secret".to_string(),
+        },
+    };
+    let serialized = toml::to_string(&config).unwrap();
+    assert_eq!(CoSignerConfig::parse(&serialized).unwrap(), config);
+    assert_eq!(
+        CoSignerConfig::parse("url = \"http://localhost:8000\"").unwrap(),
+        CoSignerConfig::default()
+    );
+}
+
+#[test]
+fn test_save_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("co_signer.toml");
+    let config = CoSignerConfig {
+        url: "https://cosigner.example.com:8000".to_string(),
+        ca_cert_path: None,
+        auth: CoSignerAuth::ClientCertificate {
+            pkcs12_path: PathBuf::from("client.p12"),
+            password: "secret".to_string(),
+        },
+    };
+    config.save_config(&path).unwrap();
+    assert_eq!(CoSignerConfig::load_config(&path).unwrap(), config);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Neither the password nor a bearer token shows in logs.
+    assert!(!format!("{:?}", config).contains("secret"));
+    let auth = CoSignerAuth::BearerToken {
+        token: "secret".to_string(),
+    };
+    assert!(!format!("{:?}", auth).contains("secret"));
+}
+
+#[test]
+fn test_credentials_over_plain_http() {
+    let config = |url: &str| CoSignerConfig {
+        url: url.to_string(),
+        ca_cert_path: None,
+        auth: CoSignerAuth::BearerToken {
+            token: "secret".to_string(),
+        },
+    };
+    assert!(config("http://cosigner.example.com:8000")
+        .client_shim()
+        .is_err());
+    assert!(config("http://localhost.example.com:8000")
+        .client_shim()
+        .is_err());
+    for url in &[
+        "https://cosigner.example.com:8000",
+        "http://localhost:8000",
+        "http://127.0.0.1:8000",
+        "http://[::1]:8000",
+    ] {
+        config(url).client_shim().unwrap();
+    }
+}
+
+#[test]
+fn test_threshold_config() {
+    use co_signer_protocol::{threshold::encode_identity_key, KeyPair};
+
+    let server_key = encode_identity_key(&KeyPair::create().public_key).unwrap();
+    let cold_key = encode_identity_key(&KeyPair::create().public_key).unwrap();
+    let config_string = |cold_key: &str| {
+        format!(
+            r#"
+            threshold = 2
+            [[co_signers]]
+            name = "server"
+            identity_key = "{}"
+            url = "https://cosigner.example.com:8000"
+            [co_signers.auth]
+            type = "bearer_token"
+            token = "secret"
+            [[co_signers]]
+            name = "cold"
+            identity_key = "{}"
+            url = "http://localhost:8001"
+            "#,
+            server_key, cold_key
+        )
+    };
+    let config = ThresholdConfig::parse(&config_string(&cold_key)).unwrap();
+    assert_eq!(
+        config.parameters(),
+        ThresholdParameters {
+            threshold: 2,
+            participants: 3
+        }
+    );
+    assert_eq!(config.co_signers[1].config.url, "http://localhost:8001");
+    assert_eq!(
+        config.co_signers[0].config.auth,
+        CoSignerAuth::BearerToken {
+            token: "secret".to_string()
+        }
+    );
+    assert_eq!(
+        config.identity_keys().unwrap()[1],
+        decode_identity_key(&cold_key).unwrap()
+    );
+    // Identity keys must be valid and distinct
+    assert!(ThresholdConfig::parse(&config_string("00")).is_err());
+    assert!(ThresholdConfig::parse(&config_string(&server_key)).is_err());
+    // 3 participants are needed out of the client and a single co-signer
+    let invalid = format!(
+        "threshold = 3\n[[co_signers]]\nname = \"server\"\nidentity_key = \"{}\"\nurl = \"\"",
+        server_key
+    );
+    assert!(ThresholdConfig::parse(&invalid).is_err());
+}
+
+#[test]
+fn test_error_from_status() {
+    let url = "https://cosigner.example.com:8000/eddsa/keygen";
+    for status in &[
+        StatusCode::INTERNAL_SERVER_ERROR,
+        StatusCode::SERVICE_UNAVAILABLE,
+    ] {
+        match error_from_status(url, *status, "down".to_string()) {
+            WalletError::CoSignerUnreachable(_) => (),
+            err => panic!("{} should be transient, got {}", status, err),
+        }
+    }
+    match error_from_status(url, StatusCode::FORBIDDEN, "over limit".to_string()) {
+        WalletError::CoSignerRejected(reason) => assert_eq!(reason, "over limit"),
+        err => panic!("403 should be a rejection, got {}", err),
+    }
+    for status in &[
+        StatusCode::BAD_REQUEST,
+        StatusCode::UNAUTHORIZED,
+        StatusCode::NOT_FOUND,
+    ] {
+        match error_from_status(url, *status, "bad".to_string()) {
+            WalletError::CoSignerProtocol(_) => (),
+            err => panic!("{} should be fatal, got {}", status, err),
+        }
+    }
+}
+
+#[test]
+fn test_unreachable_co_signer() {
+    // Nothing listens on port 1.
+    let config = CoSignerConfig {
+        url: "http://127.0.0.1:1".to_string(),
+        ..CoSignerConfig::default()
+    };
+    let result: Result<KeyGenResponse> = config.client_shim().unwrap().post("eddsa/keygen", &());
+    match result {
+        Err(WalletError::CoSignerUnreachable(_)) => (),
+        other => panic!("Expected CoSignerUnreachable, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn test_call_with_backoff() {
+    // Transient failures are retried until the call succeeds.
+    let mut calls = 0;
+    let result = call_with_backoff("test", Duration::from_millis(0), || {
+        calls += 1;
+        if calls < MAX_CO_SIGNER_ATTEMPTS {
+            Err(WalletError::CoSignerUnreachable("down".to_string()))
+        } else {
+            Ok(calls)
+        }
+    });
+    assert_eq!(result.unwrap(), MAX_CO_SIGNER_ATTEMPTS);
+
+    // ... but not forever.
+    let mut calls = 0;
+    let result: Result<()> = call_with_backoff("test", Duration::from_millis(0), || {
+        calls += 1;
+        Err(WalletError::CoSignerUnreachable("down".to_string()))
+    });
+    assert!(result.is_err());
+    assert_eq!(calls, MAX_CO_SIGNER_ATTEMPTS);
+
+    // Fatal failures are reported right away.
+    for err in vec![
+        WalletError::CoSignerRejected("over limit".to_string()),
+        WalletError::CoSignerProtocol("bad".to_string()),
+        WalletError::InvalidSignature("bad".to_string()),
+    ] {
+        let mut calls = 0;
+        let mut err = Some(err);
+        let result: Result<()> = call_with_backoff("test", Duration::from_millis(0), || {
+            calls += 1;
+            Err(err.take().unwrap())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+}
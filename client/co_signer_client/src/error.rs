@@ -0,0 +1,130 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use failure;
+use libra_crypto::hkdf::HkdfError;
+use std::{convert, error::Error, fmt, io};
+
+/// We define our own Result type in order to not have to import the libra/common/failture_ext
+pub type Result<T> = ::std::result::Result<T, WalletError>;
+
+/// Libra Wallet Error is a convenience enum for generating arbitarary WalletErrors. Besides the
+/// LibraWalletGeneric error, the two-party signing path reports co-signer failures through
+/// dedicated variants so that callers can tell an outage from a misbehaving co-signer
+pub enum WalletError {
+    /// generic error message
+    LibraWalletGeneric(String),
+    /// the co-signer could not be reached, even after retrying
+    CoSignerUnreachable(String),
+    /// the co-signer answered, but the two-party protocol failed
+    CoSignerProtocol(String),
+    /// the co-signer refused to sign, e.g. because the transaction breaks its spending policy
+    CoSignerRejected(String),
+    /// the two-party protocol produced a signature that does not verify
+    InvalidSignature(String),
+    /// the wallet is watch-only and holds no key share to sign with
+    WatchOnly(String),
+    /// the co-signer committed to a nonce it already used in another signing session
+    NonceReuse(String),
+}
+
+impl Error for WalletError {
+    fn description(&self) -> &str {
+        match *self {
+            WalletError::LibraWalletGeneric(ref s) => s,
+            WalletError::CoSignerUnreachable(ref s) => s,
+            WalletError::CoSignerProtocol(ref s) => s,
+            WalletError::CoSignerRejected(ref s) => s,
+            WalletError::InvalidSignature(ref s) => s,
+            WalletError::WatchOnly(ref s) => s,
+            WalletError::NonceReuse(ref s) => s,
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        None
+    }
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WalletError::LibraWalletGeneric(ref s) => write!(f, "LibraWalletGeneric: {}", s),
+            WalletError::CoSignerUnreachable(ref s) => write!(f, "CoSignerUnreachable: {}", s),
+            WalletError::CoSignerProtocol(ref s) => write!(f, "CoSignerProtocol: {}", s),
+            WalletError::CoSignerRejected(ref s) => write!(f, "CoSignerRejected: {}", s),
+            WalletError::InvalidSignature(ref s) => write!(f, "InvalidSignature: {}", s),
+            WalletError::WatchOnly(ref s) => write!(f, "WatchOnly: {}", s),
+            WalletError::NonceReuse(ref s) => write!(f, "NonceReuse: {}", s),
+        }
+    }
+}
+
+impl fmt::Debug for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (self as &fmt::Display).fmt(f)
+    }
+}
+
+impl convert::From<WalletError> for io::Error {
+    fn from(_err: WalletError) -> io::Error {
+        match _err {
+            WalletError::CoSignerUnreachable(s) => io::Error::new(io::ErrorKind::NotConnected, s),
+            WalletError::CoSignerRejected(s) | WalletError::WatchOnly(s) => {
+                io::Error::new(io::ErrorKind::PermissionDenied, s)
+            }
+            WalletError::LibraWalletGeneric(s)
+            | WalletError::CoSignerProtocol(s)
+            | WalletError::InvalidSignature(s)
+            | WalletError::NonceReuse(s) => io::Error::new(io::ErrorKind::Other, s),
+        }
+    }
+}
+
+impl convert::From<io::Error> for WalletError {
+    fn from(err: io::Error) -> WalletError {
+        WalletError::LibraWalletGeneric(err.description().to_string())
+    }
+}
+
+impl convert::From<failure::prelude::Error> for WalletError {
+    fn from(err: failure::prelude::Error) -> WalletError {
+        WalletError::LibraWalletGeneric(format!("{}", err))
+    }
+}
+
+impl convert::From<protobuf::error::ProtobufError> for WalletError {
+    fn from(err: protobuf::error::ProtobufError) -> WalletError {
+        WalletError::LibraWalletGeneric(err.description().to_string())
+    }
+}
+
+impl convert::From<ed25519_dalek::SignatureError> for WalletError {
+    fn from(err: ed25519_dalek::SignatureError) -> WalletError {
+        WalletError::LibraWalletGeneric(format!("{}", err))
+    }
+}
+
+impl convert::From<HkdfError> for WalletError {
+    fn from(err: HkdfError) -> WalletError {
+        WalletError::LibraWalletGeneric(format!("{}", err))
+    }
+}
+
+impl convert::From<reqwest::Error> for WalletError {
+    fn from(err: reqwest::Error) -> WalletError {
+        WalletError::LibraWalletGeneric(format!("{}", err))
+    }
+}
+
+impl convert::From<toml::de::Error> for WalletError {
+    fn from(err: toml::de::Error) -> WalletError {
+        WalletError::LibraWalletGeneric(format!("{}", err))
+    }
+}
+
+impl convert::From<toml::ser::Error> for WalletError {
+    fn from(err: toml::ser::Error) -> WalletError {
+        WalletError::LibraWalletGeneric(format!("{}", err))
+    }
+}
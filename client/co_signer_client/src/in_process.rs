@@ -0,0 +1,484 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A co-signer running the server side of the two-party protocol inside the wallet process.
+//!
+//! It holds its shares in memory and signs any transaction of its keys, without a spending
+//! policy, which makes it suitable for tests and local clusters only. Co-signers are registered
+//! by name, so that every CoSignerConfig whose url is `in-process://<name>` reaches the same
+//! instance for the lifetime of the process; the keys generated with it are lost on exit. It
+//! takes part in threshold keys as well, several in-process co-signers standing for the
+//! co-signers of a ThresholdConfig. In-process co-signers pin the identity keys of each other.
+
+use crate::{
+    co_signer::CoSigner,
+    error::{Result, WalletError},
+};
+use co_signer_protocol::{
+    aggregate_public_keys, derive_child, refresh_key_pair, signing_message, test_com,
+    threshold::{
+        authenticate_participants, KeyGenState, ParticipantCommitment, SignerSession,
+        ThresholdKeyGenCommitRequest, ThresholdKeyGenCommitResponse,
+        ThresholdKeyGenCompleteRequest, ThresholdKeyGenCompleteResponse,
+        ThresholdKeyGenShareRequest, ThresholdKeyGenShareResponse, ThresholdShare,
+        ThresholdSignCommitRequest, ThresholdSignCommitResponse, ThresholdSignRequest,
+        ThresholdSignResponse, ThresholdSignRevealRequest, ThresholdSignRevealResponse,
+        CLIENT_PARTICIPANT,
+    },
+    ECScalar, EphemeralKey, KeyAgg, KeyGenRequest, KeyGenResponse, KeyPair, RefreshConfirmRequest,
+    RefreshConfirmResponse, RefreshRequest, RefreshResponse, RotationConfirmRequest,
+    RotationConfirmResponse, SignFirstMsg, SignFirstRequest, SignFirstResponse, SignSecondMsg,
+    SignSecondRequest, SignSecondResponse, Signature, CHAIN_CODE_LENGTH, FE, GE, SERVER_INDEX,
+};
+use lazy_static::lazy_static;
+use libra_crypto::HashValue;
+use rand::{rngs::EntropyRng, RngCore};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+lazy_static! {
+    static ref REGISTRY: Mutex<HashMap<String, Arc<InProcessCoSigner>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Returns the in-process co-signer registered under name, creating it on first use.
+pub fn in_process_co_signer(name: &str) -> Arc<InProcessCoSigner> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .entry(name.to_string())
+        .or_insert_with(|| Arc::new(InProcessCoSigner::default()))
+        .clone()
+}
+
+/// Identity keys of all the in-process co-signers.
+fn in_process_identity_keys() -> Vec<GE> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .values()
+        .map(|co_signer| co_signer.identity_key())
+        .collect()
+}
+
+struct ServerShare {
+    key_pair: KeyPair,
+    key_agg: KeyAgg,
+    chain_code: Vec<u8>,
+    epoch: u64,
+    pending_refresh: Option<(KeyPair, FE)>,
+    message_signing: bool,
+}
+
+struct SignSession {
+    key_id: String,
+    message: HashValue,
+    key_pair: KeyPair,
+    key_agg: KeyAgg,
+    client_commitment: SignFirstMsg,
+    ephemeral_key: EphemeralKey,
+    server_reveal: SignSecondMsg,
+}
+
+struct ThresholdKeyGenSession {
+    state: KeyGenState,
+    participants: Option<Vec<ParticipantCommitment>>,
+}
+
+struct ThresholdSignSession {
+    key_id: String,
+    session: SignerSession,
+}
+
+/// Server side of the two-party protocol, with its shares held in memory.
+pub struct InProcessCoSigner {
+    identity: KeyPair,
+    shares: Mutex<HashMap<String, ServerShare>>,
+    sessions: Mutex<HashMap<String, SignSession>>,
+    threshold_keygen_sessions: Mutex<HashMap<String, ThresholdKeyGenSession>>,
+    threshold_shares: Mutex<HashMap<String, ThresholdShare>>,
+    threshold_sessions: Mutex<HashMap<String, ThresholdSignSession>>,
+}
+
+impl fmt::Debug for InProcessCoSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "InProcessCoSigner {{ keys: {} }}",
+            self.shares.lock().unwrap().len()
+        )
+    }
+}
+
+impl Default for InProcessCoSigner {
+    fn default() -> Self {
+        Self {
+            identity: KeyPair::create(),
+            shares: Mutex::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+            threshold_keygen_sessions: Mutex::new(HashMap::new()),
+            threshold_shares: Mutex::new(HashMap::new()),
+            threshold_sessions: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl InProcessCoSigner {
+    /// Identity key the co-signer signs its threshold key generation commitments with.
+    pub fn identity_key(&self) -> GE {
+        self.identity.public_key.clone()
+    }
+
+    /// Runs f on the share of key_id at epoch. Reaching the epoch of a pending refresh confirms
+    /// it, any other epoch is stale.
+    fn with_share<T, F>(&self, key_id: &str, epoch: u64, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut ServerShare) -> Result<T>,
+    {
+        let mut shares = self.shares.lock().unwrap();
+        let share = shares
+            .get_mut(key_id)
+            .ok_or_else(|| WalletError::CoSignerProtocol(format!("Unknown key {}", key_id)))?;
+        if epoch == share.epoch + 1 {
+            if let Some((key_pair, coefficient)) = share.pending_refresh.take() {
+                share.key_pair = key_pair;
+                share.key_agg.hash = coefficient;
+                share.epoch = epoch;
+            }
+        }
+        if epoch != share.epoch {
+            return Err(WalletError::CoSignerProtocol(format!(
+                "Share of key {} at epoch {} is stale, current epoch is {}",
+                key_id, epoch, share.epoch
+            )));
+        }
+        f(share)
+    }
+}
+
+impl CoSigner for InProcessCoSigner {
+    fn keygen(&self, request: KeyGenRequest) -> Result<KeyGenResponse> {
+        let key_pair = KeyPair::create();
+        let key_agg = aggregate_public_keys(
+            &key_pair.public_key,
+            &request.client_public_key,
+            SERVER_INDEX,
+        );
+        let mut chain_code = vec![0u8; CHAIN_CODE_LENGTH];
+        EntropyRng::new().fill_bytes(&mut chain_code);
+        let server_public_key = key_pair.public_key.clone();
+        let id = random_id();
+        self.shares.lock().unwrap().insert(
+            id.clone(),
+            ServerShare {
+                key_pair,
+                key_agg,
+                chain_code: chain_code.clone(),
+                epoch: 0,
+                pending_refresh: None,
+                message_signing: request.message_signing,
+            },
+        );
+        Ok(KeyGenResponse {
+            id,
+            server_public_key,
+            chain_code,
+        })
+    }
+
+    fn sign_first(&self, id: &str, request: SignFirstRequest) -> Result<SignFirstResponse> {
+        let (key_pair, key_agg, message) = self.with_share(id, request.epoch, |share| {
+            // Like the co-signer, message signing keys only sign prehashed messages and other
+            // keys only transactions.
+            let message = match (request.message_hash, share.message_signing) {
+                (Some(message), true) => message,
+                (None, false) => signing_message(&request.raw_txn_bytes),
+                (Some(_), false) => {
+                    return Err(WalletError::CoSignerProtocol(format!(
+                        "Key {} only signs transactions",
+                        id
+                    )));
+                }
+                (None, true) => {
+                    return Err(WalletError::CoSignerProtocol(format!(
+                        "Key {} only signs prehashed messages",
+                        id
+                    )));
+                }
+            };
+            let (key_pair, key_agg) = match request.child_number {
+                Some(child_number) => derive_child(
+                    &share.key_pair,
+                    &share.key_agg,
+                    &share.chain_code,
+                    child_number,
+                    false,
+                )
+                .map_err(|e| WalletError::CoSignerProtocol(format!("{}", e)))?,
+                None => (share.key_pair.clone(), share.key_agg.clone()),
+            };
+            Ok((key_pair, key_agg, message))
+        })?;
+        let (ephemeral_key, server_commitment, server_reveal) =
+            Signature::create_ephemeral_key_and_commit(&key_pair, message.as_ref());
+        let session_id = random_id();
+        self.sessions.lock().unwrap().insert(
+            session_id.clone(),
+            SignSession {
+                key_id: id.to_string(),
+                message,
+                key_pair,
+                key_agg,
+                client_commitment: request.client_commitment,
+                ephemeral_key,
+                server_reveal,
+            },
+        );
+        Ok(SignFirstResponse {
+            session_id,
+            server_commitment,
+        })
+    }
+
+    fn sign_second(&self, id: &str, request: SignSecondRequest) -> Result<SignSecondResponse> {
+        let session = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(&request.session_id)
+            .filter(|session| session.key_id == id)
+            .ok_or_else(|| {
+                WalletError::CoSignerProtocol(format!("Unknown session {}", request.session_id))
+            })?;
+        let client_reveal = request.client_reveal;
+        if !test_com(
+            &client_reveal.R,
+            &client_reveal.blind_factor,
+            &session.client_commitment.commitment,
+        ) {
+            return Err(WalletError::CoSignerProtocol(
+                "Client reveal does not open its commitment".to_string(),
+            ));
+        }
+        let r_tot = Signature::get_R_tot(vec![session.server_reveal.R.clone(), client_reveal.R]);
+        let k = Signature::k(&r_tot, &session.key_agg.apk, session.message.as_ref());
+        let server_partial_signature = Signature::partial_sign(
+            &session.ephemeral_key.r,
+            &session.key_pair,
+            &k,
+            &session.key_agg.hash,
+            &r_tot,
+        );
+        Ok(SignSecondResponse {
+            server_reveal: session.server_reveal,
+            server_partial_signature,
+        })
+    }
+
+    fn refresh(&self, id: &str, request: RefreshRequest) -> Result<RefreshResponse> {
+        self.with_share(id, request.epoch, |share| {
+            let server_delta = FE::new_random();
+            let delta = request.client_delta.add(&server_delta.get_element());
+            share.pending_refresh = Some(refresh_key_pair(
+                &share.key_pair,
+                &share.key_agg.hash,
+                &delta,
+                true,
+            ));
+            Ok(RefreshResponse {
+                epoch: share.epoch + 1,
+                server_delta,
+            })
+        })
+    }
+
+    fn confirm_refresh(
+        &self,
+        id: &str,
+        request: RefreshConfirmRequest,
+    ) -> Result<RefreshConfirmResponse> {
+        self.with_share(id, request.epoch, |share| {
+            Ok(RefreshConfirmResponse { epoch: share.epoch })
+        })
+    }
+
+    fn confirm_rotation(
+        &self,
+        id: &str,
+        request: RotationConfirmRequest,
+    ) -> Result<RotationConfirmResponse> {
+        // Keys are not bound to accounts here, any key signs for any sender.
+        if !self.shares.lock().unwrap().contains_key(id) {
+            return Err(WalletError::CoSignerProtocol(format!("Unknown key {}", id)));
+        }
+        Ok(RotationConfirmResponse {
+            address: request.address,
+        })
+    }
+
+    fn threshold_keygen_commit(
+        &self,
+        request: ThresholdKeyGenCommitRequest,
+    ) -> Result<ThresholdKeyGenCommitResponse> {
+        if request.index == CLIENT_PARTICIPANT {
+            return Err(WalletError::CoSignerProtocol(
+                "A co-signer cannot take the index of the client".to_string(),
+            ));
+        }
+        let state = KeyGenState::new(request.parameters, request.index).map_err(protocol_error)?;
+        let commitment = state.commitment();
+        let identity = commitment.sign_identity(request.parameters, &self.identity);
+        let session_id = random_id();
+        self.threshold_keygen_sessions.lock().unwrap().insert(
+            session_id.clone(),
+            ThresholdKeyGenSession {
+                state,
+                participants: None,
+            },
+        );
+        Ok(ThresholdKeyGenCommitResponse {
+            session_id,
+            commitment,
+            identity,
+        })
+    }
+
+    fn threshold_keygen_share(
+        &self,
+        request: ThresholdKeyGenShareRequest,
+    ) -> Result<ThresholdKeyGenShareResponse> {
+        let trusted_keys = in_process_identity_keys();
+        let mut sessions = self.threshold_keygen_sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(&request.session_id)
+            .filter(|session| session.participants.is_none())
+            .ok_or_else(|| {
+                WalletError::CoSignerProtocol(format!("Unknown session {}", request.session_id))
+            })?;
+        authenticate_participants(
+            session.state.parameters(),
+            &request.participants,
+            &request.identities,
+            &trusted_keys,
+        )
+        .map_err(protocol_error)?;
+        let sub_shares = session
+            .state
+            .sub_shares(&request.participants)
+            .map_err(protocol_error)?;
+        session.participants = Some(request.participants);
+        Ok(ThresholdKeyGenShareResponse { sub_shares })
+    }
+
+    fn threshold_keygen_complete(
+        &self,
+        request: ThresholdKeyGenCompleteRequest,
+    ) -> Result<ThresholdKeyGenCompleteResponse> {
+        let (state, participants) = self
+            .threshold_keygen_sessions
+            .lock()
+            .unwrap()
+            .remove(&request.session_id)
+            .and_then(|session| Some((session.state, session.participants?)))
+            .ok_or_else(|| {
+                WalletError::CoSignerProtocol(format!("Unknown session {}", request.session_id))
+            })?;
+        let share = state
+            .complete(&participants, &request.sub_shares)
+            .map_err(protocol_error)?;
+        let id = random_id();
+        let response = ThresholdKeyGenCompleteResponse {
+            id: id.clone(),
+            public_key: share.public_key().clone(),
+            share_public_key: share.participant_public_key(share.index),
+        };
+        self.threshold_shares.lock().unwrap().insert(id, share);
+        Ok(response)
+    }
+
+    fn threshold_sign_commit(
+        &self,
+        id: &str,
+        request: ThresholdSignCommitRequest,
+    ) -> Result<ThresholdSignCommitResponse> {
+        let share = self
+            .threshold_shares
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| WalletError::CoSignerProtocol(format!("Unknown key {}", id)))?;
+        let session = SignerSession::new(
+            &share,
+            request.signers,
+            signing_message(&request.raw_txn_bytes),
+        )
+        .map_err(protocol_error)?;
+        let commitment = session.commitment();
+        let session_id = random_id();
+        self.threshold_sessions.lock().unwrap().insert(
+            session_id.clone(),
+            ThresholdSignSession {
+                key_id: id.to_string(),
+                session,
+            },
+        );
+        Ok(ThresholdSignCommitResponse {
+            session_id,
+            commitment,
+        })
+    }
+
+    fn threshold_sign_reveal(
+        &self,
+        id: &str,
+        request: ThresholdSignRevealRequest,
+    ) -> Result<ThresholdSignRevealResponse> {
+        let mut sessions = self.threshold_sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(&request.session_id)
+            .filter(|session| session.key_id == id)
+            .ok_or_else(|| {
+                WalletError::CoSignerProtocol(format!("Unknown session {}", request.session_id))
+            })?;
+        let reveal = session
+            .session
+            .reveal(request.commitments)
+            .map_err(protocol_error)?;
+        Ok(ThresholdSignRevealResponse { reveal })
+    }
+
+    fn threshold_sign(
+        &self,
+        id: &str,
+        request: ThresholdSignRequest,
+    ) -> Result<ThresholdSignResponse> {
+        let session = self
+            .threshold_sessions
+            .lock()
+            .unwrap()
+            .remove(&request.session_id)
+            .filter(|session| session.key_id == id)
+            .ok_or_else(|| {
+                WalletError::CoSignerProtocol(format!("Unknown session {}", request.session_id))
+            })?;
+        let partial_signature = session
+            .session
+            .sign(&request.reveals)
+            .map_err(protocol_error)?;
+        Ok(ThresholdSignResponse { partial_signature })
+    }
+}
+
+fn protocol_error<E: fmt::Display>(error: E) -> WalletError {
+    WalletError::CoSignerProtocol(format!("{}", error))
+}
+
+fn random_id() -> String {
+    let mut bytes = [0u8; 16];
+    EntropyRng::new().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
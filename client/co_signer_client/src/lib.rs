@@ -0,0 +1,22 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client side of the two-party protocol run with a co-signer, shared by the wallet and the
+//! secret service: the transport to the co-signer, the signing and refresh rounds, and the log of
+//! the signing sessions.
+
+/// Co-signer endpoint configuration
+pub mod co_signer;
+
+/// Error crate
+pub mod error;
+
+/// Co-signer running inside the client process
+#[cfg(any(test, feature = "testing"))]
+pub mod in_process;
+
+/// Log of the signing sessions run with co-signers
+pub mod session_log;
+
+/// Client side of the two-party signing protocol
+pub mod two_party;
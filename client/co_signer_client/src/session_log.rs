@@ -0,0 +1,178 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A local log of the signing sessions run with co-signers.
+//!
+//! Every session opened by a co-signer is recorded with its session id, the hash of the signed
+//! message, the nonce the co-signer committed to and the outcome, so that the log can be compared
+//! with the records of the co-signer. The log is also used to detect a co-signer reusing a nonce,
+//! which would leak its share, or a co-signer that was compromised into doing so. It is kept in
+//! memory and, once opened with a path, appended to that file one JSON record per line.
+
+use crate::error::{Result, WalletError};
+use libra_crypto::HashValue;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How a signing session ended.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", content = "reason", rename_all = "snake_case")]
+pub enum SessionOutcome {
+    /// The aggregated signature verifies
+    Signed,
+    /// The co-signer returned a partial signature that does not verify, or a signature that does
+    /// not verify once aggregated
+    InvalidSignature(String),
+    /// The co-signer committed to a nonce it already used
+    NonceReuse(String),
+    /// The session failed for another reason, e.g. the co-signer refused to sign
+    Failed(String),
+}
+
+/// A signing session, as seen by the wallet.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SessionRecord {
+    /// Time the session ended, in seconds since the unix epoch
+    pub time: u64,
+    /// Id of the key at the co-signer
+    pub key_id: String,
+    /// Id of the session at the co-signer
+    pub session_id: String,
+    /// Hash of the signed message, i.e. of the RawTransaction
+    pub message_hash: HashValue,
+    /// Hex encoded nonce (R) revealed by the co-signer, if it got that far
+    pub server_nonce: Option<String>,
+    /// Outcome of the session
+    pub outcome: SessionOutcome,
+}
+
+/// Log of the signing sessions of a wallet.
+#[derive(Debug, Default)]
+pub struct SessionLog {
+    path: Option<PathBuf>,
+    records: Mutex<Vec<SessionRecord>>,
+    server_nonces: Mutex<HashSet<String>>,
+}
+
+impl SessionLog {
+    /// Opens the log stored at path, creating it on first record
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut records = vec![];
+        if path.exists() {
+            for line in fs::read_to_string(&path)?.lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                records.push(serde_json::from_str(line).map_err(|e| {
+                    WalletError::LibraWalletGeneric(format!(
+                        "Malformed session log {:?}: {}",
+                        path, e
+                    ))
+                })?);
+            }
+        }
+        let server_nonces = records
+            .iter()
+            .filter_map(|record: &SessionRecord| record.server_nonce.clone())
+            .collect();
+        Ok(Self {
+            path: Some(path),
+            records: Mutex::new(records),
+            server_nonces: Mutex::new(server_nonces),
+        })
+    }
+
+    /// Registers the nonce revealed by a co-signer, failing if it was already used in another
+    /// session
+    pub fn check_server_nonce(&self, server_nonce: &str) -> Result<()> {
+        if !self
+            .server_nonces
+            .lock()
+            .unwrap()
+            .insert(server_nonce.to_string())
+        {
+            return Err(WalletError::NonceReuse(format!(
+                "Co-signer reused nonce {}",
+                server_nonce
+            )));
+        }
+        Ok(())
+    }
+
+    /// Appends a session to the log. A failure to persist the record is only reported, so that
+    /// a full disk does not prevent signing.
+    pub fn record(
+        &self,
+        key_id: &str,
+        session_id: &str,
+        message_hash: HashValue,
+        server_nonce: Option<String>,
+        outcome: SessionOutcome,
+    ) {
+        let record = SessionRecord {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default(),
+            key_id: key_id.to_string(),
+            session_id: session_id.to_string(),
+            message_hash,
+            server_nonce,
+            outcome,
+        };
+        if let Some(path) = &self.path {
+            if let Err(e) = Self::append(path, &record) {
+                warn!("Unable to write session log {:?}: {}", path, e);
+            }
+        }
+        self.records.lock().unwrap().push(record);
+    }
+
+    fn append(path: &Path, record: &SessionRecord) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// All the sessions recorded so far, oldest first
+    pub fn records(&self) -> Vec<SessionRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+#[test]
+fn test_session_log() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let path = file.into_temp_path();
+    let log = SessionLog::open(&path).unwrap();
+    log.check_server_nonce("aa").unwrap();
+    log.record(
+        "key",
+        "session",
+        HashValue::random(),
+        Some("aa".to_string()),
+        SessionOutcome::Signed,
+    );
+
+    let log = SessionLog::open(&path).unwrap();
+    assert_eq!(log.records().len(), 1);
+    assert_eq!(log.records()[0].outcome, SessionOutcome::Signed);
+    match log.check_server_nonce("aa") {
+        Err(WalletError::NonceReuse(_)) => (),
+        _ => panic!("Nonce reuse must be detected across runs"),
+    }
+    log.check_server_nonce("bb").unwrap();
+}
@@ -0,0 +1,535 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client side of the two-party EdDSA protocol run against the co-signer.
+
+use crate::{
+    co_signer::CoSigner,
+    error::{Result, WalletError},
+    session_log::{SessionLog, SessionOutcome},
+};
+use co_signer_protocol::{
+    aggregate_public_keys, refresh_key_pair, signing_message, test_com, verify, ECPoint, ECScalar,
+    KeyAgg, KeyGenRequest, KeyPair, RefreshConfirmRequest, RefreshRequest, RotationConfirmRequest,
+    SignFirstRequest, SignSecondRequest, Signature, CLIENT_INDEX, FE, GE,
+};
+use libra_crypto::HashValue;
+use types::account_address::AccountAddress;
+
+/// Generates a new two-party key, returning the client key pair, the aggregated public key, the
+/// id under which the co-signer stores its share and the chain code child keys are derived with.
+pub fn generate_key(co_signer: &dyn CoSigner) -> Result<(KeyPair, KeyAgg, String, Vec<u8>)> {
+    keygen(co_signer, false, None)
+}
+
+/// Generates a new two-party key to replace the key stored under replaces by the same
+/// co-signer, which binds it to the account of that key once confirm_rotation is called.
+pub fn generate_replacement_key(
+    co_signer: &dyn CoSigner,
+    replaces: &str,
+) -> Result<(KeyPair, KeyAgg, String, Vec<u8>)> {
+    keygen(co_signer, false, Some(replaces.to_string()))
+}
+
+/// Generates a new two-party key signing prehashed messages with sign_message instead of
+/// transactions, e.g. the consensus key of a validator.
+pub fn generate_message_key(
+    co_signer: &dyn CoSigner,
+) -> Result<(KeyPair, KeyAgg, String, Vec<u8>)> {
+    keygen(co_signer, true, None)
+}
+
+fn keygen(
+    co_signer: &dyn CoSigner,
+    message_signing: bool,
+    replaces: Option<String>,
+) -> Result<(KeyPair, KeyAgg, String, Vec<u8>)> {
+    let key_pair = KeyPair::create();
+    let response = co_signer.keygen(KeyGenRequest {
+        client_public_key: key_pair.public_key.clone(),
+        message_signing,
+        replaces,
+    })?;
+    let key_agg = aggregate_public_keys(
+        &response.server_public_key,
+        &key_pair.public_key,
+        CLIENT_INDEX,
+    );
+    Ok((key_pair, key_agg, response.id, response.chain_code))
+}
+
+/// Co-signs a protobuf encoded RawTransaction. The co-signer decodes the transaction and checks
+/// it against its spending policy, so the raw bytes are sent rather than their hash. The nonce
+/// and the partial signature of the co-signer, as well as the aggregated signature, are verified
+/// before the signature is returned, and the session is recorded in session_log.
+pub fn sign(
+    co_signer: &dyn CoSigner,
+    raw_txn_bytes: &[u8],
+    key_pair: &KeyPair,
+    key_agg: &KeyAgg,
+    id: &str,
+    epoch: u64,
+    child_number: Option<u64>,
+    session_log: &SessionLog,
+) -> Result<Signature> {
+    co_sign(
+        co_signer,
+        signing_message(raw_txn_bytes),
+        raw_txn_bytes.to_vec(),
+        key_pair,
+        key_agg,
+        id,
+        epoch,
+        child_number,
+        session_log,
+    )
+}
+
+/// Co-signs a prehashed message with a key generated by generate_message_key. The co-signer has
+/// no policy to check such messages against, and signs them as they come.
+pub fn sign_message(
+    co_signer: &dyn CoSigner,
+    message: HashValue,
+    key_pair: &KeyPair,
+    key_agg: &KeyAgg,
+    id: &str,
+    epoch: u64,
+    session_log: &SessionLog,
+) -> Result<Signature> {
+    co_sign(
+        co_signer,
+        message,
+        vec![],
+        key_pair,
+        key_agg,
+        id,
+        epoch,
+        None,
+        session_log,
+    )
+}
+
+fn co_sign(
+    co_signer: &dyn CoSigner,
+    message: HashValue,
+    raw_txn_bytes: Vec<u8>,
+    key_pair: &KeyPair,
+    key_agg: &KeyAgg,
+    id: &str,
+    epoch: u64,
+    child_number: Option<u64>,
+    session_log: &SessionLog,
+) -> Result<Signature> {
+    let (ephemeral_key, client_commitment, client_reveal) =
+        Signature::create_ephemeral_key_and_commit(key_pair, message.as_ref());
+
+    let first = co_signer.sign_first(
+        id,
+        SignFirstRequest {
+            message_hash: if raw_txn_bytes.is_empty() {
+                Some(message)
+            } else {
+                None
+            },
+            raw_txn_bytes,
+            client_commitment,
+            epoch,
+            child_number,
+        },
+    )?;
+    let mut server_nonce = None;
+    let mut complete_session = || -> Result<Signature> {
+        let second = co_signer.sign_second(
+            id,
+            SignSecondRequest {
+                session_id: first.session_id.clone(),
+                client_reveal: client_reveal.clone(),
+            },
+        )?;
+        let server_reveal = second.server_reveal;
+        if !test_com(
+            &server_reveal.R,
+            &server_reveal.blind_factor,
+            &first.server_commitment.commitment,
+        ) {
+            return Err(WalletError::CoSignerProtocol(
+                "Server reveal does not open its commitment".to_string(),
+            ));
+        }
+        let nonce = hex::encode(server_reveal.R.pk_to_key_slice());
+        server_nonce = Some(nonce.clone());
+        session_log.check_server_nonce(&nonce)?;
+
+        let r_tot = Signature::get_R_tot(vec![server_reveal.R.clone(), client_reveal.R.clone()]);
+        let k = Signature::k(&r_tot, &key_agg.apk, message.as_ref());
+        verify_partial_signature(
+            &second.server_partial_signature,
+            &server_reveal.R,
+            &r_tot,
+            &k,
+            key_pair,
+            key_agg,
+        )?;
+        let client_partial_signature =
+            Signature::partial_sign(&ephemeral_key.r, key_pair, &k, &key_agg.hash, &r_tot);
+        let signature = Signature::add_signature_parts(vec![
+            second.server_partial_signature,
+            client_partial_signature,
+        ]);
+        verify(&signature, message.as_ref(), &key_agg.apk).map_err(|_| {
+            WalletError::InvalidSignature(
+                "Aggregated signature does not verify against the aggregated public key"
+                    .to_string(),
+            )
+        })?;
+        Ok(signature)
+    };
+    let result = complete_session();
+
+    let outcome = match &result {
+        Ok(_) => SessionOutcome::Signed,
+        Err(WalletError::InvalidSignature(reason)) => {
+            SessionOutcome::InvalidSignature(reason.clone())
+        }
+        Err(WalletError::NonceReuse(reason)) => SessionOutcome::NonceReuse(reason.clone()),
+        Err(e) => SessionOutcome::Failed(format!("{}", e)),
+    };
+    session_log.record(id, &first.session_id, message, server_nonce, outcome);
+    result
+}
+
+/// Checks the partial signature of the co-signer, s * G = R + k * a_s * X_s, R being the nonce it
+/// revealed. The contribution a_s * X_s of the co-signer to the aggregated public key is obtained
+/// as apk - a_c * X_c, which holds for refreshed and derived keys as well.
+fn verify_partial_signature(
+    partial_signature: &Signature,
+    server_nonce: &GE,
+    r_tot: &GE,
+    k: &FE,
+    key_pair: &KeyPair,
+    key_agg: &KeyAgg,
+) -> Result<()> {
+    let client_contribution = key_pair.public_key.scalar_mul(&key_agg.hash.get_element());
+    let server_contribution = key_agg.apk.sub_point(&client_contribution.get_element());
+    let expected = server_nonce.add_point(
+        &server_contribution
+            .scalar_mul(&k.get_element())
+            .get_element(),
+    );
+    let actual = GE::generator().scalar_mul(&partial_signature.s.get_element());
+    if partial_signature.R != *r_tot || actual != expected {
+        return Err(WalletError::InvalidSignature(
+            "Co-signer returned an invalid partial signature".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Refreshes the client share of a key at the given epoch, returning the refreshed key pair along
+/// with its aggregation coefficient and epoch. The aggregated public key is unchanged. The
+/// co-signer keeps its share of the current epoch until confirm_refresh is called, or the refreshed
+/// share is used to sign.
+pub fn refresh(
+    co_signer: &dyn CoSigner,
+    key_pair: &KeyPair,
+    key_agg: &KeyAgg,
+    id: &str,
+    epoch: u64,
+) -> Result<(KeyPair, FE, u64)> {
+    let client_delta = FE::new_random();
+    let response = co_signer.refresh(
+        id,
+        RefreshRequest {
+            epoch,
+            client_delta: client_delta.clone(),
+        },
+    )?;
+    if response.epoch != epoch + 1 {
+        return Err(WalletError::CoSignerProtocol(format!(
+            "Refresh from epoch {} ended at epoch {}",
+            epoch, response.epoch
+        )));
+    }
+    let delta = client_delta.add(&response.server_delta.get_element());
+    let (key_pair, coefficient) = refresh_key_pair(key_pair, &key_agg.hash, &delta, false);
+    Ok((key_pair, coefficient, response.epoch))
+}
+
+/// Tells the co-signer that the refreshed client share of the given epoch is persisted.
+pub fn confirm_refresh(co_signer: &dyn CoSigner, id: &str, epoch: u64) -> Result<()> {
+    let response = co_signer.confirm_refresh(id, RefreshConfirmRequest { epoch })?;
+    if response.epoch != epoch {
+        return Err(WalletError::CoSignerProtocol(format!(
+            "Co-signer is at epoch {} after confirming epoch {}",
+            response.epoch, epoch
+        )));
+    }
+    Ok(())
+}
+
+/// Tells the co-signer that the authentication key of address was rotated to the key stored under
+/// id, once the rotation is committed, so that the key co-signs for that account from then on.
+pub fn confirm_rotation(co_signer: &dyn CoSigner, id: &str, address: AccountAddress) -> Result<()> {
+    let response = co_signer.confirm_rotation(id, RotationConfirmRequest { address })?;
+    if response.address != address {
+        return Err(WalletError::CoSignerProtocol(format!(
+            "Co-signer bound key {} to account {} instead of {}",
+            id, response.address, address
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        co_signer::CoSignerConfig,
+        in_process::{in_process_co_signer, InProcessCoSigner},
+    };
+    use co_signer_protocol::{
+        threshold::{
+            ThresholdKeyGenCommitRequest, ThresholdKeyGenCommitResponse,
+            ThresholdKeyGenCompleteRequest, ThresholdKeyGenCompleteResponse,
+            ThresholdKeyGenShareRequest, ThresholdKeyGenShareResponse, ThresholdSignCommitRequest,
+            ThresholdSignCommitResponse, ThresholdSignRequest, ThresholdSignResponse,
+            ThresholdSignRevealRequest, ThresholdSignRevealResponse,
+        },
+        BigInt, KeyGenResponse, RefreshConfirmResponse, RefreshResponse, RotationConfirmResponse,
+        SignFirstResponse, SignSecondResponse,
+    };
+    use std::sync::{Arc, Mutex};
+
+    /// How TamperingCoSigner misbehaves.
+    #[derive(Debug)]
+    enum Tampering {
+        /// Returns partial signatures off by one
+        PartialSignature,
+        /// Reveals the nonce of its first session in every later session
+        NonceReuse,
+    }
+
+    /// Co-signer running the protocol in process, but tampering with its answers. The answers of
+    /// the last sign_first, and of the first session, are kept to replay its nonce.
+    #[derive(Debug)]
+    struct TamperingCoSigner {
+        co_signer: Arc<InProcessCoSigner>,
+        tampering: Tampering,
+        first_session: Mutex<Option<(SignFirstResponse, SignSecondResponse)>>,
+        last_first: Mutex<Option<SignFirstResponse>>,
+    }
+
+    impl TamperingCoSigner {
+        fn new(name: &str, tampering: Tampering) -> Self {
+            Self {
+                co_signer: in_process_co_signer(name),
+                tampering,
+                first_session: Mutex::new(None),
+                last_first: Mutex::new(None),
+            }
+        }
+    }
+
+    impl CoSigner for TamperingCoSigner {
+        fn keygen(&self, request: KeyGenRequest) -> Result<KeyGenResponse> {
+            self.co_signer.keygen(request)
+        }
+
+        fn sign_first(&self, id: &str, request: SignFirstRequest) -> Result<SignFirstResponse> {
+            let mut response = self.co_signer.sign_first(id, request)?;
+            *self.last_first.lock().unwrap() = Some(response.clone());
+            if let Tampering::NonceReuse = self.tampering {
+                if let Some((first, _)) = &*self.first_session.lock().unwrap() {
+                    response.server_commitment = first.server_commitment.clone();
+                }
+            }
+            Ok(response)
+        }
+
+        fn sign_second(&self, id: &str, request: SignSecondRequest) -> Result<SignSecondResponse> {
+            let mut response = self.co_signer.sign_second(id, request)?;
+            match self.tampering {
+                Tampering::PartialSignature => {
+                    let one: FE = ECScalar::from(&BigInt::from(1));
+                    response.server_partial_signature.s =
+                        response.server_partial_signature.s.add(&one.get_element());
+                }
+                Tampering::NonceReuse => {
+                    let mut first_session = self.first_session.lock().unwrap();
+                    match &*first_session {
+                        Some((_, second)) => response.server_reveal = second.server_reveal.clone(),
+                        None => {
+                            let first = self.last_first.lock().unwrap().clone().unwrap();
+                            *first_session = Some((first, response.clone()));
+                        }
+                    }
+                }
+            }
+            Ok(response)
+        }
+
+        fn refresh(&self, id: &str, request: RefreshRequest) -> Result<RefreshResponse> {
+            self.co_signer.refresh(id, request)
+        }
+
+        fn confirm_refresh(
+            &self,
+            id: &str,
+            request: RefreshConfirmRequest,
+        ) -> Result<RefreshConfirmResponse> {
+            self.co_signer.confirm_refresh(id, request)
+        }
+
+        fn confirm_rotation(
+            &self,
+            id: &str,
+            request: RotationConfirmRequest,
+        ) -> Result<RotationConfirmResponse> {
+            self.co_signer.confirm_rotation(id, request)
+        }
+
+        fn threshold_keygen_commit(
+            &self,
+            request: ThresholdKeyGenCommitRequest,
+        ) -> Result<ThresholdKeyGenCommitResponse> {
+            self.co_signer.threshold_keygen_commit(request)
+        }
+
+        fn threshold_keygen_share(
+            &self,
+            request: ThresholdKeyGenShareRequest,
+        ) -> Result<ThresholdKeyGenShareResponse> {
+            self.co_signer.threshold_keygen_share(request)
+        }
+
+        fn threshold_keygen_complete(
+            &self,
+            request: ThresholdKeyGenCompleteRequest,
+        ) -> Result<ThresholdKeyGenCompleteResponse> {
+            self.co_signer.threshold_keygen_complete(request)
+        }
+
+        fn threshold_sign_commit(
+            &self,
+            id: &str,
+            request: ThresholdSignCommitRequest,
+        ) -> Result<ThresholdSignCommitResponse> {
+            self.co_signer.threshold_sign_commit(id, request)
+        }
+
+        fn threshold_sign_reveal(
+            &self,
+            id: &str,
+            request: ThresholdSignRevealRequest,
+        ) -> Result<ThresholdSignRevealResponse> {
+            self.co_signer.threshold_sign_reveal(id, request)
+        }
+
+        fn threshold_sign(
+            &self,
+            id: &str,
+            request: ThresholdSignRequest,
+        ) -> Result<ThresholdSignResponse> {
+            self.co_signer.threshold_sign(id, request)
+        }
+    }
+
+    fn sign_messages(co_signer: &dyn CoSigner, count: usize) -> Vec<Result<Signature>> {
+        let (key_pair, key_agg, id, _) = generate_message_key(co_signer).unwrap();
+        let session_log = SessionLog::default();
+        (0..count)
+            .map(|_| {
+                let message = HashValue::random();
+                sign_message(
+                    co_signer,
+                    message,
+                    &key_pair,
+                    &key_agg,
+                    &id,
+                    0,
+                    &session_log,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_honest_co_signer() {
+        let co_signer = CoSignerConfig::in_process("test_honest_co_signer")
+            .connect()
+            .unwrap();
+        for result in sign_messages(&*co_signer, 2) {
+            result.unwrap();
+        }
+    }
+
+    #[test]
+    fn test_message_signing() {
+        let co_signer = CoSignerConfig::in_process("test_message_signing")
+            .connect()
+            .unwrap();
+        let co_signer = &*co_signer;
+        let session_log = SessionLog::default();
+        let raw_txn_bytes = b"raw transaction";
+        let message = HashValue::random();
+
+        // Message signing keys only sign prehashed messages...
+        let (key_pair, key_agg, id, _) = generate_message_key(co_signer).unwrap();
+        sign_message(co_signer, message, &key_pair, &key_agg, &id, 0, &session_log).unwrap();
+        let result = sign(
+            co_signer,
+            raw_txn_bytes,
+            &key_pair,
+            &key_agg,
+            &id,
+            0,
+            None,
+            &session_log,
+        );
+        match result {
+            Err(WalletError::CoSignerProtocol(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+
+        // ... and other keys only transactions.
+        let (key_pair, key_agg, id, _) = generate_key(co_signer).unwrap();
+        sign(
+            co_signer,
+            raw_txn_bytes,
+            &key_pair,
+            &key_agg,
+            &id,
+            0,
+            None,
+            &session_log,
+        )
+        .unwrap();
+        match sign_message(co_signer, message, &key_pair, &key_agg, &id, 0, &session_log) {
+            Err(WalletError::CoSignerProtocol(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_invalid_partial_signature() {
+        let co_signer = TamperingCoSigner::new(
+            "test_invalid_partial_signature",
+            Tampering::PartialSignature,
+        );
+        match sign_messages(&co_signer, 1).pop().unwrap() {
+            Err(WalletError::InvalidSignature(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_nonce_reuse() {
+        let co_signer = TamperingCoSigner::new("test_nonce_reuse", Tampering::NonceReuse);
+        let mut results = sign_messages(&co_signer, 2);
+        match results.pop().unwrap() {
+            Err(WalletError::NonceReuse(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        results.pop().unwrap().unwrap();
+    }
+}
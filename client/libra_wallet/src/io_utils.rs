@@ -1,48 +1,278 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-//! A module to generate, store and load known users accounts.
-//! The concept of known users can be helpful for testing to provide reproducible results.
+//! A module to store and load the client shares of a two-party wallet.
+//!
+//! The recovery file is a JSON envelope holding a format version, the scrypt parameters used to
+//! derive the encryption key from the user passphrase, and the ChaCha20-Poly1305 encrypted
+//! RecoveryPayload. The payload holds the KeyShares of the master keys, from which the children
+//! are derived, and of every child that is not derived from a master key, threshold keys
+//! included, so that a recovered wallet controls exactly the same addresses and keeps co-signing
+//! with the same server keys.
 
-use crate::*;
+use crate::{
+    co_signer::CoSignerConfig,
+    key_factory::{KeyShare, ThresholdKeyShare},
+    *,
+};
+use crypto::{
+    aead::{AeadDecryptor, AeadEncryptor},
+    chacha20poly1305::ChaCha20Poly1305,
+    scrypt::{scrypt, ScryptParams},
+};
 use failure::prelude::*;
+use rand::{rngs::EntropyRng, RngCore};
+use serde::{Deserialize, Serialize};
 use std::{
-    fs::File,
-    io::{BufRead, BufReader, Write},
-    path::Path,
+    ffi::OsString,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
 };
 
-/// Delimiter used to ser/deserialize account data.
-pub const DELIMITER: &str = ";";
+/// Version of the recovery file format written by this library.
+pub const RECOVERY_FILE_VERSION: u32 = 1;
 
-/// Recover wallet from the path specified.
-// TODO: fix recover
-pub fn recover<P: AsRef<Path>>(path: &P) -> Result<WalletLibrary> {
-    let input = File::open(path)?;
-    let mut buffered = BufReader::new(input);
+/// Additional authenticated data bound to every recovery file ciphertext.
+const RECOVERY_AAD: &[u8] = b"LIBRA_WALLET_RECOVERY";
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 8;
+const SALT_LENGTH: usize = 32;
+const TAG_LENGTH: usize = 16;
 
-    let mut line = String::new();
-    let _ = buffered.read_line(&mut line)?;
-    let parts: Vec<&str> = line.split(DELIMITER).collect();
-    ensure!(parts.len() == 2, format!("Invalid entry '{}'", line));
+/// scrypt cost parameters, stored in the file so that they can be tuned without breaking old
+/// recovery files. They come from the file, which cannot be trusted before it is decrypted, so
+/// files of a given version are only read with the parameters that version is written with:
+/// raising them would let a forged file exhaust the memory of the wallet.
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
 
-//    let mnemonic = Mnemonic::from(&parts[0].to_string()[..])?;
-    let mut wallet = WalletLibrary::new();
-    wallet.generate_addresses(parts[1].trim().to_string().parse::<u64>()?)?;
+/// Plaintext content of the recovery file.
+#[derive(Serialize, Deserialize)]
+pub struct RecoveryPayload {
+    /// Next ChildNumber to be used by the wallet
+    pub key_leaf: u64,
+    /// Co-signer new children are generated with
+    #[serde(default)]
+    pub co_signer: CoSignerConfig,
+    /// Client shares of the master keys children are derived from
+    #[serde(default)]
+    pub masters: Vec<KeyShare>,
+    /// Client shares of the generated children that are not derived from a master key
+    pub shares: Vec<KeyShare>,
+    /// Client shares of the keys generated for authentication key rotations that are not
+    /// complete yet. They are kept so that an interrupted rotation can be resumed.
+    #[serde(default)]
+    pub rotation_shares: Vec<KeyShare>,
+    /// Client shares of the threshold keys, along with the co-signers holding the other shares
+    #[serde(default)]
+    pub threshold_shares: Vec<ThresholdKeyShare>,
+}
 
-    Ok(wallet)
+#[derive(Serialize, Deserialize)]
+struct KdfParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecoveryEnvelope {
+    version: u32,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+    tag: String,
 }
 
-/// Write wallet seed to file.
-pub fn write_recovery<P: AsRef<Path>>(wallet: &WalletLibrary, path: &P) -> Result<()> {
-    let mut output = File::create(path)?;
-    writeln!(
-        output,
-        "{}{}{}",
-        "THERE IS NO SEED",
-        DELIMITER,
-        wallet.key_leaf()
+/// Recover wallet from the path specified.
+pub fn recover<P: AsRef<Path>>(path: &P, passphrase: &str) -> Result<WalletLibrary> {
+    let payload = read_payload(path, passphrase)?;
+    let wallet = WalletLibrary::from_key_shares(
+        payload.co_signer,
+        payload.key_leaf,
+        payload.masters,
+        payload.shares,
+        payload.rotation_shares,
+        payload.threshold_shares,
     )?;
+    Ok(wallet)
+}
+
+/// Write the client shares of the wallet to file, encrypted under passphrase.
+pub fn write_recovery<P: AsRef<Path>>(
+    wallet: &WalletLibrary,
+    path: &P,
+    passphrase: &str,
+) -> Result<()> {
+    let payload = RecoveryPayload {
+        key_leaf: wallet.key_leaf(),
+        co_signer: wallet.co_signer().clone(),
+        masters: wallet.master_key_shares(),
+        shares: wallet.key_shares(),
+        rotation_shares: wallet.rotation_key_shares(),
+        threshold_shares: wallet.threshold_key_shares(),
+    };
+    write_payload(&payload, path, passphrase)
+}
+
+/// Decrypt and deserialize the RecoveryPayload stored at path.
+pub fn read_payload<P: AsRef<Path>>(path: &P, passphrase: &str) -> Result<RecoveryPayload> {
+    let envelope: RecoveryEnvelope = serde_json::from_slice(&fs::read(path)?)?;
+    ensure!(
+        envelope.version == RECOVERY_FILE_VERSION,
+        "Unsupported recovery file version {}, expected {}",
+        envelope.version,
+        RECOVERY_FILE_VERSION
+    );
+    let kdf = &envelope.kdf;
+    ensure!(
+        (kdf.log_n, kdf.r, kdf.p) == (SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P),
+        "Unsupported scrypt parameters log_n = {}, r = {}, p = {} in recovery file",
+        kdf.log_n,
+        kdf.r,
+        kdf.p
+    );
+    let salt = hex::decode(&kdf.salt)?;
+    let nonce = hex::decode(&envelope.nonce)?;
+    let ciphertext = hex::decode(&envelope.ciphertext)?;
+    let tag = hex::decode(&envelope.tag)?;
+    ensure!(
+        salt.len() == SALT_LENGTH && nonce.len() == NONCE_LENGTH && tag.len() == TAG_LENGTH,
+        "Malformed recovery file"
+    );
+
+    let key = derive_key(passphrase, &salt, kdf);
+    let mut plaintext = vec![0u8; ciphertext.len()];
+    let mut cipher = ChaCha20Poly1305::new(&key, &nonce, RECOVERY_AAD);
+    ensure!(
+        cipher.decrypt(&ciphertext, &mut plaintext, &tag),
+        "Unable to decrypt recovery file, wrong passphrase or corrupted file"
+    );
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Serialize and encrypt payload under a key derived from passphrase, and write it to path.
+pub fn write_payload<P: AsRef<Path>>(
+    payload: &RecoveryPayload,
+    path: &P,
+    passphrase: &str,
+) -> Result<()> {
+    let mut rng = EntropyRng::new();
+    let mut salt = [0u8; SALT_LENGTH];
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut nonce);
+    let kdf = KdfParams {
+        log_n: SCRYPT_LOG_N,
+        r: SCRYPT_R,
+        p: SCRYPT_P,
+        salt: hex::encode(&salt[..]),
+    };
 
+    let plaintext = serde_json::to_vec(payload)?;
+    let key = derive_key(passphrase, &salt, &kdf);
+    let mut ciphertext = vec![0u8; plaintext.len()];
+    let mut tag = [0u8; TAG_LENGTH];
+    ChaCha20Poly1305::new(&key, &nonce, RECOVERY_AAD).encrypt(
+        &plaintext,
+        &mut ciphertext,
+        &mut tag,
+    );
+
+    let envelope = RecoveryEnvelope {
+        version: RECOVERY_FILE_VERSION,
+        kdf,
+        nonce: hex::encode(&nonce[..]),
+        ciphertext: hex::encode(&ciphertext),
+        tag: hex::encode(&tag[..]),
+    };
+    write_secret_file(path, &serde_json::to_vec_pretty(&envelope)?)
+}
+
+/// Write a file holding secrets: only the owner can read it, and it is written to a temporary
+/// file renamed over path, so that an interrupted write never leaves a truncated file behind.
+pub fn write_secret_file<P: AsRef<Path>>(path: &P, contents: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let mut tmp_file_name = path
+        .file_name()
+        .map(OsString::from)
+        .ok_or_else(|| format_err!("Invalid file path {}", path.display()))?;
+    tmp_file_name.push(".tmp");
+    let tmp_path: PathBuf = path.with_file_name(tmp_file_name);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
     Ok(())
 }
+
+fn derive_key(passphrase: &str, salt: &[u8], kdf: &KdfParams) -> [u8; KEY_LENGTH] {
+    let mut key = [0u8; KEY_LENGTH];
+    let params = ScryptParams::new(kdf.log_n, kdf.r, kdf.p);
+    scrypt(passphrase.as_bytes(), salt, &params, &mut key);
+    key
+}
+
+#[test]
+fn test_roundtrip_payload() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let path = file.into_temp_path();
+    let payload = RecoveryPayload {
+        key_leaf: 7,
+        co_signer: CoSignerConfig::default(),
+        masters: vec![],
+        shares: vec![],
+        rotation_shares: vec![],
+        threshold_shares: vec![],
+    };
+    write_payload(&payload, &path, "passphrase").unwrap();
+
+    let recovered = read_payload(&path, "passphrase").unwrap();
+    assert_eq!(recovered.key_leaf, 7);
+    assert!(recovered.shares.is_empty());
+    assert!(read_payload(&path, "wrong passphrase").is_err());
+
+    // Files asking for other scrypt parameters, or another salt length, are refused before any
+    // key is derived.
+    let tamper = |tamper: &dyn Fn(&mut RecoveryEnvelope)| {
+        let mut envelope: RecoveryEnvelope =
+            serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        tamper(&mut envelope);
+        let tampered = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+        write_secret_file(&tampered, &serde_json::to_vec(&envelope).unwrap()).unwrap();
+        read_payload(&tampered, "passphrase").map(|_| ())
+    };
+    tamper(&|_| ()).unwrap();
+    assert!(tamper(&|envelope| envelope.kdf.log_n = 63).is_err());
+    assert!(tamper(&|envelope| envelope.kdf.r = u32::max_value()).is_err());
+    assert!(tamper(&|envelope| envelope.kdf.p = 0).is_err());
+    assert!(tamper(&|envelope| envelope.kdf.salt = String::new()).is_err());
+}
+
+#[test]
+fn test_write_secret_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("recovery");
+    write_secret_file(&path, b"first").unwrap();
+    write_secret_file(&path, b"second").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"second");
+    // The temporary file is renamed over the target.
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
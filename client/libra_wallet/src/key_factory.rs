@@ -5,26 +5,34 @@
 //! LibraWallet.
 //!
 //! Note that the Libra Blockchain makes use of ed25519 Edwards Digital Signature Algorithm
-//! (EdDSA) and therefore, BIP32 Public Key derivation is not available without falling back to
-//! a non-deterministic Schnorr signature scheme. As LibraWallet is meant to be a minimalist
-//! reference implementation of a simple wallet, the following does not deviate from the
-//! ed25519 spec. In a future iteration of this wallet, we will also provide an implementation
-//! of a Schnorr variant over curve25519 and demonstrate our proposal for BIP32-like public key
-//! derivation.
+//! (EdDSA). Two-party keys aggregate the public keys of both parties, which allows a BIP32-like,
+//! non-hardened, derivation: a single master key is generated together with the co-signer, and
+//! the aggregated public keys of its children, as well as both parties' child shares, are
+//! derived from it by ChildNumber without any further interaction. Addresses can therefore be
+//! computed offline, and a wallet is recovered from its master shares and its key_leaf.
 //!
-//! Note further that the Key Derivation Function (KDF) chosen in the derivation of Child
-//! Private Keys adheres to [HKDF RFC 5869](https://tools.ietf.org/html/rfc5869).
+//! Keys that are not derived from a master, i.e. keys of wallets created before masters were
+//! introduced and keys an account was rotated to, are kept and persisted individually. So are
+//! threshold keys, shared between the client and several co-signers, any threshold of which
+//! sign together. Their shares cannot be refreshed, nor can their accounts be rotated yet.
 
+use co_signer_protocol::{
+    derive_child, ed25519_public_key, ed25519_signature, signing_message,
+    threshold::{ThresholdParameters, ThresholdShare},
+    KeyAgg, KeyPair, Signature, GE,
+};
 use ed25519_dalek;
-use libra_crypto::{hash::HashValue};
 use serde::{Deserialize, Serialize};
-use std::{convert::TryFrom, ops::AddAssign, collections::HashMap};
+use std::{collections::HashMap, convert::TryFrom, ops::AddAssign, sync::Arc};
 use tiny_keccak::Keccak;
 use types::account_address::AccountAddress;
-use two_party_eddsa_client::api::*;
-use hex::FromHex;
 
-use crate::error::Result;
+use crate::{
+    co_signer::{call_with_retries, CoSigner, CoSignerConfig, ThresholdConfig},
+    error::{Result, WalletError},
+    session_log::SessionLog,
+    threshold, two_party,
+};
 
 /// Master is a set of raw bytes that are used for child key derivation
 pub struct Master([u8; 32]);
@@ -66,116 +74,842 @@ impl std::convert::AsMut<u64> for ChildNumber {
     }
 }
 
+/// Serializable client side of a two-party key. Holds everything an ExtendedPrivKey needs,
+/// including how to reach its co-signer, and is what ends up in the recovery file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyShare {
+    /// ChildNumber the key was generated for
+    pub child_number: ChildNumber,
+    /// Client share of the two-party key
+    pub key_pair: KeyPair,
+    /// Aggregation of the client and server public keys
+    pub aggregated_public_key: KeyAgg,
+    /// Id under which the co-signer stores its share
+    pub id: String,
+    /// Co-signer holding the server share
+    #[serde(default)]
+    pub co_signer: CoSignerConfig,
+    /// Account the key signs for when it is not the one derived from its public key, i.e. after
+    /// the authentication key of the account was rotated to this key
+    #[serde(default)]
+    pub account_address: Option<AccountAddress>,
+    /// Number of times the shares were refreshed
+    #[serde(default)]
+    pub epoch: u64,
+    /// Chain code children are derived with, if the key is used as a master key
+    #[serde(default)]
+    pub chain_code: Vec<u8>,
+}
+
 /// Derived private key.
 // TODO: delete after debugging
 #[derive(Debug, Clone)]
 pub struct ExtendedPrivKey {
-    client_shim: ClientShim,
+    co_signer_client: Arc<dyn CoSigner>,
+    co_signer: CoSignerConfig,
+    child_number: ChildNumber,
     key_pair: KeyPair,
     aggregated_public_key: KeyAgg,
     id: String,
+    account_address: Option<AccountAddress>,
+    epoch: u64,
+    chain_code: Vec<u8>,
+    /// Whether the key is derived from a master key, whose id it shares
+    derived: bool,
 }
 
 impl ExtendedPrivKey {
-    pub fn new(client_shim: ClientShim, key_pair: KeyPair, aggregated_public_key: KeyAgg, id: String) -> Self {
-        Self {
-            client_shim,
+    pub fn new(
+        co_signer: CoSignerConfig,
+        child_number: ChildNumber,
+        key_pair: KeyPair,
+        aggregated_public_key: KeyAgg,
+        id: String,
+    ) -> Result<Self> {
+        Ok(Self {
+            co_signer_client: co_signer.connect()?,
+            co_signer,
+            child_number,
             key_pair,
             aggregated_public_key,
             id,
+            account_address: None,
+            epoch: 0,
+            chain_code: vec![],
+            derived: false,
+        })
+    }
+
+    /// Rebuild an ExtendedPrivKey from a KeyShare
+    pub fn from_share(share: KeyShare) -> Result<Self> {
+        let mut extended_priv_key = Self::new(
+            share.co_signer,
+            share.child_number,
+            share.key_pair,
+            share.aggregated_public_key,
+            share.id,
+        )?;
+        extended_priv_key.account_address = share.account_address;
+        extended_priv_key.epoch = share.epoch;
+        extended_priv_key.chain_code = share.chain_code;
+        Ok(extended_priv_key)
+    }
+
+    /// Export the client side of this key so that it can be persisted
+    pub fn share(&self) -> KeyShare {
+        KeyShare {
+            child_number: self.child_number,
+            key_pair: self.key_pair.clone(),
+            aggregated_public_key: self.aggregated_public_key.clone(),
+            id: self.id.clone(),
+            co_signer: self.co_signer.clone(),
+            account_address: self.account_address,
+            epoch: self.epoch,
+            chain_code: self.chain_code.clone(),
         }
     }
 
-    pub fn get_public(&self) -> ed25519_dalek::PublicKey {
-        let public_key_bytes = hex::decode(self.aggregated_public_key.apk.bytes_compressed_to_big_int().to_hex()).unwrap();
-        ed25519_dalek::PublicKey::from_bytes(&public_key_bytes.as_slice())
-            .expect("Error while creating public key from bytes")
+    /// Derive the child of this master key at child_number, without contacting the co-signer
+    pub fn derive_child(&self, child_number: ChildNumber) -> Result<ExtendedPrivKey> {
+        let (key_pair, aggregated_public_key) = derive_child(
+            &self.key_pair,
+            &self.aggregated_public_key,
+            &self.chain_code,
+            child_number.0,
+            true,
+        )
+        .map_err(|e| WalletError::LibraWalletGeneric(format!("Cannot derive child: {}", e)))?;
+        Ok(ExtendedPrivKey {
+            co_signer_client: self.co_signer_client.clone(),
+            co_signer: self.co_signer.clone(),
+            child_number,
+            key_pair,
+            aggregated_public_key,
+            id: self.id.clone(),
+            account_address: None,
+            epoch: self.epoch,
+            chain_code: vec![],
+            derived: true,
+        })
+    }
+
+    /// Whether this key is derived from a master key
+    pub fn is_derived(&self) -> bool {
+        self.derived
+    }
+
+    /// Get the co-signer holding the server share of this key
+    pub fn co_signer(&self) -> &CoSignerConfig {
+        &self.co_signer
+    }
+
+    /// Get the ChildNumber this key was generated for
+    pub fn child_number(&self) -> ChildNumber {
+        self.child_number
+    }
+
+    /// Get the number of times the shares of this key were refreshed
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Refresh the shares of this key together with the co-signer, returning the key holding the
+    /// refreshed client share. The address and public key are unchanged. The refreshed share has
+    /// to be persisted before calling confirm_refresh on it.
+    pub fn refresh(&self) -> Result<ExtendedPrivKey> {
+        let (key_pair, coefficient, epoch) = call_with_retries("Two-party share refresh", || {
+            two_party::refresh(
+                &*self.co_signer_client,
+                &self.key_pair,
+                &self.aggregated_public_key,
+                &self.id,
+                self.epoch,
+            )
+        })?;
+        let mut extended_priv_key = self.clone();
+        extended_priv_key.key_pair = key_pair;
+        extended_priv_key.aggregated_public_key.hash = coefficient;
+        extended_priv_key.epoch = epoch;
+        Ok(extended_priv_key)
+    }
+
+    /// Let the co-signer drop its share of the previous epoch
+    pub fn confirm_refresh(&self) -> Result<()> {
+        call_with_retries("Two-party share refresh confirmation", || {
+            two_party::confirm_refresh(&*self.co_signer_client, &self.id, self.epoch)
+        })
+    }
+
+    /// Let the co-signer bind this key to the account whose authentication key was rotated to
+    /// it, once the rotation is committed
+    pub fn confirm_rotation(&self) -> Result<()> {
+        let address = self.get_address()?;
+        call_with_retries("Key rotation confirmation", || {
+            two_party::confirm_rotation(&*self.co_signer_client, &self.id, address)
+        })
+    }
+
+    /// Get the aggregated public key, i.e. the ed25519 public key the co-signed signatures verify
+    /// against
+    pub fn get_public(&self) -> Result<ed25519_dalek::PublicKey> {
+        public_key(&self.aggregated_public_key.apk)
     }
 
     /// Computes the sha3 hash of the PublicKey and attempts to construct a Libra AccountAddress
-    /// from the raw bytes of the pubkey hash
-    pub fn get_address(&self) -> Result<AccountAddress> {
-        let public_key = self.get_public();
-        let mut keccak = Keccak::new_sha3_256();
-        let mut hash = [0u8; 32];
-        keccak.update(&public_key.to_bytes());
-        keccak.finalize(&mut hash);
-        let addr = AccountAddress::try_from(&hash[..])?;
-        Ok(addr)
+    /// from the raw bytes of the pubkey hash. This is the authentication key an account has to
+    /// hold for this key to sign its transactions.
+    pub fn get_authentication_key(&self) -> Result<AccountAddress> {
+        authentication_key(&self.get_public()?)
     }
 
+    /// Get the address of the account this key signs for. Unless the key was rotated in, it is
+    /// the address derived from the PublicKey.
+    pub fn get_address(&self) -> Result<AccountAddress> {
+        match self.account_address {
+            Some(account_address) => Ok(account_address),
+            None => self.get_authentication_key(),
+        }
+    }
 
-    /// Libra specific sign function that is capable of signing an arbitrary HashValue
+    /// Libra specific sign function, co-signing a protobuf encoded RawTransaction.
     /// NOTE: In Libra, we do not sign the raw bytes of a transaction, instead we sign the raw
     /// bytes of the sha3 hash of the raw bytes of a transaction. It is important to note that the
     /// raw bytes of the sha3 hash will be hashed again as part of the ed25519 signature algorithm.
-    /// In other words: In Libra, the message used for signature and verification is the sha3 hash
-    /// of the transaction. This sha3 hash is then hashed again using SHA512 to arrive at the
-    /// deterministic nonce for the EdDSA.
-    #[allow(non_snake_case)]
-    pub fn sign(&self, msg: HashValue) -> ed25519_dalek::Signature {
-        let message = BigInt::from(msg.to_vec().as_slice());
-        let signature =
-            two_party_eddsa_client::api::sign(
-                &self.client_shim, message,
+    /// The co-signer computes that hash by itself, after checking the transaction against its
+    /// spending policy, which is why the raw bytes are passed here. The signature is verified
+    /// against the public key of the account before it is returned.
+    pub fn sign(
+        &self,
+        raw_txn_bytes: &[u8],
+        session_log: &SessionLog,
+    ) -> Result<ed25519_dalek::Signature> {
+        let signature = call_with_retries("Two-party signing", || {
+            two_party::sign(
+                &*self.co_signer_client,
+                raw_txn_bytes,
                 &self.key_pair,
-                &self.aggregated_public_key, &self.id)
-            .expect("Error while signing");
-        let R = format!("{:0>64}", signature.R.bytes_compressed_to_big_int().to_hex());
-        let s_src = hex::decode(format!("{:0>64}",signature.s.to_big_int().to_hex())).unwrap();
-        // to little endian
-        let mut s_dst: [u8; 32] = [0; 32];
-        for i in 0..32 {
-            s_dst[i] = s_src[31 - i];
-        }
-        let s = format!("{}", hex::encode(s_dst));
-        // gather R and s
-        let v = Vec::from_hex(format!("{}{}", R, s)).unwrap();
+                &self.aggregated_public_key,
+                &self.id,
+                self.epoch,
+                if self.derived {
+                    Some(self.child_number.0)
+                } else {
+                    None
+                },
+                session_log,
+            )
+        })?;
+        verified_signature(&signature, &self.get_public()?, raw_txn_bytes)
+    }
+}
+
+/// A co-signer holding a share of a threshold key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdCoSigner {
+    /// Name the co-signer is chosen by when signing
+    pub name: String,
+    /// Index of the co-signer among the participants of the key
+    pub index: u64,
+    /// Id under which the co-signer stores its share
+    pub id: String,
+    /// Endpoint of the co-signer
+    pub co_signer: CoSignerConfig,
+}
+
+/// Serializable client side of a threshold key, as it ends up in the recovery file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdKeyShare {
+    /// ChildNumber the key was generated for
+    pub child_number: ChildNumber,
+    /// Client share of the threshold key
+    pub share: ThresholdShare,
+    /// Co-signers holding the other shares, in participant order
+    pub co_signers: Vec<ThresholdCoSigner>,
+}
+
+/// Client side of a threshold key, signing together with any large enough subset of its
+/// co-signers.
+#[derive(Debug, Clone)]
+pub struct ThresholdPrivKey {
+    share: ThresholdKeyShare,
+    co_signer_clients: Vec<Arc<dyn CoSigner>>,
+}
 
-        ed25519_dalek::Signature::from_bytes(v.as_slice()).unwrap()
+impl ThresholdPrivKey {
+    /// Rebuild a ThresholdPrivKey from a ThresholdKeyShare
+    pub fn from_share(share: ThresholdKeyShare) -> Result<Self> {
+        let co_signer_clients = share
+            .co_signers
+            .iter()
+            .map(|co_signer| co_signer.co_signer.connect())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            share,
+            co_signer_clients,
+        })
+    }
+
+    /// Export the client side of this key so that it can be persisted
+    pub fn share(&self) -> ThresholdKeyShare {
+        self.share.clone()
+    }
+
+    /// Get the ChildNumber this key was generated for
+    pub fn child_number(&self) -> ChildNumber {
+        self.share.child_number
+    }
+
+    /// Get the number of participants of this key, and how many of them sign
+    pub fn parameters(&self) -> ThresholdParameters {
+        self.share.share.parameters
+    }
+
+    /// Get the co-signers holding the other shares of this key
+    pub fn co_signers(&self) -> &[ThresholdCoSigner] {
+        &self.share.co_signers
+    }
+
+    /// Get the ed25519 public key the threshold signatures verify against
+    pub fn get_public(&self) -> Result<ed25519_dalek::PublicKey> {
+        public_key(self.share.share.public_key())
+    }
+
+    /// Get the address of the account this key signs for, i.e. its authentication key
+    pub fn get_address(&self) -> Result<AccountAddress> {
+        authentication_key(&self.get_public()?)
+    }
+
+    /// Sign a protobuf encoded RawTransaction together with the co-signers named co_signers, or
+    /// with the first co-signers of the key if none is named. The client share takes part unless
+    /// the co-signers named reach the threshold on their own, so threshold - 1 co-signers at
+    /// least are needed. The signature is verified against the public key of the account before
+    /// it is returned.
+    pub fn sign(
+        &self,
+        raw_txn_bytes: &[u8],
+        co_signers: &[String],
+        session_log: &SessionLog,
+    ) -> Result<ed25519_dalek::Signature> {
+        let needed = self.parameters().threshold as usize - 1;
+        let mut positions = if co_signers.is_empty() {
+            (0..needed).collect::<Vec<_>>()
+        } else {
+            co_signers
+                .iter()
+                .map(|name| {
+                    self.share
+                        .co_signers
+                        .iter()
+                        .position(|co_signer| co_signer.name == *name)
+                        .ok_or_else(|| {
+                            WalletError::LibraWalletGeneric(format!(
+                                "Co-signer {} does not hold a share of this key",
+                                name
+                            ))
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
+        positions.sort();
+        positions.dedup();
+        let client_signs = positions.len() <= needed;
+        if positions.len() < needed {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "{} co-signers chosen, {} needed to reach the threshold of {}",
+                positions.len(),
+                needed,
+                needed + 1
+            )));
+        }
+        let signers = positions
+            .iter()
+            .map(|position| {
+                let co_signer = &self.share.co_signers[*position];
+                (
+                    co_signer.index,
+                    &*self.co_signer_clients[*position],
+                    co_signer.id.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let signature = call_with_retries("Threshold signing", || {
+            threshold::sign(
+                raw_txn_bytes,
+                &self.share.share,
+                &signers,
+                client_signs,
+                session_log,
+            )
+        })?;
+        verified_signature(&signature, &self.get_public()?, raw_txn_bytes)
     }
 }
 
+fn public_key(point: &GE) -> Result<ed25519_dalek::PublicKey> {
+    let public_key = ed25519_public_key(point).map_err(|e| {
+        WalletError::CoSignerProtocol(format!("Invalid aggregated public key: {}", e))
+    })?;
+    Ok(ed25519_dalek::PublicKey::from_bytes(
+        &public_key.to_slice(),
+    )?)
+}
+
+fn authentication_key(public_key: &ed25519_dalek::PublicKey) -> Result<AccountAddress> {
+    let mut keccak = Keccak::new_sha3_256();
+    let mut hash = [0u8; 32];
+    keccak.update(&public_key.to_bytes());
+    keccak.finalize(&mut hash);
+    let addr = AccountAddress::try_from(&hash[..])?;
+    Ok(addr)
+}
+
+/// Converts an aggregated signature of a RawTransaction to ed25519, checking it against
+/// public_key.
+fn verified_signature(
+    signature: &Signature,
+    public_key: &ed25519_dalek::PublicKey,
+    raw_txn_bytes: &[u8],
+) -> Result<ed25519_dalek::Signature> {
+    let signature = ed25519_signature(signature)
+        .map_err(|e| WalletError::InvalidSignature(format!("{}", e)))?;
+    let signature = ed25519_dalek::Signature::from_bytes(&signature.to_compact())
+        .map_err(|e| WalletError::InvalidSignature(format!("{}", e)))?;
+    public_key
+        .verify(signing_message(raw_txn_bytes).as_ref(), &signature)
+        .map_err(|e| WalletError::InvalidSignature(format!("{}", e)))?;
+    Ok(signature)
+}
+
 /// Wrapper struct from which we derive child keys
 pub struct KeyFactory {
-    client_shim: ClientShim,
+    co_signer: CoSignerConfig,
+    co_signer_client: Arc<dyn CoSigner>,
     children: HashMap<u64, ExtendedPrivKey>,
+    /// Keys generated to replace a child whose authentication key rotation is not complete yet
+    rotations: HashMap<u64, ExtendedPrivKey>,
+    /// Master keys, ordered by the first ChildNumber derived from them
+    masters: Vec<ExtendedPrivKey>,
+    /// Children holding a threshold key
+    threshold_children: HashMap<u64, ThresholdPrivKey>,
 }
 
 impl KeyFactory {
-
-    pub fn new() -> Result<Self> {
-        let client_shim = ClientShim::new("http://localhost:8000".to_string());
+    /// Constructor, new children are generated together with the supplied co-signer
+    pub fn new(co_signer: CoSignerConfig) -> Result<Self> {
+        let co_signer_client = co_signer.connect()?;
         let children = HashMap::new();
+        let rotations = HashMap::new();
+        let masters = vec![];
+        let threshold_children = HashMap::new();
 
         Ok(Self {
-            client_shim,
-            children
+            co_signer,
+            co_signer_client,
+            children,
+            rotations,
+            masters,
+            threshold_children,
         })
     }
 
+    /// Switch the co-signer used for children generated from now on. Existing children keep
+    /// the co-signer they were generated with.
+    pub fn set_co_signer(&mut self, co_signer: CoSignerConfig) -> Result<()> {
+        self.co_signer_client = co_signer.connect()?;
+        self.co_signer = co_signer;
+        Ok(())
+    }
+
+    /// Get the co-signer used for new children
+    pub fn co_signer(&self) -> &CoSignerConfig {
+        &self.co_signer
+    }
+
+    /// Constructor that restores the children below key_leaf, and the keys of pending rotations.
+    /// Children without a KeyShare or a ThresholdKeyShare of their own are derived from the last
+    /// master key whose first ChildNumber is not above theirs.
+    pub fn from_shares(
+        co_signer: CoSignerConfig,
+        key_leaf: u64,
+        master_shares: Vec<KeyShare>,
+        shares: Vec<KeyShare>,
+        rotation_shares: Vec<KeyShare>,
+        threshold_shares: Vec<ThresholdKeyShare>,
+    ) -> Result<Self> {
+        let mut key_factory = Self::new(co_signer)?;
+        for share in master_shares {
+            key_factory
+                .masters
+                .push(ExtendedPrivKey::from_share(share)?);
+        }
+        key_factory
+            .masters
+            .sort_by_key(|master| master.child_number.0);
+        for share in shares {
+            Self::insert_share(&mut key_factory.children, share)?;
+        }
+        for share in rotation_shares {
+            Self::insert_share(&mut key_factory.rotations, share)?;
+        }
+        for share in threshold_shares {
+            let child_number = share.child_number;
+            if key_factory.children.contains_key(&child_number.0)
+                || key_factory
+                    .threshold_children
+                    .insert(child_number.0, ThresholdPrivKey::from_share(share)?)
+                    .is_some()
+            {
+                return Err(WalletError::LibraWalletGeneric(format!(
+                    "Duplicate key share for child number {}",
+                    child_number.0
+                )));
+            }
+        }
+        for i in 0..key_leaf {
+            if key_factory.children.contains_key(&i)
+                || key_factory.threshold_children.contains_key(&i)
+            {
+                continue;
+            }
+            let master = key_factory
+                .masters
+                .iter()
+                .rev()
+                .find(|master| master.child_number.0 <= i)
+                .ok_or_else(|| {
+                    WalletError::LibraWalletGeneric(format!(
+                        "Missing key share for child number {}",
+                        i
+                    ))
+                })?;
+            let child = master.derive_child(ChildNumber(i))?;
+            key_factory.children.insert(i, child);
+        }
+        Ok(key_factory)
+    }
+
+    fn insert_share(keys: &mut HashMap<u64, ExtendedPrivKey>, share: KeyShare) -> Result<()> {
+        let child_number = share.child_number;
+        let extended_priv_key = ExtendedPrivKey::from_share(share)?;
+        if keys.insert(child_number.0, extended_priv_key).is_some() {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Duplicate key share for child number {}",
+                child_number.0
+            )));
+        }
+        Ok(())
+    }
+
+    fn sorted_shares(keys: &HashMap<u64, ExtendedPrivKey>) -> Vec<KeyShare> {
+        let mut shares = keys
+            .values()
+            .map(ExtendedPrivKey::share)
+            .collect::<Vec<_>>();
+        shares.sort_by_key(|share| share.child_number.0);
+        shares
+    }
+
+    /// Export the KeyShares of all children that are not derived from a master key, ordered by
+    /// ChildNumber
+    pub fn shares(&self) -> Vec<KeyShare> {
+        let mut shares = self
+            .children
+            .values()
+            .filter(|child| !child.is_derived())
+            .map(ExtendedPrivKey::share)
+            .collect::<Vec<_>>();
+        shares.sort_by_key(|share| share.child_number.0);
+        shares
+    }
+
+    /// Export the KeyShares of the master keys, ordered by their first ChildNumber
+    pub fn master_shares(&self) -> Vec<KeyShare> {
+        self.masters.iter().map(ExtendedPrivKey::share).collect()
+    }
+
+    /// Export the KeyShares of the keys of pending rotations, ordered by ChildNumber
+    pub fn rotation_shares(&self) -> Vec<KeyShare> {
+        Self::sorted_shares(&self.rotations)
+    }
+
+    /// Export the ThresholdKeyShares of the children holding a threshold key, ordered by
+    /// ChildNumber
+    pub fn threshold_shares(&self) -> Vec<ThresholdKeyShare> {
+        let mut shares = self
+            .threshold_children
+            .values()
+            .map(ThresholdPrivKey::share)
+            .collect::<Vec<_>>();
+        shares.sort_by_key(|share| share.child_number.0);
+        shares
+    }
+
+    /// Get the key replacing a child, generating it together with the co-signer of the child
+    /// unless a rotation of that child is already pending. The new key is bound to the address
+    /// of the child, but the child keeps signing until complete_rotation is called.
+    pub fn rotation_child(&mut self, child_number: ChildNumber) -> Result<ExtendedPrivKey> {
+        if let Some(extended_priv_key) = self.rotations.get(child_number.as_ref()) {
+            return Ok(extended_priv_key.clone());
+        }
+        let current = self.existing_child(child_number)?;
+        let mut extended_priv_key =
+            Self::generate_key(&current.co_signer, child_number, Some(&current.id))?;
+        extended_priv_key.account_address = Some(current.get_address()?);
+        self.rotations
+            .insert(child_number.0, extended_priv_key.clone());
+        Ok(extended_priv_key)
+    }
+
+    /// Refresh the shares of a child, replacing it with the key holding the refreshed client
+    /// share. Refreshing a child derived from a master key refreshes the master key, and thereby
+    /// all of its children.
+    pub fn refresh_child(&mut self, child_number: ChildNumber) -> Result<ExtendedPrivKey> {
+        let child = self.existing_child(child_number)?;
+        if !child.is_derived() {
+            let extended_priv_key = child.refresh()?;
+            self.children
+                .insert(child_number.0, extended_priv_key.clone());
+            return Ok(extended_priv_key);
+        }
+
+        let master = self
+            .masters
+            .iter_mut()
+            .find(|master| master.id == child.id)
+            .ok_or_else(|| {
+                WalletError::LibraWalletGeneric(format!(
+                    "Missing master key of child number {}",
+                    child_number.0
+                ))
+            })?;
+        *master = master.refresh()?;
+        let master = master.clone();
+        for child in self.children.values_mut() {
+            if child.is_derived() && child.id == master.id {
+                *child = master.derive_child(child.child_number)?;
+            }
+        }
+        self.existing_child(child_number)
+    }
+
+    /// Replace a child with the key of its pending rotation, once the authentication key of the
+    /// account was rotated on chain. The co-signer only signs for the account with the new key
+    /// from then on.
+    pub fn complete_rotation(&mut self, child_number: ChildNumber) -> Result<()> {
+        let extended_priv_key = self.rotations.get(child_number.as_ref()).ok_or_else(|| {
+            WalletError::LibraWalletGeneric(format!(
+                "No pending key rotation for child number {}",
+                child_number.0
+            ))
+        })?;
+        extended_priv_key.confirm_rotation()?;
+        let extended_priv_key = self.rotations.remove(child_number.as_ref()).unwrap();
+        self.children.insert(child_number.0, extended_priv_key);
+        Ok(())
+    }
+
+    /// Get a child that was already generated, without contacting the co-signer
+    pub fn existing_child(&self, child_number: ChildNumber) -> Result<ExtendedPrivKey> {
+        self.ensure_not_threshold(child_number)?;
+        self.children
+            .get(child_number.as_ref())
+            .cloned()
+            .ok_or_else(|| {
+                WalletError::LibraWalletGeneric(format!(
+                    "Missing key share for child number {}",
+                    child_number.0
+                ))
+            })
+    }
+
+    /// Get the child at child_number. New children are derived from the latest master key,
+    /// which is only generated together with the co-signer when there is no master key for the
+    /// current co-signer yet.
     pub fn private_child(&mut self, child_number: ChildNumber) -> Result<ExtendedPrivKey> {
-        match self.children.get(child_number.as_ref()) {
-            Some(extended_priv_key) => {
-                Ok(extended_priv_key.clone())
-            },
-            None => {
-                let (key_pair, aggregated_public_key, id) =
-                    two_party_eddsa_client::api::generate_key(&self.client_shim).unwrap();
-                let extended_priv_key = ExtendedPrivKey {
-                    client_shim: self.client_shim.clone(),
-                    key_pair,
-                    aggregated_public_key,
-                    id
-                };
-                self.children.insert(child_number.as_ref().clone(), extended_priv_key.clone());
-                Ok(extended_priv_key.clone())
+        self.ensure_not_threshold(child_number)?;
+        if let Some(extended_priv_key) = self.children.get(child_number.as_ref()) {
+            return Ok(extended_priv_key.clone());
+        }
+        let extended_priv_key = match self.masters.last() {
+            Some(master)
+                if master.co_signer == self.co_signer
+                    && master.child_number.0 <= child_number.0 =>
+            {
+                master.derive_child(child_number)?
             }
+            // Deriving from a new master would break the order masters are recovered in.
+            Some(master) if master.child_number.0 > child_number.0 => {
+                Self::generate_key(&self.co_signer, child_number, None)?
+            }
+            _ => {
+                let master = Self::generate_key(&self.co_signer, child_number, None)?;
+                self.masters.push(master.clone());
+                master.derive_child(child_number)?
+            }
+        };
+        self.children
+            .insert(child_number.0, extended_priv_key.clone());
+        Ok(extended_priv_key)
+    }
+
+    /// Get the child at child_number if it holds a threshold key
+    pub fn threshold_child(&self, child_number: ChildNumber) -> Option<&ThresholdPrivKey> {
+        self.threshold_children.get(child_number.as_ref())
+    }
+
+    /// Generate a threshold key at child_number together with the co-signers of config
+    pub fn new_threshold_child(
+        &mut self,
+        child_number: ChildNumber,
+        config: &ThresholdConfig,
+    ) -> Result<ThresholdPrivKey> {
+        if self.children.contains_key(child_number.as_ref())
+            || self.threshold_children.contains_key(child_number.as_ref())
+        {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Child number {} is already generated",
+                child_number.0
+            )));
+        }
+        let co_signer_clients = config
+            .co_signers
+            .iter()
+            .map(|co_signer| co_signer.config.connect())
+            .collect::<Result<Vec<_>>>()?;
+        let clients = co_signer_clients
+            .iter()
+            .map(|client| &**client)
+            .collect::<Vec<_>>();
+        let identity_keys = config.identity_keys()?;
+        let (share, ids) = call_with_retries("Threshold key generation", || {
+            threshold::generate_key(config.parameters(), &clients, &identity_keys)
+        })?;
+        let co_signers = config
+            .co_signers
+            .iter()
+            .zip(ids)
+            .enumerate()
+            .map(|(position, (co_signer, id))| ThresholdCoSigner {
+                name: co_signer.name.clone(),
+                index: threshold::co_signer_index(position),
+                id,
+                co_signer: co_signer.config.clone(),
+            })
+            .collect();
+        let threshold_priv_key = ThresholdPrivKey {
+            share: ThresholdKeyShare {
+                child_number,
+                share,
+                co_signers,
+            },
+            co_signer_clients,
+        };
+        self.threshold_children
+            .insert(child_number.0, threshold_priv_key.clone());
+        Ok(threshold_priv_key)
+    }
+
+    /// Get the public key of a child that was already generated, whatever its kind of key
+    pub fn public_key(&self, child_number: ChildNumber) -> Result<ed25519_dalek::PublicKey> {
+        match self.threshold_child(child_number) {
+            Some(threshold_priv_key) => threshold_priv_key.get_public(),
+            None => self.existing_child(child_number)?.get_public(),
+        }
+    }
+
+    /// Get the address of a child that was already generated, whatever its kind of key
+    pub fn address(&self, child_number: ChildNumber) -> Result<AccountAddress> {
+        match self.threshold_child(child_number) {
+            Some(threshold_priv_key) => threshold_priv_key.get_address(),
+            None => self.existing_child(child_number)?.get_address(),
         }
     }
+
+    fn ensure_not_threshold(&self, child_number: ChildNumber) -> Result<()> {
+        if self.threshold_children.contains_key(child_number.as_ref()) {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Child number {} holds a threshold key, which cannot be refreshed nor rotated",
+                child_number.0
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run a two-party key generation with co_signer, for a key replacing the key stored under
+    /// replaces if any
+    fn generate_key(
+        co_signer: &CoSignerConfig,
+        child_number: ChildNumber,
+        replaces: Option<&str>,
+    ) -> Result<ExtendedPrivKey> {
+        let co_signer_client = co_signer.connect()?;
+        let (key_pair, aggregated_public_key, id, chain_code) =
+            call_with_retries("Two-party key generation", || match replaces {
+                Some(replaces) => two_party::generate_replacement_key(&*co_signer_client, replaces),
+                None => two_party::generate_key(&*co_signer_client),
+            })?;
+        Ok(ExtendedPrivKey {
+            co_signer_client,
+            co_signer: co_signer.clone(),
+            child_number,
+            key_pair,
+            aggregated_public_key,
+            id,
+            account_address: None,
+            epoch: 0,
+            chain_code,
+            derived: false,
+        })
+    }
 }
 
 #[test]
 fn assert_default_child_number() {
     assert_eq!(ChildNumber::default(), ChildNumber(0));
 }
+
+#[test]
+fn test_derived_children() {
+    use crate::WalletLibrary;
+    use std::time::Duration;
+    use types::transaction::{Program, RawTransaction};
+
+    let co_signer = CoSignerConfig::in_process("test_derived_children");
+    let mut wallet = WalletLibrary::new(co_signer.clone()).unwrap();
+    wallet.generate_addresses(3).unwrap();
+    let addresses = wallet.get_addresses().unwrap();
+    let masters = wallet.master_key_shares();
+    assert_eq!(masters.len(), 1);
+
+    // The addresses are those of the public keys derived from the master key, which the children
+    // sign for together with the co-signer.
+    let master = ExtendedPrivKey::from_share(masters[0].clone()).unwrap();
+    let raw_txn = |sender| {
+        RawTransaction::new(
+            sender,
+            0,
+            Program::new(vec![], vec![], vec![]),
+            0,
+            0,
+            Duration::from_secs(0),
+        )
+    };
+    for (i, address) in addresses.iter().enumerate() {
+        let child = master.derive_child(ChildNumber(i as u64)).unwrap();
+        assert!(child.is_derived());
+        assert_eq!(child.get_address().unwrap(), *address);
+        let signed_txn = wallet.sign_txn(address, raw_txn(*address)).unwrap();
+        signed_txn.verify_signature().unwrap();
+        assert_eq!(signed_txn.authenticator().authentication_key(), *address);
+    }
+
+    // A wallet restored from its master share derives the same children.
+    let mut restored =
+        WalletLibrary::from_key_shares(co_signer, 3, masters, vec![], vec![], vec![]).unwrap();
+    assert_eq!(restored.get_addresses().unwrap(), addresses);
+    restored
+        .sign_txn(&addresses[2], raw_txn(addresses[2]))
+        .unwrap()
+        .verify_signature()
+        .unwrap();
+}
@@ -1,9 +1,6 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-/// Error crate
-pub mod error;
-
 /// Internal macros
 #[macro_use]
 pub mod internal_macros;
@@ -17,8 +14,27 @@ pub mod key_factory;
 /// Utils for mnemonic seed
 pub mod mnemonic;
 
+/// Collection of the signatures of MultiEd25519 accounts
+pub mod multi_sig;
+
+/// Human writable backup of the client share
+pub mod paper_backup;
+
+/// Client side of the threshold signing protocol
+pub mod threshold;
+
 /// Utils for wallet library
 pub mod wallet_library;
 
+/// Watch-only wallet descriptors
+pub mod watch_only;
+
+/// Co-signer client modules, shared with the secret service
+pub use co_signer_client::{co_signer, error, session_log, two_party};
+
+/// Co-signer running inside the wallet process
+#[cfg(any(test, feature = "testing"))]
+pub use co_signer_client::in_process;
+
 /// Default imports
 pub use crate::{mnemonic::Mnemonic, wallet_library::WalletLibrary};
@@ -32,7 +32,7 @@ impl ToString for Mnemonic {
 impl Mnemonic {
     /// Generate mnemonic from string
     pub fn from(s: &str) -> Result<Mnemonic> {
-        let words: Vec<_> = s.split(' ').collect();
+        let words: Vec<_> = s.split_whitespace().collect();
         if words.len() < 6 || words.len() % 6 != 0 {
             return Err(WalletError::LibraWalletGeneric(
                 "Mnemonic must have a word count divisible with 6".to_string(),
@@ -87,6 +87,39 @@ impl Mnemonic {
         Ok(Mnemonic(memo))
     }
 
+    /// Decode the mnemonic back to the byte-array it was generated from, verifying its checksum
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bits = Vec::with_capacity(self.0.len() * 11);
+        for word in &self.0 {
+            let idx = WORDS.binary_search(word).map_err(|_| {
+                WalletError::LibraWalletGeneric("Mnemonic contains an unknown word".to_string())
+            })?;
+            for j in 0..11 {
+                bits.push((idx & (1 << (10 - j))) > 0);
+            }
+        }
+        let len = bits.len() * 4 / 33;
+        if len % 4 != 0 || len * 33 / 4 != bits.len() {
+            return Err(WalletError::LibraWalletGeneric(
+                "Mnemonic has an invalid word count".to_string(),
+            ));
+        }
+        let mut data = vec![0u8; len];
+        for i in 0..len {
+            for j in 0..8 {
+                if bits[i * 8 + j] {
+                    data[i] |= 1 << (7 - j);
+                }
+            }
+        }
+        if Self::mnemonic(&data)?.0 != self.0 {
+            return Err(WalletError::LibraWalletGeneric(
+                "Mnemonic checksum mismatch, please check the words".to_string(),
+            ));
+        }
+        Ok(data)
+    }
+
     /// Write mnemonic to output_file_path
     pub fn write(&self, output_file_path: &Path) -> Result<()> {
         if output_file_path.exists() && !output_file_path.is_file() {
@@ -123,6 +156,12 @@ fn test_roundtrip_mnemonic() {
     mnemonic.write(&path).unwrap();
     let other_mnemonic = Mnemonic::read(&path).unwrap();
     assert_eq!(mnemonic.to_string(), other_mnemonic.to_string());
+    assert_eq!(other_mnemonic.to_bytes().unwrap(), buf.to_vec());
+
+    let zeros = Mnemonic::mnemonic(&[0u8; 32]).unwrap();
+    assert!(zeros.to_string().ends_with(" art"));
+    let corrupted = Mnemonic::from(&vec!["abandon"; 24].join(" ")).unwrap();
+    assert!(corrupted.to_bytes().is_err());
 }
 
 const WORDS: [&str; 2048] = [
@@ -0,0 +1,256 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Collection of the signatures of a MultiEd25519 account.
+//!
+//! The transaction to sign is written to a file together with the keys of the account, and the
+//! file travels between the holders of the keys, each adding their signature. Holders may also
+//! sign copies of the file in parallel, the copies being merged afterwards. Once the threshold of
+//! the account is reached the file turns into a SignedTransaction ready to submit.
+
+use crate::error::*;
+use libra_crypto::{hash::CryptoHash, HashValue, PublicKey, Signature};
+use proto_conv::FromProto;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+use types::{
+    account_address::AccountAddress,
+    proto::transaction::RawTransaction as ProtoRawTransaction,
+    transaction::{
+        MultiEd25519PublicKey, MultiEd25519Signature, RawTransaction, RawTransactionBytes,
+        SignedTransaction, TransactionAuthenticator,
+    },
+};
+
+/// A transaction of a MultiEd25519 account, with the signatures collected so far.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PartiallySignedTransaction {
+    /// Hex encoded protobuf bytes of the RawTransaction, which is what every holder signs
+    pub raw_txn: String,
+    /// Number of signatures required
+    pub threshold: u8,
+    /// Hex encoded ed25519 public keys of the account, in the order of its authentication key
+    pub public_keys: Vec<String>,
+    /// Hex encoded signatures, by index of the public key that produced them
+    pub signatures: BTreeMap<u8, String>,
+}
+
+impl PartiallySignedTransaction {
+    /// Start collecting signatures of raw_txn_bytes, the protobuf bytes of a RawTransaction sent
+    /// by the account of public_key.
+    pub fn new(raw_txn_bytes: &[u8], public_key: &MultiEd25519PublicKey) -> Result<Self> {
+        let partial = Self {
+            raw_txn: hex::encode(raw_txn_bytes),
+            threshold: public_key.threshold(),
+            public_keys: public_key
+                .public_keys()
+                .iter()
+                .map(|key| hex::encode(key.to_slice()))
+                .collect(),
+            signatures: BTreeMap::new(),
+        };
+        let sender = partial.raw_txn()?.sender();
+        let address = AccountAddress::from(public_key);
+        if sender != address {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "The sender {} is not the account {} of these keys",
+                sender, address
+            )));
+        }
+        Ok(partial)
+    }
+
+    /// Protobuf bytes of the RawTransaction
+    pub fn raw_txn_bytes(&self) -> Result<Vec<u8>> {
+        decode_hex("raw transaction", &self.raw_txn)
+    }
+
+    /// The RawTransaction being signed
+    pub fn raw_txn(&self) -> Result<RawTransaction> {
+        let proto_raw_txn =
+            protobuf::parse_from_bytes::<ProtoRawTransaction>(&self.raw_txn_bytes()?)?;
+        Ok(RawTransaction::from_proto(proto_raw_txn)?)
+    }
+
+    /// The hash every holder signs
+    pub fn hash(&self) -> Result<HashValue> {
+        Ok(RawTransactionBytes(&self.raw_txn_bytes()?).hash())
+    }
+
+    /// The key of the sender account
+    pub fn public_key(&self) -> Result<MultiEd25519PublicKey> {
+        let public_keys = self
+            .public_keys
+            .iter()
+            .map(|key| Ok(PublicKey::from_slice(&decode_hex("public key", key)?)?))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(MultiEd25519PublicKey::new(public_keys, self.threshold)?)
+    }
+
+    /// Number of signatures still missing to reach the threshold
+    pub fn missing_signatures(&self) -> usize {
+        (self.threshold as usize).saturating_sub(self.signatures.len())
+    }
+
+    /// Add the signature of the holder of public_key, after checking it. Returns the index of the
+    /// key. Signing twice with the same key replaces the previous signature.
+    pub fn add_signature(&mut self, public_key: &PublicKey, signature: &Signature) -> Result<u8> {
+        let index = self.public_key()?.index_of(public_key).ok_or_else(|| {
+            WalletError::LibraWalletGeneric(format!(
+                "{} is not one of the keys of the sender",
+                public_key
+            ))
+        })?;
+        libra_crypto::signing::verify_message(self.hash()?, signature, public_key).map_err(
+            |e| WalletError::InvalidSignature(format!("Signature of key {}: {}", index, e)),
+        )?;
+        self.signatures
+            .insert(index, hex::encode(&signature.to_compact()[..]));
+        Ok(index)
+    }
+
+    /// Add the signatures of other, a copy of the same transaction signed by other holders.
+    pub fn merge(&mut self, other: &Self) -> Result<()> {
+        if self.raw_txn != other.raw_txn
+            || self.threshold != other.threshold
+            || self.public_keys != other.public_keys
+        {
+            return Err(WalletError::LibraWalletGeneric(
+                "Cannot merge signatures of different transactions or keys".to_string(),
+            ));
+        }
+        let public_key = self.public_key()?;
+        for (index, signature) in other.collected_signatures()? {
+            let key = public_key
+                .public_keys()
+                .get(index as usize)
+                .ok_or_else(|| {
+                    WalletError::LibraWalletGeneric(format!("No key at index {}", index))
+                })?;
+            self.add_signature(key, &signature)?;
+        }
+        Ok(())
+    }
+
+    /// The SignedTransaction, once the threshold is reached
+    pub fn into_signed_transaction(self) -> Result<SignedTransaction> {
+        let missing = self.missing_signatures();
+        if missing > 0 {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "{} more signature(s) needed",
+                missing
+            )));
+        }
+        let authenticator = TransactionAuthenticator::MultiEd25519 {
+            public_key: self.public_key()?,
+            signature: MultiEd25519Signature::new(self.collected_signatures()?)?,
+        };
+        Ok(SignedTransaction::from_raw_txn_bytes(
+            self.raw_txn_bytes()?,
+            authenticator,
+        )?)
+    }
+
+    fn collected_signatures(&self) -> Result<Vec<(Signature, u8)>> {
+        self.signatures
+            .iter()
+            .map(|(index, signature)| {
+                let bytes = decode_hex("signature", signature)?;
+                Ok((Signature::from_compact(&bytes)?, *index))
+            })
+            .collect()
+    }
+}
+
+fn decode_hex(what: &str, value: &str) -> Result<Vec<u8>> {
+    hex::decode(value)
+        .map_err(|e| WalletError::LibraWalletGeneric(format!("Invalid {} {}: {}", what, value, e)))
+}
+
+/// Write the partially signed transaction to path.
+pub fn write_partial<P: AsRef<Path>>(partial: &PartiallySignedTransaction, path: &P) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(partial)
+        .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Read the partially signed transaction stored at path.
+pub fn read_partial<P: AsRef<Path>>(path: &P) -> Result<PartiallySignedTransaction> {
+    let partial: PartiallySignedTransaction =
+        serde_json::from_slice(&fs::read(path)?).map_err(|e| {
+            WalletError::LibraWalletGeneric(format!("Malformed partially signed file: {}", e))
+        })?;
+    partial.public_key()?;
+    partial.raw_txn()?;
+    Ok(partial)
+}
+
+#[test]
+fn test_collect_multi_ed25519_signatures() {
+    use libra_crypto::signing::{generate_keypair, sign_message};
+    use proto_conv::IntoProto;
+    use protobuf::Message;
+    use types::transaction::Program;
+
+    let keypairs = vec![generate_keypair(), generate_keypair(), generate_keypair()];
+    let public_key = MultiEd25519PublicKey::new(
+        keypairs.iter().map(|(_, public_key)| *public_key).collect(),
+        2,
+    )
+    .unwrap();
+    let raw_txn = RawTransaction::new(
+        AccountAddress::from(&public_key),
+        0,
+        Program::new(vec![], vec![], vec![]),
+        0,
+        0,
+        std::time::Duration::from_secs(0),
+    );
+    let raw_txn_bytes = raw_txn.into_proto().write_to_bytes().unwrap();
+    let mut first = PartiallySignedTransaction::new(&raw_txn_bytes, &public_key).unwrap();
+    let hash = first.hash().unwrap();
+
+    // Another account cannot be signed for with these keys.
+    let other_txn = RawTransaction::new(
+        AccountAddress::random(),
+        0,
+        Program::new(vec![], vec![], vec![]),
+        0,
+        0,
+        std::time::Duration::from_secs(0),
+    );
+    let other_bytes = other_txn.into_proto().write_to_bytes().unwrap();
+    assert!(PartiallySignedTransaction::new(&other_bytes, &public_key).is_err());
+
+    // Two holders sign copies of the file in parallel.
+    let mut second = first.clone();
+    let signature = sign_message(hash, &keypairs[2].0).unwrap();
+    assert_eq!(first.add_signature(&keypairs[2].1, &signature).unwrap(), 2);
+    let wrong_signature = sign_message(hash, &keypairs[0].0).unwrap();
+    match second.add_signature(&keypairs[1].1, &wrong_signature) {
+        Err(WalletError::InvalidSignature(_)) => (),
+        _ => panic!("A signature by another key must be rejected"),
+    }
+    let (stranger_private_key, stranger_public_key) = generate_keypair();
+    let stranger_signature = sign_message(hash, &stranger_private_key).unwrap();
+    assert!(second
+        .add_signature(&stranger_public_key, &stranger_signature)
+        .is_err());
+    let signature = sign_message(hash, &keypairs[1].0).unwrap();
+    second.add_signature(&keypairs[1].1, &signature).unwrap();
+    assert_eq!(second.missing_signatures(), 1);
+    assert!(second.clone().into_signed_transaction().is_err());
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let path = file.into_temp_path();
+    write_partial(&second, &path).unwrap();
+    first.merge(&read_partial(&path).unwrap()).unwrap();
+    assert_eq!(first.missing_signatures(), 0);
+    let signed_txn = first.into_signed_transaction().unwrap();
+    assert!(signed_txn.verify_signature().is_ok());
+    assert_eq!(
+        signed_txn.authenticator().authentication_key(),
+        AccountAddress::from(&public_key)
+    );
+}
@@ -0,0 +1,292 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A human writable backup of the client share of a two-party wallet.
+//!
+//! A paper backup is made of a mnemonic phrase and a metadata string. The mnemonic encodes the
+//! secret part of the backup: the client share of the master key of the wallet, weighted by its
+//! aggregation coefficient, followed by the chain code the accounts are derived with. The
+//! metadata string holds the public part: the number of accounts, the epoch of the share, the
+//! aggregated public key of the master key and the id of the key at the co-signer, followed by a
+//! checksum. Both are checksummed, so that a typo is detected when the backup is read back.
+//!
+//! The co-signer is not part of the backup, a wallet is restored with the co-signer configured
+//! in the client. The share changes on every refresh, after which a new backup has to be written.
+//!
+//! A backup file starts with the PAPER_BACKUP_HEADER line, followed by the mnemonic and the
+//! metadata lines. Only its owner can read it.
+
+use crate::{
+    co_signer::CoSignerConfig,
+    error::*,
+    io_utils,
+    key_factory::{ChildNumber, KeyShare},
+    mnemonic::Mnemonic,
+};
+use co_signer_protocol::{
+    aggregated_public_key_from_bytes, ed25519_public_key, key_pair_from_weighted_share,
+    scalar_from_bytes, scalar_to_bytes, weighted_share, KeyAgg, CHAIN_CODE_LENGTH,
+};
+use crypto::{digest::Digest, sha2::Sha256};
+use std::{fs, path::Path};
+
+/// Version of the metadata string written by this library.
+pub const PAPER_BACKUP_VERSION: u32 = 1;
+/// First line of a paper backup file.
+pub const PAPER_BACKUP_HEADER: &str = "libra paper backup";
+
+const METADATA_SEPARATOR: char = ':';
+const CHECKSUM_LENGTH: usize = 4;
+const SHARE_LENGTH: usize = 32;
+
+/// Public part of a paper backup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaperBackupMetadata {
+    /// Number of accounts of the wallet
+    pub key_leaf: u64,
+    /// Epoch of the backed up share
+    pub epoch: u64,
+    /// ed25519 encoding of the aggregated public key of the master key
+    pub aggregated_public_key: Vec<u8>,
+    /// Id under which the co-signer stores its share of the master key
+    pub key_id: String,
+}
+
+impl PaperBackupMetadata {
+    /// Encode the metadata as `<version>:<key_leaf>:<epoch>:<public key>:<key id>:<checksum>`
+    pub fn encode(&self) -> String {
+        let body = format!(
+            "{}{sep}{}{sep}{}{sep}{}{sep}{}",
+            PAPER_BACKUP_VERSION,
+            self.key_leaf,
+            self.epoch,
+            hex::encode(&self.aggregated_public_key),
+            self.key_id,
+            sep = METADATA_SEPARATOR
+        );
+        format!("{}{}{}", body, METADATA_SEPARATOR, checksum(&body))
+    }
+
+    /// Decode metadata encoded by encode, verifying its checksum
+    pub fn decode(metadata: &str) -> Result<Self> {
+        let malformed = |reason: &str| {
+            WalletError::LibraWalletGeneric(format!("Malformed metadata: {}", reason))
+        };
+        let metadata = metadata.trim();
+        let separator = metadata
+            .rfind(METADATA_SEPARATOR)
+            .ok_or_else(|| malformed("missing checksum"))?;
+        let (body, sum) = (&metadata[..separator], &metadata[separator + 1..]);
+        if checksum(body) != sum.to_lowercase() {
+            return Err(malformed("checksum mismatch, please check the metadata"));
+        }
+        let fields: Vec<_> = body.splitn(5, METADATA_SEPARATOR).collect();
+        if fields.len() != 5 {
+            return Err(malformed("missing fields"));
+        }
+        let version: u32 = fields[0]
+            .parse()
+            .map_err(|_| malformed("invalid version"))?;
+        if version != PAPER_BACKUP_VERSION {
+            return Err(malformed(&format!(
+                "unsupported version {}, expected {}",
+                version, PAPER_BACKUP_VERSION
+            )));
+        }
+        Ok(Self {
+            key_leaf: fields[1]
+                .parse()
+                .map_err(|_| malformed("invalid number of accounts"))?,
+            epoch: fields[2].parse().map_err(|_| malformed("invalid epoch"))?,
+            aggregated_public_key: hex::decode(fields[3])
+                .map_err(|_| malformed("invalid public key"))?,
+            key_id: fields[4].to_string(),
+        })
+    }
+}
+
+/// Mnemonic phrase and metadata string backing up the master key of a wallet.
+pub struct PaperBackup {
+    /// Secret part of the backup
+    pub mnemonic: Mnemonic,
+    /// Public part of the backup, as encoded by PaperBackupMetadata
+    pub metadata: String,
+}
+
+impl PaperBackup {
+    /// Back up the share of the master key of a wallet with key_leaf accounts
+    pub fn new(master: &KeyShare, key_leaf: u64) -> Result<Self> {
+        if master.chain_code.len() != CHAIN_CODE_LENGTH {
+            return Err(WalletError::LibraWalletGeneric(
+                "Only master keys can be backed up on paper".to_string(),
+            ));
+        }
+        let share = scalar_to_bytes(&weighted_share(
+            &master.key_pair,
+            &master.aggregated_public_key.hash,
+        ))
+        .map_err(|e| WalletError::CoSignerProtocol(format!("{}", e)))?;
+        let mut secret = share;
+        secret.extend_from_slice(&master.chain_code);
+        let aggregated_public_key = ed25519_public_key(&master.aggregated_public_key.apk)
+            .map_err(|e| WalletError::CoSignerProtocol(format!("{}", e)))?
+            .to_slice()
+            .to_vec();
+        let metadata = PaperBackupMetadata {
+            key_leaf,
+            epoch: master.epoch,
+            aggregated_public_key,
+            key_id: master.id.clone(),
+        };
+        Ok(Self {
+            mnemonic: Mnemonic::mnemonic(&secret)?,
+            metadata: metadata.encode(),
+        })
+    }
+
+    /// Parse a backup typed in by the user
+    pub fn from(mnemonic: &str, metadata: &str) -> Result<Self> {
+        PaperBackupMetadata::decode(metadata)?;
+        Ok(Self {
+            mnemonic: Mnemonic::from(mnemonic.trim())?,
+            metadata: metadata.trim().to_string(),
+        })
+    }
+
+    /// Restore the share of the master key, co-signed with co_signer, and the number of accounts
+    /// of the wallet
+    pub fn master_share(&self, co_signer: CoSignerConfig) -> Result<(KeyShare, u64)> {
+        let metadata = PaperBackupMetadata::decode(&self.metadata)?;
+        let secret = self.mnemonic.to_bytes()?;
+        if secret.len() != SHARE_LENGTH + CHAIN_CODE_LENGTH {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Mnemonic encodes {} bytes, expected {}",
+                secret.len(),
+                SHARE_LENGTH + CHAIN_CODE_LENGTH
+            )));
+        }
+        let (key_pair, hash) = scalar_from_bytes(&secret[..SHARE_LENGTH])
+            .map(|share| key_pair_from_weighted_share(&share))
+            .map_err(|e| WalletError::CoSignerProtocol(format!("{}", e)))?;
+        let apk = aggregated_public_key_from_bytes(&metadata.aggregated_public_key)
+            .map_err(|e| WalletError::CoSignerProtocol(format!("{}", e)))?;
+        let share = KeyShare {
+            child_number: ChildNumber(0),
+            key_pair,
+            aggregated_public_key: KeyAgg { apk, hash },
+            id: metadata.key_id,
+            co_signer,
+            account_address: None,
+            epoch: metadata.epoch,
+            chain_code: secret[SHARE_LENGTH..].to_vec(),
+        };
+        Ok((share, metadata.key_leaf))
+    }
+
+    /// Write the backup to path, readable by its owner only: the header line, then the mnemonic
+    /// and the metadata lines
+    pub fn write<P: AsRef<Path>>(&self, path: &P) -> Result<()> {
+        let content = format!(
+            "{}\n{}\n{}\n",
+            PAPER_BACKUP_HEADER,
+            self.mnemonic.to_string(),
+            self.metadata
+        );
+        io_utils::write_secret_file(path, content.as_bytes())
+    }
+
+    /// Read a backup written by write
+    pub fn read<P: AsRef<Path>>(path: &P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let lines: Vec<_> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.len() != 3 || lines[0] != PAPER_BACKUP_HEADER {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "A paper backup holds a \"{}\" line, a mnemonic line and a metadata line",
+                PAPER_BACKUP_HEADER
+            )));
+        }
+        Self::from(lines[1], lines[2])
+    }
+
+    /// Whether the file at path starts with PAPER_BACKUP_HEADER, rather than being an encrypted
+    /// recovery file
+    pub fn is_paper_backup<P: AsRef<Path>>(path: &P) -> bool {
+        fs::read_to_string(path)
+            .map(|content| {
+                content.lines().map(str::trim).find(|line| !line.is_empty())
+                    == Some(PAPER_BACKUP_HEADER)
+            })
+            .unwrap_or(false)
+    }
+}
+
+fn checksum(body: &str) -> String {
+    let mut hash = [0u8; 32];
+    let mut sha2 = Sha256::new();
+    sha2.input(body.as_bytes());
+    sha2.result(&mut hash);
+    hex::encode(&hash[..CHECKSUM_LENGTH])
+}
+
+#[test]
+fn test_paper_backup() {
+    use crate::WalletLibrary;
+
+    let co_signer = CoSignerConfig::in_process("test_paper_backup");
+    let mut wallet = WalletLibrary::new(co_signer.clone()).unwrap();
+    wallet.generate_addresses(3).unwrap();
+    let (address, _) = wallet.new_address().unwrap();
+    wallet.refresh_key_shares(&address).unwrap();
+    wallet.confirm_key_share_refresh(&address).unwrap();
+
+    let backup = wallet.paper_backup().unwrap();
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let path = file.into_temp_path();
+    backup.write(&path).unwrap();
+    assert!(PaperBackup::is_paper_backup(&path));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let mut recovered =
+        WalletLibrary::from_paper_backup(co_signer, &PaperBackup::read(&path).unwrap()).unwrap();
+    assert_eq!(
+        recovered.get_addresses().unwrap(),
+        wallet.get_addresses().unwrap()
+    );
+    let raw_txn = types::transaction::RawTransaction::new(
+        address,
+        0,
+        types::transaction::Program::new(vec![], vec![], vec![]),
+        0,
+        0,
+        std::time::Duration::from_secs(0),
+    );
+    recovered
+        .sign_txn(&address, raw_txn)
+        .unwrap()
+        .verify_signature()
+        .unwrap();
+
+    let mut metadata = backup.metadata.clone();
+    metadata.insert(0, '2');
+    assert!(PaperBackup::from(&backup.mnemonic.to_string(), &metadata).is_err());
+
+    // Without its header, a backup is neither detected nor read.
+    fs::write(
+        &path,
+        format!("{}\n{}\n", backup.mnemonic.to_string(), backup.metadata),
+    )
+    .unwrap();
+    assert!(!PaperBackup::is_paper_backup(&path));
+    assert!(PaperBackup::read(&path).is_err());
+    wallet.write_recovery(&path, "passphrase").unwrap();
+    assert!(!PaperBackup::is_paper_backup(&path));
+}
@@ -0,0 +1,356 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client side of the threshold protocol run against several co-signers.
+//!
+//! The client takes part in every key as participant CLIENT_PARTICIPANT and relays all the
+//! messages between the co-signers, which never talk to each other.
+
+use crate::{
+    co_signer::CoSigner,
+    error::{Result, WalletError},
+    session_log::{SessionLog, SessionOutcome},
+};
+use co_signer_protocol::{
+    signing_message,
+    threshold::{
+        aggregate_nonce, authenticate_participants, KeyGenState, SignerSession,
+        ThresholdKeyGenCommitRequest, ThresholdKeyGenCompleteRequest, ThresholdKeyGenShareRequest,
+        ThresholdParameters, ThresholdShare, ThresholdSignCommitRequest, ThresholdSignRequest,
+        ThresholdSignRevealRequest, CLIENT_PARTICIPANT,
+    },
+    verify, ECPoint, Signature, GE,
+};
+
+/// Index of the co-signer at position in the participant set, the client coming first.
+pub fn co_signer_index(position: usize) -> u64 {
+    CLIENT_PARTICIPANT + 1 + position as u64
+}
+
+/// Generates a new threshold key shared by the client and co_signers, in that order, returning
+/// the client share and the ids under which the co-signers store theirs. The commitment of every
+/// co-signer must be signed by its pinned key in identity_keys before any sub-share is sent, and
+/// every co-signer is checked to end up with the same public key, and with the share the client
+/// expects.
+pub fn generate_key(
+    parameters: ThresholdParameters,
+    co_signers: &[&dyn CoSigner],
+    identity_keys: &[GE],
+) -> Result<(ThresholdShare, Vec<String>)> {
+    if parameters.participants != co_signers.len() as u64 + 1 {
+        return Err(WalletError::LibraWalletGeneric(format!(
+            "{} participants expected, the client and {} co-signers given",
+            parameters.participants,
+            co_signers.len()
+        )));
+    }
+    if identity_keys.len() != co_signers.len() {
+        return Err(WalletError::LibraWalletGeneric(format!(
+            "{} identity keys given for {} co-signers",
+            identity_keys.len(),
+            co_signers.len()
+        )));
+    }
+    let state = KeyGenState::new(parameters, CLIENT_PARTICIPANT).map_err(protocol_error)?;
+
+    let mut participants = vec![state.commitment()];
+    let mut identities = vec![];
+    let mut session_ids = vec![];
+    for (position, co_signer) in co_signers.iter().enumerate() {
+        let index = co_signer_index(position);
+        let response = co_signer
+            .threshold_keygen_commit(ThresholdKeyGenCommitRequest { parameters, index })?;
+        if response.commitment.index != index {
+            return Err(WalletError::CoSignerProtocol(format!(
+                "Co-signer {} committed as participant {}",
+                index, response.commitment.index
+            )));
+        }
+        if response.identity.identity_key != identity_keys[position] {
+            return Err(WalletError::CoSignerProtocol(format!(
+                "Co-signer {} answered with an identity key that is not pinned for it",
+                index
+            )));
+        }
+        participants.push(response.commitment);
+        identities.push(response.identity);
+        session_ids.push(response.session_id);
+    }
+    authenticate_participants(parameters, &participants, &identities, identity_keys)
+        .map_err(protocol_error)?;
+
+    let mut sub_shares = state.sub_shares(&participants).map_err(protocol_error)?;
+    for (position, (co_signer, session_id)) in co_signers.iter().zip(&session_ids).enumerate() {
+        let index = co_signer_index(position);
+        let response = co_signer.threshold_keygen_share(ThresholdKeyGenShareRequest {
+            session_id: session_id.clone(),
+            participants: participants.clone(),
+            identities: identities.clone(),
+        })?;
+        sub_shares.extend(
+            response
+                .sub_shares
+                .into_iter()
+                .filter(|sub_share| sub_share.sender == index),
+        );
+    }
+
+    let client_sub_shares = sub_shares
+        .iter()
+        .filter(|sub_share| sub_share.recipient == CLIENT_PARTICIPANT)
+        .cloned()
+        .collect::<Vec<_>>();
+    let share = state
+        .complete(&participants, &client_sub_shares)
+        .map_err(protocol_error)?;
+    let mut ids = vec![];
+    for (position, (co_signer, session_id)) in co_signers.iter().zip(&session_ids).enumerate() {
+        let index = co_signer_index(position);
+        let response = co_signer.threshold_keygen_complete(ThresholdKeyGenCompleteRequest {
+            session_id: session_id.clone(),
+            sub_shares: sub_shares
+                .iter()
+                .filter(|sub_share| sub_share.recipient == index)
+                .cloned()
+                .collect(),
+        })?;
+        if response.public_key != *share.public_key()
+            || response.share_public_key != share.participant_public_key(index)
+        {
+            return Err(WalletError::CoSignerProtocol(format!(
+                "Co-signer {} completed key generation with a different key",
+                index
+            )));
+        }
+        ids.push(response.id);
+    }
+    Ok((share, ids))
+}
+
+/// Signs a protobuf encoded RawTransaction with co_signers, given as their index, the co-signer
+/// and the id of their share, and with share too if client_signs. Otherwise the client only
+/// relays the messages of the co-signers, share serving to check their contributions. Every
+/// co-signer checks the transaction against its own policy. The nonces and partial signatures of
+/// the co-signers, as well as the aggregated signature, are verified before the signature is
+/// returned, and the session of every co-signer is recorded in session_log.
+pub fn sign(
+    raw_txn_bytes: &[u8],
+    share: &ThresholdShare,
+    co_signers: &[(u64, &dyn CoSigner, &str)],
+    client_signs: bool,
+    session_log: &SessionLog,
+) -> Result<Signature> {
+    let message = signing_message(raw_txn_bytes);
+    let mut signers = co_signers
+        .iter()
+        .map(|(index, _, _)| *index)
+        .collect::<Vec<_>>();
+    if client_signs {
+        signers.push(share.index);
+    }
+    signers.sort();
+    share
+        .parameters
+        .check_signers(&signers)
+        .map_err(protocol_error)?;
+    let mut session = if client_signs {
+        Some(SignerSession::new(share, signers.clone(), message).map_err(protocol_error)?)
+    } else {
+        None
+    };
+
+    let mut commitments = session
+        .iter()
+        .map(SignerSession::commitment)
+        .collect::<Vec<_>>();
+    let mut session_ids = vec![];
+    for (index, co_signer, id) in co_signers {
+        let response = co_signer.threshold_sign_commit(
+            id,
+            ThresholdSignCommitRequest {
+                raw_txn_bytes: raw_txn_bytes.to_vec(),
+                signers: signers.clone(),
+            },
+        )?;
+        if response.commitment.index != *index {
+            return Err(WalletError::CoSignerProtocol(format!(
+                "Co-signer {} committed as signer {}",
+                index, response.commitment.index
+            )));
+        }
+        commitments.push(response.commitment);
+        session_ids.push(response.session_id);
+    }
+    commitments.sort_by_key(|commitment| commitment.index);
+
+    let mut server_nonces = vec![None; co_signers.len()];
+    let mut complete_session = || -> Result<Signature> {
+        let mut reveals = match &mut session {
+            Some(session) => vec![session
+                .reveal(commitments.clone())
+                .map_err(protocol_error)?],
+            None => vec![],
+        };
+        for (position, ((index, co_signer, id), session_id)) in
+            co_signers.iter().zip(&session_ids).enumerate()
+        {
+            let response = co_signer.threshold_sign_reveal(
+                id,
+                ThresholdSignRevealRequest {
+                    session_id: session_id.clone(),
+                    commitments: commitments.clone(),
+                },
+            )?;
+            if response.reveal.index != *index {
+                return Err(WalletError::CoSignerProtocol(format!(
+                    "Co-signer {} revealed as signer {}",
+                    index, response.reveal.index
+                )));
+            }
+            let nonce = hex::encode(response.reveal.reveal.R.pk_to_key_slice());
+            server_nonces[position] = Some(nonce.clone());
+            session_log.check_server_nonce(&nonce)?;
+            reveals.push(response.reveal);
+        }
+        reveals.sort_by_key(|reveal| reveal.index);
+        let r_tot = aggregate_nonce(&commitments, &reveals).map_err(protocol_error)?;
+        let k = Signature::k(&r_tot, share.public_key(), message.as_ref());
+
+        let mut partial_signatures = match &session {
+            Some(session) => vec![session.sign(&reveals).map_err(protocol_error)?],
+            None => vec![],
+        };
+        for ((index, co_signer, id), session_id) in co_signers.iter().zip(&session_ids) {
+            let response = co_signer.threshold_sign(
+                id,
+                ThresholdSignRequest {
+                    session_id: session_id.clone(),
+                    reveals: reveals.clone(),
+                },
+            )?;
+            let nonce = &reveals
+                .iter()
+                .find(|reveal| reveal.index == *index)
+                .expect("Every signer revealed its nonce")
+                .reveal
+                .R;
+            share
+                .verify_partial_signature(
+                    *index,
+                    &signers,
+                    &response.partial_signature,
+                    nonce,
+                    &r_tot,
+                    &k,
+                )
+                .map_err(|e| WalletError::InvalidSignature(format!("{}", e)))?;
+            partial_signatures.push(response.partial_signature);
+        }
+        let signature = Signature::add_signature_parts(partial_signatures);
+        verify(&signature, message.as_ref(), share.public_key()).map_err(|_| {
+            WalletError::InvalidSignature(
+                "Aggregated signature does not verify against the threshold public key".to_string(),
+            )
+        })?;
+        Ok(signature)
+    };
+    let result = complete_session();
+
+    let outcome = match &result {
+        Ok(_) => SessionOutcome::Signed,
+        Err(WalletError::InvalidSignature(reason)) => {
+            SessionOutcome::InvalidSignature(reason.clone())
+        }
+        Err(WalletError::NonceReuse(reason)) => SessionOutcome::NonceReuse(reason.clone()),
+        Err(e) => SessionOutcome::Failed(format!("{}", e)),
+    };
+    for (((_, _, id), session_id), server_nonce) in
+        co_signers.iter().zip(&session_ids).zip(server_nonces)
+    {
+        session_log.record(id, session_id, message, server_nonce, outcome.clone());
+    }
+    result
+}
+
+fn protocol_error<E: std::fmt::Display>(error: E) -> WalletError {
+    WalletError::CoSignerProtocol(format!("{}", error))
+}
+
+#[test]
+fn test_threshold_signing() {
+    use crate::{
+        co_signer::{CoSignerConfig, NamedCoSigner, ThresholdConfig},
+        WalletLibrary,
+    };
+    use types::transaction::{Program, RawTransaction};
+
+    let named =
+        |name: &str| NamedCoSigner::in_process(name, &format!("test_threshold_{}", name)).unwrap();
+    let config = ThresholdConfig {
+        threshold: 2,
+        co_signers: vec![named("server"), named("cold")],
+    };
+    let mut wallet = WalletLibrary::new(CoSignerConfig::in_process("test_threshold")).unwrap();
+    let (two_party_address, _) = wallet.new_address().unwrap();
+
+    // Co-signers whose commitments are not signed by their pinned identity key are refused.
+    let mut swapped = config.clone();
+    swapped.co_signers[0].identity_key = config.co_signers[1].identity_key.clone();
+    swapped.co_signers[1].identity_key = config.co_signers[0].identity_key.clone();
+    assert!(wallet.new_threshold_address(&swapped).is_err());
+    let (address, child) = wallet.new_threshold_address(&config).unwrap();
+    assert_eq!(child.as_ref(), &1);
+    let raw_txn = |sequence_number| {
+        RawTransaction::new(
+            address,
+            sequence_number,
+            Program::new(vec![], vec![], vec![]),
+            0,
+            0,
+            std::time::Duration::from_secs(0),
+        )
+    };
+
+    // Naming threshold co-signers leaves the client share out.
+    let mut sequence_number = 0;
+    for co_signers in &[
+        vec![],
+        vec!["server"],
+        vec!["cold"],
+        vec!["server", "cold"],
+        vec!["cold", "server"],
+    ] {
+        let co_signers = co_signers
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        wallet
+            .sign_txn_with_co_signers(&address, raw_txn(sequence_number), &co_signers)
+            .unwrap()
+            .verify_signature()
+            .unwrap();
+        sequence_number += 1;
+    }
+    assert!(wallet
+        .sign_txn_with_co_signers(&address, raw_txn(5), &["backup".to_string()])
+        .is_err());
+    assert!(wallet
+        .sign_txn_with_co_signers(&two_party_address, raw_txn(5), &["cold".to_string()])
+        .is_err());
+    assert!(wallet.refresh_key_shares(&address).is_err());
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let path = file.into_temp_path();
+    wallet.write_recovery(&path, "passphrase").unwrap();
+    let mut recovered = WalletLibrary::recover(&path, "passphrase").unwrap();
+    assert_eq!(
+        recovered.get_addresses().unwrap(),
+        wallet.get_addresses().unwrap()
+    );
+    recovered
+        .sign_txn_with_co_signers(&address, raw_txn(5), &["cold".to_string()])
+        .unwrap()
+        .verify_signature()
+        .unwrap();
+    assert!(recovered.paper_backup().is_err());
+}
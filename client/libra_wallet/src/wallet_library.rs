@@ -12,18 +12,24 @@
 //! https://github.com/rust-bitcoin/rust-wallet/blob/master/wallet/src/walletlibrary.rs
 
 use crate::{
+    co_signer::{CoSignerConfig, ThresholdConfig},
     error::*,
     io_utils,
-    key_factory::{ChildNumber, KeyFactory},
+    key_factory::{ChildNumber, KeyFactory, KeyShare, ThresholdKeyShare, ThresholdPrivKey},
+    multi_sig::PartiallySignedTransaction,
+    paper_backup::PaperBackup,
+    session_log::{SessionLog, SessionRecord},
+    watch_only::{WatchOnlyAccount, WatchOnlyDescriptor, WATCH_ONLY_DESCRIPTOR_VERSION},
 };
-use libra_crypto::hash::CryptoHash;
+use libra_crypto::{PublicKey, Signature};
+use log::warn;
 use proto_conv::{FromProto, IntoProto};
 use protobuf::Message;
 use std::{collections::HashMap, path::Path};
 use types::{
     account_address::AccountAddress,
     proto::transaction::SignedTransaction as ProtoSignedTransaction,
-    transaction::{RawTransaction, RawTransactionBytes, SignedTransaction},
+    transaction::{RawTransaction, SignedTransaction},
 };
 
 /// WalletLibrary contains all the information needed to recreate a particular wallet
@@ -31,34 +37,271 @@ pub struct WalletLibrary {
     key_factory: KeyFactory,
     addr_map: HashMap<AccountAddress, ChildNumber>,
     key_leaf: ChildNumber,
+    /// Descriptor the wallet was imported from, if it is watch-only
+    watch_only: Option<WatchOnlyDescriptor>,
+    /// Signing sessions run with the co-signers
+    session_log: SessionLog,
 }
 
 impl WalletLibrary {
-    /// Constructor that generates a Mnemonic from OS randomness and subsequently instantiates an
-    /// empty WalletLibrary from that Mnemonic
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
-        Self {
-            key_factory: KeyFactory::new().unwrap(),
+    /// Constructor that instantiates an empty WalletLibrary whose keys are generated together
+    /// with the supplied co-signer
+    pub fn new(co_signer: CoSignerConfig) -> Result<Self> {
+        Ok(Self {
+            key_factory: KeyFactory::new(co_signer)?,
             addr_map: HashMap::new(),
             key_leaf: ChildNumber(0),
+            watch_only: None,
+            session_log: SessionLog::default(),
+        })
+    }
+
+    /// Constructor that restores a WalletLibrary from the KeyShares of its master keys, of its
+    /// children that are not derived from a master key, of the keys of pending rotations and of
+    /// its threshold keys. Every ChildNumber below key_leaf must be covered by at most one
+    /// KeyShare or ThresholdKeyShare, the other children are derived from the master keys.
+    pub fn from_key_shares(
+        co_signer: CoSignerConfig,
+        key_leaf: u64,
+        master_shares: Vec<KeyShare>,
+        shares: Vec<KeyShare>,
+        rotation_shares: Vec<KeyShare>,
+        threshold_shares: Vec<ThresholdKeyShare>,
+    ) -> Result<Self> {
+        let key_factory = KeyFactory::from_shares(
+            co_signer,
+            key_leaf,
+            master_shares,
+            shares,
+            rotation_shares,
+            threshold_shares,
+        )?;
+        let mut addr_map = HashMap::new();
+        for i in 0..key_leaf {
+            let child = ChildNumber(i);
+            let address = key_factory.address(child)?;
+            if addr_map.insert(address, child).is_some() {
+                return Err(WalletError::LibraWalletGeneric(format!(
+                    "Duplicate address {} in key shares",
+                    address
+                )));
+            }
+        }
+        Ok(Self {
+            key_factory,
+            addr_map,
+            key_leaf: ChildNumber(key_leaf),
+            watch_only: None,
+            session_log: SessionLog::default(),
+        })
+    }
+
+    /// Constructor that restores a watch-only WalletLibrary from a descriptor. The wallet knows
+    /// the addresses of the accounts, but holds no key share: every operation that needs one
+    /// fails with WalletError::WatchOnly.
+    pub fn from_watch_only_descriptor(descriptor: WatchOnlyDescriptor) -> Result<Self> {
+        descriptor.validate()?;
+        let mut addr_map = HashMap::new();
+        for account in &descriptor.accounts {
+            let address = account.address()?;
+            if addr_map
+                .insert(address, ChildNumber(account.child_number))
+                .is_some()
+            {
+                return Err(WalletError::LibraWalletGeneric(format!(
+                    "Duplicate address {} in watch-only descriptor",
+                    address
+                )));
+            }
+        }
+        Ok(Self {
+            key_factory: KeyFactory::new(CoSignerConfig::default())?,
+            addr_map,
+            key_leaf: ChildNumber(descriptor.accounts.len() as u64),
+            watch_only: Some(descriptor),
+            session_log: SessionLog::default(),
+        })
+    }
+
+    /// Export the addresses and aggregated public keys of the accounts of the wallet, without
+    /// any key share
+    pub fn watch_only_descriptor(&self) -> Result<WatchOnlyDescriptor> {
+        if let Some(descriptor) = &self.watch_only {
+            return Ok(descriptor.clone());
+        }
+        let mut accounts = vec![];
+        for (i, address) in self.get_addresses()?.into_iter().enumerate() {
+            let public_key = self.key_factory.public_key(ChildNumber(i as u64))?;
+            accounts.push(WatchOnlyAccount {
+                child_number: i as u64,
+                address: hex::encode(address),
+                public_key: hex::encode(public_key.to_bytes()),
+            });
+        }
+        Ok(WatchOnlyDescriptor {
+            version: WATCH_ONLY_DESCRIPTOR_VERSION,
+            accounts,
+        })
+    }
+
+    /// Whether the wallet is watch-only, i.e. cannot sign
+    pub fn is_watch_only(&self) -> bool {
+        self.watch_only.is_some()
+    }
+
+    fn ensure_not_watch_only(&self, operation: &str) -> Result<()> {
+        if self.watch_only.is_some() {
+            return Err(WalletError::WatchOnly(format!(
+                "Cannot {} with a watch-only wallet",
+                operation
+            )));
         }
+        Ok(())
     }
 
-    /// Function that writes the wallet Mnemonic to file
-    /// NOTE: This is not secure, and in general the Mnemonic would need to be decrypted before it
-    /// can be written to file; otherwise the encrypted Mnemonic should be written to file
-    pub fn write_recovery(&self, output_file_path: &Path) -> Result<()> {
-        io_utils::write_recovery(&self, &output_file_path)?;
+    /// Function that writes the client key shares of the wallet to file, encrypted under a key
+    /// derived from passphrase
+    pub fn write_recovery(&self, output_file_path: &Path, passphrase: &str) -> Result<()> {
+        self.ensure_not_watch_only("write key shares")?;
+        let in_process = self
+            .master_key_shares()
+            .into_iter()
+            .chain(self.key_shares())
+            .chain(self.rotation_key_shares())
+            .any(|share| share.co_signer.is_in_process());
+        if in_process {
+            warn!(
+                "Writing recovery file {:?} of keys co-signed in process: their server shares are \
+                 lost when the process exits, the file cannot restore them",
+                output_file_path
+            );
+        }
+        io_utils::write_recovery(&self, &output_file_path, passphrase)?;
         Ok(())
     }
 
     /// Recover wallet from input_file_path
-    pub fn recover(input_file_path: &Path) -> Result<WalletLibrary> {
-        let wallet = io_utils::recover(&input_file_path)?;
+    pub fn recover(input_file_path: &Path, passphrase: &str) -> Result<WalletLibrary> {
+        let wallet = io_utils::recover(&input_file_path, passphrase)?;
         Ok(wallet)
     }
 
+    /// Export a paper backup of the wallet, i.e. a mnemonic phrase and a metadata string from
+    /// which its accounts can be restored. Only wallets whose accounts are all derived from a
+    /// single master key can be backed up on paper; other wallets rely on the recovery file.
+    pub fn paper_backup(&self) -> Result<PaperBackup> {
+        self.ensure_not_watch_only("write key shares")?;
+        let masters = self.master_key_shares();
+        match masters.first() {
+            Some(master)
+                if masters.len() == 1
+                    && master.child_number.0 == 0
+                    && self.key_shares().is_empty()
+                    && self.rotation_key_shares().is_empty()
+                    && self.threshold_key_shares().is_empty() =>
+            {
+                PaperBackup::new(master, self.key_leaf())
+            }
+            Some(_) => Err(WalletError::LibraWalletGeneric(
+                "Some accounts are not derived from the master key of the wallet, only the \
+                 recovery file can back them up"
+                    .to_string(),
+            )),
+            None => Err(WalletError::LibraWalletGeneric(
+                "The wallet has no account to back up yet".to_string(),
+            )),
+        }
+    }
+
+    /// Restore a WalletLibrary from a paper backup, co-signing with co_signer
+    pub fn from_paper_backup(co_signer: CoSignerConfig, backup: &PaperBackup) -> Result<Self> {
+        let (master, key_leaf) = backup.master_share(co_signer.clone())?;
+        Self::from_key_shares(co_signer, key_leaf, vec![master], vec![], vec![], vec![])
+    }
+
+    /// Get the co-signer new addresses are generated with
+    pub fn co_signer(&self) -> &CoSignerConfig {
+        self.key_factory.co_signer()
+    }
+
+    /// Switch the co-signer new addresses are generated with. Existing addresses keep the
+    /// co-signer they were generated with.
+    pub fn set_co_signer(&mut self, co_signer: CoSignerConfig) -> Result<()> {
+        self.ensure_not_watch_only("set the co-signer")?;
+        self.key_factory.set_co_signer(co_signer)
+    }
+
+    /// Export the client key shares of the generated children that are not derived from a
+    /// master key
+    pub fn key_shares(&self) -> Vec<KeyShare> {
+        self.key_factory.shares()
+    }
+
+    /// Export the client key shares of the master keys children are derived from
+    pub fn master_key_shares(&self) -> Vec<KeyShare> {
+        self.key_factory.master_shares()
+    }
+
+    /// Export the client key shares of the keys of pending rotations
+    pub fn rotation_key_shares(&self) -> Vec<KeyShare> {
+        self.key_factory.rotation_shares()
+    }
+
+    /// Export the client key shares of the threshold keys
+    pub fn threshold_key_shares(&self) -> Vec<ThresholdKeyShare> {
+        self.key_factory.threshold_shares()
+    }
+
+    /// Get the threshold key of an account, if it is a threshold account
+    pub fn threshold_key(&self, addr: &AccountAddress) -> Result<Option<&ThresholdPrivKey>> {
+        let child = self.child_number(addr)?;
+        Ok(self.key_factory.threshold_child(child))
+    }
+
+    /// Function that starts the rotation of the key of an account, and returns the
+    /// authentication key the account has to be rotated to. The new key is generated together
+    /// with the co-signer of the current key, or reused if a rotation of the account is already
+    /// pending. The current key keeps signing the transactions of the account, including the
+    /// rotation transaction, until complete_key_rotation is called.
+    pub fn start_key_rotation(&mut self, addr: &AccountAddress) -> Result<AccountAddress> {
+        self.ensure_not_watch_only("rotate keys")?;
+        let child = self.child_number(addr)?;
+        self.key_factory
+            .rotation_child(child)?
+            .get_authentication_key()
+    }
+
+    /// Function that makes the key of the pending rotation of an account the one signing its
+    /// transactions. To be called once the rotation transaction is committed.
+    pub fn complete_key_rotation(&mut self, addr: &AccountAddress) -> Result<()> {
+        self.ensure_not_watch_only("rotate keys")?;
+        let child = self.child_number(addr)?;
+        self.key_factory.complete_rotation(child)
+    }
+
+    /// Function that refreshes the key shares of an account, and returns their new epoch. The
+    /// address of the account is unchanged. The wallet has to be persisted before calling
+    /// confirm_key_share_refresh.
+    pub fn refresh_key_shares(&mut self, addr: &AccountAddress) -> Result<u64> {
+        self.ensure_not_watch_only("refresh key shares")?;
+        let child = self.child_number(addr)?;
+        Ok(self.key_factory.refresh_child(child)?.epoch())
+    }
+
+    /// Function that lets the co-signer of an account drop its share of the previous epoch, once
+    /// the refreshed client share is persisted
+    pub fn confirm_key_share_refresh(&self, addr: &AccountAddress) -> Result<()> {
+        self.ensure_not_watch_only("refresh key shares")?;
+        let child = self.child_number(addr)?;
+        self.key_factory.existing_child(child)?.confirm_refresh()
+    }
+
+    fn child_number(&self, addr: &AccountAddress) -> Result<ChildNumber> {
+        self.addr_map.get(addr).cloned().ok_or_else(|| {
+            WalletError::LibraWalletGeneric(format!("Address {} is not in this wallet", addr))
+        })
+    }
+
     /// Get the current ChildNumber in u64 format
     pub fn key_leaf(&self) -> u64 {
         self.key_leaf.0
@@ -66,6 +309,7 @@ impl WalletLibrary {
 
     /// Function that iterates from the current key_leaf until the supplied depth
     pub fn generate_addresses(&mut self, depth: u64) -> Result<()> {
+        self.ensure_not_watch_only("generate addresses")?;
         let current = self.key_leaf.0;
         if current > depth {
             return Err(WalletError::LibraWalletGeneric(
@@ -83,6 +327,7 @@ impl WalletLibrary {
         &mut self,
         child_number: ChildNumber,
     ) -> Result<AccountAddress> {
+        self.ensure_not_watch_only("generate addresses")?;
         let child = self.key_factory.private_child(child_number)?;
         child.get_address()
     }
@@ -90,6 +335,7 @@ impl WalletLibrary {
     /// Function that generates a new key and adds it to the addr_map and subsequently returns the
     /// AccountAddress associated to the PrivateKey, along with it's ChildNumber
     pub fn new_address(&mut self) -> Result<(AccountAddress, ChildNumber)> {
+        self.ensure_not_watch_only("generate addresses")?;
         let child = self.key_factory.private_child(self.key_leaf)?;
         let address = child.get_address()?;
         let child = self.key_leaf;
@@ -102,6 +348,28 @@ impl WalletLibrary {
         }
     }
 
+    /// Function that generates a new threshold key together with the co-signers of config, adds
+    /// it to the addr_map and returns the AccountAddress associated to it, along with its
+    /// ChildNumber
+    pub fn new_threshold_address(
+        &mut self,
+        config: &ThresholdConfig,
+    ) -> Result<(AccountAddress, ChildNumber)> {
+        self.ensure_not_watch_only("generate addresses")?;
+        let child = self.key_leaf;
+        let address = self
+            .key_factory
+            .new_threshold_child(child, config)?
+            .get_address()?;
+        self.key_leaf.increment();
+        match self.addr_map.insert(address, child) {
+            Some(_) => Err(WalletError::LibraWalletGeneric(
+                "This address is already in your wallet".to_string(),
+            )),
+            None => Ok((address, child)),
+        }
+    }
+
     /// Returns a list of all addresses controlled by this wallet that are currently held by the
     /// addr_map
     pub fn get_addresses(&self) -> Result<Vec<AccountAddress>> {
@@ -128,6 +396,18 @@ impl WalletLibrary {
         Ok(ret)
     }
 
+    /// Persist the signing sessions of the wallet to path, loading the sessions already recorded
+    /// there. Nonces revealed in earlier sessions are then refused by later ones.
+    pub fn set_session_log<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.session_log = SessionLog::open(path)?;
+        Ok(())
+    }
+
+    /// Signing sessions run with the co-signers, oldest first
+    pub fn session_records(&self) -> Vec<SessionRecord> {
+        self.session_log.records()
+    }
+
     /// Simple public function that allows to sign a Libra RawTransaction with the PrivateKey
     /// associated to a particular AccountAddress. If the PrivateKey associated to an
     /// AccountAddress is not contained in the addr_map, then this function will return an Error
@@ -136,20 +416,93 @@ impl WalletLibrary {
         addr: &AccountAddress,
         txn: RawTransaction,
     ) -> Result<SignedTransaction> {
-        if let Some(child) = self.addr_map.get(addr) {
-            let raw_bytes = txn.into_proto().write_to_bytes()?;
-            let txn_hashvalue = RawTransactionBytes(&raw_bytes).hash();
+        self.sign_txn_with_co_signers(addr, txn, &[])
+    }
 
-            let child_key = self.key_factory.private_child(child.clone())?;
-            let signature = child_key.sign(txn_hashvalue);
-            let public_key = child_key.get_public();
+    /// Sign a RawTransaction like sign_txn, choosing by name the co-signers that take part when
+    /// the account holds a threshold key. The first co-signers of the key are used when none is
+    /// named, and the client share is left out when the co-signers named reach the threshold.
+    /// Two-party accounts always sign with their single co-signer, naming any is an error.
+    pub fn sign_txn_with_co_signers(
+        &mut self,
+        addr: &AccountAddress,
+        txn: RawTransaction,
+        co_signers: &[String],
+    ) -> Result<SignedTransaction> {
+        self.ensure_not_watch_only("sign transactions")?;
+        let raw_bytes = txn.into_proto().write_to_bytes()?;
+        let (signature, public_key) = self.sign_raw_txn_bytes(addr, &raw_bytes, co_signers)?;
 
-            let mut signed_txn = ProtoSignedTransaction::new();
-            signed_txn.set_raw_txn_bytes(raw_bytes.to_vec());
-            signed_txn.set_sender_public_key(public_key.to_bytes().to_vec());
-            signed_txn.set_sender_signature(signature.to_bytes().to_vec());
+        let mut signed_txn = ProtoSignedTransaction::new();
+        signed_txn.set_raw_txn_bytes(raw_bytes.to_vec());
+        signed_txn.set_sender_public_key(public_key.to_bytes().to_vec());
+        signed_txn.set_sender_signature(signature.to_bytes().to_vec());
 
-            Ok(SignedTransaction::from_proto(signed_txn)?)
+        Ok(SignedTransaction::from_proto(signed_txn)?)
+    }
+
+    /// Add to partial, a transaction of a MultiEd25519 account, the signature of the account addr
+    /// of this wallet, whose key has to be one of the keys of the MultiEd25519 account. Returns
+    /// the index of the key among them.
+    pub fn sign_multi_ed25519_txn(
+        &mut self,
+        addr: &AccountAddress,
+        partial: &mut PartiallySignedTransaction,
+    ) -> Result<u8> {
+        self.ensure_not_watch_only("sign transactions")?;
+        let public_key = self.get_public_key(addr)?;
+        if partial.public_key()?.index_of(&public_key).is_none() {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "The key of account {} is not one of the keys of the sender",
+                addr
+            )));
+        }
+        let (signature, _) = self.sign_raw_txn_bytes(addr, &partial.raw_txn_bytes()?, &[])?;
+        partial.add_signature(
+            &public_key,
+            &Signature::from_compact(&signature.to_bytes())?,
+        )
+    }
+
+    /// The public key of the account addr of this wallet, whatever its kind of key. Works on
+    /// watch-only wallets too, e.g. to simulate a transaction before it is signed.
+    pub fn get_public_key(&self, addr: &AccountAddress) -> Result<PublicKey> {
+        Ok(PublicKey::from_slice(&self.public_key(addr)?.to_bytes())?)
+    }
+
+    fn public_key(&self, addr: &AccountAddress) -> Result<ed25519_dalek::PublicKey> {
+        match self.addr_map.get(addr) {
+            Some(child) => self.key_factory.public_key(*child),
+            None => Err(WalletError::LibraWalletGeneric(
+                "Well, that address is nowhere to be found... This is awkward".to_string(),
+            )),
+        }
+    }
+
+    fn sign_raw_txn_bytes(
+        &mut self,
+        addr: &AccountAddress,
+        raw_bytes: &[u8],
+        co_signers: &[String],
+    ) -> Result<(ed25519_dalek::Signature, ed25519_dalek::PublicKey)> {
+        if let Some(child) = self.addr_map.get(addr).cloned() {
+            match self.key_factory.threshold_child(child) {
+                Some(threshold_key) => Ok((
+                    threshold_key.sign(raw_bytes, co_signers, &self.session_log)?,
+                    threshold_key.get_public()?,
+                )),
+                None if !co_signers.is_empty() => Err(WalletError::LibraWalletGeneric(format!(
+                    "Account {} is not a threshold account, its co-signers cannot be chosen",
+                    addr
+                ))),
+                None => {
+                    let child_key = self.key_factory.private_child(child)?;
+                    Ok((
+                        child_key.sign(raw_bytes, &self.session_log)?,
+                        child_key.get_public()?,
+                    ))
+                }
+            }
         } else {
             Err(WalletError::LibraWalletGeneric(
                 "Well, that address is nowhere to be found... This is awkward".to_string(),
@@ -157,3 +510,35 @@ impl WalletLibrary {
         }
     }
 }
+
+#[test]
+fn test_in_process_signing() {
+    use crate::co_signer::CoSignerConfig;
+    use types::transaction::{Program, RawTransaction};
+
+    let mut wallet = WalletLibrary::new(CoSignerConfig::in_process("test_in_process")).unwrap();
+    let (address, _) = wallet.new_address().unwrap();
+    let raw_txn = |sequence_number| {
+        RawTransaction::new(
+            address,
+            sequence_number,
+            Program::new(vec![], vec![], vec![]),
+            0,
+            0,
+            std::time::Duration::from_secs(0),
+        )
+    };
+    let signed_txn = wallet.sign_txn(&address, raw_txn(0)).unwrap();
+    signed_txn.verify_signature().unwrap();
+
+    assert_eq!(wallet.refresh_key_shares(&address).unwrap(), 1);
+    wallet.confirm_key_share_refresh(&address).unwrap();
+    let signed_txn = wallet.sign_txn(&address, raw_txn(1)).unwrap();
+    signed_txn.verify_signature().unwrap();
+    assert_eq!(wallet.get_addresses().unwrap(), vec![address]);
+    let records = wallet.session_records();
+    assert_eq!(records.len(), 2);
+    assert!(records
+        .iter()
+        .all(|record| record.outcome == crate::session_log::SessionOutcome::Signed));
+}
@@ -0,0 +1,141 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A module to export and import the public part of a two-party wallet.
+//!
+//! A watch-only descriptor lists, for every account of the wallet, its ChildNumber, its address
+//! and the aggregated public key its transactions are signed with. It holds no key share, so it
+//! can be handed out to monitor the accounts of a wallet without being able to sign for them.
+
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fs, path::Path};
+use types::account_address::AccountAddress;
+
+/// Version of the watch-only descriptor format written by this library.
+pub const WATCH_ONLY_DESCRIPTOR_VERSION: u32 = 1;
+
+/// Public information about a single account of the wallet.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WatchOnlyAccount {
+    /// ChildNumber of the account in the wallet
+    pub child_number: u64,
+    /// Hex encoded address of the account
+    pub address: String,
+    /// Hex encoded aggregated ed25519 public key the transactions of the account are signed with
+    pub public_key: String,
+}
+
+impl WatchOnlyAccount {
+    /// Parse the address of the account
+    pub fn address(&self) -> Result<AccountAddress> {
+        let bytes = hex::decode(&self.address).map_err(|e| {
+            WalletError::LibraWalletGeneric(format!("Invalid address {}: {}", self.address, e))
+        })?;
+        Ok(AccountAddress::try_from(&bytes[..])?)
+    }
+
+    /// Parse the aggregated public key of the account
+    pub fn public_key(&self) -> Result<ed25519_dalek::PublicKey> {
+        let bytes = hex::decode(&self.public_key).map_err(|e| {
+            WalletError::LibraWalletGeneric(format!(
+                "Invalid public key {}: {}",
+                self.public_key, e
+            ))
+        })?;
+        Ok(ed25519_dalek::PublicKey::from_bytes(&bytes)?)
+    }
+}
+
+/// Public part of a wallet, ordered by ChildNumber.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct WatchOnlyDescriptor {
+    /// Format version
+    pub version: u32,
+    /// Accounts of the wallet
+    pub accounts: Vec<WatchOnlyAccount>,
+}
+
+impl WatchOnlyDescriptor {
+    /// Check that the descriptor is well formed: every account parses, and the ChildNumbers of
+    /// the accounts are exactly 0..accounts.len(), as in the wallet it was exported from.
+    pub fn validate(&self) -> Result<()> {
+        if self.version != WATCH_ONLY_DESCRIPTOR_VERSION {
+            return Err(WalletError::LibraWalletGeneric(format!(
+                "Unsupported watch-only descriptor version {}, expected {}",
+                self.version, WATCH_ONLY_DESCRIPTOR_VERSION
+            )));
+        }
+        for (i, account) in self.accounts.iter().enumerate() {
+            if account.child_number != i as u64 {
+                return Err(WalletError::LibraWalletGeneric(format!(
+                    "Expected child number {} in watch-only descriptor, found {}",
+                    i, account.child_number
+                )));
+            }
+            account.address()?;
+            account.public_key()?;
+        }
+        Ok(())
+    }
+}
+
+/// Write the watch-only descriptor to path.
+pub fn write_descriptor<P: AsRef<Path>>(descriptor: &WatchOnlyDescriptor, path: &P) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(descriptor)
+        .map_err(|e| WalletError::LibraWalletGeneric(format!("{}", e)))?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Read and validate the watch-only descriptor stored at path.
+pub fn read_descriptor<P: AsRef<Path>>(path: &P) -> Result<WatchOnlyDescriptor> {
+    let descriptor: WatchOnlyDescriptor = serde_json::from_slice(&fs::read(path)?)
+        .map_err(|e| WalletError::LibraWalletGeneric(format!("Malformed descriptor: {}", e)))?;
+    descriptor.validate()?;
+    Ok(descriptor)
+}
+
+#[test]
+fn test_watch_only_wallet() {
+    use crate::WalletLibrary;
+    use types::transaction::{Program, RawTransaction};
+
+    let secret_key = ed25519_dalek::SecretKey::from_bytes(&[1u8; 32]).unwrap();
+    let public_key = ed25519_dalek::PublicKey::from(&secret_key);
+    let address = AccountAddress::random();
+    let descriptor = WatchOnlyDescriptor {
+        version: WATCH_ONLY_DESCRIPTOR_VERSION,
+        accounts: vec![WatchOnlyAccount {
+            child_number: 0,
+            address: hex::encode(address),
+            public_key: hex::encode(public_key.to_bytes()),
+        }],
+    };
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let path = file.into_temp_path();
+    write_descriptor(&descriptor, &path).unwrap();
+
+    let mut wallet =
+        WalletLibrary::from_watch_only_descriptor(read_descriptor(&path).unwrap()).unwrap();
+    assert!(wallet.is_watch_only());
+    assert_eq!(wallet.get_addresses().unwrap(), vec![address]);
+    assert_eq!(wallet.watch_only_descriptor().unwrap(), descriptor);
+    let raw_txn = RawTransaction::new(
+        address,
+        0,
+        Program::new(vec![], vec![], vec![]),
+        0,
+        0,
+        std::time::Duration::from_secs(0),
+    );
+    match wallet.sign_txn(&address, raw_txn) {
+        Err(WalletError::WatchOnly(_)) => (),
+        _ => panic!("A watch-only wallet must not sign"),
+    }
+    assert!(wallet.new_address().is_err());
+
+    let mut gap = descriptor.clone();
+    gap.accounts[0].child_number = 1;
+    assert!(gap.validate().is_err());
+}
@@ -16,10 +16,16 @@ impl Command for AccountCommand {
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
         let commands: Vec<Box<dyn Command>> = vec![
             Box::new(AccountCommandCreate {}),
+            Box::new(AccountCommandCreateThreshold {}),
             Box::new(AccountCommandListAccounts {}),
             Box::new(AccountCommandRecoverWallet {}),
             Box::new(AccountCommandWriteRecovery {}),
+            Box::new(AccountCommandPaperBackup {}),
+            Box::new(AccountCommandExportWatchOnly {}),
             Box::new(AccountCommandMint {}),
+            Box::new(AccountCommandSetCoSigner {}),
+            Box::new(AccountCommandRotate {}),
+            Box::new(AccountCommandRefresh {}),
         ];
 
         subcommand_execute(&params[0], commands, client, &params[1..]);
@@ -49,6 +55,32 @@ impl Command for AccountCommandCreate {
     }
 }
 
+/// Sub command to create a threshold account shared with several co-signers.
+pub struct AccountCommandCreateThreshold {}
+
+impl Command for AccountCommandCreateThreshold {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["create_threshold", "ct"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<threshold_config_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Create a t-of-n account with the co-signers described in the TOML config"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Creating threshold account");
+        match client.create_threshold_account(&params) {
+            Ok(account_data) => println!(
+                "Created threshold account #{} address {}",
+                account_data.index,
+                hex::encode(account_data.address)
+            ),
+            Err(e) => report_error("Error creating threshold account", e),
+        }
+    }
+}
+
 /// Sub command to recover wallet from the file specified.
 pub struct AccountCommandRecoverWallet {}
 
@@ -60,7 +92,7 @@ impl Command for AccountCommandRecoverWallet {
         "<file_path>"
     }
     fn get_description(&self) -> &'static str {
-        "Recover Libra wallet from the file path"
+        "Recover Libra wallet from the file path, either a recovery file or a paper backup"
     }
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
         println!(">> Recovering Wallet");
@@ -90,13 +122,65 @@ impl Command for AccountCommandWriteRecovery {
         "<file_path>"
     }
     fn get_description(&self) -> &'static str {
-        "Save Libra wallet mnemonic recovery seed to disk"
+        "Save encrypted Libra wallet key shares to disk"
     }
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        println!(">> Saving Libra wallet mnemonic recovery seed to disk");
+        println!(">> Saving encrypted Libra wallet key shares to disk");
         match client.write_recovery(&params) {
-            Ok(_) => println!("Saved mnemonic seed to disk"),
-            Err(e) => report_error("Error writing mnemonic recovery seed to file", e),
+            Ok(_) => println!("Saved wallet key shares to disk"),
+            Err(e) => report_error("Error writing wallet key shares to file", e),
+        }
+    }
+}
+
+/// Sub command to write a paper backup of the wallet to the file specified.
+pub struct AccountCommandPaperBackup {}
+
+impl Command for AccountCommandPaperBackup {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["backup", "b"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Save a mnemonic backup of the wallet key share to disk, restorable with `account recover`"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Writing paper backup to disk");
+        match client.write_paper_backup(&params) {
+            Ok(backup) => {
+                println!("Metadata: {}", backup.metadata);
+                println!(
+                    "Saved paper backup to {}. Write down its mnemonic and metadata lines, the \
+                     mnemonic is secret, then delete the file. The backup has to be written \
+                     again after refreshing the key shares",
+                    params[1]
+                );
+            }
+            Err(e) => report_error("Error writing paper backup", e),
+        }
+    }
+}
+
+/// Sub command to export the watch-only descriptor of the wallet to the file specified.
+pub struct AccountCommandExportWatchOnly {}
+
+impl Command for AccountCommandExportWatchOnly {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["export_watch_only", "ew"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Save the addresses and public keys of the wallet accounts, without key shares, to disk"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Exporting watch-only descriptor to disk");
+        match client.export_watch_only(&params) {
+            Ok(_) => println!("Saved watch-only descriptor to disk"),
+            Err(e) => report_error("Error writing watch-only descriptor to file", e),
         }
     }
 }
@@ -124,7 +208,7 @@ impl Command for AccountCommandMint {
         vec!["mint", "mintb", "m", "mb"]
     }
     fn get_params_help(&self) -> &'static str {
-        "<receiver_account_ref_id>|<receiver_account_address> <number_of_coins>"
+        "<receiver_account_ref_id>|<receiver_account_address>|<receiver_label> <number_of_coins>"
     }
     fn get_description(&self) -> &'static str {
         "Mint coins to the account. Suffix 'b' is for blocking"
@@ -149,3 +233,72 @@ impl Command for AccountCommandMint {
         }
     }
 }
+
+/// Sub command to set the co-signer used for new accounts.
+pub struct AccountCommandSetCoSigner {}
+
+impl Command for AccountCommandSetCoSigner {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["co_signer", "cs"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<co_signer_config_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Use the co-signer described in the TOML config for accounts created from now on"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Setting co-signer for new accounts");
+        match client.set_co_signer(&params) {
+            Ok(co_signer) => println!("New accounts will be co-signed by {}", co_signer.url),
+            Err(e) => report_error("Error setting co-signer", e),
+        }
+    }
+}
+
+/// Sub command to rotate the authentication key of an account.
+pub struct AccountCommandRotate {}
+
+impl Command for AccountCommandRotate {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["rotate", "ro"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Replace the key shares of the account with freshly generated ones, keeping its address"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Rotating authentication key");
+        match client.rotate_authentication_key(&params) {
+            Ok(new_key) => println!(
+                "Authentication key rotated to {}, the account is now signed with the new key",
+                hex::encode(new_key)
+            ),
+            Err(e) => report_error("Error rotating authentication key", e),
+        }
+    }
+}
+
+/// Sub command to refresh the key shares of an account.
+pub struct AccountCommandRefresh {}
+
+impl Command for AccountCommandRefresh {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["refresh", "rf"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<account_ref_id>|<account_address>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Re-randomize the key shares of the account with its co-signer, keeping its public key"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Refreshing key shares");
+        match client.refresh_key_shares(&params) {
+            Ok(epoch) => println!("Key shares refreshed, now at epoch {}", epoch),
+            Err(e) => report_error("Error refreshing key shares", e),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::commands::is_address;
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+};
+use types::account_address::AccountAddress;
+
+/// An address saved under a label.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressBookEntry {
+    /// Address the label stands for
+    pub address: AccountAddress,
+    /// Free text about the address, e.g. who it belongs to
+    pub note: Option<String>,
+}
+
+/// How an entry is written to the address book file, the address being hex encoded.
+#[derive(Deserialize, Serialize)]
+struct StoredEntry {
+    address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+/// Labels of the addresses the user deals with, persisted to a TOML file after every change.
+///
+/// A label can stand for any address, or for one of the accounts of the wallet, in which case it
+/// is the nickname of that account. Labels can be used wherever the client takes an account index
+/// or address, so they can be neither of these.
+#[derive(Debug)]
+pub struct AddressBook {
+    path: PathBuf,
+    entries: BTreeMap<String, AddressBookEntry>,
+}
+
+impl AddressBook {
+    /// Loads the address book persisted at path, which starts out empty if the file does not
+    /// exist yet.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = BTreeMap::new();
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|_| format!("Unable to read address book {:?}", path))?;
+            let stored: BTreeMap<String, StoredEntry> = toml::from_str(&content)
+                .with_context(|_| format!("Malformed address book {:?}", path))?;
+            for (label, entry) in stored {
+                validate_label(&label)?;
+                let bytes = hex::decode(&entry.address)?;
+                entries.insert(
+                    label,
+                    AddressBookEntry {
+                        address: AccountAddress::try_from(&bytes[..])?,
+                        note: entry.note,
+                    },
+                );
+            }
+        }
+        Ok(Self { path, entries })
+    }
+
+    /// Saves address under label, along with an optional note. A label already in use has to be
+    /// removed first, so that an address is never replaced by mistake.
+    pub fn add(
+        &mut self,
+        label: &str,
+        address: AccountAddress,
+        note: Option<String>,
+    ) -> Result<()> {
+        validate_label(label)?;
+        ensure!(
+            !self.entries.contains_key(label),
+            "Label {} already stands for {}, remove it first",
+            label,
+            hex::encode(self.entries[label].address)
+        );
+        self.entries
+            .insert(label.to_string(), AddressBookEntry { address, note });
+        self.save()
+    }
+
+    /// Removes label, returning the entry it stood for.
+    pub fn remove(&mut self, label: &str) -> Result<AddressBookEntry> {
+        let entry = self
+            .entries
+            .remove(label)
+            .ok_or_else(|| format_err!("No address labeled {}", label))?;
+        self.save()?;
+        Ok(entry)
+    }
+
+    /// The address label stands for, if any.
+    pub fn get(&self, label: &str) -> Option<AccountAddress> {
+        self.entries.get(label).map(|entry| entry.address)
+    }
+
+    /// Labels of address, in alphabetical order.
+    pub fn labels_of(&self, address: &AccountAddress) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.address == *address)
+            .map(|(label, _)| label.as_str())
+            .collect()
+    }
+
+    /// Whether address has a label.
+    pub fn contains_address(&self, address: &AccountAddress) -> bool {
+        self.entries.values().any(|entry| entry.address == *address)
+    }
+
+    /// All the entries, by label in alphabetical order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &AddressBookEntry)> {
+        self.entries
+            .iter()
+            .map(|(label, entry)| (label.as_str(), entry))
+    }
+
+    fn save(&self) -> Result<()> {
+        let stored = self
+            .entries
+            .iter()
+            .map(|(label, entry)| {
+                (
+                    label.clone(),
+                    StoredEntry {
+                        address: hex::encode(entry.address),
+                        note: entry.note.clone(),
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        fs::write(&self.path, toml::to_string(&stored)?)
+            .with_context(|_| format!("Unable to write address book {:?}", self.path))?;
+        Ok(())
+    }
+}
+
+/// Checks that label cannot be mistaken for an account index or address, nor split by the
+/// command line.
+pub fn validate_label(label: &str) -> Result<()> {
+    ensure!(
+        !label.is_empty()
+            && label
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.'),
+        "Invalid label {:?}: use letters, digits, '_', '-' and '.'",
+        label
+    );
+    ensure!(
+        label.parse::<usize>().is_err() && !is_address(label),
+        "Invalid label {}: it reads as an account index or address",
+        label
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_book() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("client.mnemonic.addresses");
+        let alice = AccountAddress::random();
+        let bob = AccountAddress::random();
+
+        let mut book = AddressBook::load(&path).unwrap();
+        book.add("alice", alice, Some("rent".to_string())).unwrap();
+        book.add("savings", bob, None).unwrap();
+        book.add("bob", bob, None).unwrap();
+        assert!(book.add("alice", bob, None).is_err());
+        assert!(book.add("12", bob, None).is_err());
+        assert!(book.add(&hex::encode(bob), bob, None).is_err());
+        assert!(book.add("co_signers=x", bob, None).is_err());
+        assert!(book.add("two words", bob, None).is_err());
+
+        let mut book = AddressBook::load(&path).unwrap();
+        assert_eq!(book.get("alice"), Some(alice));
+        assert_eq!(book.labels_of(&bob), vec!["bob", "savings"]);
+        assert_eq!(
+            book.entries().next().unwrap().1.note,
+            Some("rent".to_string())
+        );
+        assert_eq!(book.remove("alice").unwrap().address, alice);
+        assert!(book.remove("alice").is_err());
+
+        let book = AddressBook::load(&path).unwrap();
+        assert!(!book.contains_address(&alice));
+        assert!(book.contains_address(&bob));
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client_proxy::ClientProxy, commands::*};
+
+/// Major command for address book operations.
+pub struct AddressCommand {}
+
+impl Command for AddressCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["address", "ad"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Address book operations"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        let commands: Vec<Box<dyn Command>> = vec![
+            Box::new(AddressCommandAdd {}),
+            Box::new(AddressCommandList {}),
+            Box::new(AddressCommandRemove {}),
+        ];
+
+        subcommand_execute(&params[0], commands, client, &params[1..]);
+    }
+}
+
+/// Sub command to label an address.
+pub struct AddressCommandAdd {}
+
+impl Command for AddressCommandAdd {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["add", "a"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<label> <account_address>|<account_ref_id> [note]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Label an address, or nickname one of your accounts. The label can then be used \
+         wherever an account address or ref id is expected"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.add_address(&params) {
+            Ok(address) => println!("Labeled {} as {}", hex::encode(address), params[1]),
+            Err(e) => report_error("Error adding address", e),
+        }
+    }
+}
+
+/// Sub command to list the address book.
+pub struct AddressCommandList {}
+
+impl Command for AddressCommandList {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["list", "l"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Print all labeled addresses"
+    }
+    fn execute(&self, client: &mut ClientProxy, _params: &[&str]) {
+        client.print_address_book();
+    }
+}
+
+/// Sub command to remove a label from the address book.
+pub struct AddressCommandRemove {}
+
+impl Command for AddressCommandRemove {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["remove", "r"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<label>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Remove a label from the address book"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.remove_address(&params) {
+            Ok(entry) => println!("Removed {} ({})", params[1], hex::encode(entry.address)),
+            Err(e) => report_error("Error removing address", e),
+        }
+    }
+}
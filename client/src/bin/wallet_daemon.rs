@@ -0,0 +1,73 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use client::{
+    client_proxy::{ClientProxy, WALLET_PASSPHRASE_ENV},
+    daemon::{DaemonConfig, WalletDaemon},
+};
+use libra_wallet::co_signer::CoSignerConfig;
+use logger::set_default_global_logger;
+use std::process;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "wallet_daemon",
+    author = "The Libra Association",
+    about = "Libra wallet serving applications over a local Unix socket"
+)]
+struct Args {
+    /// Admission Control port to connect to.
+    #[structopt(short = "p", long = "port", default_value = "30307")]
+    pub port: String,
+    /// Host address/name to connect to.
+    #[structopt(short = "a", long = "host")]
+    pub host: String,
+    /// File location of the config of trusted validators, as for the client.
+    #[structopt(short = "s", long = "validator_set_file")]
+    pub validator_set_file: String,
+    /// File location from which to load the encrypted wallet key shares. If not passed, a new
+    /// recovery file is generated in the current directory. The file is encrypted under the
+    /// passphrase read from the LIBRA_WALLET_PASSPHRASE environment variable.
+    #[structopt(short = "n", long = "mnemonic_file")]
+    pub mnemonic_file: Option<String>,
+    /// TOML file describing the co-signer holding the server share of new accounts. If not
+    /// passed, the co-signer stored in the wallet recovery file is used.
+    #[structopt(short = "c", long = "co_signer_config")]
+    pub co_signer_config: Option<String>,
+    /// TOML file describing the socket to listen on and the callers allowed to use it.
+    #[structopt(short = "d", long = "daemon_config")]
+    pub daemon_config: String,
+}
+
+fn main() {
+    let _logger = set_default_global_logger(false /* async */, None);
+    crash_handler::setup_panic_handler();
+    let args = Args::from_args();
+
+    if let Err(e) = run(&args) {
+        eprintln!("Wallet daemon stopped: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> failure::Result<()> {
+    let config = DaemonConfig::load_config(&args.daemon_config)?;
+    let co_signer = match &args.co_signer_config {
+        Some(path) => Some(CoSignerConfig::load_config(path)?),
+        None => None,
+    };
+    let mut client_proxy = ClientProxy::new(
+        &args.host,
+        &args.port,
+        &args.validator_set_file,
+        "", /* faucet_account_file */
+        None,
+        args.mnemonic_file.clone(),
+        std::env::var(WALLET_PASSPHRASE_ENV).ok(),
+        co_signer,
+    )?;
+    client_proxy.test_validator_connection()?;
+    client_proxy.load_wallet_accounts()?;
+    WalletDaemon::new(client_proxy, config).run()
+}
@@ -1,24 +1,41 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{commands::*, grpc_client::GRPCClient, AccountData, AccountStatus};
-use admission_control_proto::proto::admission_control::SubmitTransactionRequest;
+use crate::{
+    address_book::{AddressBook, AddressBookEntry},
+    commands::*,
+    grpc_client::GRPCClient,
+    AccountData, AccountStatus,
+};
+use admission_control_proto::{
+    proto::admission_control::SubmitTransactionRequest, SimulateTransactionResponse,
+    SubmittedTransactionStatus,
+};
 use chrono::Utc;
 use config::trusted_peers::TrustedPeersConfig;
 use crypto::{
     hash::CryptoHash,
     signing::{sign_message, KeyPair},
+    HashValue, PublicKey,
 };
 use failure::prelude::*;
 use futures::{future::Future, stream::Stream};
 use hyper;
-use libra_wallet::{io_utils, wallet_library::WalletLibrary};
+use libra_wallet::{
+    co_signer::{CoSignerConfig, ThresholdConfig},
+    error::WalletError,
+    io_utils,
+    multi_sig::{self, PartiallySignedTransaction},
+    paper_backup::PaperBackup,
+    wallet_library::WalletLibrary,
+    watch_only,
+};
 use logger::prelude::*;
 use num_traits::{
     cast::{FromPrimitive, ToPrimitive},
     identities::Zero,
 };
-use proto_conv::IntoProto;
+use proto_conv::{FromProto, IntoProto};
 use protobuf::Message;
 use rust_decimal::Decimal;
 use std::{
@@ -26,7 +43,7 @@ use std::{
     convert::TryFrom,
     fs,
     io::{stdout, Write},
-    path::Path,
+    path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
     thread, time,
@@ -41,14 +58,32 @@ use types::{
     },
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::{ContractEvent, EventWithProof},
-    transaction::{Program, RawTransaction, RawTransactionBytes, SignedTransaction, Version},
+    get_with_proof::RequestItem,
+    proto::transaction::{
+        RawTransaction as ProtoRawTransaction, SignedTransaction as ProtoSignedTransaction,
+    },
+    transaction::{
+        MultiEd25519PublicKey, Program, RawTransaction, RawTransactionBytes, SignedTransaction,
+        TransactionStatus, Version,
+    },
     validator_verifier::ValidatorVerifier,
+    vm_error::{ExecutionStatus, VMStatus},
 };
 
 const CLIENT_WALLET_MNEMONIC_FILE: &str = "client.mnemonic";
+/// Environment variable holding the passphrase of the wallet recovery file.
+pub const WALLET_PASSPHRASE_ENV: &str = "LIBRA_WALLET_PASSPHRASE";
 const GAS_UNIT_PRICE: u64 = 0;
 const MAX_GAS_AMOUNT: u64 = 10_000;
+/// Margin, in percent, added to the gas a transaction used when simulated to estimate its max gas
+/// amount, in case the state changes before it executes.
+const GAS_ESTIMATE_MARGIN_PERCENT: u64 = 20;
 const TX_EXPIRATION: i64 = 100;
+/// Prefix of the argument naming the co-signers taking part in a threshold signature.
+const CO_SIGNERS_PREFIX: &str = "co_signers=";
+/// Default validity of the transactions built for offline signing, which have to travel to the
+/// signing machine and back before being submitted.
+const OFFLINE_TX_EXPIRATION: i64 = 3600;
 
 /// Enum used for error formatting.
 #[derive(Debug)]
@@ -89,6 +124,12 @@ pub struct ClientProxy {
     pub faucet_account: Option<AccountData>,
     /// Wallet library managing user accounts.
     wallet: WalletLibrary,
+    /// File the wallet key shares are persisted to after every change.
+    wallet_recovery_file: PathBuf,
+    /// Passphrase the wallet recovery file is encrypted under.
+    wallet_passphrase: String,
+    /// Labels of the addresses the user deals with, kept next to the wallet recovery file.
+    address_book: AddressBook,
 }
 
 impl ClientProxy {
@@ -100,18 +141,10 @@ impl ClientProxy {
         faucet_account_file: &str,
         faucet_server: Option<String>,
         mnemonic_file: Option<String>,
+        wallet_passphrase: Option<String>,
+        co_signer: Option<CoSignerConfig>,
     ) -> Result<Self> {
-        let validators_config = TrustedPeersConfig::load_config(Path::new(validator_set_file));
-        let validators = validators_config.get_trusted_consensus_peers();
-        ensure!(
-            !validators.is_empty(),
-            "Not able to load validators from trusted peers config!"
-        );
-        // Total 3f + 1 validators, 2f + 1 correct signatures are required.
-        // If < 4 validators, all validators have to agree.
-        let quorum_size = validators.len() * 2 / 3 + 1;
-        let validator_verifier = Arc::new(ValidatorVerifier::new(validators, quorum_size));
-        let client = GRPCClient::new(host, ac_port, validator_verifier)?;
+        let client = Self::connect(host, ac_port, validator_set_file)?;
 
         let accounts = vec![];
 
@@ -141,19 +174,88 @@ impl ClientProxy {
             .map(|(ref_id, acc_data): (usize, &AccountData)| (acc_data.address, ref_id))
             .collect::<HashMap<AccountAddress, usize>>();
 
+        let wallet_recovery_file = Self::get_wallet_recovery_file(mnemonic_file)?;
+        let wallet_passphrase = wallet_passphrase.unwrap_or_default();
+        if wallet_passphrase.is_empty() {
+            warn!("No wallet passphrase supplied, the wallet recovery file is weakly protected");
+            println!(
+                "WARNING: the wallet recovery file {} is encrypted under an empty passphrase: \
+                 anyone who can read it controls your accounts. Set {} to protect it.",
+                wallet_recovery_file.display(),
+                WALLET_PASSPHRASE_ENV
+            );
+        }
+        let wallet = Self::get_libra_wallet(&wallet_recovery_file, &wallet_passphrase, co_signer)?;
+        let address_book = AddressBook::load(Self::address_book_path(&wallet_recovery_file))?;
+
         Ok(ClientProxy {
             client,
             accounts,
             address_to_ref_id,
             faucet_server,
             faucet_account,
-            wallet: Self::get_libra_wallet(mnemonic_file)?,
+            wallet,
+            wallet_recovery_file,
+            wallet_passphrase,
+            address_book,
         })
     }
 
+    /// Construct a watch-only client from the descriptor exported by a wallet. The accounts of
+    /// the descriptor can be queried, but every command that has to sign fails.
+    pub fn new_watch_only(
+        host: &str,
+        ac_port: &str,
+        validator_set_file: &str,
+        faucet_server: Option<String>,
+        watch_only_file: &str,
+    ) -> Result<Self> {
+        let client = Self::connect(host, ac_port, validator_set_file)?;
+        let faucet_server = faucet_server.unwrap_or_else(|| host.replace("ac", "faucet"));
+        let wallet_recovery_file = PathBuf::from(watch_only_file);
+        let descriptor = watch_only::read_descriptor(&wallet_recovery_file)?;
+        let wallet = WalletLibrary::from_watch_only_descriptor(descriptor)?;
+        let address_book = AddressBook::load(Self::address_book_path(&wallet_recovery_file))?;
+
+        let mut client_proxy = ClientProxy {
+            client,
+            accounts: vec![],
+            address_to_ref_id: HashMap::new(),
+            faucet_server,
+            faucet_account: None,
+            wallet,
+            wallet_recovery_file,
+            wallet_passphrase: "".to_string(),
+            address_book,
+        };
+        for address in client_proxy.wallet.get_addresses()? {
+            let account_data =
+                Self::get_account_data_from_address(&client_proxy.client, address, None)?;
+            client_proxy.insert_account_data(account_data);
+        }
+        Ok(client_proxy)
+    }
+
+    fn connect(host: &str, ac_port: &str, validator_set_file: &str) -> Result<GRPCClient> {
+        let validators_config = TrustedPeersConfig::load_config(Path::new(validator_set_file));
+        let validators = validators_config.get_trusted_consensus_peers();
+        ensure!(
+            !validators.is_empty(),
+            "Not able to load validators from trusted peers config!"
+        );
+        // Total 3f + 1 validators, 2f + 1 correct signatures are required.
+        // If < 4 validators, all validators have to agree.
+        let quorum_size = validators.len() * 2 / 3 + 1;
+        let validator_verifier = Arc::new(ValidatorVerifier::new(validators, quorum_size));
+        GRPCClient::new(host, ac_port, validator_verifier)
+    }
+
     /// Returns the account index that should be used by user to reference this account
     pub fn create_next_account(&mut self) -> Result<AddressAndIndex> {
         let (address, _) = self.wallet.new_address()?;
+        // Persist the new key share right away, it cannot be regenerated later on.
+        self.wallet
+            .write_recovery(&self.wallet_recovery_file, &self.wallet_passphrase)?;
 
         let account_data = Self::get_account_data_from_address(&self.client, address, None)?;
 
@@ -166,12 +268,18 @@ impl ClientProxy {
             println!("No user accounts");
         } else {
             for (ref index, ref account) in self.accounts.iter().enumerate() {
+                let labels = self.address_book.labels_of(&account.address);
                 println!(
-                    "User account index: {}, address: {}, sequence number: {}, status: {:?}",
+                    "User account index: {}, address: {}, sequence number: {}, status: {:?}{}",
                     index,
                     hex::encode(&account.address),
                     account.sequence_number,
                     account.status,
+                    if labels.is_empty() {
+                        "".to_string()
+                    } else {
+                        format!(", labels: {}", labels.join(", "))
+                    },
                 );
             }
         }
@@ -302,6 +410,7 @@ impl ClientProxy {
         num_coins: u64,
         gas_unit_price: Option<u64>,
         max_gas_amount: Option<u64>,
+        co_signers: &[String],
         is_blocking: bool,
     ) -> Result<IndexAndSequence> {
         let sender_address;
@@ -313,11 +422,14 @@ impl ClientProxy {
             })?.clone();
 
             let program = vm_genesis::encode_transfer_program(&receiver_address, num_coins);
-            let req = self.create_submit_transaction_req(
+            let max_gas_amount = max_gas_amount
+                .or_else(|| self.estimate_max_gas_amount(program.clone(), sender, gas_unit_price));
+            let req = self.create_submit_transaction_req_with_co_signers(
                 program,
                 sender,
                 gas_unit_price, /* gas_unit_price */
                 max_gas_amount, /* max_gas_amount */
+                co_signers,
             )?;
             let sender_mut = self
                 .accounts
@@ -340,12 +452,14 @@ impl ClientProxy {
         })
     }
 
-    /// Transfers coins from sender to receiver.
+    /// Transfers coins from sender to receiver. The co-signers of a threshold sender can be
+    /// chosen with a trailing co_signers=<name>[,<name>...] argument.
     pub fn transfer_coins(
         &mut self,
         space_delim_strings: &[&str],
         is_blocking: bool,
     ) -> Result<IndexAndSequence> {
+        let (space_delim_strings, co_signers) = Self::split_co_signers(space_delim_strings);
         ensure!(
             space_delim_strings.len() >= 4 && space_delim_strings.len() <= 6,
             "Invalid number of arguments for transfer"
@@ -400,10 +514,366 @@ impl ClientProxy {
             num_coins,
             gas_unit_price,
             max_gas_amount,
+            &co_signers,
             is_blocking,
         )
     }
 
+    /// Splits the trailing co_signers=<name>[,<name>...] argument, if any, from the arguments of
+    /// a command, returning the other arguments and the co-signer names.
+    pub fn split_co_signers<'a, 'b>(
+        space_delim_strings: &'a [&'b str],
+    ) -> (&'a [&'b str], Vec<String>) {
+        match space_delim_strings.split_last() {
+            Some((last, rest)) if last.starts_with(CO_SIGNERS_PREFIX) => (
+                rest,
+                last[CO_SIGNERS_PREFIX.len()..]
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(|name| name.to_string())
+                    .collect(),
+            ),
+            _ => (space_delim_strings, vec![]),
+        }
+    }
+
+    /// Builds a transfer without signing it, and writes the protobuf encoded RawTransaction, hex
+    /// encoded, to the file specified. The sender does not have to be an account of this client,
+    /// so that e.g. a watch-only client can prepare the transactions of an offline wallet. The
+    /// sequence number of the sender is read from the validator unless passed.
+    pub fn build_offline_transfer(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<RawTransaction> {
+        ensure!(
+            space_delim_strings.len() >= 5 && space_delim_strings.len() <= 9,
+            "Invalid number of arguments for building an offline transfer"
+        );
+        let sender_address = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let receiver_address = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let num_coins = Self::convert_to_micro_libras(space_delim_strings[3])?;
+        let output_file = Path::new(space_delim_strings[4]);
+        let gas_unit_price = Self::parse_optional_u64(space_delim_strings, 5, "gas_unit_price")?;
+        let max_gas_amount = Self::parse_optional_u64(space_delim_strings, 6, "max_gas_amount")?;
+        let sequence_number = Self::parse_optional_u64(space_delim_strings, 7, "sequence_number")?;
+        let expiration_secs = Self::parse_optional_u64(space_delim_strings, 8, "expiration_secs")?;
+
+        let raw_txn = self.build_transfer(
+            sender_address,
+            receiver_address,
+            num_coins,
+            gas_unit_price,
+            max_gas_amount,
+            sequence_number,
+            expiration_secs,
+        )?;
+        let bytes = raw_txn.clone().into_proto().write_to_bytes()?;
+        Self::write_hex_file(output_file, &bytes)?;
+        Ok(raw_txn)
+    }
+
+    /// Builds a transfer of num_coins microlibras without signing it. The sequence number of the
+    /// sender is read from the validator unless passed, and the transaction expires after
+    /// expiration_secs, one hour by default.
+    pub fn build_transfer(
+        &mut self,
+        sender_address: AccountAddress,
+        receiver_address: AccountAddress,
+        num_coins: u64,
+        gas_unit_price: Option<u64>,
+        max_gas_amount: Option<u64>,
+        sequence_number: Option<u64>,
+        expiration_secs: Option<u64>,
+    ) -> Result<RawTransaction> {
+        let sequence_number = match sequence_number {
+            Some(sequence_number) => sequence_number,
+            None => self.client.get_sequence_number(sender_address)?,
+        };
+        let expiration_secs = expiration_secs.unwrap_or(OFFLINE_TX_EXPIRATION as u64);
+        Ok(RawTransaction::new(
+            sender_address,
+            sequence_number,
+            vm_genesis::encode_transfer_program(&receiver_address, num_coins),
+            max_gas_amount.unwrap_or(MAX_GAS_AMOUNT),
+            gas_unit_price.unwrap_or(GAS_UNIT_PRICE),
+            std::time::Duration::new(Utc::now().timestamp() as u64 + expiration_secs, 0),
+        ))
+    }
+
+    /// Runs a transfer from an account of this client against the latest state of the validator,
+    /// without signing nor submitting it, to learn its status, the gas it uses, its events and
+    /// its writes.
+    pub fn simulate_transfer(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<SimulateTransactionResponse> {
+        ensure!(
+            space_delim_strings.len() >= 4 && space_delim_strings.len() <= 6,
+            "Invalid number of arguments for simulating a transfer"
+        );
+        let sender_address = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let receiver_address = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let num_coins = Self::convert_to_micro_libras(space_delim_strings[3])?;
+        let gas_unit_price = Self::parse_optional_u64(space_delim_strings, 4, "gas_unit_price")?;
+        let max_gas_amount = Self::parse_optional_u64(space_delim_strings, 5, "max_gas_amount")?;
+
+        let raw_txn = self.build_transfer(
+            sender_address,
+            receiver_address,
+            num_coins,
+            gas_unit_price,
+            max_gas_amount,
+            None, /* sequence_number */
+            None, /* expiration_secs */
+        )?;
+        self.simulate_raw_transaction(raw_txn)
+    }
+
+    /// Signs the RawTransaction read from the first file specified, with the key of its sender,
+    /// and writes the protobuf encoded SignedTransaction, hex encoded, to the second file.
+    /// Returns the signed transaction so that it can be reviewed.
+    pub fn sign_offline_transaction(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<SignedTransaction> {
+        ensure!(
+            space_delim_strings.len() == 3,
+            "Invalid number of arguments for signing a transaction"
+        );
+        let bytes = Self::read_hex_file(Path::new(space_delim_strings[1]))?;
+        let (signed_txn, signed_txn_bytes) = self.sign_raw_transaction_bytes(&bytes, &[])?;
+        Self::write_hex_file(Path::new(space_delim_strings[2]), &signed_txn_bytes)?;
+        Ok(signed_txn)
+    }
+
+    /// Signs a protobuf encoded RawTransaction with the key of its sender and, for a threshold
+    /// sender, the co-signers named. Returns the signed transaction and its protobuf encoding.
+    pub fn sign_raw_transaction_bytes(
+        &mut self,
+        bytes: &[u8],
+        co_signers: &[String],
+    ) -> Result<(SignedTransaction, Vec<u8>)> {
+        let proto_raw_txn = protobuf::parse_from_bytes::<ProtoRawTransaction>(bytes)?;
+        let raw_txn = RawTransaction::from_proto(proto_raw_txn)?;
+        let key_pair = self.local_key_pair(raw_txn.sender());
+        let signed_txn = self.sign_transaction(raw_txn, &key_pair, co_signers)?;
+        let signed_txn_bytes = signed_txn.clone().into_proto().write_to_bytes()?;
+        // The signature covers the bytes as encoded by the signer, make sure they are the ones
+        // that were reviewed when building the transaction.
+        ensure!(
+            protobuf::parse_from_bytes::<ProtoSignedTransaction>(&signed_txn_bytes)?
+                .get_raw_txn_bytes()
+                == bytes,
+            "Signed transaction differs from the transaction it was asked to sign"
+        );
+        Ok((signed_txn, signed_txn_bytes))
+    }
+
+    /// Submits the SignedTransaction read from the file specified, and returns its sender and
+    /// sequence number. If is_blocking = true, it will keep querying validator till the sequence
+    /// number is bumped up in validator.
+    pub fn submit_signed_transaction(
+        &mut self,
+        space_delim_strings: &[&str],
+        is_blocking: bool,
+    ) -> Result<(AccountAddress, u64)> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for submitting a transaction"
+        );
+        let bytes = Self::read_hex_file(Path::new(space_delim_strings[1]))?;
+        self.submit_signed_transaction_bytes(&bytes, is_blocking)
+    }
+
+    /// Submits a protobuf encoded SignedTransaction, and returns its sender and sequence number.
+    /// If is_blocking = true, it will keep querying validator till the sequence number is bumped
+    /// up in validator.
+    pub fn submit_signed_transaction_bytes(
+        &mut self,
+        bytes: &[u8],
+        is_blocking: bool,
+    ) -> Result<(AccountAddress, u64)> {
+        let signed_txn = Self::decode_signed_transaction(bytes)?;
+        let sender = signed_txn.sender();
+        let sequence_number = signed_txn.sequence_number();
+
+        let mut sender_account = AccountData {
+            address: sender,
+            key_pair: None,
+            sequence_number,
+            status: AccountStatus::Unknown,
+        };
+        let mut req = SubmitTransactionRequest::new();
+        req.set_signed_txn(signed_txn.into_proto());
+        let resp = self.client.submit_transaction(&mut sender_account, &req);
+        if let Some(ref_id) = self.address_to_ref_id.get(&sender) {
+            let account = &mut self.accounts[*ref_id];
+            account.sequence_number = account.sequence_number.max(sender_account.sequence_number);
+        }
+        resp?;
+
+        if is_blocking {
+            self.wait_for_transaction(sender, sequence_number + 1);
+        }
+        Ok((sender, sequence_number))
+    }
+
+    /// Returns the address of the MultiEd25519 account of the threshold and hex encoded public
+    /// keys specified, i.e. the authentication key the account is created with.
+    pub fn multi_ed25519_address(&self, space_delim_strings: &[&str]) -> Result<AccountAddress> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments for a MultiEd25519 address"
+        );
+        let public_key = Self::parse_multi_ed25519_key(&space_delim_strings[1..])?;
+        Ok(AccountAddress::from(&public_key))
+    }
+
+    /// Starts collecting the signatures of the RawTransaction read from the first file specified,
+    /// sent by the MultiEd25519 account of the threshold and public keys specified, and writes
+    /// the partially signed transaction to the second file.
+    pub fn start_multi_ed25519_transaction(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<PartiallySignedTransaction> {
+        ensure!(
+            space_delim_strings.len() >= 5,
+            "Invalid number of arguments for starting a MultiEd25519 transaction"
+        );
+        let bytes = Self::read_hex_file(Path::new(space_delim_strings[1]))?;
+        let public_key = Self::parse_multi_ed25519_key(&space_delim_strings[3..])?;
+        let partial = PartiallySignedTransaction::new(&bytes, &public_key)?;
+        multi_sig::write_partial(&partial, &Path::new(space_delim_strings[2]))?;
+        Ok(partial)
+    }
+
+    /// Adds to the partially signed transaction in the file specified the signature of the
+    /// account specified, whose key has to be one of the keys of the sender. Returns the index of
+    /// the key and the number of signatures still missing.
+    pub fn sign_multi_ed25519_transaction(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<(u8, usize)> {
+        ensure!(
+            space_delim_strings.len() == 3,
+            "Invalid number of arguments for signing a MultiEd25519 transaction"
+        );
+        let path = Path::new(space_delim_strings[1]);
+        let mut partial = multi_sig::read_partial(&path)?;
+        let signer = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let index = match self.local_key_pair(signer) {
+            Some(key_pair) => {
+                let signature = sign_message(partial.hash()?, &key_pair.private_key())?;
+                partial.add_signature(&key_pair.public_key(), &signature)?
+            }
+            None => self.wallet.sign_multi_ed25519_txn(&signer, &mut partial)?,
+        };
+        multi_sig::write_partial(&partial, &path)?;
+        Ok((index, partial.missing_signatures()))
+    }
+
+    /// Merges the signatures of the partially signed transactions read from all but the first
+    /// file specified, and writes the protobuf encoded SignedTransaction, hex encoded, to the
+    /// first file, to be submitted with `submit`. Fails unless the threshold is reached.
+    pub fn combine_multi_ed25519_transaction(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<SignedTransaction> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments for combining MultiEd25519 signatures"
+        );
+        let mut partial = multi_sig::read_partial(&Path::new(space_delim_strings[2]))?;
+        for path in &space_delim_strings[3..] {
+            partial.merge(&multi_sig::read_partial(&Path::new(path))?)?;
+        }
+        let signed_txn = partial.into_signed_transaction()?;
+        let bytes = signed_txn.clone().into_proto().write_to_bytes()?;
+        Self::write_hex_file(Path::new(space_delim_strings[1]), &bytes)?;
+        Ok(signed_txn)
+    }
+
+    /// Rotates the authentication key of a wallet account to a freshly generated two-party key,
+    /// keeping the account address. The rotation transaction is co-signed with the current key,
+    /// and the wallet only switches to the new key once the rotation is committed. The new key
+    /// share is persisted before anything is submitted, so that an interrupted rotation can be
+    /// resumed by running the command again. Returns the new authentication key.
+    pub fn rotate_authentication_key(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<AccountAddress> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for rotating authentication key"
+        );
+        let address = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let account_ref_id = *self.address_to_ref_id.get(&address).ok_or_else(|| {
+            format_err!(
+                "Unable to find existing managing account by address: {}, to see all existing \
+                 accounts, run: 'account list'",
+                address
+            )
+        })?;
+        let sender = self.accounts[account_ref_id].clone();
+        ensure!(
+            sender.key_pair.is_none(),
+            "Account {} is not a two-party wallet account",
+            address
+        );
+        let authentication_key = |proxy: &mut Self| -> Result<Vec<u8>> {
+            let (blob, _) = proxy.get_account_state_and_update(address)?;
+            ensure!(
+                blob.is_some(),
+                "Account {} does not exist on chain",
+                address
+            );
+            Ok(get_account_resource_or_default(&blob)?
+                .authentication_key()
+                .as_bytes()
+                .to_vec())
+        };
+
+        let new_key = self.wallet.start_key_rotation(&address)?;
+        self.wallet
+            .write_recovery(&self.wallet_recovery_file, &self.wallet_passphrase)?;
+
+        // A previous attempt may have been committed after the client stopped waiting for it.
+        if authentication_key(self)? != new_key.to_vec() {
+            let program = vm_genesis::rotate_authentication_key_program(new_key);
+            let req = self.create_submit_transaction_req(program, &sender, None, None)?;
+            let sender_mut = &mut self.accounts[account_ref_id];
+            self.client.submit_transaction(sender_mut, &req)?;
+            let sequence_number = sender_mut.sequence_number;
+            self.wait_for_transaction(address, sequence_number);
+            ensure!(
+                authentication_key(self)? == new_key.to_vec(),
+                "Rotation transaction was not applied, account {} still uses its current key",
+                address
+            );
+        }
+
+        self.wallet.complete_key_rotation(&address)?;
+        self.wallet
+            .write_recovery(&self.wallet_recovery_file, &self.wallet_passphrase)?;
+        Ok(new_key)
+    }
+
+    /// Refreshes the key shares of a wallet account together with its co-signer, keeping the
+    /// account address. The refreshed client share is persisted before the co-signer is allowed
+    /// to drop its previous share. Returns the new epoch of the shares.
+    pub fn refresh_key_shares(&mut self, space_delim_strings: &[&str]) -> Result<u64> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for refreshing key shares"
+        );
+        let address = self.get_account_address_from_parameter(space_delim_strings[1])?;
+        let epoch = self.wallet.refresh_key_shares(&address)?;
+        self.wallet
+            .write_recovery(&self.wallet_recovery_file, &self.wallet_passphrase)?;
+        self.wallet.confirm_key_share_refresh(&address)?;
+        Ok(epoch)
+    }
+
     /// Get the latest account state from validator.
     pub fn get_latest_account_state(
         &mut self,
@@ -449,6 +919,37 @@ impl ClientProxy {
             .get_txn_by_acc_seq(account, sequence_number, fetch_events)
     }
 
+    /// Get where a submitted txn stands, by its hash or by account and sequence number
+    pub fn get_txn_status(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<SubmittedTransactionStatus> {
+        let lookup = match space_delim_strings.len() {
+            2 => RequestItem::GetTransactionByHash {
+                hash: HashValue::from_slice(&hex::decode(space_delim_strings[1])?)?,
+                fetch_events: true,
+            },
+            3 => {
+                let account = self.get_account_address_from_parameter(space_delim_strings[1])?;
+                let sequence_number = space_delim_strings[2].parse::<u64>().map_err(|error| {
+                    format_parse_data_error(
+                        "account_sequence_number",
+                        InputType::UnsignedInt,
+                        space_delim_strings[2],
+                        error,
+                    )
+                })?;
+                RequestItem::GetAccountTransactionBySequenceNumber {
+                    account,
+                    sequence_number,
+                    fetch_events: true,
+                }
+            }
+            _ => bail!("Invalid number of arguments to get transaction status"),
+        };
+        self.client.get_txn_status(lookup)
+    }
+
     /// Get committed txn by account and sequence number
     pub fn get_committed_txn_by_range(
         &mut self,
@@ -488,14 +989,18 @@ impl ClientProxy {
     }
 
     /// Get account address from parameter. If the parameter is string of address, try to convert
-    /// it to address, otherwise, try to convert to u64 and looking at TestClient::accounts.
+    /// it to address, otherwise, look it up in the address book, and failing that try to convert
+    /// to u64 and looking at TestClient::accounts.
     pub fn get_account_address_from_parameter(&self, para: &str) -> Result<AccountAddress> {
         match is_address(para) {
             true => ClientProxy::address_from_strings(para),
             false => {
+                if let Some(address) = self.address_book.get(para) {
+                    return Ok(address);
+                }
                 let account_ref_id = para.parse::<usize>().map_err(|error| {
                     format_parse_data_error(
-                        "account_reference_id/account_address",
+                        "account_reference_id/account_address/label",
                         InputType::Usize,
                         para,
                         error,
@@ -513,6 +1018,68 @@ impl ClientProxy {
         }
     }
 
+    /// Whether address is one of the accounts of this client, the faucet account or labeled in
+    /// the address book, i.e. whether the user has dealt with it before.
+    pub fn is_known_address(&self, address: &AccountAddress) -> bool {
+        self.address_to_ref_id.contains_key(address)
+            || self
+                .faucet_account
+                .as_ref()
+                .map_or(false, |faucet_account| faucet_account.address == *address)
+            || self.address_book.contains_address(address)
+    }
+
+    /// Labels the address, account index or label specified with the label specified, followed
+    /// by an optional note. Returns the address labeled.
+    pub fn add_address(&mut self, space_delim_strings: &[&str]) -> Result<AccountAddress> {
+        ensure!(
+            space_delim_strings.len() >= 3,
+            "Invalid number of arguments for adding an address"
+        );
+        let address = self.get_account_address_from_parameter(space_delim_strings[2])?;
+        let note = if space_delim_strings.len() > 3 {
+            Some(space_delim_strings[3..].join(" "))
+        } else {
+            None
+        };
+        self.address_book
+            .add(space_delim_strings[1], address, note)?;
+        Ok(address)
+    }
+
+    /// Removes the label specified from the address book.
+    pub fn remove_address(&mut self, space_delim_strings: &[&str]) -> Result<AddressBookEntry> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for removing an address"
+        );
+        self.address_book.remove(space_delim_strings[1])
+    }
+
+    /// Print the address book, marking the labels of the accounts of this client.
+    pub fn print_address_book(&self) {
+        let mut entries = self.address_book.entries().peekable();
+        if entries.peek().is_none() {
+            println!("No labeled addresses");
+        }
+        for (label, entry) in entries {
+            let account = match self.address_to_ref_id.get(&entry.address) {
+                Some(index) => format!(" (account #{})", index),
+                None => "".to_string(),
+            };
+            match &entry.note {
+                Some(note) => println!(
+                    "{}: {}{}, {}",
+                    label,
+                    hex::encode(entry.address),
+                    account,
+                    note
+                ),
+                None => println!("{}: {}{}", label, hex::encode(entry.address), account),
+            }
+        }
+    }
+
     /// Get events by account and event type with start sequence number and limit.
     pub fn get_events_by_account_and_type(
         &mut self,
@@ -555,7 +1122,7 @@ impl ClientProxy {
             .get_events_by_access_path(access_path, start_seq_number, ascending, limit)
     }
 
-    /// Write mnemonic recover to the file specified.
+    /// Write the encrypted wallet key shares to the file specified.
     pub fn write_recovery(&self, space_delim_strings: &[&str]) -> Result<()> {
         ensure!(
             space_delim_strings.len() == 2,
@@ -563,10 +1130,68 @@ impl ClientProxy {
         );
 
         self.wallet
-            .write_recovery(&Path::new(space_delim_strings[1]))?;
+            .write_recovery(&Path::new(space_delim_strings[1]), &self.wallet_passphrase)?;
+        Ok(())
+    }
+
+    /// Write a paper backup of the wallet, i.e. its mnemonic phrase and metadata string, to the
+    /// file specified.
+    pub fn write_paper_backup(&self, space_delim_strings: &[&str]) -> Result<PaperBackup> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for writing paper backup"
+        );
+        let backup = self.wallet.paper_backup()?;
+        backup.write(&Path::new(space_delim_strings[1]))?;
+        Ok(backup)
+    }
+
+    /// Write the watch-only descriptor of the wallet, i.e. the addresses and public keys of its
+    /// accounts without any key share, to the file specified.
+    pub fn export_watch_only(&self, space_delim_strings: &[&str]) -> Result<()> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for exporting watch-only descriptor"
+        );
+        let descriptor = self.wallet.watch_only_descriptor()?;
+        watch_only::write_descriptor(&descriptor, &Path::new(space_delim_strings[1]))?;
         Ok(())
     }
 
+    /// Create a threshold account shared with the co-signers of the TOML config specified, and
+    /// return the account index that should be used by user to reference it.
+    pub fn create_threshold_account(
+        &mut self,
+        space_delim_strings: &[&str],
+    ) -> Result<AddressAndIndex> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for creating a threshold account"
+        );
+        let config = ThresholdConfig::load_config(Path::new(space_delim_strings[1]))?;
+        let (address, _) = self.wallet.new_threshold_address(&config)?;
+        // Persist the new key share right away, it cannot be regenerated later on.
+        self.wallet
+            .write_recovery(&self.wallet_recovery_file, &self.wallet_passphrase)?;
+
+        let account_data = Self::get_account_data_from_address(&self.client, address, None)?;
+
+        Ok(self.insert_account_data(account_data))
+    }
+
+    /// Set the co-signer used for accounts created from now on, from the TOML config specified.
+    pub fn set_co_signer(&mut self, space_delim_strings: &[&str]) -> Result<CoSignerConfig> {
+        ensure!(
+            space_delim_strings.len() == 2,
+            "Invalid number of arguments for setting co-signer"
+        );
+        let co_signer = CoSignerConfig::load_config(Path::new(space_delim_strings[1]))?;
+        self.wallet.set_co_signer(co_signer.clone())?;
+        self.wallet
+            .write_recovery(&self.wallet_recovery_file, &self.wallet_passphrase)?;
+        Ok(co_signer)
+    }
+
     /// Recover wallet accounts from file and return vec<(account_address, index)>.
     pub fn recover_wallet_accounts(
         &mut self,
@@ -576,8 +1201,20 @@ impl ClientProxy {
             space_delim_strings.len() == 2,
             "Invalid number of arguments for recovering wallets"
         );
+        if self.wallet.is_watch_only() {
+            return Err(WalletError::WatchOnly("Cannot recover a wallet".to_string()).into());
+        }
 
-        let wallet = WalletLibrary::recover(&Path::new(space_delim_strings[1]))?;
+        let path = Path::new(space_delim_strings[1]);
+        let mut wallet = if PaperBackup::is_paper_backup(&path) {
+            WalletLibrary::from_paper_backup(
+                self.wallet.co_signer().clone(),
+                &PaperBackup::read(&path)?,
+            )?
+        } else {
+            WalletLibrary::recover(&path, &self.wallet_passphrase)?
+        };
+        wallet.set_session_log(Self::session_log_path(&self.wallet_recovery_file))?;
         let wallet_addresses = wallet.get_addresses()?;
         let mut account_data = Vec::new();
         for address in wallet_addresses {
@@ -588,10 +1225,26 @@ impl ClientProxy {
             )?);
         }
         self.set_wallet(wallet);
+        self.wallet
+            .write_recovery(&self.wallet_recovery_file, &self.wallet_passphrase)?;
         // Clear current cached AccountData as we always swap the entire wallet completely.
         Ok(self.set_accounts(account_data))
     }
 
+    /// Load the accounts of the current wallet, e.g. when starting from an existing recovery
+    /// file, and return vec<(account_address, index)>.
+    pub fn load_wallet_accounts(&mut self) -> Result<Vec<AddressAndIndex>> {
+        let mut account_data = Vec::new();
+        for address in self.wallet.get_addresses()? {
+            account_data.push(Self::get_account_data_from_address(
+                &self.client,
+                address,
+                None,
+            )?);
+        }
+        Ok(self.set_accounts(account_data))
+    }
+
     /// Insert the account data to Client::accounts and return its address and index.s
     pub fn insert_account_data(&mut self, account_data: AccountData) -> AddressAndIndex {
         let address = account_data.address;
@@ -670,7 +1323,7 @@ impl ClientProxy {
         })
     }
 
-    fn get_libra_wallet(mnemonic_file: Option<String>) -> Result<WalletLibrary> {
+    fn get_wallet_recovery_file(mnemonic_file: Option<String>) -> Result<PathBuf> {
         let wallet_recovery_file_path = if let Some(input_mnemonic_word) = mnemonic_file {
             Path::new(&input_mnemonic_word).to_path_buf()
         } else {
@@ -678,17 +1331,54 @@ impl ClientProxy {
             file_path.push(CLIENT_WALLET_MNEMONIC_FILE);
             file_path
         };
+        Ok(wallet_recovery_file_path)
+    }
 
-        let wallet = if let Ok(recovered_wallet) = io_utils::recover(&wallet_recovery_file_path) {
-            recovered_wallet
+    fn get_libra_wallet(
+        wallet_recovery_file_path: &Path,
+        wallet_passphrase: &str,
+        co_signer: Option<CoSignerConfig>,
+    ) -> Result<WalletLibrary> {
+        // Only start from scratch when there is nothing to recover. An existing recovery file
+        // that cannot be read must never be overwritten, as it holds the only copy of the keys.
+        let is_empty = fs::metadata(wallet_recovery_file_path)
+            .map(|metadata| metadata.len() == 0)
+            .unwrap_or(true);
+        let mut wallet = if is_empty {
+            WalletLibrary::new(co_signer.clone().unwrap_or_default())?
         } else {
-            let new_wallet = WalletLibrary::new();
-            new_wallet.write_recovery(&wallet_recovery_file_path)?;
-            new_wallet
+            io_utils::recover(&wallet_recovery_file_path, wallet_passphrase).map_err(|e| {
+                format_err!(
+                    "Unable to recover wallet from {:?}: {}",
+                    wallet_recovery_file_path,
+                    e
+                )
+            })?
         };
+        // An explicitly configured co-signer takes over for new accounts only, accounts that
+        // already exist keep the co-signer holding their server share.
+        if let Some(co_signer) = co_signer {
+            wallet.set_co_signer(co_signer)?;
+        }
+        wallet.write_recovery(wallet_recovery_file_path, wallet_passphrase)?;
+        wallet.set_session_log(Self::session_log_path(wallet_recovery_file_path))?;
         Ok(wallet)
     }
 
+    /// The address book of a wallet is kept next to its recovery file.
+    fn address_book_path(wallet_recovery_file_path: &Path) -> PathBuf {
+        let mut path = wallet_recovery_file_path.as_os_str().to_owned();
+        path.push(".addresses");
+        PathBuf::from(path)
+    }
+
+    /// The signing sessions of a wallet are logged next to its recovery file.
+    fn session_log_path(wallet_recovery_file_path: &Path) -> PathBuf {
+        let mut path = wallet_recovery_file_path.as_os_str().to_owned();
+        path.push(".sessions");
+        PathBuf::from(path)
+    }
+
     /// Set wallet instance used by this client.
     fn set_wallet(&mut self, wallet: WalletLibrary) {
         self.wallet = wallet;
@@ -814,6 +1504,25 @@ impl ClientProxy {
         sender_account: &AccountData,
         gas_unit_price: Option<u64>,
         max_gas_amount: Option<u64>,
+    ) -> Result<SubmitTransactionRequest> {
+        self.create_submit_transaction_req_with_co_signers(
+            program,
+            sender_account,
+            gas_unit_price,
+            max_gas_amount,
+            &[],
+        )
+    }
+
+    /// Craft a transaction request, signed together with the co-signers named if the sender is
+    /// a threshold account.
+    pub fn create_submit_transaction_req_with_co_signers(
+        &mut self,
+        program: Program,
+        sender_account: &AccountData,
+        gas_unit_price: Option<u64>,
+        max_gas_amount: Option<u64>,
+        co_signers: &[String],
     ) -> Result<SubmitTransactionRequest> {
         let raw_txn = RawTransaction::new(
             sender_account.address,
@@ -824,7 +1533,73 @@ impl ClientProxy {
             std::time::Duration::new((Utc::now().timestamp() + TX_EXPIRATION) as u64, 0),
         );
 
-        let signed_txn = match &sender_account.key_pair {
+        let signed_txn = self.sign_transaction(raw_txn, &sender_account.key_pair, co_signers)?;
+
+        let mut req = SubmitTransactionRequest::new();
+        req.set_signed_txn(signed_txn.into_proto());
+        Ok(req)
+    }
+
+    /// Simulates raw_txn, sent by an account of this client, without signing it.
+    fn simulate_raw_transaction(
+        &self,
+        raw_txn: RawTransaction,
+    ) -> Result<SimulateTransactionResponse> {
+        let public_key = match self.local_key_pair(raw_txn.sender()) {
+            Some(key_pair) => key_pair.public_key(),
+            None => self.wallet.get_public_key(&raw_txn.sender())?,
+        };
+        let mut signed_txn = ProtoSignedTransaction::new();
+        signed_txn.set_raw_txn_bytes(raw_txn.into_proto().write_to_bytes()?);
+        signed_txn.set_sender_public_key(public_key.to_slice().to_vec());
+        self.client
+            .simulate_transaction(signed_txn, true /* skip_signature_check */)
+    }
+
+    /// Estimates the max gas amount of a transaction of sender running program, from the gas it
+    /// uses when simulated plus a margin. Returns None, so that the default max gas amount is
+    /// used, if the simulation fails.
+    fn estimate_max_gas_amount(
+        &self,
+        program: Program,
+        sender: &AccountData,
+        gas_unit_price: Option<u64>,
+    ) -> Option<u64> {
+        let raw_txn = RawTransaction::new(
+            sender.address,
+            sender.sequence_number,
+            program,
+            MAX_GAS_AMOUNT,
+            gas_unit_price.unwrap_or(GAS_UNIT_PRICE),
+            std::time::Duration::new((Utc::now().timestamp() + TX_EXPIRATION) as u64, 0),
+        );
+        match self.simulate_raw_transaction(raw_txn) {
+            Ok(response) => match response.status {
+                TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)) => {
+                    let estimate = response.gas_used * (100 + GAS_ESTIMATE_MARGIN_PERCENT) / 100;
+                    Some(std::cmp::min(estimate, MAX_GAS_AMOUNT))
+                }
+                status => {
+                    warn!("The simulated transaction did not execute: {:?}", status);
+                    None
+                }
+            },
+            Err(e) => {
+                warn!("Unable to estimate the gas of the transaction: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Sign a transaction with key_pair if any, or else with the wallet key of its sender and,
+    /// for a threshold sender, the co-signers named.
+    fn sign_transaction(
+        &mut self,
+        raw_txn: RawTransaction,
+        key_pair: &Option<KeyPair>,
+        co_signers: &[String],
+    ) -> Result<SignedTransaction> {
+        Ok(match key_pair {
             Some(key_pair) => {
                 let bytes = raw_txn.clone().into_proto().write_to_bytes()?;
                 let hash = RawTransactionBytes(&bytes).hash();
@@ -837,18 +1612,77 @@ impl ClientProxy {
                     signature,
                 )
             }
-            None => self.wallet.sign_txn(&sender_account.address, raw_txn)?,
-        };
+            None => {
+                let sender = raw_txn.sender();
+                self.wallet
+                    .sign_txn_with_co_signers(&sender, raw_txn, co_signers)?
+            }
+        })
+    }
 
-        let mut req = SubmitTransactionRequest::new();
-        req.set_signed_txn(signed_txn.into_proto());
-        Ok(req)
+    fn parse_optional_u64(
+        space_delim_strings: &[&str],
+        index: usize,
+        field: &str,
+    ) -> Result<Option<u64>> {
+        match space_delim_strings.get(index) {
+            Some(value) => Ok(Some(value.parse::<u64>().map_err(|error| {
+                format_parse_data_error(field, InputType::UnsignedInt, value, error)
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    /// The key pair of address, if it is an account of this client, or the faucet account, that
+    /// is not signed for by the wallet.
+    fn local_key_pair(&self, address: AccountAddress) -> Option<KeyPair> {
+        match self.address_to_ref_id.get(&address) {
+            Some(ref_id) => self.accounts[*ref_id].key_pair.clone(),
+            None => self
+                .faucet_account
+                .as_ref()
+                .filter(|faucet_account| faucet_account.address == address)
+                .and_then(|faucet_account| faucet_account.key_pair.clone()),
+        }
+    }
+
+    /// Parses a threshold followed by hex encoded public keys.
+    fn parse_multi_ed25519_key(params: &[&str]) -> Result<MultiEd25519PublicKey> {
+        let threshold = params[0].parse::<u8>().map_err(|error| {
+            format_parse_data_error("threshold", InputType::UnsignedInt, params[0], error)
+        })?;
+        let public_keys = params[1..]
+            .iter()
+            .map(|key| PublicKey::from_slice(&hex::decode(key)?))
+            .collect::<Result<Vec<_>>>()?;
+        MultiEd25519PublicKey::new(public_keys, threshold)
+    }
+
+    fn write_hex_file(path: &Path, bytes: &[u8]) -> Result<()> {
+        fs::write(path, hex::encode(bytes))
+            .with_context(|_| format!("Unable to write {:?}", path))?;
+        Ok(())
+    }
+
+    /// Decodes a protobuf encoded SignedTransaction and checks its signature.
+    fn decode_signed_transaction(bytes: &[u8]) -> Result<SignedTransaction> {
+        let proto_signed_txn = protobuf::parse_from_bytes::<ProtoSignedTransaction>(bytes)?;
+        let signed_txn = SignedTransaction::from_proto(proto_signed_txn)?;
+        signed_txn.verify_signature()?;
+        Ok(signed_txn)
+    }
+
+    fn read_hex_file(path: &Path) -> Result<Vec<u8>> {
+        let content =
+            fs::read_to_string(path).with_context(|_| format!("Unable to read {:?}", path))?;
+        Ok(hex::decode(content.trim())?)
     }
 
     fn mut_account_from_parameter(&mut self, para: &str) -> Result<&mut AccountData> {
-        let account_ref_id = match is_address(para) {
-            true => {
-                let account_address = ClientProxy::address_from_strings(para)?;
+        let account_ref_id = match para.parse::<usize>() {
+            Ok(account_ref_id) => account_ref_id,
+            Err(_) => {
+                let account_address = self.get_account_address_from_parameter(para)?;
                 *self
                     .address_to_ref_id
                     .get(&account_address)
@@ -859,7 +1693,6 @@ impl ClientProxy {
                         )
                     })?
             }
-            false => para.parse::<usize>()?,
         };
         let account_data = self
             .accounts
@@ -889,8 +1722,9 @@ fn format_parse_data_error<T: std::fmt::Debug>(
 mod tests {
     use crate::client_proxy::{AddressAndIndex, ClientProxy};
     use config::trusted_peers::TrustedPeersConfigHelpers;
-    use libra_wallet::io_utils;
+    use libra_wallet::{co_signer::CoSignerConfig, io_utils};
     use proptest::prelude::*;
+    use std::fs;
     use tempfile::NamedTempFile;
 
     fn generate_accounts_from_wallet(count: usize) -> (ClientProxy, Vec<AddressAndIndex>) {
@@ -914,6 +1748,8 @@ mod tests {
             &"",
             None,
             Some(mnemonic_path),
+            Some("passphrase".to_string()),
+            Some(CoSignerConfig::in_process("client_proxy_test")),
         )
         .unwrap();
         for _ in 0..count {
@@ -946,6 +1782,42 @@ mod tests {
         assert_eq!(accounts.len(), num);
     }
 
+    #[test]
+    fn test_offline_transaction_roundtrip() {
+        let (mut client, accounts) = generate_accounts_from_wallet(2);
+        let dir = tempfile::tempdir().unwrap();
+        let raw_txn_path = dir.path().join("raw_txn");
+        let signed_txn_path = dir.path().join("signed_txn");
+        let raw_txn_file = raw_txn_path.to_str().unwrap();
+        let signed_txn_file = signed_txn_path.to_str().unwrap();
+
+        // The sequence number is given, so that nothing is read from a validator.
+        let raw_txn = client
+            .build_offline_transfer(&["b", "0", "1", "10", raw_txn_file, "1", "1000", "5"])
+            .unwrap();
+        assert_eq!(raw_txn.sender(), accounts[0].address);
+        assert_eq!(raw_txn.sequence_number(), 5);
+        assert_eq!(raw_txn.gas_unit_price(), 1);
+        assert_eq!(raw_txn.max_gas_amount(), 1000);
+
+        let signed_txn = client
+            .sign_offline_transaction(&["s", raw_txn_file, signed_txn_file])
+            .unwrap();
+        let bytes = ClientProxy::read_hex_file(&signed_txn_path).unwrap();
+        let read_txn = ClientProxy::decode_signed_transaction(&bytes).unwrap();
+        assert_eq!(read_txn, signed_txn);
+
+        // A signed transaction altered after signing is refused.
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(ClientProxy::decode_signed_transaction(&tampered).is_err());
+        // So is a file which is not hex encoded.
+        fs::write(&raw_txn_path, "not hex").unwrap();
+        assert!(client
+            .sign_offline_transaction(&["s", raw_txn_file, signed_txn_file])
+            .is_err());
+    }
+
     #[test]
     fn test_write_recover() {
         let num = 15;
@@ -954,11 +1826,17 @@ mod tests {
 
         let file = NamedTempFile::new().unwrap();
         let path = file.into_temp_path();
-        io_utils::write_recovery(&client.wallet, &path).expect("failed to write to file");
+        io_utils::write_recovery(&client.wallet, &path, "passphrase")
+            .expect("failed to write to file");
 
-        let wallet = io_utils::recover(&path).expect("failed to load from file");
+        let wallet = io_utils::recover(&path, "passphrase").expect("failed to load from file");
 
-        assert_eq!(client.wallet.mnemonic(), wallet.mnemonic());
+        assert_eq!(
+            client.wallet.get_addresses().unwrap(),
+            wallet.get_addresses().unwrap()
+        );
+        assert_eq!(client.wallet.key_leaf(), wallet.key_leaf());
+        assert!(io_utils::recover(&path, "wrong passphrase").is_err());
     }
 
     proptest! {
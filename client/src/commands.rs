@@ -2,11 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    account_commands::AccountCommand, client_proxy::ClientProxy, query_commands::QueryCommand,
+    account_commands::AccountCommand,
+    address_commands::AddressCommand,
+    client_proxy::ClientProxy,
+    dev_commands::DevCommand,
+    offline_commands::{OfflineCommand, SubmitCommand},
+    query_commands::QueryCommand,
     transfer_commands::TransferCommand,
 };
 
 use failure::prelude::*;
+use libra_wallet::error::WalletError;
 use metrics::counters::*;
 use std::{collections::HashMap, sync::Arc};
 use types::account_address::ADDRESS_LENGTH;
@@ -31,6 +37,33 @@ fn pretty_format_error(e: Error) -> String {
         }
     }
 
+    match e.downcast_ref::<WalletError>() {
+        Some(WalletError::CoSignerUnreachable(reason)) => {
+            return format!(
+                "Co-signer unavailable ({}), please retry and/or check the co-signer \
+                 configured for this account",
+                reason
+            );
+        }
+        Some(WalletError::CoSignerRejected(reason)) => {
+            return format!("Co-signer refused to sign the transaction: {}", reason);
+        }
+        Some(WalletError::InvalidSignature(reason)) | Some(WalletError::NonceReuse(reason)) => {
+            return format!(
+                "Co-signer misbehaved ({}), the transaction was not submitted. Check the \
+                 session log of the wallet and the co-signer before signing again",
+                reason
+            );
+        }
+        Some(WalletError::WatchOnly(reason)) => {
+            return format!(
+                "{}: the client runs in watch-only mode and holds no key share",
+                reason
+            );
+        }
+        _ => {}
+    }
+
     return format!("{}", e);
 }
 
@@ -52,6 +85,22 @@ pub fn is_address(data: &str) -> bool {
     }
 }
 
+/// Warn before sending coins to an address the user never dealt with, i.e. that is neither one of
+/// their accounts nor in their address book: a mistyped address cannot be told apart from a
+/// valid one, and coins sent to it are lost.
+pub fn warn_if_unknown_receiver(client: &ClientProxy, receiver: &str) {
+    if let Ok(address) = client.get_account_address_from_parameter(receiver) {
+        if !client.is_known_address(&address) {
+            println!(
+                "[WARNING] {} is not in your address book, double check it before sending. \
+                 Label it with: address add <label> {}",
+                hex::encode(address),
+                hex::encode(address)
+            );
+        }
+    }
+}
+
 /// Returns all the commands available, as well as the reverse index from the aliases to the
 /// commands.
 pub fn get_commands() -> (
@@ -60,8 +109,12 @@ pub fn get_commands() -> (
 ) {
     let commands: Vec<Arc<dyn Command>> = vec![
         Arc::new(AccountCommand {}),
+        Arc::new(AddressCommand {}),
         Arc::new(QueryCommand {}),
         Arc::new(TransferCommand {}),
+        Arc::new(OfflineCommand {}),
+        Arc::new(SubmitCommand {}),
+        Arc::new(DevCommand {}),
     ];
     let mut alias_to_cmd = HashMap::new();
     for command in &commands {
@@ -0,0 +1,833 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Wallet daemon serving a local API to the applications of the machine.
+//!
+//! The daemon listens on a Unix socket. Every connection sends one JSON request per line and
+//! reads one JSON response per line. A request carries the token of its caller, e.g.
+//!
+//! ```json
+//! {"token": "...", "request": {"method": "build_transfer", "params": {"sender": "...",
+//!  "receiver": "...", "amount": 1000000}}}
+//! ```
+//!
+//! and is only served if the caller is allowed to run the method, from the uid it is bound to if
+//! any, and for the sender accounts it is restricted to if any. Amounts are in microlibras, and
+//! transactions are exchanged as hex encoded protobufs, as in the offline commands of the CLI.
+//!
+//! Requests are run one at a time against the wallet, except for the wait for submitted
+//! transactions to be committed, which only locks the wallet for each query to the validator.
+
+use crate::{client_proxy::ClientProxy, AccountStatus};
+use failure::prelude::*;
+use logger::prelude::*;
+use proto_conv::{FromProtoBytes, IntoProtoBytes};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::{
+        fs::PermissionsExt,
+        io::AsRawFd,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use types::{
+    account_address::AccountAddress,
+    transaction::{RawTransaction, SignedTransaction},
+};
+
+/// Permissions of the socket, only reachable by the user running the daemon by default.
+const DEFAULT_SOCKET_MODE: u32 = 0o600;
+
+/// Connections served at the same time by default, each on its own thread.
+const DEFAULT_MAX_CONNECTIONS: usize = 16;
+
+/// Time a connection may stay idle before it is closed, freeing its thread.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Time a submitted transaction has to be committed when the caller waits for it.
+const COMMIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Delay between two queries of the sequence number of the sender of a submitted transaction.
+const COMMIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Configuration of the daemon, loaded from a TOML file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DaemonConfig {
+    /// Path of the Unix socket to listen on
+    pub socket_path: PathBuf,
+    /// Permissions of the socket file. Callers running as other users than the daemon need the
+    /// socket to be group or world accessible.
+    #[serde(default = "default_socket_mode")]
+    pub socket_mode: u32,
+    /// Connections served at the same time, further connections being refused
+    #[serde(default = "default_max_connections")]
+    pub max_connections: usize,
+    /// Applications allowed to use the daemon
+    #[serde(default)]
+    pub callers: Vec<CallerConfig>,
+}
+
+fn default_socket_mode() -> u32 {
+    DEFAULT_SOCKET_MODE
+}
+
+fn default_max_connections() -> usize {
+    DEFAULT_MAX_CONNECTIONS
+}
+
+impl DaemonConfig {
+    /// Reads the config file. Relative paths used in the config are relative to the config
+    /// location.
+    pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let config_string = fs::read_to_string(path)
+            .with_context(|_| format!("Cannot read DaemonConfig file {:?}", path))?;
+        let mut config = Self::parse(&config_string)
+            .with_context(|_| format!("Cannot parse DaemonConfig file {:?}", path))?;
+        config.socket_path = path.with_file_name(&config.socket_path);
+        Ok(config)
+    }
+
+    /// Parses the config file into a DaemonConfig object, checking that caller names and tokens
+    /// are unique.
+    pub fn parse(config_string: &str) -> Result<Self> {
+        let config: Self = toml::from_str(config_string)?;
+        let mut names = HashSet::new();
+        let mut tokens = HashSet::new();
+        for caller in &config.callers {
+            ensure!(
+                !caller.token.is_empty(),
+                "Caller {} has no token",
+                caller.name
+            );
+            ensure!(
+                names.insert(&caller.name),
+                "Caller {} is configured twice",
+                caller.name
+            );
+            ensure!(
+                tokens.insert(&caller.token),
+                "Caller {} shares its token with another caller",
+                caller.name
+            );
+            for account in caller.accounts.iter().flatten() {
+                parse_address(account)?;
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Method of the daemon API a caller may be allowed to run.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Create a new wallet account
+    CreateAccount,
+    /// List the wallet accounts
+    ListAccounts,
+    /// Build unsigned transactions
+    BuildTransaction,
+    /// Sign transactions with the wallet keys
+    SignTransaction,
+    /// Submit signed transactions to admission control
+    SubmitTransaction,
+}
+
+/// An application allowed to use the daemon.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CallerConfig {
+    /// Name of the caller, used in the logs
+    pub name: String,
+    /// Secret the caller sends with every request
+    pub token: String,
+    /// If set, the caller must connect as this user
+    #[serde(default)]
+    pub uid: Option<u32>,
+    /// Methods the caller may run
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    /// If set, hex encoded addresses of the only accounts the caller may build, sign and submit
+    /// transactions for
+    #[serde(default)]
+    pub accounts: Option<Vec<String>>,
+}
+
+impl CallerConfig {
+    /// Checks that the caller may run command, as far as its configuration goes.
+    pub fn authorize(&self, command: &DaemonCommand) -> Result<()> {
+        let permission = command.permission();
+        ensure!(
+            self.permissions.contains(&permission),
+            "Caller {} is not allowed to {:?}",
+            self.name,
+            permission
+        );
+        if let (Some(accounts), Some(sender)) = (&self.accounts, command.sender()?) {
+            let mut allowed = false;
+            for account in accounts {
+                allowed |= parse_address(account)? == sender;
+            }
+            ensure!(
+                allowed,
+                "Caller {} is not allowed to use account {}",
+                self.name,
+                hex::encode(sender)
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A request to the daemon.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DaemonRequest {
+    /// Token of the caller
+    pub token: String,
+    /// What the caller asks for
+    pub request: DaemonCommand,
+}
+
+/// Methods of the daemon API.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum DaemonCommand {
+    /// Creates the next wallet account
+    CreateAccount,
+    /// Lists the wallet accounts
+    ListAccounts,
+    /// Builds an unsigned transfer
+    BuildTransfer {
+        /// Hex encoded address of the sender
+        sender: String,
+        /// Hex encoded address of the receiver
+        receiver: String,
+        /// Amount in microlibras
+        amount: u64,
+        /// Price of a gas unit in microlibras
+        #[serde(default)]
+        gas_unit_price: Option<u64>,
+        /// Maximal amount of gas units the transaction can use
+        #[serde(default)]
+        max_gas_amount: Option<u64>,
+        /// Sequence number of the transaction, read from the validator if omitted
+        #[serde(default)]
+        sequence_number: Option<u64>,
+        /// Validity of the transaction in seconds, one hour by default
+        #[serde(default)]
+        expiration_secs: Option<u64>,
+    },
+    /// Signs a transaction with the wallet key of its sender
+    SignTransaction {
+        /// Hex encoded protobuf RawTransaction
+        raw_txn: String,
+        /// Co-signers taking part in the signature, for threshold accounts
+        #[serde(default)]
+        co_signers: Vec<String>,
+    },
+    /// Submits a signed transaction to admission control
+    SubmitTransaction {
+        /// Hex encoded protobuf SignedTransaction
+        signed_txn: String,
+        /// Whether to wait until the transaction is committed
+        #[serde(default)]
+        wait: bool,
+    },
+}
+
+impl DaemonCommand {
+    /// The permission needed to run the command
+    pub fn permission(&self) -> Permission {
+        match self {
+            DaemonCommand::CreateAccount => Permission::CreateAccount,
+            DaemonCommand::ListAccounts => Permission::ListAccounts,
+            DaemonCommand::BuildTransfer { .. } => Permission::BuildTransaction,
+            DaemonCommand::SignTransaction { .. } => Permission::SignTransaction,
+            DaemonCommand::SubmitTransaction { .. } => Permission::SubmitTransaction,
+        }
+    }
+
+    /// The account the command acts for, if any
+    pub fn sender(&self) -> Result<Option<AccountAddress>> {
+        Ok(match self {
+            DaemonCommand::CreateAccount | DaemonCommand::ListAccounts => None,
+            DaemonCommand::BuildTransfer { sender, .. } => Some(parse_address(sender)?),
+            DaemonCommand::SignTransaction { raw_txn, .. } => {
+                Some(RawTransaction::from_proto_bytes(&hex::decode(raw_txn)?)?.sender())
+            }
+            DaemonCommand::SubmitTransaction { signed_txn, .. } => {
+                Some(SignedTransaction::from_proto_bytes(&hex::decode(signed_txn)?)?.sender())
+            }
+        })
+    }
+}
+
+/// A wallet account, as listed by the daemon.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AccountInfo {
+    /// Index of the account in the wallet
+    pub index: usize,
+    /// Hex encoded address of the account
+    pub address: String,
+    /// Latest sequence number known to the daemon
+    pub sequence_number: u64,
+    /// Whether the account exists on chain
+    pub status: AccountStatus,
+}
+
+/// Result of a successful request.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonResult {
+    /// The account created
+    Account(AccountInfo),
+    /// The wallet accounts
+    Accounts(Vec<AccountInfo>),
+    /// Hex encoded protobuf RawTransaction
+    RawTransaction(String),
+    /// Hex encoded protobuf SignedTransaction
+    SignedTransaction(String),
+    /// Sender and sequence number of the transaction submitted
+    Submitted {
+        /// Hex encoded address of the sender
+        sender: String,
+        /// Sequence number of the transaction
+        sequence_number: u64,
+    },
+}
+
+/// Response to a request, one JSON object per line.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonResponse {
+    /// The request was served
+    Ok(DaemonResult),
+    /// The request failed or was refused
+    Error(String),
+}
+
+/// Serves the wallet of a ClientProxy to the callers of config.
+pub struct WalletDaemon {
+    client_proxy: Mutex<ClientProxy>,
+    config: DaemonConfig,
+}
+
+impl WalletDaemon {
+    /// Creates a daemon serving the wallet of client_proxy.
+    pub fn new(client_proxy: ClientProxy, config: DaemonConfig) -> Self {
+        Self {
+            client_proxy: Mutex::new(client_proxy),
+            config,
+        }
+    }
+
+    /// Listens on the socket of the config, serving every connection on its own thread, up to
+    /// max_connections at a time.
+    pub fn run(self) -> Result<()> {
+        let socket_path = &self.config.socket_path;
+        if socket_path.exists() {
+            // Left over by a previous run, a live daemon would still be listening on it.
+            ensure!(
+                UnixStream::connect(socket_path).is_err(),
+                "Another daemon is listening on {:?}",
+                socket_path
+            );
+            fs::remove_file(socket_path)?;
+        }
+        let listener = UnixListener::bind(socket_path)
+            .with_context(|_| format!("Cannot listen on {:?}", socket_path))?;
+        fs::set_permissions(
+            socket_path,
+            fs::Permissions::from_mode(self.config.socket_mode),
+        )?;
+        info!("Wallet daemon listening on {:?}", socket_path);
+
+        let daemon = Arc::new(self);
+        let connections = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming() {
+            match stream {
+                Ok(mut stream) => {
+                    let slot = match ConnectionSlot::acquire(
+                        &connections,
+                        daemon.config.max_connections,
+                    ) {
+                        Some(slot) => slot,
+                        None => {
+                            warn!("Wallet daemon refused a connection: too many connections");
+                            let response =
+                                DaemonResponse::Error("Too many connections".to_string());
+                            let _ = write_response(&mut stream, &response);
+                            continue;
+                        }
+                    };
+                    let daemon = Arc::clone(&daemon);
+                    thread::spawn(move || {
+                        let _slot = slot;
+                        if let Err(e) = daemon.serve(stream) {
+                            warn!("Wallet daemon connection failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Wallet daemon failed to accept a connection: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    fn serve(&self, stream: UnixStream) -> Result<()> {
+        let peer_uid = peer_uid(&stream);
+        stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<DaemonRequest>(&line) {
+                Ok(request) => self.handle(request, peer_uid),
+                Err(e) => DaemonResponse::Error(format!("Malformed request: {}", e)),
+            };
+            write_response(&mut writer, &response)?;
+        }
+        Ok(())
+    }
+
+    /// Authorizes and runs request, sent from a connection of peer_uid.
+    pub fn handle(&self, request: DaemonRequest, peer_uid: Option<u32>) -> DaemonResponse {
+        let caller = match self.authorize(&request, peer_uid) {
+            Ok(caller) => caller,
+            Err(e) => {
+                warn!("Wallet daemon refused a request: {}", e);
+                return DaemonResponse::Error(format!("{}", e));
+            }
+        };
+        info!(
+            "Wallet daemon running {:?} for {}",
+            request.request.permission(),
+            caller.name
+        );
+        let wait = match &request.request {
+            DaemonCommand::SubmitTransaction { wait, .. } => *wait,
+            _ => false,
+        };
+        let result = execute(&mut self.lock_client_proxy(), request.request);
+        let result = match result {
+            Ok(DaemonResult::Submitted {
+                sender,
+                sequence_number,
+            }) if wait => parse_address(&sender)
+                .and_then(|address| self.wait_for_commit(address, sequence_number))
+                .map(|()| DaemonResult::Submitted {
+                    sender,
+                    sequence_number,
+                }),
+            result => result,
+        };
+        match result {
+            Ok(result) => DaemonResponse::Ok(result),
+            Err(e) => DaemonResponse::Error(format!("{}", e)),
+        }
+    }
+
+    /// Locks the wallet. A request that panicked while holding the lock does not take the daemon
+    /// down with it: the wallet is used as that request left it.
+    fn lock_client_proxy(&self) -> MutexGuard<ClientProxy> {
+        self.client_proxy.lock().unwrap_or_else(|poisoned| {
+            warn!("Wallet daemon recovered the wallet from a failed request");
+            poisoned.into_inner()
+        })
+    }
+
+    /// Polls the validator until the transaction of sender at sequence_number is committed, for
+    /// at most COMMIT_TIMEOUT. The wallet is only locked for each query, so that other callers
+    /// are served meanwhile.
+    fn wait_for_commit(&self, sender: AccountAddress, sequence_number: u64) -> Result<()> {
+        let deadline = Instant::now() + COMMIT_TIMEOUT;
+        loop {
+            let chain_sequence_number = self.lock_client_proxy().client.get_sequence_number(sender);
+            match chain_sequence_number {
+                Ok(chain_sequence_number) if chain_sequence_number > sequence_number => {
+                    return Ok(());
+                }
+                Ok(_) => (),
+                Err(e) => debug!("Cannot read the sequence number of {}: {}", sender, e),
+            }
+            ensure!(
+                Instant::now() < deadline,
+                "Transaction {} of {} is not committed after {:?}",
+                sequence_number,
+                hex::encode(sender),
+                COMMIT_TIMEOUT
+            );
+            thread::sleep(COMMIT_POLL_INTERVAL);
+        }
+    }
+
+    fn authorize(&self, request: &DaemonRequest, peer_uid: Option<u32>) -> Result<&CallerConfig> {
+        let caller = self
+            .config
+            .callers
+            .iter()
+            .find(|caller| constant_time_eq(caller.token.as_bytes(), request.token.as_bytes()))
+            .ok_or_else(|| format_err!("Unknown caller token"))?;
+        if let Some(uid) = caller.uid {
+            ensure!(
+                peer_uid == Some(uid),
+                "Caller {} must connect as uid {}",
+                caller.name,
+                uid
+            );
+        }
+        caller.authorize(&request.request)?;
+        Ok(caller)
+    }
+}
+
+fn execute(client_proxy: &mut ClientProxy, command: DaemonCommand) -> Result<DaemonResult> {
+    Ok(match command {
+        DaemonCommand::CreateAccount => {
+            let account = client_proxy.create_next_account()?;
+            DaemonResult::Account(
+                account_infos(client_proxy)
+                    .into_iter()
+                    .nth(account.index)
+                    .ok_or_else(|| format_err!("Account {} was not created", account.index))?,
+            )
+        }
+        DaemonCommand::ListAccounts => DaemonResult::Accounts(account_infos(client_proxy)),
+        DaemonCommand::BuildTransfer {
+            sender,
+            receiver,
+            amount,
+            gas_unit_price,
+            max_gas_amount,
+            sequence_number,
+            expiration_secs,
+        } => {
+            let raw_txn = client_proxy.build_transfer(
+                parse_address(&sender)?,
+                parse_address(&receiver)?,
+                amount,
+                gas_unit_price,
+                max_gas_amount,
+                sequence_number,
+                expiration_secs,
+            )?;
+            DaemonResult::RawTransaction(hex::encode(raw_txn.into_proto_bytes()?))
+        }
+        DaemonCommand::SignTransaction {
+            raw_txn,
+            co_signers,
+        } => {
+            let (_, signed_txn_bytes) =
+                client_proxy.sign_raw_transaction_bytes(&hex::decode(raw_txn)?, &co_signers)?;
+            DaemonResult::SignedTransaction(hex::encode(signed_txn_bytes))
+        }
+        DaemonCommand::SubmitTransaction { signed_txn, .. } => {
+            // The daemon waits for the transaction itself, without holding the wallet.
+            let (sender, sequence_number) =
+                client_proxy.submit_signed_transaction_bytes(&hex::decode(signed_txn)?, false)?;
+            DaemonResult::Submitted {
+                sender: hex::encode(sender),
+                sequence_number,
+            }
+        }
+    })
+}
+
+/// One of the connections the daemon serves at the same time, released when dropped.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    /// Takes a slot out of max, unless all are taken.
+    fn acquire(connections: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        if connections.fetch_add(1, Ordering::SeqCst) >= max {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(ConnectionSlot(Arc::clone(connections)))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn write_response<W: Write>(writer: &mut W, response: &DaemonResponse) -> Result<()> {
+    let mut line = serde_json::to_vec(response)?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    Ok(())
+}
+
+fn account_infos(client_proxy: &ClientProxy) -> Vec<AccountInfo> {
+    client_proxy
+        .copy_all_accounts()
+        .into_iter()
+        .enumerate()
+        .map(|(index, account)| AccountInfo {
+            index,
+            address: hex::encode(account.address),
+            sequence_number: account.sequence_number,
+            status: account.status,
+        })
+        .collect()
+}
+
+fn parse_address(address: &str) -> Result<AccountAddress> {
+    AccountAddress::try_from(address.trim_start_matches("0x").to_string())
+        .map_err(|e| format_err!("Invalid address {}: {}", address, e))
+}
+
+/// Compares tokens without leaking, through timing, how much of a guess is right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Returns the uid of the process at the other end of stream.
+#[cfg(target_os = "linux")]
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut credentials = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut length = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut libc::ucred as *mut libc::c_void,
+            &mut length,
+        )
+    };
+    if ret == 0 {
+        Some(credentials.uid)
+    } else {
+        None
+    }
+}
+
+/// Peer credentials are only read on Linux, callers bound to a uid cannot connect elsewhere.
+#[cfg(not(target_os = "linux"))]
+fn peer_uid(_stream: &UnixStream) -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::trusted_peers::TrustedPeersConfigHelpers;
+    use libra_wallet::co_signer::CoSignerConfig;
+    use tempfile::TempDir;
+
+    /// A daemon serving a wallet of two accounts, not connected to any validator, to the callers
+    /// of config. The callers may refer to the accounts as {0} and {1}. The wallet files live in
+    /// the directory returned.
+    fn start_daemon(config: &str) -> (WalletDaemon, Vec<AccountAddress>, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let mnemonic_path = dir.path().join("wallet.recovery");
+        let trusted_peers_path = dir.path().join("trusted_peers.config.toml");
+        let (_, trusted_peers) = TrustedPeersConfigHelpers::get_test_config(1, None);
+        trusted_peers.save_config(&trusted_peers_path);
+        let mut client_proxy = ClientProxy::new(
+            "", /* host */
+            "", /* port */
+            trusted_peers_path.to_str().unwrap(),
+            "", /* faucet_account_file */
+            None,
+            Some(mnemonic_path.to_str().unwrap().to_string()),
+            Some("passphrase".to_string()),
+            Some(CoSignerConfig::in_process("daemon_test")),
+        )
+        .unwrap();
+        let accounts = (0..2)
+            .map(|_| client_proxy.create_next_account().unwrap().address)
+            .collect::<Vec<_>>();
+        let config = config
+            .replace("{0}", &hex::encode(accounts[0]))
+            .replace("{1}", &hex::encode(accounts[1]));
+        let config = DaemonConfig::parse(&config).unwrap();
+        (WalletDaemon::new(client_proxy, config), accounts, dir)
+    }
+
+    fn request(token: &str, command: &str) -> DaemonRequest {
+        serde_json::from_str(&format!(
+            r#"{{"token": "{}", "request": {}}}"#,
+            token, command
+        ))
+        .unwrap()
+    }
+
+    fn expect_ok(response: DaemonResponse) -> DaemonResult {
+        match response {
+            DaemonResponse::Ok(result) => result,
+            DaemonResponse::Error(e) => panic!("Request failed: {}", e),
+        }
+    }
+
+    fn build_transfer(sender: AccountAddress) -> String {
+        format!(
+            r#"{{"method": "build_transfer", "params": {{"sender": "{}", "receiver": "{}",
+            "amount": 10, "sequence_number": 0}}}}"#,
+            hex::encode(sender),
+            hex::encode(AccountAddress::random())
+        )
+    }
+
+    #[test]
+    fn test_handle_requests() {
+        let (daemon, accounts, _dir) = start_daemon(
+            "socket_path = \"wallet.sock\"\n\
+             [[callers]]\n\
+             name = \"admin\"\n\
+             token = \"admin\"\n\
+             permissions = [\"create_account\", \"list_accounts\"]\n\
+             [[callers]]\n\
+             name = \"payments\"\n\
+             token = \"payments\"\n\
+             uid = 4242\n\
+             permissions = [\"build_transaction\", \"sign_transaction\"]\n\
+             accounts = [\"{0}\"]\n",
+        );
+        let admin = |command: &str| daemon.handle(request("admin", command), None);
+        let payments = |command: &str| daemon.handle(request("payments", command), Some(4242));
+
+        match expect_ok(admin(r#"{"method": "create_account"}"#)) {
+            DaemonResult::Account(account) => assert_eq!(account.index, 2),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        match expect_ok(admin(r#"{"method": "list_accounts"}"#)) {
+            DaemonResult::Accounts(listed) => {
+                assert_eq!(listed.len(), 3);
+                assert_eq!(listed[1].address, hex::encode(accounts[1]));
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+
+        let raw_txn = match expect_ok(payments(&build_transfer(accounts[0]))) {
+            DaemonResult::RawTransaction(raw_txn) => raw_txn,
+            result => panic!("Unexpected result {:?}", result),
+        };
+        let sign = format!(
+            r#"{{"method": "sign_transaction", "params": {{"raw_txn": "{}"}}}}"#,
+            raw_txn
+        );
+        let signed_txn = match expect_ok(payments(&sign)) {
+            DaemonResult::SignedTransaction(signed_txn) => signed_txn,
+            result => panic!("Unexpected result {:?}", result),
+        };
+        let signed_txn =
+            SignedTransaction::from_proto_bytes(&hex::decode(signed_txn).unwrap()).unwrap();
+        assert_eq!(signed_txn.sender(), accounts[0]);
+        signed_txn.verify_signature().unwrap();
+
+        // Unknown tokens, methods or accounts the caller is not granted, and connections from
+        // another uid than the one the caller is bound to are refused.
+        let refused = [
+            daemon.handle(request("unknown", r#"{"method": "list_accounts"}"#), None),
+            admin(&sign),
+            payments(r#"{"method": "list_accounts"}"#),
+            payments(&build_transfer(accounts[1])),
+            daemon.handle(request("payments", &build_transfer(accounts[0])), Some(1)),
+            daemon.handle(request("payments", &build_transfer(accounts[0])), None),
+        ];
+        for response in refused.iter() {
+            match response {
+                DaemonResponse::Error(_) => (),
+                result => panic!("Unexpected result {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_poisoned_wallet_lock() {
+        let (daemon, _, _dir) = start_daemon(
+            "socket_path = \"wallet.sock\"\n\
+             [[callers]]\n\
+             name = \"admin\"\n\
+             token = \"admin\"\n\
+             permissions = [\"list_accounts\"]\n",
+        );
+        let daemon = Arc::new(daemon);
+        let poisoner = Arc::clone(&daemon);
+        assert!(thread::spawn(move || {
+            let _client_proxy = poisoner.client_proxy.lock().unwrap();
+            panic!("request failed while holding the wallet");
+        })
+        .join()
+        .is_err());
+        assert!(daemon.client_proxy.is_poisoned());
+        match expect_ok(daemon.handle(request("admin", r#"{"method": "list_accounts"}"#), None)) {
+            DaemonResult::Accounts(listed) => assert_eq!(listed.len(), 2),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_connection_slots() {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let first = ConnectionSlot::acquire(&connections, 2).unwrap();
+        let _second = ConnectionSlot::acquire(&connections, 2).unwrap();
+        assert!(ConnectionSlot::acquire(&connections, 2).is_none());
+        drop(first);
+        assert!(ConnectionSlot::acquire(&connections, 2).is_some());
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_caller_authorization() {
+        let allowed = AccountAddress::random();
+        let config = DaemonConfig::parse(&format!(
+            "socket_path = \"wallet.sock\"\n\
+             [[callers]]\n\
+             name = \"payments\"\n\
+             token = \"secret\"\n\
+             permissions = [\"build_transaction\", \"list_accounts\"]\n\
+             accounts = [\"{}\"]\n",
+            hex::encode(allowed)
+        ))
+        .unwrap();
+        assert_eq!(config.socket_mode, DEFAULT_SOCKET_MODE);
+        assert_eq!(config.max_connections, DEFAULT_MAX_CONNECTIONS);
+        let caller = &config.callers[0];
+        let build = |sender: AccountAddress| DaemonCommand::BuildTransfer {
+            sender: hex::encode(sender),
+            receiver: hex::encode(AccountAddress::random()),
+            amount: 1,
+            gas_unit_price: None,
+            max_gas_amount: None,
+            sequence_number: None,
+            expiration_secs: None,
+        };
+        caller.authorize(&DaemonCommand::ListAccounts).unwrap();
+        caller.authorize(&build(allowed)).unwrap();
+        assert!(caller.authorize(&build(AccountAddress::random())).is_err());
+        assert!(caller.authorize(&DaemonCommand::CreateAccount).is_err());
+
+        let request: DaemonRequest =
+            serde_json::from_str(r#"{"token": "secret", "request": {"method": "list_accounts"}}"#)
+                .unwrap();
+        assert_eq!(request.request.permission(), Permission::ListAccounts);
+        assert!(DaemonConfig::parse(
+            "socket_path = \"wallet.sock\"\n\
+             [[callers]]\nname = \"a\"\ntoken = \"secret\"\n\
+             [[callers]]\nname = \"b\"\ntoken = \"secret\"\n"
+        )
+        .is_err());
+    }
+}
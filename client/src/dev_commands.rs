@@ -0,0 +1,62 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client_proxy::ClientProxy, commands::*};
+
+/// Major command for development tools.
+pub struct DevCommand {}
+
+impl Command for DevCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["dev"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Development tools, e.g. dry runs of transactions"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        let commands: Vec<Box<dyn Command>> = vec![Box::new(DevCommandSimulate {})];
+
+        subcommand_execute(&params[0], commands, client, &params[1..]);
+    }
+}
+
+/// Sub command to simulate a transfer.
+pub struct DevCommandSimulate {}
+
+impl Command for DevCommandSimulate {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["simulate", "s"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "\n\t<sender_account_address>|<sender_account_ref_id>|<sender_label> \
+         <receiver_account_address>|<receiver_account_ref_id>|<receiver_label> <number_of_coins> \
+         [gas_unit_price_in_micro_libras (default=0)] [max_gas_amount_in_micro_libras (default 10000)]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Run a transfer (in libra) against the latest state of the validator, without signing nor \
+         submitting it, to see its status, the gas it uses, its events and its writes"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Simulating transfer");
+        match client.simulate_transfer(&params) {
+            Ok(response) => {
+                println!("Simulated against version {}", response.version);
+                println!("Status: {:?}", response.status);
+                println!("Gas used: {}", response.gas_used);
+                if response.events.is_empty() {
+                    println!("No events emitted");
+                } else {
+                    println!("Events:");
+                    for event in &response.events {
+                        println!("{}", event);
+                    }
+                }
+                println!("Writes:");
+                for (access_path, write_op) in &response.write_set {
+                    println!("{}: {:?}", access_path, write_op);
+                }
+            }
+            Err(e) => report_error("Error simulating transfer", e),
+        }
+    }
+}
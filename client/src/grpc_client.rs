@@ -5,14 +5,22 @@ use crate::AccountData;
 use admission_control_proto::{
     proto::{
         admission_control::{
+            GetTransactionStatusRequest,
+            GetTransactionStatusResponse as ProtoGetTransactionStatusResponse,
+            SimulateTransactionRequest,
+            SimulateTransactionResponse as ProtoSimulateTransactionResponse,
             SubmitTransactionRequest, SubmitTransactionResponse as ProtoSubmitTransactionResponse,
+            SubmitTransactionsRequest,
+            SubmitTransactionsResponse as ProtoSubmitTransactionsResponse,
+            SubscribeToEventsRequest, SubscribeToLedgerInfosRequest,
         },
         admission_control_grpc::AdmissionControlClient,
     },
-    AdmissionControlStatus, SubmitTransactionResponse,
+    AdmissionControlStatus, GetTransactionStatusResponse, SimulateTransactionResponse,
+    SubmitTransactionResponse, SubmittedTransactionStatus,
 };
 use failure::prelude::*;
-use futures::Future;
+use futures::{Future, Stream};
 use grpcio::{CallOption, ChannelBuilder, EnvBuilder};
 use logger::prelude::*;
 use proto_conv::{FromProto, IntoProto};
@@ -24,8 +32,11 @@ use types::{
     account_state_blob::{AccountStateBlob, AccountStateWithProof},
     contract_event::{ContractEvent, EventWithProof},
     get_with_proof::{
-        RequestItem, ResponseItem, UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse,
+        verify_update_to_latest_ledger_response, RequestItem, ResponseItem,
+        UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse,
     },
+    ledger_info::LedgerInfoWithSignatures,
+    proto::transaction::SignedTransaction as ProtoSignedTransaction,
     transaction::{SignedTransaction, Version},
     validator_verifier::ValidatorVerifier,
     vm_error::{VMStatus, VMValidationStatus},
@@ -74,6 +85,12 @@ impl GRPCClient {
             if ac_status == AdmissionControlStatus::Accepted {
                 // Bump up sequence_number if transaction is accepted.
                 sender_account.sequence_number += 1;
+            } else if let Some(reason) = completed_resp.ac_status_reason {
+                bail!(
+                    "Transaction failed with AC status: {:?} ({})",
+                    ac_status,
+                    reason
+                );
             } else {
                 bail!("Transaction failed with AC status: {:?}", ac_status,);
             }
@@ -125,6 +142,236 @@ impl GRPCClient {
             .submit_transaction_opt(resp, Self::get_default_grpc_call_option())?)
     }
 
+    /// Submits many transactions at once, e.g. transactions from the same sender with consecutive
+    /// sequence numbers. Returns the response to the submission of each transaction, in order:
+    /// some may be accepted while others are not.
+    pub fn submit_transactions(
+        &self,
+        signed_txns: Vec<ProtoSignedTransaction>,
+    ) -> Result<Vec<SubmitTransactionResponse>> {
+        let signed_txn_count = signed_txns.len();
+        let mut req = SubmitTransactionsRequest::new();
+        req.set_signed_txns(::protobuf::RepeatedField::from_vec(signed_txns));
+        let mut resp = self.submit_transactions_opt(&req);
+
+        let mut try_cnt = 0_u64;
+        while Self::need_to_retry(&mut try_cnt, &resp) {
+            resp = self.submit_transactions_opt(&req);
+        }
+
+        let responses = resp?
+            .take_responses()
+            .into_iter()
+            .map(SubmitTransactionResponse::from_proto)
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(
+            responses.len() == signed_txn_count,
+            "Got {} responses for {} transactions",
+            responses.len(),
+            signed_txn_count
+        );
+        Ok(responses)
+    }
+
+    fn submit_transactions_opt(
+        &self,
+        req: &SubmitTransactionsRequest,
+    ) -> Result<ProtoSubmitTransactionsResponse> {
+        Ok(self
+            .client
+            .submit_transactions_opt(req, Self::get_default_grpc_call_option())?)
+    }
+
+    /// Runs a transaction through the VM of the validator against its latest state, without
+    /// submitting it. The signature of the transaction is not checked if skip_signature_check is
+    /// set, and can then be left empty.
+    pub fn simulate_transaction(
+        &self,
+        signed_txn: ProtoSignedTransaction,
+        skip_signature_check: bool,
+    ) -> Result<SimulateTransactionResponse> {
+        let mut req = SimulateTransactionRequest::new();
+        req.set_signed_txn(signed_txn);
+        req.set_skip_signature_check(skip_signature_check);
+        let mut resp = self.simulate_transaction_opt(&req);
+
+        let mut try_cnt = 0_u64;
+        while Self::need_to_retry(&mut try_cnt, &resp) {
+            resp = self.simulate_transaction_opt(&req);
+        }
+
+        SimulateTransactionResponse::from_proto(resp?)
+    }
+
+    fn simulate_transaction_opt(
+        &self,
+        req: &SimulateTransactionRequest,
+    ) -> Result<ProtoSimulateTransactionResponse> {
+        Ok(self
+            .client
+            .simulate_transaction_opt(req, Self::get_default_grpc_call_option())?)
+    }
+
+    /// Gets where a submitted transaction stands. `lookup` is either a `GetTransactionByHash` or
+    /// a `GetAccountTransactionBySequenceNumber` request item. The ledger info, and the proof of a
+    /// committed transaction, are verified.
+    pub fn get_txn_status(&self, lookup: RequestItem) -> Result<SubmittedTransactionStatus> {
+        let mut req = GetTransactionStatusRequest::new();
+        match &lookup {
+            RequestItem::GetTransactionByHash { hash, fetch_events } => {
+                req.set_hash(hash.to_vec());
+                req.set_fetch_events(*fetch_events);
+            }
+            RequestItem::GetAccountTransactionBySequenceNumber {
+                account,
+                sequence_number,
+                fetch_events,
+            } => {
+                req.set_sender(account.as_ref().to_vec());
+                req.set_sequence_number(*sequence_number);
+                req.set_fetch_events(*fetch_events);
+            }
+            _ => bail!("Transactions are looked up by hash or by account and sequence number"),
+        }
+        let mut resp = self.get_txn_status_opt(&req);
+
+        let mut try_cnt = 0_u64;
+        while Self::need_to_retry(&mut try_cnt, &resp) {
+            resp = self.get_txn_status_opt(&req);
+        }
+
+        let resp = GetTransactionStatusResponse::from_proto(resp?)?;
+        // Check the proof of a committed transaction as if it was returned by a get_with_proof
+        // query. Only the ledger info signatures can be checked otherwise.
+        let (request_items, response_items) = match &resp.status {
+            SubmittedTransactionStatus::Committed(signed_transaction_with_proof) => {
+                let signed_transaction_with_proof = Some(signed_transaction_with_proof.clone());
+                let response_item = match lookup {
+                    RequestItem::GetTransactionByHash { .. } => {
+                        ResponseItem::GetTransactionByHash {
+                            signed_transaction_with_proof,
+                        }
+                    }
+                    _ => ResponseItem::GetAccountTransactionBySequenceNumber {
+                        signed_transaction_with_proof,
+                        proof_of_current_sequence_number: None,
+                    },
+                };
+                (vec![lookup], vec![response_item])
+            }
+            _ => (vec![], vec![]),
+        };
+        verify_update_to_latest_ledger_response(
+            Arc::clone(&self.validator_verifier),
+            0,
+            &request_items,
+            &response_items,
+            &resp.ledger_info_with_sigs,
+        )?;
+        Ok(resp.status)
+    }
+
+    fn get_txn_status_opt(
+        &self,
+        req: &GetTransactionStatusRequest,
+    ) -> Result<ProtoGetTransactionStatusResponse> {
+        Ok(self
+            .client
+            .get_transaction_status_opt(req, Self::get_default_grpc_call_option())?)
+    }
+
+    /// Subscribes to the ledger info of each version after `client_known_version`, as the
+    /// validator commits it. Each ledger info is verified before it is yielded. The keepalives,
+    /// which repeat the ledger info of the latest version, are verified but not yielded.
+    pub fn subscribe_to_ledger_infos(
+        &self,
+        client_known_version: Version,
+    ) -> Result<impl Stream<Item = LedgerInfoWithSignatures, Error = failure::Error>> {
+        let mut req = SubscribeToLedgerInfosRequest::new();
+        req.set_client_known_version(client_known_version);
+        let validator_verifier = Arc::clone(&self.validator_verifier);
+        let mut known_version = client_known_version;
+        // The subscription lives on, so there is no timeout.
+        let responses = self
+            .client
+            .subscribe_to_ledger_infos_opt(&req, CallOption::default())?;
+        Ok(responses
+            .map_err(failure::Error::from)
+            .and_then(move |mut resp| {
+                let ledger_info_with_sigs =
+                    LedgerInfoWithSignatures::from_proto(resp.take_ledger_info_with_sigs())?;
+                verify_update_to_latest_ledger_response(
+                    Arc::clone(&validator_verifier),
+                    known_version,
+                    &[],
+                    &[],
+                    &ledger_info_with_sigs,
+                )?;
+                let version = ledger_info_with_sigs.ledger_info().version();
+                if version == known_version {
+                    return Ok(None);
+                }
+                known_version = version;
+                Ok(Some(ledger_info_with_sigs))
+            })
+            .filter_map(|ledger_info_with_sigs| ledger_info_with_sigs))
+    }
+
+    /// Subscribes to the events of `access_path` from `start_event_seq_num` on, as the validator
+    /// commits them. Events come in batches, each with consecutive sequence numbers and verified
+    /// against the ledger info it comes with before it is yielded. After a reconnect, subscribe
+    /// again from the sequence number after the last event received.
+    pub fn subscribe_to_events(
+        &self,
+        access_path: AccessPath,
+        start_event_seq_num: u64,
+    ) -> Result<
+        impl Stream<Item = (Vec<EventWithProof>, LedgerInfoWithSignatures), Error = failure::Error>,
+    > {
+        let mut req = SubscribeToEventsRequest::new();
+        req.set_access_path(access_path.clone().into_proto());
+        req.set_start_event_seq_num(start_event_seq_num);
+        let validator_verifier = Arc::clone(&self.validator_verifier);
+        let mut next_event_seq_num = start_event_seq_num;
+        // The subscription lives on, so there is no timeout.
+        let responses = self
+            .client
+            .subscribe_to_events_opt(&req, CallOption::default())?;
+        Ok(responses
+            .map_err(failure::Error::from)
+            .and_then(move |mut resp| {
+                let events_with_proof = resp
+                    .take_events_with_proof()
+                    .into_iter()
+                    .map(EventWithProof::from_proto)
+                    .collect::<Result<Vec<_>>>()?;
+                let ledger_info_with_sigs =
+                    LedgerInfoWithSignatures::from_proto(resp.take_ledger_info_with_sigs())?;
+                // Check the events as if they were returned by a get_with_proof query.
+                let request_item = RequestItem::GetEventsByEventAccessPath {
+                    access_path: access_path.clone(),
+                    start_event_seq_num: next_event_seq_num,
+                    ascending: true,
+                    limit: events_with_proof.len() as u64,
+                };
+                let response_item = ResponseItem::GetEventsByEventAccessPath {
+                    events_with_proof,
+                    proof_of_latest_event: None,
+                };
+                verify_update_to_latest_ledger_response(
+                    Arc::clone(&validator_verifier),
+                    0,
+                    &[request_item],
+                    &[response_item.clone()],
+                    &ledger_info_with_sigs,
+                )?;
+                let (events_with_proof, _) =
+                    response_item.into_get_events_by_access_path_response()?;
+                next_event_seq_num += events_with_proof.len() as u64;
+                Ok((events_with_proof, ledger_info_with_sigs))
+            }))
+    }
+
     fn get_with_proof_async(
         &self,
         requested_items: Vec<RequestItem>,
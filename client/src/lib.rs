@@ -12,12 +12,19 @@ use serde::{Deserialize, Serialize};
 use types::account_address::AccountAddress;
 
 pub(crate) mod account_commands;
+/// Labels of the addresses the user deals with.
+pub mod address_book;
+pub(crate) mod address_commands;
 /// Main instance of client holding corresponding information, e.g. account address.
 pub mod client_proxy;
 /// Command struct to interact with client.
 pub mod commands;
+/// Wallet daemon serving a local API to applications.
+pub mod daemon;
+pub(crate) mod dev_commands;
 /// gRPC client wrapper to connect to validator.
 pub(crate) mod grpc_client;
+pub(crate) mod offline_commands;
 pub(crate) mod query_commands;
 pub(crate) mod transfer_commands;
 
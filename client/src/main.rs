@@ -1,7 +1,11 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use client::{client_proxy::ClientProxy, commands::*};
+use client::{
+    client_proxy::{ClientProxy, WALLET_PASSPHRASE_ENV},
+    commands::*,
+};
+use libra_wallet::co_signer::{CoSignerAuth, CoSignerConfig};
 use logger::set_default_global_logger;
 use rustyline::{config::CompletionType, error::ReadlineError, Config, Editor};
 use structopt::StructOpt;
@@ -30,9 +34,10 @@ struct Args {
     /// If not passed, will be derived from host parameter
     #[structopt(short = "f", long = "faucet_server")]
     pub faucet_server: Option<String>,
-    /// File location from which to load mnemonic word for user account address/key generation.
-    /// If not passed, a new mnemonic file will be generated by libra_wallet in the current
-    /// directory.
+    /// File location from which to load the encrypted wallet key shares for user accounts.
+    /// If not passed, a new recovery file will be generated by libra_wallet in the current
+    /// directory. The file is encrypted under the passphrase read from the
+    /// LIBRA_WALLET_PASSPHRASE environment variable.
     #[structopt(short = "n", long = "mnemonic_file")]
     pub mnemonic_file: Option<String>,
     /// File location from which to load config of trusted validators. It is used to verify
@@ -44,6 +49,47 @@ struct Args {
     /// But the preferred method is to simply use libra-swarm to run local networks
     #[structopt(short = "s", long = "validator_set_file")]
     pub validator_set_file: String,
+    /// TOML file describing the co-signer holding the server share of new accounts: its url,
+    /// an optional CA bundle and the client credentials. The flags below override its content.
+    /// If nothing is passed, the co-signer stored in the wallet recovery file is used.
+    #[structopt(short = "c", long = "co_signer_config")]
+    pub co_signer_config: Option<String>,
+    /// Co-signer URL, e.g. https://cosigner.example.com:8000
+    #[structopt(long = "co_signer_url")]
+    pub co_signer_url: Option<String>,
+    /// PEM file with the CA certificate(s), or the pinned certificate, of the co-signer.
+    #[structopt(long = "co_signer_ca_cert")]
+    pub co_signer_ca_cert: Option<String>,
+    /// File holding the bearer token used to authenticate to the co-signer.
+    #[structopt(long = "co_signer_token_file")]
+    pub co_signer_token_file: Option<String>,
+    /// Watch-only descriptor exported by a wallet with `account export_watch_only`. The client
+    /// then only monitors the accounts of the descriptor: no wallet file is used, and every
+    /// command that has to sign a transaction fails.
+    #[structopt(short = "w", long = "watch_only")]
+    pub watch_only: Option<String>,
+}
+
+/// Build the co-signer config from the command line, if any co-signer option was passed.
+fn co_signer_from_args(args: &Args) -> std::io::Result<Option<CoSignerConfig>> {
+    let mut co_signer = match &args.co_signer_config {
+        Some(path) => Some(CoSignerConfig::load_config(path)?),
+        None => None,
+    };
+    if let Some(url) = &args.co_signer_url {
+        co_signer.get_or_insert_with(CoSignerConfig::default).url = url.clone();
+    }
+    if let Some(ca_cert) = &args.co_signer_ca_cert {
+        co_signer
+            .get_or_insert_with(CoSignerConfig::default)
+            .ca_cert_path = Some(ca_cert.into());
+    }
+    if let Some(token_file) = &args.co_signer_token_file {
+        let token = std::fs::read_to_string(token_file)?.trim().to_string();
+        co_signer.get_or_insert_with(CoSignerConfig::default).auth =
+            CoSignerAuth::BearerToken { token };
+    }
+    Ok(co_signer)
 }
 
 fn main() -> std::io::Result<()> {
@@ -53,17 +99,33 @@ fn main() -> std::io::Result<()> {
     let (commands, alias_to_cmd) = get_commands();
 
     let args = Args::from_args();
-    let faucet_account_file = args.faucet_account_file.unwrap_or_else(|| "".to_string());
+    let faucet_account_file = args
+        .faucet_account_file
+        .clone()
+        .unwrap_or_else(|| "".to_string());
+    let co_signer = co_signer_from_args(&args)?;
 
-    let mut client_proxy = ClientProxy::new(
-        &args.host,
-        &args.port,
-        &args.validator_set_file,
-        &faucet_account_file,
-        args.faucet_server,
-        args.mnemonic_file,
-    )
-    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, &format!("{}", e)[..]))?;
+    let client_proxy = match &args.watch_only {
+        Some(watch_only_file) => ClientProxy::new_watch_only(
+            &args.host,
+            &args.port,
+            &args.validator_set_file,
+            args.faucet_server.clone(),
+            watch_only_file,
+        ),
+        None => ClientProxy::new(
+            &args.host,
+            &args.port,
+            &args.validator_set_file,
+            &faucet_account_file,
+            args.faucet_server.clone(),
+            args.mnemonic_file.clone(),
+            std::env::var(WALLET_PASSPHRASE_ENV).ok(),
+            co_signer,
+        ),
+    };
+    let mut client_proxy = client_proxy
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, &format!("{}", e)[..]))?;
 
     // Test connection to validator
     let test_ret = client_proxy.test_validator_connection();
@@ -0,0 +1,224 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client_proxy::ClientProxy, commands::*};
+use vm_genesis::get_transaction_name;
+
+/// Major command to build and sign transactions that are submitted from another machine.
+pub struct OfflineCommand {}
+
+impl Command for OfflineCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["offline", "o"]
+    }
+    fn get_description(&self) -> &'static str {
+        "Offline transaction building and signing"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        let commands: Vec<Box<dyn Command>> = vec![
+            Box::new(OfflineCommandBuildTransfer {}),
+            Box::new(OfflineCommandSign {}),
+            Box::new(OfflineCommandMultiEd25519Address {}),
+            Box::new(OfflineCommandMultiEd25519Start {}),
+            Box::new(OfflineCommandMultiEd25519Sign {}),
+            Box::new(OfflineCommandMultiEd25519Combine {}),
+        ];
+
+        subcommand_execute(&params[0], commands, client, &params[1..]);
+    }
+}
+
+/// Sub command to build an unsigned transfer and write it to the file specified.
+pub struct OfflineCommandBuildTransfer {}
+
+impl Command for OfflineCommandBuildTransfer {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["transfer", "t"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "\n\t<sender_account_address>|<sender_account_ref_id>|<sender_label> \
+         <receiver_account_address>|<receiver_account_ref_id>|<receiver_label> <number_of_coins> <file_path> \
+         [gas_unit_price_in_micro_libras (default=0)] [max_gas_amount_in_micro_libras (default 10000)] \
+         [sequence_number (default=current)] [expiration_in_secs (default=3600)]"
+    }
+    fn get_description(&self) -> &'static str {
+        "Save an unsigned transfer (in libra) to disk, to be signed with `offline sign`"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        if params.len() > 2 {
+            warn_if_unknown_receiver(client, params[2]);
+        }
+        println!(">> Building unsigned transfer");
+        match client.build_offline_transfer(&params) {
+            Ok(raw_txn) => {
+                println!("{}", raw_txn.format_for_client(get_transaction_name));
+                println!("Saved unsigned transaction to disk");
+            }
+            Err(e) => report_error("Error building transaction", e),
+        }
+    }
+}
+
+/// Sub command to sign the transaction read from a file, writing it to another file.
+pub struct OfflineCommandSign {}
+
+impl Command for OfflineCommandSign {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["sign", "s"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<unsigned_file_path> <signed_file_path>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Sign a transaction saved to disk, to be submitted with `submit`"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Signing transaction");
+        match client.sign_offline_transaction(&params) {
+            Ok(signed_txn) => {
+                println!("{}", signed_txn.format_for_client(get_transaction_name));
+                println!("Saved signed transaction to disk");
+            }
+            Err(e) => report_error("Error signing transaction", e),
+        }
+    }
+}
+
+/// Sub command to print the address of a MultiEd25519 account.
+pub struct OfflineCommandMultiEd25519Address {}
+
+impl Command for OfflineCommandMultiEd25519Address {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["multisig_address", "ma"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<threshold> <public_key_hex>..."
+    }
+    fn get_description(&self) -> &'static str {
+        "Print the address of the account any threshold of the public keys may sign for"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.multi_ed25519_address(&params) {
+            Ok(address) => println!("MultiEd25519 account address: {}", hex::encode(address)),
+            Err(e) => report_error("Error computing MultiEd25519 address", e),
+        }
+    }
+}
+
+/// Sub command to start collecting the signatures of a transaction of a MultiEd25519 account.
+pub struct OfflineCommandMultiEd25519Start {}
+
+impl Command for OfflineCommandMultiEd25519Start {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["multisig_start", "mst"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<unsigned_file_path> <partial_file_path> <threshold> <public_key_hex>..."
+    }
+    fn get_description(&self) -> &'static str {
+        "Prepare a transaction saved to disk for the holders of the keys of its sender to sign"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.start_multi_ed25519_transaction(&params) {
+            Ok(partial) => println!(
+                "Saved transaction to disk, {} signature(s) needed",
+                partial.missing_signatures()
+            ),
+            Err(e) => report_error("Error preparing MultiEd25519 transaction", e),
+        }
+    }
+}
+
+/// Sub command to add a signature to a transaction of a MultiEd25519 account.
+pub struct OfflineCommandMultiEd25519Sign {}
+
+impl Command for OfflineCommandMultiEd25519Sign {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["multisig_sign", "mss"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<partial_file_path> <signer_account_address>|<signer_account_ref_id>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Sign a MultiEd25519 transaction with the key of a local account"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Signing MultiEd25519 transaction");
+        match client.sign_multi_ed25519_transaction(&params) {
+            Ok((index, missing)) => println!(
+                "Added the signature of key #{}, {} more signature(s) needed",
+                index, missing
+            ),
+            Err(e) => report_error("Error signing MultiEd25519 transaction", e),
+        }
+    }
+}
+
+/// Sub command to merge the signatures collected for a transaction of a MultiEd25519 account.
+pub struct OfflineCommandMultiEd25519Combine {}
+
+impl Command for OfflineCommandMultiEd25519Combine {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["multisig_combine", "mc"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<signed_file_path> <partial_file_path>..."
+    }
+    fn get_description(&self) -> &'static str {
+        "Merge the signatures of MultiEd25519 transactions, to be submitted with `submit`"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        match client.combine_multi_ed25519_transaction(&params) {
+            Ok(signed_txn) => {
+                println!("{}", signed_txn.format_for_client(get_transaction_name));
+                println!("Saved signed transaction to disk");
+            }
+            Err(e) => report_error("Error combining MultiEd25519 signatures", e),
+        }
+    }
+}
+
+/// Command to submit a transaction signed offline.
+pub struct SubmitCommand {}
+
+impl Command for SubmitCommand {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["submit", "submitb"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<signed_file_path> Suffix 'b' is for blocking."
+    }
+    fn get_description(&self) -> &'static str {
+        "Submit a transaction signed with `offline sign`"
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        if params.len() != 2 {
+            println!("Invalid number of arguments for submit");
+            println!(
+                "{} {}",
+                self.get_aliases().join(" | "),
+                self.get_params_help()
+            );
+            return;
+        }
+
+        println!(">> Submitting transaction");
+        let is_blocking = blocking_cmd(&params[0]);
+        match client.submit_signed_transaction(&params, is_blocking) {
+            Ok((sender, sequence_number)) => {
+                if is_blocking {
+                    println!("Finished transaction!");
+                } else {
+                    println!("Transaction submitted to validator");
+                }
+                println!(
+                    "To query for transaction status, run: query txn_acc_seq {} {} \
+                     <fetch_events=true|false>",
+                    hex::encode(sender),
+                    sequence_number
+                );
+            }
+            Err(e) => report_error("Failed to submit transaction", e),
+        }
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{client_proxy::ClientProxy, commands::*};
+use admission_control_proto::SubmittedTransactionStatus;
 use types::account_config::get_account_resource_or_default;
 use vm_genesis::get_transaction_name;
 
@@ -22,6 +23,7 @@ impl Command for QueryCommand {
             Box::new(QueryCommandGetLatestAccountState {}),
             Box::new(QueryCommandGetTxnByAccountSeq {}),
             Box::new(QueryCommandGetTxnByRange {}),
+            Box::new(QueryCommandGetTxnStatus {}),
             Box::new(QueryCommandGetEvent {}),
         ];
 
@@ -154,6 +156,51 @@ impl Command for QueryCommandGetTxnByAccountSeq {
     }
 }
 
+/// Sub command to get where a submitted transaction stands.
+pub struct QueryCommandGetTxnStatus {}
+
+impl Command for QueryCommandGetTxnStatus {
+    fn get_aliases(&self) -> Vec<&'static str> {
+        vec!["txn_status", "tst"]
+    }
+    fn get_params_help(&self) -> &'static str {
+        "<txn_hash> | <account_ref_id>|<account_address> <sequence_number>"
+    }
+    fn get_description(&self) -> &'static str {
+        "Get the status of a submitted transaction by hash, or by account and sequence number: \
+         unknown, pending in mempool, committed, expired or discarded."
+    }
+    fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
+        println!(">> Getting transaction status");
+        match client.get_txn_status(&params) {
+            Ok(status) => match status {
+                SubmittedTransactionStatus::Unknown => println!("Transaction unknown"),
+                SubmittedTransactionStatus::Pending(timeline_state) => {
+                    println!("Transaction pending in mempool: {:?}", timeline_state)
+                }
+                SubmittedTransactionStatus::Committed(txn_with_proof) => {
+                    println!(
+                        "Transaction committed at version {}: {}",
+                        txn_with_proof.version,
+                        txn_with_proof
+                            .signed_transaction
+                            .format_for_client(get_transaction_name)
+                    );
+                    if let Some(events) = &txn_with_proof.events {
+                        println!("Events: ");
+                        for event in events {
+                            println!("{}", event);
+                        }
+                    }
+                }
+                SubmittedTransactionStatus::Expired => println!("Transaction expired"),
+                SubmittedTransactionStatus::Discarded => println!("Transaction discarded"),
+            },
+            Err(e) => report_error("Error getting transaction status", e),
+        }
+    }
+}
+
 /// Sub command to query transactions by range from validator.
 pub struct QueryCommandGetTxnByRange {}
 
@@ -11,16 +11,20 @@ impl Command for TransferCommand {
         vec!["transfer", "transferb", "t", "tb"]
     }
     fn get_params_help(&self) -> &'static str {
-        "\n\t<sender_account_address>|<sender_account_ref_id> \
-         <receiver_account_address>|<receiver_account_ref_id> <number_of_coins> \
-         [gas_unit_price_in_micro_libras (default=0)] [max_gas_amount_in_micro_libras (default 10000)] \
-         Suffix 'b' is for blocking. "
+        "\n\t<sender_account_address>|<sender_account_ref_id>|<sender_label> \
+         <receiver_account_address>|<receiver_account_ref_id>|<receiver_label> <number_of_coins> \
+         [gas_unit_price_in_micro_libras (default=0)] [max_gas_amount_in_micro_libras (default=estimated)] \
+         [co_signers=<name>[,<name>...]] Suffix 'b' is for blocking. The co-signers sign along \
+         with a threshold sender, by default the first ones of its config, and without the \
+         client share when they reach the threshold. The max gas amount is estimated by \
+         simulating the transfer, falling back to 10000. "
     }
     fn get_description(&self) -> &'static str {
         "Transfer coins (in libra) from account to another."
     }
     fn execute(&self, client: &mut ClientProxy, params: &[&str]) {
-        if params.len() < 4 || params.len() > 6 {
+        let (positional, _) = ClientProxy::split_co_signers(params);
+        if positional.len() < 4 || positional.len() > 6 {
             println!("Invalid number of arguments for transfer");
             println!(
                 "{} {}",
@@ -30,6 +34,7 @@ impl Command for TransferCommand {
             return;
         }
 
+        warn_if_unknown_receiver(client, positional[2]);
         println!(">> Transferring");
         let is_blocking = blocking_cmd(&params[0]);
         match client.transfer_coins(&params, is_blocking) {
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::proto::{
-    node_debug_interface::{DumpJemallocHeapProfileRequest, GetNodeDetailsRequest},
+    node_debug_interface::{
+        DumpJemallocHeapProfileRequest, GetNodeDetailsRequest, GetSenderBlacklistRequest,
+        UpdateSenderBlacklistRequest,
+    },
     node_debug_interface_grpc::NodeDebugInterfaceClient,
 };
 use failure::prelude::*;
@@ -76,4 +79,27 @@ impl NodeDebugClient {
 
         Ok(response.status_code)
     }
+
+    /// Adds a sender to, or removes it from, the blacklist of admission control. Returns false if
+    /// the blacklist already was as requested.
+    pub fn update_sender_blacklist(&self, address: &[u8], blacklisted: bool) -> Result<bool> {
+        let mut request = UpdateSenderBlacklistRequest::new();
+        request.set_address(address.to_vec());
+        request.set_blacklisted(blacklisted);
+        let response = self
+            .client
+            .update_sender_blacklist(&request)
+            .context("Unable to update the sender blacklist")?;
+
+        Ok(response.changed)
+    }
+
+    pub fn get_sender_blacklist(&self) -> Result<Vec<Vec<u8>>> {
+        let response = self
+            .client
+            .get_sender_blacklist(&GetSenderBlacklistRequest::new())
+            .context("Unable to query the sender blacklist")?;
+
+        Ok(response.addresses.into_vec())
+    }
 }
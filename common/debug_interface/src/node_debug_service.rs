@@ -6,21 +6,57 @@
 use crate::proto::{
     node_debug_interface::{
         DumpJemallocHeapProfileRequest, DumpJemallocHeapProfileResponse, GetNodeDetailsRequest,
-        GetNodeDetailsResponse,
+        GetNodeDetailsResponse, GetSenderBlacklistRequest, GetSenderBlacklistResponse,
+        UpdateSenderBlacklistRequest, UpdateSenderBlacklistResponse,
     },
     node_debug_interface_grpc::NodeDebugInterface,
 };
+use failure::prelude::*;
 use futures::Future;
+use grpcio::{RpcStatus, RpcStatusCode};
 use logger::prelude::*;
 use metrics::counters::COUNTER_ADMISSION_CONTROL_CANNOT_SEND_REPLY;
+use std::sync::Arc;
+
+/// The blacklist of senders admission control refuses transactions from, as the debug interface
+/// manages it. Addresses are raw account address bytes.
+pub trait SenderBlacklistManager: Send + Sync {
+    /// Adds the sender to the blacklist, or removes it, and returns whether the blacklist changed.
+    fn update(&self, address: &[u8], blacklisted: bool) -> Result<bool>;
+
+    /// Returns the blacklisted senders.
+    fn senders(&self) -> Vec<Vec<u8>>;
+}
 
 #[derive(Clone, Default)]
-pub struct NodeDebugService {}
+pub struct NodeDebugService {
+    /// Blacklist of the admission control running in the same process, if any.
+    sender_blacklist: Option<Arc<dyn SenderBlacklistManager>>,
+}
 
 impl NodeDebugService {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Debug service which can also manage the sender blacklist of admission control.
+    pub fn with_sender_blacklist(sender_blacklist: Arc<dyn SenderBlacklistManager>) -> Self {
+        Self {
+            sender_blacklist: Some(sender_blacklist),
+        }
+    }
+
+    fn sender_blacklist(&self) -> std::result::Result<&dyn SenderBlacklistManager, RpcStatus> {
+        self.sender_blacklist
+            .as_ref()
+            .map(Arc::as_ref)
+            .ok_or_else(|| {
+                RpcStatus::new(
+                    RpcStatusCode::Unimplemented,
+                    Some("No admission control runs in this process".to_string()),
+                )
+            })
+    }
 }
 
 impl NodeDebugInterface for NodeDebugService {
@@ -52,6 +88,51 @@ impl NodeDebugInterface for NodeDebugService {
         let f = sink.success(resp).map_err(default_reply_error_logger);
         ctx.spawn(f)
     }
+
+    fn update_sender_blacklist(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: UpdateSenderBlacklistRequest,
+        sink: ::grpcio::UnarySink<UpdateSenderBlacklistResponse>,
+    ) {
+        info!(
+            "[GRPC] update_sender_blacklist: {}, blacklisted: {}",
+            hex::encode(&req.address),
+            req.blacklisted
+        );
+        let result = self.sender_blacklist().and_then(|blacklist| {
+            blacklist
+                .update(&req.address, req.blacklisted)
+                .map_err(|e| RpcStatus::new(RpcStatusCode::InvalidArgument, Some(e.to_string())))
+        });
+        let f = match result {
+            Ok(changed) => {
+                let mut resp = UpdateSenderBlacklistResponse::new();
+                resp.changed = changed;
+                sink.success(resp)
+            }
+            Err(status) => sink.fail(status),
+        };
+        ctx.spawn(f.map_err(default_reply_error_logger))
+    }
+
+    fn get_sender_blacklist(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        _req: GetSenderBlacklistRequest,
+        sink: ::grpcio::UnarySink<GetSenderBlacklistResponse>,
+    ) {
+        trace!("[GRPC] get_sender_blacklist");
+        let f = match self.sender_blacklist() {
+            Ok(blacklist) => {
+                let mut resp = GetSenderBlacklistResponse::new();
+                resp.addresses = blacklist.senders().into();
+                sink.success(resp)
+            }
+            Err(status) => sink.fail(status),
+        };
+        ctx.spawn(f.map_err(default_reply_error_logger))
+    }
 }
 
 fn default_reply_error_logger<T: ::std::fmt::Debug>(e: T) {
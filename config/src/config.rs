@@ -291,6 +291,16 @@ impl LoggerConfig {
 pub struct SecretServiceConfig {
     pub address: String,
     pub secret_service_port: u16,
+    // TOML file of the co-signer two-party keys are generated with, see
+    // libra_wallet::co_signer::CoSignerConfig. Without it, two-party keys are not available.
+    #[serde(default)]
+    pub co_signer_config: Option<PathBuf>,
+    // Directory keys are persisted to, encrypted under the passphrase read from
+    // key_store_passphrase_file. Without it, keys are lost when the secret service stops.
+    #[serde(default)]
+    pub key_store_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub key_store_passphrase_file: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -298,6 +308,24 @@ pub struct AdmissionControlConfig {
     pub address: String,
     pub admission_control_service_port: u16,
     pub need_to_check_mempool_before_validation: bool,
+    // Hex-encoded addresses of the senders whose transactions are refused. The blacklist can be
+    // changed at runtime through the debug interface; changes are not written back here.
+    #[serde(default)]
+    pub blacklisted_senders: Vec<String>,
+    // Token-bucket limits on transaction submissions, per sender and per peer IP. Submissions are
+    // not limited if unset.
+    #[serde(default)]
+    pub sender_rate_limit: Option<RateLimitConfig>,
+    #[serde(default)]
+    pub peer_ip_rate_limit: Option<RateLimitConfig>,
+}
+
+/// A token bucket: holds up to `burst` submissions and refills at `per_second` submissions per
+/// second. Both must be positive.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RateLimitConfig {
+    pub burst: u32,
+    pub per_second: f64,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -345,6 +373,11 @@ pub struct ConsensusConfig {
     contiguous_rounds: u32,
     max_pruned_blocks_in_mem: Option<u64>,
     pacemaker_initial_timeout_ms: Option<u64>,
+    // Hex encoded id of the consensus key held by the secret service. When set, consensus
+    // messages are signed by the secret service at secret_service.address and the consensus
+    // private key of peer_keypairs is not used.
+    #[serde(default)]
+    secret_service_key_id: Option<String>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -379,6 +412,10 @@ impl ConsensusConfig {
     pub fn pacemaker_initial_timeout_ms(&self) -> &Option<u64> {
         &self.pacemaker_initial_timeout_ms
     }
+
+    pub fn secret_service_key_id(&self) -> &Option<String> {
+        &self.secret_service_key_id
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
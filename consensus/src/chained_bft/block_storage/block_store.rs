@@ -458,7 +458,7 @@ impl<T: Payload> BlockReader for BlockStore<T> {
         payload: Self::Payload,
         round: Round,
         timestamp_usecs: u64,
-    ) -> Block<Self::Payload> {
+    ) -> failure::Result<Block<Self::Payload>> {
         if self.enforce_increasing_timestamps {
             checked_precondition!(parent.timestamp_usecs() < timestamp_usecs);
         }
@@ -52,7 +52,9 @@ fn build_simple_tree() -> (Vec<Arc<Block<Vec<usize>>>>, Arc<BlockStore<Vec<usize
 fn test_block_store_create_block() {
     let block_store = build_empty_tree();
     let genesis = block_store.root();
-    let a1 = block_store.create_block(Arc::clone(&genesis), vec![1], 1, 1);
+    let a1 = block_store
+        .create_block(Arc::clone(&genesis), vec![1], 1, 1)
+        .unwrap();
     assert_eq!(a1.parent_id(), genesis.id());
     assert_eq!(a1.round(), 1);
     assert_eq!(a1.height(), 1);
@@ -68,10 +70,13 @@ fn test_block_store_create_block() {
         block_store.signer().author(),
         placeholder_ledger_info(),
         block_store.signer(),
-    );
+    )
+    .unwrap();
     block_on(block_store.insert_vote_and_qc(vote_msg, 1));
 
-    let b1 = block_store.create_block(Arc::clone(&a1_ref), vec![2], 2, 2);
+    let b1 = block_store
+        .create_block(Arc::clone(&a1_ref), vec![2], 2, 2)
+        .unwrap();
     assert_eq!(b1.parent_id(), a1_ref.id());
     assert_eq!(b1.round(), 2);
     assert_eq!(b1.height(), 2);
@@ -83,11 +88,11 @@ fn test_highest_block_and_quorum_cert() {
     let block_store = build_empty_tree();
     assert_eq!(
         block_store.highest_certified_block().as_ref(),
-        &Block::make_genesis_block()
+        &Block::make_genesis_block().unwrap()
     );
     assert_eq!(
         block_store.highest_quorum_cert().as_ref(),
-        &QuorumCert::certificate_for_genesis()
+        &QuorumCert::certificate_for_genesis().unwrap()
     );
 
     let genesis = block_store.root();
@@ -97,11 +102,11 @@ fn test_highest_block_and_quorum_cert() {
     let block_round_1 = inserter.insert_block(genesis.as_ref(), 1);
     assert_eq!(
         block_store.highest_certified_block().as_ref(),
-        &Block::make_genesis_block()
+        &Block::make_genesis_block().unwrap()
     );
     assert_eq!(
         block_store.highest_quorum_cert().as_ref(),
-        &QuorumCert::certificate_for_genesis()
+        &QuorumCert::certificate_for_genesis().unwrap()
     );
 
     // block_round_1 block and quorum certificate is now the highest
@@ -340,7 +345,8 @@ fn test_insert_vote() {
             voter.author(),
             placeholder_ledger_info(),
             voter,
-        );
+        )
+        .unwrap();
         let vote_res = block_on(block_store.insert_vote_and_qc(vote_msg.clone(), qc_size));
 
         // first vote of an author is accepted
@@ -363,7 +369,8 @@ fn test_insert_vote() {
         final_voter.author(),
         placeholder_ledger_info(),
         final_voter,
-    );
+    )
+    .unwrap();
     match block_on(block_store.insert_vote_and_qc(vote_msg, qc_size)) {
         VoteReceptionResult::NewQuorumCertificate(qc) => {
             assert_eq!(qc.certified_block_id(), block.id());
@@ -388,9 +395,10 @@ fn test_illegal_timestamp() {
         1,
         // This timestamp is illegal, it is the same as genesis
         genesis.timestamp_usecs(),
-        QuorumCert::certificate_for_genesis(),
+        QuorumCert::certificate_for_genesis().unwrap(),
         block_store.signer(),
-    );
+    )
+    .unwrap();
     let result = block_on(block_store.execute_and_insert_block(block_with_illegal_timestamp));
     assert!(result.is_err());
     assert_eq!(result.err().unwrap(), InsertError::NonIncreasingTimestamp);
@@ -429,7 +437,7 @@ fn test_need_fetch_for_qc() {
         HashValue::zero(),
         a3.round() + 1,
     );
-    let too_old_qc = QuorumCert::certificate_for_genesis();
+    let too_old_qc = QuorumCert::certificate_for_genesis().unwrap();
     let can_insert_qc =
         placeholder_certificate_for_block(vec![block_tree.signer().clone()], a3.id(), a3.round());
     let duplicate_qc = block_tree.get_quorum_cert_for_block(a2.id()).unwrap();
@@ -482,7 +490,10 @@ fn test_need_sync_for_qc() {
         false,
     );
     assert_eq!(
-        block_tree.need_sync_for_quorum_cert(genesis.id(), &QuorumCert::certificate_for_genesis()),
+        block_tree.need_sync_for_quorum_cert(
+            genesis.id(),
+            &QuorumCert::certificate_for_genesis().unwrap()
+        ),
         false
     );
 }
@@ -25,7 +25,7 @@ use execution_proto::proto::execution_grpc::ExecutionClient;
 use failure::prelude::*;
 use logger::prelude::*;
 use mempool::proto::mempool_grpc::MempoolClient;
-use std::{convert::TryFrom, sync::Arc};
+use std::sync::Arc;
 use tokio::runtime;
 use types::{
     account_address::AccountAddress, transaction::SignedTransaction,
@@ -51,6 +51,7 @@ pub struct ChainedBftProvider {
 impl ChainedBftProvider {
     pub fn new(
         node_config: &NodeConfig,
+        signer: ValidatorSigner,
         network_sender: ConsensusNetworkSender,
         network_events: ConsensusNetworkEvents,
         mempool_client: Arc<MempoolClient>,
@@ -61,7 +62,7 @@ impl ChainedBftProvider {
             .build()
             .expect("Failed to create Tokio runtime!");
 
-        let initial_setup = Self::initialize_setup(node_config);
+        let initial_setup = Self::initialize_setup(node_config, signer);
         let network = ConsensusNetworkImpl::new(
             initial_setup.author,
             network_sender.clone(),
@@ -108,15 +109,10 @@ impl ChainedBftProvider {
 
     /// Retrieve the initial "state" for consensus. This function is synchronous and returns after
     /// reading the local persistent store and retrieving the initial state from the executor.
-    fn initialize_setup(node_config: &NodeConfig) -> InitialSetup {
+    fn initialize_setup(node_config: &NodeConfig, signer: ValidatorSigner) -> InitialSetup {
         // Keeping the initial set of validators in a node config is embarrassing and we should
         // all feel bad about it.
-        let peer_id_str = node_config.base.peer_id.clone();
-        let author =
-            AccountAddress::try_from(peer_id_str).expect("Failed to parse peer id of a validator");
-        let private_key = node_config.base.peer_keypairs.get_consensus_private();
-        let public_key = node_config.base.peer_keypairs.get_consensus_public();
-        let signer = ValidatorSigner::new(author, public_key, private_key);
+        let author = signer.author();
         let peers_with_public_keys = node_config.base.trusted_peers.get_trusted_consensus_peers();
         let peers = Arc::new(
             peers_with_public_keys
@@ -102,7 +102,7 @@ where
     T: Serialize + Default + CanonicalSerialize,
 {
     // Make an empty genesis block
-    pub fn make_genesis_block() -> Self {
+    pub fn make_genesis_block() -> Result<Self> {
         let ancestor_id = HashValue::zero();
         let genesis_validator_signer = ValidatorSigner::genesis();
         let state = ExecutedState::state_for_genesis();
@@ -125,11 +125,9 @@ where
             ),
         );
         let genesis_id = *GENESIS_BLOCK_ID;
-        let signature = genesis_validator_signer
-            .sign_message(genesis_id)
-            .expect("Failed to sign genesis id.");
+        let signature = genesis_validator_signer.sign_message(genesis_id)?;
 
-        Block {
+        Ok(Block {
             id: genesis_id,
             payload: T::default(),
             parent_id: HashValue::zero(),
@@ -139,7 +137,7 @@ where
             quorum_cert: genesis_quorum_cert,
             author: genesis_validator_signer.author(),
             signature,
-        }
+        })
     }
 
     // Create a block directly.  Most users should prefer make_block() as it ensures correct block
@@ -152,7 +150,7 @@ where
         timestamp_usecs: u64,
         quorum_cert: QuorumCert,
         validator_signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self> {
         let block_internal = BlockSerializer {
             parent_id,
             payload: &payload,
@@ -164,11 +162,9 @@ where
         };
 
         let id = block_internal.hash();
-        let signature = validator_signer
-            .sign_message(id)
-            .expect("Failed to sign message");
+        let signature = validator_signer.sign_message(id)?;
 
-        Block {
+        Ok(Block {
             id,
             payload,
             parent_id,
@@ -178,7 +174,7 @@ where
             quorum_cert,
             author: validator_signer.author(),
             signature,
-        }
+        })
     }
 
     pub fn make_block(
@@ -188,7 +184,7 @@ where
         timestamp_usecs: u64,
         quorum_cert: QuorumCert,
         validator_signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self> {
         // A block must carry a QC to its parent.
         checked_precondition_eq!(quorum_cert.certified_block_id(), parent_block.id());
         checked_precondition!(round > parent_block.round());
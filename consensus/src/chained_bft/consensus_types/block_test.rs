@@ -7,13 +7,15 @@ use crate::chained_bft::{
     test_utils::placeholder_certificate_for_block,
 };
 
-use crypto::{HashValue, PrivateKey, PublicKey};
+use crypto::{HashValue, PrivateKey, PublicKey, Signature};
+use failure::prelude::*;
 use proptest::prelude::*;
 use std::{
     panic,
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
-use types::validator_signer::{self, ValidatorSigner};
+use types::validator_signer::{self, MessageSigner, ValidatorSigner};
 
 type LinearizedBlockForest<T> = Vec<Block<T>>;
 
@@ -41,15 +43,15 @@ prop_compose! {
             round,
             height,
             get_current_timestamp().as_micros() as u64,
-            QuorumCert::certificate_for_genesis(),
+            QuorumCert::certificate_for_genesis().unwrap(),
             &signer,
-        )
+        ).unwrap()
     }
 }
 
 /// This produces the genesis block
 pub fn genesis_strategy() -> impl Strategy<Value = Block<Vec<usize>>> {
-    Just(Block::make_genesis_block())
+    Just(Block::make_genesis_block().unwrap())
 }
 
 prop_compose! {
@@ -188,7 +190,7 @@ pub fn block_forest_and_its_keys(
 #[test]
 fn test_genesis() {
     // Test genesis and the next block
-    let genesis_block = Block::<i64>::make_genesis_block();
+    let genesis_block = Block::<i64>::make_genesis_block().unwrap();
     assert_eq!(genesis_block.height(), 0);
     assert_eq!(genesis_block.parent_id(), HashValue::zero());
     assert_ne!(genesis_block.id(), HashValue::zero());
@@ -199,8 +201,8 @@ fn test_genesis() {
 fn test_block_relation() {
     let signer = ValidatorSigner::random();
     // Test genesis and the next block
-    let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let genesis_block = Block::make_genesis_block().unwrap();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
     let payload = 101;
     let next_block = Block::make_block(
         &genesis_block,
@@ -209,7 +211,8 @@ fn test_block_relation() {
         get_current_timestamp().as_micros() as u64,
         quorum_cert,
         &signer,
-    );
+    )
+    .unwrap();
     assert_eq!(next_block.round(), 1);
     assert_eq!(next_block.height(), 1);
     assert_eq!(genesis_block.is_parent_of(&next_block), true);
@@ -228,8 +231,8 @@ fn test_block_qc() {
     // Verify that it's impossible to create a block with QC that doesn't point to a parent.
     let signer = ValidatorSigner::random();
     // Test genesis and the next block
-    let genesis_block = Block::make_genesis_block();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_block = Block::make_genesis_block().unwrap();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
 
     let payload = 42;
     let a1 = Block::make_block(
@@ -239,7 +242,8 @@ fn test_block_qc() {
         get_current_timestamp().as_micros() as u64,
         genesis_qc.clone(),
         &signer,
-    );
+    )
+    .unwrap();
     let a1_qc = placeholder_certificate_for_block(vec![signer.clone()], a1.id(), a1.round());
 
     let result = panic::catch_unwind(|| {
@@ -251,7 +255,8 @@ fn test_block_qc() {
             get_current_timestamp().as_micros() as u64,
             genesis_qc.clone(),
             &signer,
-        );
+        )
+        .unwrap();
     });
     assert!(result.is_err());
 
@@ -263,10 +268,43 @@ fn test_block_qc() {
         get_current_timestamp().as_micros() as u64,
         a1_qc.clone(),
         &signer,
-    );
+    )
+    .unwrap();
     assert_eq!(a2.height(), 2);
 }
 
+/// Signer whose private key is unavailable, e.g. a secret service that cannot be reached.
+#[derive(Debug)]
+struct UnavailableSigner;
+
+impl MessageSigner for UnavailableSigner {
+    fn sign_message(&self, _message: HashValue) -> Result<Signature> {
+        bail!("secret service unavailable")
+    }
+}
+
+#[test]
+fn test_block_signing_failure() {
+    let signer = ValidatorSigner::random();
+    let unavailable_signer = ValidatorSigner::with_signer(
+        signer.author(),
+        signer.public_key(),
+        Arc::new(UnavailableSigner),
+    );
+    let genesis_block = Block::make_genesis_block().unwrap();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
+
+    assert!(Block::make_block(
+        &genesis_block,
+        42,
+        1,
+        get_current_timestamp().as_micros() as u64,
+        genesis_qc,
+        &unavailable_signer,
+    )
+    .is_err());
+}
+
 // Using current_timestamp in this test
 // because it's a bit hard to generate incremental timestamps in proptests
 fn get_current_timestamp() -> Duration {
@@ -9,7 +9,7 @@ fn test_put_get() {
     let tmp_dir = tempdir().unwrap();
     let db = ConsensusDB::new(&tmp_dir);
 
-    let block = Block::<i64>::make_genesis_block();
+    let block = Block::<i64>::make_genesis_block().unwrap();
     let blocks = vec![block];
 
     let old_blocks = db.get_blocks::<i64>().unwrap();
@@ -19,7 +19,7 @@ fn test_put_get() {
 
     db.save_state(vec![0x01, 0x02, 0x03]).unwrap();
 
-    let qcs = vec![QuorumCert::certificate_for_genesis()];
+    let qcs = vec![QuorumCert::certificate_for_genesis().unwrap()];
 
     db.save_blocks_and_quorum_certificates(blocks, qcs).unwrap();
 
@@ -37,10 +37,10 @@ fn test_delete_block_and_qc() {
     assert_eq!(db.get_blocks::<i64>().unwrap().len(), 0);
     assert_eq!(db.get_quorum_certificates().unwrap().len(), 0);
 
-    let blocks = vec![Block::<i64>::make_genesis_block()];
+    let blocks = vec![Block::<i64>::make_genesis_block().unwrap()];
     let block_id = blocks[0].id();
 
-    let qcs = vec![QuorumCert::certificate_for_genesis()];
+    let qcs = vec![QuorumCert::certificate_for_genesis().unwrap()];
     let qc_id = qcs[0].certified_block_id();
 
     db.save_blocks_and_quorum_certificates(blocks, qcs).unwrap();
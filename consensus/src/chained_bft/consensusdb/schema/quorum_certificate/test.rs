@@ -6,6 +6,6 @@ use schemadb::schema::assert_encode_decode;
 
 #[test]
 fn test_encode_decode() {
-    let qc = QuorumCert::certificate_for_genesis();
+    let qc = QuorumCert::certificate_for_genesis().unwrap();
     assert_encode_decode::<QCSchema>(&qc.certified_block_id(), &qc);
 }
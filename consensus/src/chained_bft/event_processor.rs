@@ -408,12 +408,25 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
             round
         );
 
-        Some(NewRoundMsg::new(
-            self.block_store.highest_quorum_cert().as_ref().clone(),
-            self.block_store.highest_ledger_info().as_ref().clone(),
-            PacemakerTimeout::new(round, self.block_store.signer()),
-            self.block_store.signer(),
-        ))
+        let new_round_msg =
+            PacemakerTimeout::new(round, self.block_store.signer()).and_then(|pacemaker_timeout| {
+                NewRoundMsg::new(
+                    self.block_store.highest_quorum_cert().as_ref().clone(),
+                    self.block_store.highest_ledger_info().as_ref().clone(),
+                    pacemaker_timeout,
+                    self.block_store.signer(),
+                )
+            });
+        match new_round_msg {
+            Ok(new_round_msg) => Some(new_round_msg),
+            Err(e) => {
+                error!(
+                    "Failed to sign the new round message at round {} due to {:?}",
+                    round, e
+                );
+                None
+            }
+        }
     }
 
     /// This function processes a proposal that was chosen as a representative of its round:
@@ -543,14 +556,20 @@ impl<T: Payload, P: ProposerInfo> EventProcessor<T, P> {
         let ledger_info_placeholder = self
             .block_store
             .ledger_info_placeholder(vote_info.potential_commit_id());
-        let vote_msg = VoteMsg::new(
+        let vote_msg = match VoteMsg::new(
             proposal_id,
             executed_state,
             block.round(),
             self.author.get_author(),
             ledger_info_placeholder,
             self.block_store.signer(),
-        );
+        ) {
+            Ok(vote_msg) => vote_msg,
+            Err(e) => {
+                error!("Failed to sign the vote for {}: {:?}", block, e);
+                return;
+            }
+        };
 
         let recipients: Vec<Author> = self
             .proposer_election
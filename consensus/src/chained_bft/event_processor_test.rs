@@ -254,8 +254,11 @@ fn basic_new_rank_event_test() {
     let node = &nodes[0];
     let genesis = node.block_store.root();
     let mut inserter = TreeInserter::new(node.block_store.clone());
-    let a1 =
-        inserter.insert_block_with_qc(QuorumCert::certificate_for_genesis(), genesis.as_ref(), 1);
+    let a1 = inserter.insert_block_with_qc(
+        QuorumCert::certificate_for_genesis().unwrap(),
+        genesis.as_ref(),
+        1,
+    );
     block_on(async move {
         let new_round = 1;
         node.event_processor
@@ -295,7 +298,8 @@ fn basic_new_rank_event_test() {
             node.block_store.signer().author(),
             placeholder_ledger_info(),
             node.block_store.signer(),
-        );
+        )
+        .unwrap();
         node.block_store.insert_vote_and_qc(vote_msg, 0).await;
         node.event_processor
             .process_new_round_event(NewRoundEvent {
@@ -339,7 +343,7 @@ fn process_successful_proposal_test() {
     let node = &nodes[1];
 
     let genesis = node.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
     block_on(async move {
         let proposal_info = ProposalInfo::<TestPayload, Author> {
             proposal: Block::make_block(
@@ -349,7 +353,8 @@ fn process_successful_proposal_test() {
                 1,
                 genesis_qc.clone(),
                 node.block_store.signer(),
-            ),
+            )
+            .unwrap(),
             proposer_info: node.author,
             timeout_certificate: None,
             highest_ledger_info: genesis_qc.clone(),
@@ -387,7 +392,7 @@ fn process_old_proposal_test() {
     let nodes = NodeSetup::create_nodes(&mut playground, runtime.executor(), 2);
     let node = &nodes[1];
     let genesis = node.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
     let new_block = Block::make_block(
         genesis.as_ref(),
         vec![1],
@@ -395,7 +400,8 @@ fn process_old_proposal_test() {
         1,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let new_block_id = new_block.id();
     let old_block = Block::make_block(
         genesis.as_ref(),
@@ -404,7 +410,8 @@ fn process_old_proposal_test() {
         2,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let old_block_id = old_block.id();
     block_on(async move {
         node.event_processor
@@ -452,7 +459,7 @@ fn process_round_mismatch_test() {
         .pop()
         .unwrap();
     let genesis = node.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
     let correct_block = Block::make_block(
         genesis.as_ref(),
         vec![1],
@@ -460,7 +467,8 @@ fn process_round_mismatch_test() {
         1,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let correct_block_id = correct_block.id();
     let block_skip_round = Block::make_block(
         genesis.as_ref(),
@@ -469,7 +477,8 @@ fn process_round_mismatch_test() {
         2,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     block_on(async move {
         node.event_processor
             .process_proposal(ProposalInfo::<TestPayload, Author> {
@@ -510,7 +519,8 @@ fn process_new_round_msg_test() {
     let genesis = non_proposer.block_store.root();
     let block_0 = non_proposer
         .block_store
-        .create_block(genesis, vec![1], 1, 1);
+        .create_block(genesis, vec![1], 1, 1)
+        .unwrap();
     let block_0_id = block_0.id();
     block_on(
         non_proposer
@@ -555,14 +565,15 @@ fn process_new_round_msg_test() {
     // As the static proposer processes the new round message it should learn about
     // block_0_quorum_cert at round 1.
     block_on(
-        static_proposer
-            .event_processor
-            .process_new_round_msg(NewRoundMsg::new(
+        static_proposer.event_processor.process_new_round_msg(
+            NewRoundMsg::new(
                 block_0_quorum_cert,
-                QuorumCert::certificate_for_genesis(),
-                PacemakerTimeout::new(2, &non_proposer.signer),
+                QuorumCert::certificate_for_genesis().unwrap(),
+                PacemakerTimeout::new(2, &non_proposer.signer).unwrap(),
                 &non_proposer.signer,
-            )),
+            )
+            .unwrap(),
+        ),
     );
     assert_eq!(
         static_proposer
@@ -584,7 +595,7 @@ fn process_proposer_mismatch_test() {
     let incorrect_proposer = nodes.pop().unwrap();
     let mut node = nodes.pop().unwrap();
     let genesis = node.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
     let correct_block = Block::make_block(
         genesis.as_ref(),
         vec![1],
@@ -592,7 +603,8 @@ fn process_proposer_mismatch_test() {
         1,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let correct_block_id = correct_block.id();
     let block_incorrect_proposer = Block::make_block(
         genesis.as_ref(),
@@ -601,7 +613,8 @@ fn process_proposer_mismatch_test() {
         1,
         genesis_qc.clone(),
         incorrect_proposer.block_store.signer(),
-    );
+    )
+    .unwrap();
     block_on(async move {
         node.event_processor
             .process_proposal(ProposalInfo::<TestPayload, Author> {
@@ -641,7 +654,7 @@ fn process_timeout_certificate_test() {
         .pop()
         .unwrap();
     let genesis = node.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
     let correct_block = Block::make_block(
         genesis.as_ref(),
         vec![1],
@@ -649,7 +662,8 @@ fn process_timeout_certificate_test() {
         1,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let _correct_block_id = correct_block.id();
     let block_skip_round = Block::make_block(
         genesis.as_ref(),
@@ -658,9 +672,11 @@ fn process_timeout_certificate_test() {
         2,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let block_skip_round_id = block_skip_round.id();
-    let tc = PacemakerTimeoutCertificate::new(1, vec![PacemakerTimeout::new(1, &node.signer)]);
+    let tc =
+        PacemakerTimeoutCertificate::new(1, vec![PacemakerTimeout::new(1, &node.signer).unwrap()]);
     block_on(async move {
         node.event_processor
             .process_proposal(ProposalInfo::<TestPayload, Author> {
@@ -699,8 +715,11 @@ fn process_votes_basic_test() {
         .unwrap();
     let genesis = node.block_store.root();
     let mut inserter = TreeInserter::new(node.block_store.clone());
-    let a1 =
-        inserter.insert_block_with_qc(QuorumCert::certificate_for_genesis(), genesis.as_ref(), 1);
+    let a1 = inserter.insert_block_with_qc(
+        QuorumCert::certificate_for_genesis().unwrap(),
+        genesis.as_ref(),
+        1,
+    );
     let vote_msg = VoteMsg::new(
         a1.id(),
         node.block_store.get_state_for_block(a1.id()).unwrap(),
@@ -708,7 +727,8 @@ fn process_votes_basic_test() {
         node.block_store.signer().author(),
         placeholder_ledger_info(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     block_on(async move {
         node.event_processor.process_vote(vote_msg, 1).await;
         let new_round_event = node.new_rounds_receiver.next().await.unwrap();
@@ -727,7 +747,7 @@ fn process_chunk_retrieval() {
         .unwrap();
 
     let genesis = node.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
 
     let block = Block::make_block(
         genesis.as_ref(),
@@ -736,7 +756,8 @@ fn process_chunk_retrieval() {
         1,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let proposal_info = ProposalInfo::<TestPayload, Author> {
         proposal: block.clone(),
         proposer_info: node.author,
@@ -778,7 +799,7 @@ fn process_block_retrieval() {
         .unwrap();
 
     let genesis = node.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
 
     let block = Block::make_block(
         genesis.as_ref(),
@@ -787,7 +808,8 @@ fn process_block_retrieval() {
         1,
         genesis_qc.clone(),
         node.block_store.signer(),
-    );
+    )
+    .unwrap();
     let block_id = block.id();
     let proposal_info = ProposalInfo::<TestPayload, Author> {
         proposal: block.clone(),
@@ -871,7 +893,7 @@ fn basic_restart_test() {
     let node_mut = &mut node;
 
     let genesis = node_mut.block_store.root();
-    let genesis_qc = QuorumCert::certificate_for_genesis();
+    let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
     let mut proposals = Vec::new();
     let proposals_mut = &mut proposals;
     let num_proposals = 100;
@@ -886,7 +908,8 @@ fn basic_restart_test() {
                     1,
                     genesis_qc.clone(),
                     node_mut.block_store.signer(),
-                ),
+                )
+                .unwrap(),
                 proposer_info: node_mut.author,
                 timeout_certificate: None,
                 highest_ledger_info: genesis_qc.clone(),
@@ -84,7 +84,7 @@ fn test_timeout_certificate() {
         // accumulated into single timeout certificate
         for round in 1..rounds {
             let signer = &signers[round - 1];
-            let pacemaker_timeout = PacemakerTimeout::new(round as u64, signer);
+            let pacemaker_timeout = PacemakerTimeout::new(round as u64, signer).unwrap();
             tx.send(PacemakerEvent::RemoteTimeout { pacemaker_timeout })
                 .await
                 .unwrap();
@@ -60,17 +60,15 @@ pub struct PacemakerTimeout {
 
 impl PacemakerTimeout {
     /// Creates new PacemakerTimeoutMsg
-    pub fn new(round: Round, validator_signer: &ValidatorSigner) -> Self {
+    pub fn new(round: Round, validator_signer: &ValidatorSigner) -> failure::Result<Self> {
         let author = validator_signer.author();
         let digest = PacemakerTimeoutSerializer { round, author }.hash();
-        let signature = validator_signer
-            .sign_message(digest)
-            .expect("Failed to sign PacemakerTimeoutMsg");
-        PacemakerTimeout {
+        let signature = validator_signer.sign_message(digest)?;
+        Ok(PacemakerTimeout {
             round,
             author,
             signature,
-        }
+        })
     }
 
     fn pacemaker_timeout_digest(author: AccountAddress, round: Round) -> HashValue {
@@ -181,22 +179,20 @@ impl NewRoundMsg {
         highest_ledger_info: QuorumCert,
         pacemaker_timeout: PacemakerTimeout,
         validator_signer: &ValidatorSigner,
-    ) -> NewRoundMsg {
+    ) -> failure::Result<NewRoundMsg> {
         let author = validator_signer.author();
         let digest = Self::new_round_digest(
             highest_quorum_certificate.certified_block_id(),
             pacemaker_timeout.digest(),
         );
-        let signature = validator_signer
-            .sign_message(digest)
-            .expect("Failed to sign PacemakerTimeoutMsg");
-        NewRoundMsg {
+        let signature = validator_signer.sign_message(digest)?;
+        Ok(NewRoundMsg {
             highest_quorum_certificate,
             highest_ledger_info,
             pacemaker_timeout,
             author,
             signature,
-        }
+        })
     }
 
     fn new_round_digest(
@@ -37,6 +37,9 @@ pub enum ProposalGenerationError {
     /// Already proposed at this round (only a single proposal per round is allowed)
     #[fail(display = "CurrentTimeTooOld")]
     AlreadyProposed(Round),
+    /// The signer failed to sign the proposed block
+    #[fail(display = "SigningError")]
+    SigningError,
 }
 
 /// ProposalGenerator is responsible for generating the proposed block on demand: it's typically
@@ -207,12 +210,12 @@ impl<T: Payload> ProposalGenerator<T> {
             .pull_txns(self.max_block_size, exclude_payload)
             .await
         {
-            Ok(txns) => Ok(block_store.create_block(
-                hqc_block,
-                txns,
-                round,
-                block_timestamp.as_micros() as u64,
-            )),
+            Ok(txns) => block_store
+                .create_block(hqc_block, txns, round, block_timestamp.as_micros() as u64)
+                .map_err(|e| {
+                    error!("Failed to sign the proposal at round {}: {:?}", round, e);
+                    ProposalGenerationError::SigningError
+                }),
             Err(_) => Err(ProposalGenerationError::TxnRetrievalError),
         }
     }
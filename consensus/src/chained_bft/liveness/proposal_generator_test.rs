@@ -81,7 +81,8 @@ fn test_proposal_generation_parent() {
         block_store.signer().author(),
         placeholder_ledger_info(),
         block_store.signer(),
-    );
+    )
+    .unwrap();
     block_on(block_store.insert_vote_and_qc(vote_msg_a1, 1));
     let a1_child_res =
         block_on(proposal_generator.generate_proposal(11, minute_from_now())).unwrap();
@@ -98,7 +99,8 @@ fn test_proposal_generation_parent() {
         block_store.signer().author(),
         placeholder_ledger_info(),
         block_store.signer(),
-    );
+    )
+    .unwrap();
 
     block_on(block_store.insert_vote_and_qc(vote_msg_b1, 1));
     let b1_child_res =
@@ -129,7 +131,8 @@ fn test_old_proposal_generation() {
         block_store.signer().author(),
         placeholder_ledger_info(),
         block_store.signer(),
-    );
+    )
+    .unwrap();
     block_on(block_store.insert_vote_and_qc(vote_msg_a1, 1));
 
     let proposal_err = block_on(proposal_generator.generate_proposal(1, minute_from_now())).err();
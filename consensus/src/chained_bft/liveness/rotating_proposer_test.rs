@@ -33,8 +33,8 @@ fn test_rotating_proposer() {
     // follow the round-robin rotation.
 
     // Test genesis and the next block
-    let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let genesis_block = Block::make_genesis_block().unwrap();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
 
     let good_proposal = ProposalInfo {
         proposal: Block::make_block(
@@ -44,7 +44,8 @@ fn test_rotating_proposer() {
             1,
             quorum_cert.clone(),
             &another_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: another_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -57,7 +58,8 @@ fn test_rotating_proposer() {
             2,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -70,7 +72,8 @@ fn test_rotating_proposer() {
             3,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -112,8 +115,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
     // follow the round-robin rotation with 3 contiguous rounds.
 
     // Test genesis and the next block
-    let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let genesis_block = Block::make_genesis_block().unwrap();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
 
     let good_proposal = ProposalInfo {
         proposal: Block::make_block(
@@ -123,7 +126,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
             1,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -136,7 +140,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
             2,
             quorum_cert.clone(),
             &another_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: another_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -149,7 +154,8 @@ fn test_rotating_proposer_with_three_contiguous_rounds() {
             3,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -187,8 +193,8 @@ fn test_fixed_proposer() {
     // from the chosen author.
 
     // Test genesis and the next block
-    let genesis_block = Block::make_genesis_block();
-    let quorum_cert = QuorumCert::certificate_for_genesis();
+    let genesis_block = Block::make_genesis_block().unwrap();
+    let quorum_cert = QuorumCert::certificate_for_genesis().unwrap();
 
     let good_proposal = ProposalInfo {
         proposal: Block::make_block(
@@ -198,7 +204,8 @@ fn test_fixed_proposer() {
             1,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -211,7 +218,8 @@ fn test_fixed_proposer() {
             2,
             quorum_cert.clone(),
             &another_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: another_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -224,7 +232,8 @@ fn test_fixed_proposer() {
             3,
             quorum_cert.clone(),
             &chosen_validator_signer,
-        ),
+        )
+        .unwrap(),
         proposer_info: chosen_author,
         timeout_certificate: None,
         highest_ledger_info: quorum_cert.clone(),
@@ -363,12 +363,13 @@ fn test_network_api() {
         peers[0],
         placeholder_ledger_info(),
         &signers[0],
-    );
+    )
+    .unwrap();
     let proposal = ProposalInfo {
-        proposal: Block::make_genesis_block(),
+        proposal: Block::make_genesis_block().unwrap(),
         proposer_info: ValidatorSigner::genesis().author(),
         timeout_certificate: None,
-        highest_ledger_info: QuorumCert::certificate_for_genesis(),
+        highest_ledger_info: QuorumCert::certificate_for_genesis().unwrap(),
     };
     block_on(async move {
         nodes[0].send_vote(vote.clone(), peers[2..5].to_vec()).await;
@@ -433,7 +434,7 @@ fn test_rpc() {
         nodes.push(node);
     }
     let receiver_1 = receivers.remove(1);
-    let genesis = Arc::new(Block::<u64>::make_genesis_block());
+    let genesis = Arc::new(Block::<u64>::make_genesis_block().unwrap());
     let genesis_clone = Arc::clone(&genesis);
 
     // verify request block rpc
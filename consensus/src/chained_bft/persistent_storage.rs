@@ -326,8 +326,11 @@ impl<T: Payload> PersistentStorage<T> for StorageWriteProxy {
         let mut quorum_certs: Vec<_> = initial_data.3;
         // bootstrap the empty store with genesis block and qc.
         if blocks.is_empty() && quorum_certs.is_empty() {
-            blocks.push(Block::make_genesis_block());
-            quorum_certs.push(QuorumCert::certificate_for_genesis());
+            blocks.push(Block::make_genesis_block().expect("unable to sign the genesis block"));
+            quorum_certs.push(
+                QuorumCert::certificate_for_genesis()
+                    .expect("unable to sign the genesis quorum certificate"),
+            );
             proxy
                 .save_tree(vec![blocks[0].clone()], vec![quorum_certs[0].clone()])
                 .expect("unable to bootstrap the storage with genesis block");
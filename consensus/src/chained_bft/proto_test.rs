@@ -16,7 +16,7 @@ use types::validator_signer::ValidatorSigner;
 
 #[test]
 fn test_proto_convert_block() {
-    let block: Block<u64> = Block::make_genesis_block();
+    let block: Block<u64> = Block::make_genesis_block().unwrap();
     assert_protobuf_encode_decode(&block);
 }
 
@@ -24,10 +24,10 @@ fn test_proto_convert_block() {
 fn test_proto_convert_proposal() {
     let author = ValidatorSigner::random().author();
     let proposal = ProposalInfo {
-        proposal: Block::<u64>::make_genesis_block(),
+        proposal: Block::<u64>::make_genesis_block().unwrap(),
         proposer_info: author,
         timeout_certificate: None,
-        highest_ledger_info: QuorumCert::certificate_for_genesis(),
+        highest_ledger_info: QuorumCert::certificate_for_genesis().unwrap(),
     };
     assert_protobuf_encode_decode(&proposal);
 }
@@ -42,6 +42,7 @@ fn test_proto_convert_vote() {
         signer.author(),
         placeholder_ledger_info(),
         &signer,
-    );
+    )
+    .unwrap();
     assert_protobuf_encode_decode(&vote);
 }
@@ -107,23 +107,21 @@ impl VoteMsg {
         author: Author,
         mut ledger_info_placeholder: LedgerInfo,
         validator_signer: &ValidatorSigner,
-    ) -> Self {
+    ) -> ProtoResult<Self> {
         ledger_info_placeholder.set_consensus_data_hash(Self::vote_digest(
             proposed_block_id,
             executed_state,
             round,
         ));
-        let li_sig = validator_signer
-            .sign_message(ledger_info_placeholder.hash())
-            .expect("Failed to sign LedgerInfo");
-        Self {
+        let li_sig = validator_signer.sign_message(ledger_info_placeholder.hash())?;
+        Ok(Self {
             proposed_block_id,
             executed_state,
             round,
             author,
             ledger_info: ledger_info_placeholder,
             signature: li_sig,
-        }
+        })
     }
 
     /// Return the proposed block id
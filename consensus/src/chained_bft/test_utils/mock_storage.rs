@@ -155,8 +155,8 @@ impl<T: Payload> PersistentStorage<T> for MockStorage<T> {
         // The current assumption is that the genesis block version is 0.
         storage
             .save_tree(
-                vec![Block::make_genesis_block()],
-                vec![QuorumCert::certificate_for_genesis()],
+                vec![Block::make_genesis_block().unwrap()],
+                vec![QuorumCert::certificate_for_genesis().unwrap()],
             )
             .unwrap();
         (
@@ -199,8 +199,8 @@ impl<T: Payload> PersistentStorage<T> for EmptyStorage {
     }
 
     fn start(_: &NodeConfig) -> (Arc<Self>, RecoveryData<T>) {
-        let genesis = Block::make_genesis_block();
-        let genesis_qc = QuorumCert::certificate_for_genesis();
+        let genesis = Block::make_genesis_block().unwrap();
+        let genesis_qc = QuorumCert::certificate_for_genesis().unwrap();
         let htc = HighestTimeoutCertificates::new(None, None);
         (
             Arc::new(EmptyStorage),
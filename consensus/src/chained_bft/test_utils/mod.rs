@@ -90,14 +90,19 @@ impl TreeInserter {
         round: Round,
     ) -> Arc<Block<Vec<usize>>> {
         self.payload_val += 1;
-        block_on(self.block_store.insert_block_with_qc(Block::make_block(
-            parent,
-            vec![self.payload_val],
-            round,
-            parent.timestamp_usecs() + 1,
-            parent_qc,
-            self.block_store.signer(),
-        )))
+        block_on(
+            self.block_store.insert_block_with_qc(
+                Block::make_block(
+                    parent,
+                    vec![self.payload_val],
+                    round,
+                    parent.timestamp_usecs() + 1,
+                    parent_qc,
+                    self.block_store.signer(),
+                )
+                .unwrap(),
+            ),
+        )
         .unwrap()
     }
 
@@ -122,7 +127,8 @@ impl TreeInserter {
             block.timestamp_usecs(),
             parent_qc,
             block_signer,
-        );
+        )
+        .unwrap();
         block_on(self.block_store.insert_block_with_qc(new_block)).unwrap()
     }
 }
@@ -11,6 +11,7 @@ use grpcio::{ChannelBuilder, EnvBuilder};
 use mempool::proto::mempool_grpc::MempoolClient;
 use std::sync::Arc;
 use storage_client::{StorageRead, StorageReadServiceClient};
+use types::validator_signer::ValidatorSigner;
 
 /// Public interface to a consensus protocol.
 pub trait ConsensusProvider {
@@ -25,14 +26,16 @@ pub trait ConsensusProvider {
     fn stop(&mut self);
 }
 
-/// Helper function to create a ConsensusProvider based on configuration
+/// Helper function to create a ConsensusProvider based on configuration, signing with signer
 pub fn make_consensus_provider(
     node_config: &NodeConfig,
+    signer: ValidatorSigner,
     network_sender: ConsensusNetworkSender,
     network_receiver: ConsensusNetworkEvents,
 ) -> Box<dyn ConsensusProvider> {
     Box::new(ChainedBftProvider::new(
         node_config,
+        signer,
         network_sender,
         network_receiver,
         create_mempool_client(node_config),
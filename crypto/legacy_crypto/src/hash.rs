@@ -556,6 +556,11 @@ define_hasher! {
     (ContractEventHasher, CONTRACT_EVENT_HASHER, b"ContractEvent")
 }
 
+define_hasher! {
+    /// The hasher used to chain the records of the co-signer audit log.
+    (CoSignerAuditHasher, CO_SIGNER_AUDIT_HASHER, b"CoSignerAudit")
+}
+
 define_hasher! {
     /// The hasher used only for testing. It doesn't have a salt.
     (TestOnlyHasher, TEST_ONLY_HASHER, b"")
@@ -113,9 +113,9 @@ impl SigningKey for BLS12381PrivateKey {
 }
 
 impl Uniform for BLS12381PrivateKey {
-    fn generate_for_testing<R>(rng: &mut R) -> Self
+    fn generate<R>(rng: &mut R) -> Self
     where
-        R: SeedableCryptoRng,
+        R: ::rand::RngCore + ::rand::CryptoRng,
     {
         let mut fr_repr: [u64; 4usize] = rng.gen();
         // Since field modulus is 381-bit prime, drop the 3 highest-order bits
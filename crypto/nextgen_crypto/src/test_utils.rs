@@ -3,7 +3,7 @@
 
 //! Internal module containing convenience utility functions mainly for testing
 
-use crate::traits::{SeedableCryptoRng, Uniform};
+use crate::traits::Uniform;
 use bincode::serialize;
 use serde::Serialize;
 
@@ -34,11 +34,11 @@ where
     S: Uniform,
     for<'a> P: From<&'a S>,
 {
-    fn generate_for_testing<R>(rng: &mut R) -> Self
+    fn generate<R>(rng: &mut R) -> Self
     where
-        R: SeedableCryptoRng,
+        R: ::rand::RngCore + ::rand::CryptoRng,
     {
-        let private_key = S::generate_for_testing(rng);
+        let private_key = S::generate(rng);
         private_key.into()
     }
 }
@@ -199,10 +199,19 @@ pub trait SeedableCryptoRng = ::rand::SeedableRng + ::rand::RngCore + ::rand::Cr
 /// A type family for schemes which know how to generate key material from
 /// a cryptographically-secure [`CryptoRng`][::rand::CryptoRng].
 pub trait Uniform {
+    /// Generate key material from a cryptographically-secure RNG, e.g. the OS entropy source.
+    fn generate<R>(rng: &mut R) -> Self
+    where
+        R: ::rand::RngCore + ::rand::CryptoRng;
+
     /// Generate key material from an RNG for testing purposes.
     fn generate_for_testing<R>(rng: &mut R) -> Self
     where
-        R: SeedableCryptoRng;
+        R: SeedableCryptoRng,
+        Self: Sized,
+    {
+        Self::generate(rng)
+    }
 }
 
 /// A type family with a by-convention notion of genesis private key.
@@ -116,9 +116,9 @@ impl VRFExpandedPrivateKey {
 }
 
 impl Uniform for VRFPrivateKey {
-    fn generate_for_testing<R>(rng: &mut R) -> Self
+    fn generate<R>(rng: &mut R) -> Self
     where
-        R: SeedableCryptoRng,
+        R: ::rand::RngCore + ::rand::CryptoRng,
     {
         VRFPrivateKey(ed25519_PrivateKey::generate(rng))
     }
@@ -0,0 +1,271 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Persistence of the keys held by the secret service.
+//!
+//! The secret service keeps its keys in memory and writes every key it generates to a KeyStore,
+//! from which the keys are reloaded on startup. The VolatileKeyStore persists nothing, the keys
+//! are then lost on restart. The EncryptedFileKeyStore writes every key to its own file,
+//! encrypted with ChaCha20-Poly1305 under a key derived from a passphrase with HKDF-SHA3-256 and
+//! a random salt. The key id is bound to the ciphertext, so that files cannot be swapped.
+
+use crate::{
+    crypto_wrappers::{GenericPrivateKey, KeyID},
+    two_party_key::TwoPartyKeyShare,
+};
+use crypto::{hash::HashValue, hkdf::Hkdf};
+use failure::prelude::*;
+use rand::{rngs::OsRng, RngCore};
+use rust_crypto::{
+    aead::{AeadDecryptor, AeadEncryptor},
+    chacha20poly1305::ChaCha20Poly1305,
+};
+use serde::{Deserialize, Serialize};
+use sha3::Sha3_256;
+use std::{
+    fs::{self, DirBuilder, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Version of the key file format written by the EncryptedFileKeyStore.
+pub const KEY_FILE_VERSION: u32 = 1;
+
+const KEY_FILE_EXTENSION: &str = "key";
+const HKDF_INFO: &[u8] = b"LIBRA_SECRET_SERVICE_KEY_STORE";
+const KEY_AAD_PREFIX: &[u8] = b"LIBRA_SECRET_SERVICE_KEY";
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 8;
+const SALT_LENGTH: usize = 32;
+const TAG_LENGTH: usize = 16;
+
+/// What may be done with a key besides signing. The policy is set when the key is generated and
+/// never changes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct KeyPolicy {
+    /// The key can be deleted
+    pub deletable: bool,
+    /// The private key can be exported
+    pub exportable: bool,
+}
+
+/// Secret material of a stored key.
+#[derive(Deserialize, Serialize)]
+pub enum StoredKey {
+    /// Key held entirely by the secret service
+    Local(GenericPrivateKey),
+    /// Share of a key split with a co-signer
+    TwoParty(TwoPartyKeyShare),
+}
+
+/// A key as persisted by a KeyStore.
+#[derive(Deserialize, Serialize)]
+pub struct KeyEntry {
+    /// Secret material
+    pub key: StoredKey,
+    /// Policy of the key
+    pub policy: KeyPolicy,
+}
+
+/// Backend the secret service persists its keys to.
+pub trait KeyStore: Send + Sync {
+    /// Persists a new key.
+    fn store(&self, keyid: &KeyID, entry: &KeyEntry) -> Result<()>;
+    /// Removes a key for good.
+    fn delete(&self, keyid: &KeyID) -> Result<()>;
+    /// Returns all the persisted keys.
+    fn load(&self) -> Result<Vec<(KeyID, KeyEntry)>>;
+}
+
+/// KeyStore that persists nothing: keys only live in the memory of the secret service.
+#[derive(Default)]
+pub struct VolatileKeyStore;
+
+impl KeyStore for VolatileKeyStore {
+    fn store(&self, _keyid: &KeyID, _entry: &KeyEntry) -> Result<()> {
+        Ok(())
+    }
+
+    fn delete(&self, _keyid: &KeyID) -> Result<()> {
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<(KeyID, KeyEntry)>> {
+        Ok(vec![])
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct KeyFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+    tag: String,
+}
+
+/// KeyStore writing every key to its own encrypted file in a directory.
+pub struct EncryptedFileKeyStore {
+    dir: PathBuf,
+    passphrase: Vec<u8>,
+}
+
+impl EncryptedFileKeyStore {
+    /// Opens the store in dir, creating the directory if needed. Only the owner can read the
+    /// directory and the key files.
+    pub fn new(dir: &Path, passphrase: &[u8]) -> Result<Self> {
+        ensure!(!passphrase.is_empty(), "The key store passphrase is empty");
+        let mut builder = DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder
+            .create(dir)
+            .with_context(|_| format!("Cannot create key store directory {:?}", dir))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            passphrase: passphrase.to_vec(),
+        })
+    }
+
+    /// Opens the store in dir, with the passphrase read from passphrase_file. Trailing
+    /// whitespace of the passphrase file is ignored.
+    pub fn with_passphrase_file(dir: &Path, passphrase_file: &Path) -> Result<Self> {
+        let passphrase = fs::read_to_string(passphrase_file).with_context(|_| {
+            format!(
+                "Cannot read key store passphrase from {:?}",
+                passphrase_file
+            )
+        })?;
+        Self::new(dir, passphrase.trim_end().as_bytes())
+    }
+
+    fn path(&self, keyid: &KeyID) -> PathBuf {
+        self.dir
+            .join(format!("{:x}.{}", keyid.0, KEY_FILE_EXTENSION))
+    }
+
+    fn derive_key(&self, salt: &[u8]) -> Result<Vec<u8>> {
+        Hkdf::<Sha3_256>::extract_then_expand(
+            Some(salt),
+            &self.passphrase,
+            Some(HKDF_INFO),
+            KEY_LENGTH,
+        )
+        .map_err(|e| format_err!("Cannot derive key store encryption key: {:?}", e))
+    }
+
+    fn encrypt(&self, keyid: &KeyID, entry: &KeyEntry) -> Result<KeyFile> {
+        let mut rng = OsRng::new()?;
+        let mut salt = [0u8; SALT_LENGTH];
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+        let key = self.derive_key(&salt)?;
+
+        let plaintext = serde_json::to_vec(entry)?;
+        let mut ciphertext = vec![0u8; plaintext.len()];
+        let mut tag = [0u8; TAG_LENGTH];
+        ChaCha20Poly1305::new(&key, &nonce, &aad(keyid)).encrypt(
+            &plaintext,
+            &mut ciphertext,
+            &mut tag,
+        );
+        Ok(KeyFile {
+            version: KEY_FILE_VERSION,
+            salt: hex::encode(&salt[..]),
+            nonce: hex::encode(&nonce[..]),
+            ciphertext: hex::encode(&ciphertext),
+            tag: hex::encode(&tag[..]),
+        })
+    }
+
+    fn decrypt(&self, keyid: &KeyID, file: &KeyFile) -> Result<KeyEntry> {
+        ensure!(
+            file.version == KEY_FILE_VERSION,
+            "Unsupported key file version {}, expected {}",
+            file.version,
+            KEY_FILE_VERSION
+        );
+        let salt = hex::decode(&file.salt)?;
+        let nonce = hex::decode(&file.nonce)?;
+        let ciphertext = hex::decode(&file.ciphertext)?;
+        let tag = hex::decode(&file.tag)?;
+        ensure!(
+            nonce.len() == NONCE_LENGTH && tag.len() == TAG_LENGTH,
+            "Malformed key file"
+        );
+        let key = self.derive_key(&salt)?;
+        let mut plaintext = vec![0u8; ciphertext.len()];
+        ensure!(
+            ChaCha20Poly1305::new(&key, &nonce, &aad(keyid)).decrypt(
+                &ciphertext,
+                &mut plaintext,
+                &tag
+            ),
+            "Unable to decrypt key {:x}, wrong passphrase or corrupted file",
+            keyid.0
+        );
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+impl KeyStore for EncryptedFileKeyStore {
+    fn store(&self, keyid: &KeyID, entry: &KeyEntry) -> Result<()> {
+        let path = self.path(keyid);
+        ensure!(!path.exists(), "Key {:x} is already stored", keyid.0);
+        let file = self.encrypt(keyid, entry)?;
+        // Write then rename, so that a crash never leaves a truncated key behind.
+        let tmp_path = path.with_extension("tmp");
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut tmp_file = options.open(&tmp_path)?;
+        tmp_file.write_all(&serde_json::to_vec(&file)?)?;
+        tmp_file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    fn delete(&self, keyid: &KeyID) -> Result<()> {
+        fs::remove_file(self.path(keyid))?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Vec<(KeyID, KeyEntry)>> {
+        let mut keys = vec![];
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != KEY_FILE_EXTENSION)
+            {
+                continue;
+            }
+            let keyid = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| hex::decode(stem).ok())
+                .and_then(|bytes| HashValue::from_slice(&bytes).ok())
+                .map(KeyID)
+                .ok_or_else(|| format_err!("Malformed key file name {:?}", path))?;
+            let file: KeyFile = serde_json::from_slice(&fs::read(&path)?)?;
+            let entry = self.decrypt(&keyid, &file)?;
+            keys.push((keyid, entry));
+        }
+        Ok(keys)
+    }
+}
+
+fn aad(keyid: &KeyID) -> Vec<u8> {
+    let mut aad = KEY_AAD_PREFIX.to_vec();
+    aad.extend_from_slice(&keyid.to_vec());
+    aad
+}
@@ -5,8 +5,10 @@
 //! A secret service providing cryptographic operations on secret keys, will be used in future
 //! releases.
 pub mod crypto_wrappers;
+pub mod key_store;
 pub mod proto;
 pub mod secret_service_client;
 
 pub mod secret_service_node;
 pub mod secret_service_server;
+pub mod two_party_key;
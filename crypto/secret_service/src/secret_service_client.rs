@@ -5,19 +5,27 @@
 //! This simple key manager will become more complicated in future versions,
 //! now it asks the secret service to generate an ed25519 key on creation,
 //! it can then transfer to the secret service the requests to get consensus public key and to sign
-//! a consensus message.
+//! a consensus message. A ConsensusKeyManager can also back a ValidatorSigner, so that a validator
+//! signs its votes and proposals without ever holding the consensus private key.
 
 use crate::{
     crypto_wrappers::{GenericPublicKey, GenericSignature, KeyID},
     proto::{
-        secret_service::{GenerateKeyRequest, KeyType, PublicKeyRequest, SignRequest},
+        secret_service::{ErrorCode, GenerateKeyRequest, KeyType, PublicKeyRequest, SignRequest},
         secret_service_grpc::SecretServiceClient,
     },
 };
-use crypto::hash::HashValue;
+use crypto::{hash::HashValue, PublicKey, Signature};
 use failure::prelude::*;
-use nextgen_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
-use std::{convert::TryFrom, sync::Arc};
+use nextgen_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    traits::ValidKey,
+};
+use std::{convert::TryFrom, fmt, sync::Arc};
+use types::{
+    account_address::AccountAddress,
+    validator_signer::{MessageSigner, ValidatorSigner},
+};
 
 /// A consensus key manager - interface between consensus and the secret service.
 pub struct ConsensusKeyManager {
@@ -42,11 +50,44 @@ impl ConsensusKeyManager {
         })
     }
 
+    /// Uses the key keyid, already held by the secret service, as the consensus key.
+    pub fn with_key_id(secret_service: Arc<SecretServiceClient>, keyid: KeyID) -> Self {
+        Self {
+            secret_service,
+            signing_keyid: keyid,
+        }
+    }
+
+    /// Makes a ValidatorSigner for author that signs through the secret service, after checking
+    /// that the consensus key held by the secret service is the one of public_key.
+    pub fn into_validator_signer(
+        self,
+        author: AccountAddress,
+        public_key: PublicKey,
+    ) -> Result<ValidatorSigner> {
+        let consensus_public_key = self.get_consensus_public_key()?;
+        ensure!(
+            consensus_public_key.to_bytes()[..] == public_key.to_slice()[..],
+            "The consensus key held by the secret service does not match public key {}",
+            public_key
+        );
+        Ok(ValidatorSigner::with_signer(
+            author,
+            public_key,
+            Arc::new(self),
+        ))
+    }
+
     /// Asks the secret service for the public key and returns it.
     pub fn get_consensus_public_key(&self) -> Result<GenericPublicKey> {
         let mut pk_req: PublicKeyRequest = PublicKeyRequest::new();
         pk_req.set_key_id(self.signing_keyid.to_vec());
         let response = self.secret_service.get_public_key(&pk_req)?;
+        ensure!(
+            response.get_code() == ErrorCode::Success,
+            "Secret service failed to return the consensus public key: {:?}",
+            response.get_code()
+        );
         let public_key: &[u8] = response.get_public_key();
 
         Ok(GenericPublicKey::Ed(Ed25519PublicKey::try_from(
@@ -60,8 +101,30 @@ impl ConsensusKeyManager {
         sig_req.set_key_id(self.signing_keyid.to_vec());
         sig_req.set_message_hash(message.to_vec());
         let response = self.secret_service.sign(&sig_req)?;
+        ensure!(
+            response.get_code() == ErrorCode::Success,
+            "Secret service failed to sign the consensus message: {:?}",
+            response.get_code()
+        );
         let signature = response.get_signature();
 
         Ok(GenericSignature::Ed(Ed25519Signature::try_from(signature)?))
     }
 }
+
+impl fmt::Debug for ConsensusKeyManager {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ConsensusKeyManager {{ signing_keyid: {:?} }}",
+            self.signing_keyid.0
+        )
+    }
+}
+
+impl MessageSigner for ConsensusKeyManager {
+    fn sign_message(&self, message: HashValue) -> Result<Signature> {
+        let signature = self.sign_consensus_message(&message)?;
+        Signature::from_compact(&signature.to_bytes())
+    }
+}
@@ -34,7 +34,7 @@ impl SecretServiceNode {
     pub fn run(&self) -> Result<()> {
         info!("Starting secret service node");
 
-        let handle = SecretServiceServer::new();
+        let handle = SecretServiceServer::from_config(&self.node_config.secret_service)?;
         let service = secret_service_grpc::create_secret_service(handle);
         let _ss_service_handle = spawn_service_thread(
             service,
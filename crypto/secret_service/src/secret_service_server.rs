@@ -3,24 +3,33 @@
 
 //! The Secret service server stores the secret key and performs operations on these keys.
 //! Right now the service supports requests to generate the secret key (of Ed25519 or BLS12-381
-//! type), return the corresponding public key and sign.
+//! type, or two-party Ed25519 with a configured co-signer), return the corresponding public key,
+//! sign, and delete or export the key when its policy allows it. Keys are persisted to a
+//! crate::key_store::KeyStore and reloaded from it on startup.
 
 use crate::{
     crypto_wrappers::{GenericPrivateKey, GenericPublicKey, GenericSignature, KeyID},
+    key_store::{
+        EncryptedFileKeyStore, KeyEntry, KeyPolicy, KeyStore, StoredKey, VolatileKeyStore,
+    },
     proto::{
         secret_service::{
-            ErrorCode, GenerateKeyRequest, GenerateKeyResponse, KeyType, PublicKeyRequest,
+            self, DeleteKeyRequest, DeleteKeyResponse, ErrorCode, ExportKeyRequest,
+            ExportKeyResponse, GenerateKeyRequest, GenerateKeyResponse, KeyType, PublicKeyRequest,
             PublicKeyResponse, SignRequest, SignResponse,
         },
         secret_service_grpc,
     },
+    two_party_key::TwoPartyKey,
 };
+use co_signer_client::co_signer::{CoSigner, CoSignerConfig};
+use config::config::SecretServiceConfig;
 use crypto::hash::HashValue;
 use failure::prelude::*;
 use grpc_helpers::provide_grpc_response;
+use logger::prelude::*;
 use nextgen_crypto::{bls12381::BLS12381PrivateKey, ed25519::Ed25519PrivateKey, traits::*};
-use rand::{rngs::EntropyRng, Rng};
-use rand_chacha::ChaChaRng;
+use rand::rngs::OsRng;
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
@@ -32,7 +41,7 @@ mod secret_service_test;
 
 /// Secret service server that holds the secret keys and implements the necessary operations on
 /// them.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct SecretServiceServer {
     // RwLock is chosen over Mutex because the RwLock won't get poisoned if a panic occurs during
     // read
@@ -40,31 +49,110 @@ pub struct SecretServiceServer {
                                                            * RwLock for being write-accessible
                                                            * by one
                                                            * thread at a time */
+    two_party_keys: Arc<RwLock<HashMap<KeyID, TwoPartyKey>>>,
+    /// Policies of all the keys above
+    policies: Arc<RwLock<HashMap<KeyID, KeyPolicy>>>,
+    /// Co-signer holding the other share of two-party keys, if any is configured
+    co_signer: Option<Arc<dyn CoSigner>>,
+    /// Where keys are persisted
+    store: Arc<dyn KeyStore>,
+}
+
+impl Default for SecretServiceServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&secret_service::KeyPolicy> for KeyPolicy {
+    fn from(policy: &secret_service::KeyPolicy) -> Self {
+        KeyPolicy {
+            deletable: policy.get_deletable(),
+            exportable: policy.get_exportable(),
+        }
+    }
 }
 
 /// SecretServiceServer matches the API of proto/secret_service.proto but operates on our own Crypto
 /// API structures
 impl SecretServiceServer {
-    /// A fresh secret service creates an empty HashMap for the keys.
+    /// A fresh secret service creates an empty HashMap for the keys, which are not persisted.
     pub fn new() -> Self {
         SecretServiceServer {
             keys: Arc::new(RwLock::new(HashMap::new())),
+            two_party_keys: Arc::new(RwLock::new(HashMap::new())),
+            policies: Arc::new(RwLock::new(HashMap::new())),
+            co_signer: None,
+            store: Arc::new(VolatileKeyStore),
         }
     }
 
-    /// Generates a new secret key (for now this is the code for testing).
+    /// A fresh secret service that generates two-party keys together with co_signer.
+    pub fn with_co_signer(co_signer: &CoSignerConfig) -> Result<Self> {
+        Self::with_key_store(Arc::new(VolatileKeyStore), Some(co_signer))
+    }
+
+    /// A secret service persisting its keys to store, starting with the keys already in it.
+    /// Two-party keys can only be generated or loaded with a co_signer.
+    pub fn with_key_store(
+        store: Arc<dyn KeyStore>,
+        co_signer: Option<&CoSignerConfig>,
+    ) -> Result<Self> {
+        let mut server = Self::new();
+        if let Some(co_signer) = co_signer {
+            server.co_signer = Some(co_signer.connect()?);
+        }
+        for (keyid, entry) in store.load()? {
+            server.insert_entry(keyid, entry)?;
+        }
+        server.store = store;
+        Ok(server)
+    }
+
+    /// A secret service set up as described by config: with an encrypted key store if
+    /// key_store_dir is set, and with a co-signer if co_signer_config is set.
+    pub fn from_config(config: &SecretServiceConfig) -> Result<Self> {
+        let store: Arc<dyn KeyStore> =
+            match (&config.key_store_dir, &config.key_store_passphrase_file) {
+                (Some(dir), Some(passphrase_file)) => Arc::new(
+                    EncryptedFileKeyStore::with_passphrase_file(dir, passphrase_file)?,
+                ),
+                (Some(_), None) => bail!("key_store_dir is set without key_store_passphrase_file"),
+                (None, _) => Arc::new(VolatileKeyStore),
+            };
+        let co_signer = match &config.co_signer_config {
+            Some(path) => Some(CoSignerConfig::load_config(path)?),
+            None => None,
+        };
+        Self::with_key_store(store, co_signer.as_ref())
+    }
+
+    /// Generates a new secret key that can neither be deleted nor exported.
     pub fn generate_key_inner(&mut self, spec: KeyType) -> Result<KeyID> {
-        let seed: [u8; 32] = EntropyRng::new().gen();
+        self.generate_key_with_policy_inner(spec, KeyPolicy::default())
+    }
 
-        let private_key: GenericPrivateKey = {
-            let mut rng = ChaChaRng::from_seed(seed);
+    /// Generates a new secret key from the OS entropy source, governed by policy.
+    pub fn generate_key_with_policy_inner(
+        &mut self,
+        spec: KeyType,
+        policy: KeyPolicy,
+    ) -> Result<KeyID> {
+        let key = {
+            let mut rng = OsRng::new()?;
 
             match spec {
                 KeyType::Ed25519 => {
-                    GenericPrivateKey::Ed(Ed25519PrivateKey::generate_for_testing(&mut rng))
+                    StoredKey::Local(GenericPrivateKey::Ed(Ed25519PrivateKey::generate(&mut rng)))
                 }
-                KeyType::BLS12381 => {
-                    GenericPrivateKey::BLS(BLS12381PrivateKey::generate_for_testing(&mut rng))
+                KeyType::BLS12381 => StoredKey::Local(GenericPrivateKey::BLS(
+                    BLS12381PrivateKey::generate(&mut rng),
+                )),
+                KeyType::TwoPartyEd25519 => {
+                    let co_signer = self.co_signer.clone().ok_or_else(|| {
+                        format_err!("No co-signer is configured for two-party keys")
+                    })?;
+                    StoredKey::TwoParty(TwoPartyKey::generate(co_signer)?.share())
                 }
             }
         };
@@ -80,13 +168,87 @@ impl SecretServiceServer {
         let keyid = KeyID(HashValue::from_slice(ed25519_public_key.as_bytes()).unwrap());
          */
 
-        let result = keyid.clone();
-        let mut keys = self
-            .keys
+        // The key is persisted before it is handed out, so that it survives a restart.
+        let entry = KeyEntry { key, policy };
+        self.store.store(&keyid, &entry)?;
+        self.insert_entry(keyid.clone(), entry)?;
+        Ok(keyid)
+    }
+
+    /// Deletes a key if its policy allows it.
+    pub fn delete_key_inner(&mut self, keyid: &KeyID) -> std::result::Result<(), ErrorCode> {
+        let policy = self.policy(keyid).ok_or(ErrorCode::KeyIdNotFound)?;
+        if !policy.deletable {
+            return Err(ErrorCode::OperationNotPermitted);
+        }
+        self.store.delete(keyid).map_err(|e| {
+            error!("Failed to delete key {}: {}", keyid.0, e);
+            ErrorCode::Unspecified
+        })?;
+        self.keys
+            .write()
+            .expect("[deleting key] acquire keys lock")
+            .remove(keyid);
+        self.two_party_keys
             .write()
-            .expect("[generating new key] acquire keys lock");
-        keys.insert(keyid, private_key);
-        Ok(result)
+            .expect("[deleting key] acquire two-party keys lock")
+            .remove(keyid);
+        self.policies
+            .write()
+            .expect("[deleting key] acquire policies lock")
+            .remove(keyid);
+        Ok(())
+    }
+
+    /// Returns the serialized private key if its policy allows it. Only Ed25519 keys can be
+    /// exported: the secret service never holds the whole of a two-party key.
+    pub fn export_key_inner(&self, keyid: &KeyID) -> std::result::Result<Vec<u8>, ErrorCode> {
+        let policy = self.policy(keyid).ok_or(ErrorCode::KeyIdNotFound)?;
+        if !policy.exportable {
+            return Err(ErrorCode::OperationNotPermitted);
+        }
+        let keys = self.keys.read().expect("[exporting key] acquire keys lock");
+        match keys.get(keyid) {
+            Some(GenericPrivateKey::Ed(private_key)) => Ok(private_key.to_bytes().to_vec()),
+            _ => Err(ErrorCode::InvalidParameters),
+        }
+    }
+
+    fn insert_entry(&mut self, keyid: KeyID, entry: KeyEntry) -> Result<()> {
+        match entry.key {
+            StoredKey::Local(private_key) => {
+                let mut keys = self
+                    .keys
+                    .write()
+                    .expect("[inserting key] acquire keys lock");
+                keys.insert(keyid.clone(), private_key);
+            }
+            StoredKey::TwoParty(share) => {
+                let co_signer = self.co_signer.clone().ok_or_else(|| {
+                    format_err!("No co-signer is configured for two-party key {}", keyid.0)
+                })?;
+                let two_party_key = TwoPartyKey::from_share(co_signer, share)?;
+                let mut two_party_keys = self
+                    .two_party_keys
+                    .write()
+                    .expect("[inserting key] acquire two-party keys lock");
+                two_party_keys.insert(keyid.clone(), two_party_key);
+            }
+        }
+        let mut policies = self
+            .policies
+            .write()
+            .expect("[inserting key] acquire policies lock");
+        policies.insert(keyid, entry.policy);
+        Ok(())
+    }
+
+    fn policy(&self, keyid: &KeyID) -> Option<KeyPolicy> {
+        let policies = self
+            .policies
+            .read()
+            .expect("[getting policy] acquire policies lock");
+        policies.get(keyid).cloned()
     }
 
     /// Computes and returns the public key of the corresponding secret key.
@@ -95,17 +257,41 @@ impl SecretServiceServer {
             .keys
             .read()
             .expect("[getting public key] acquire keys lock");
-        keys.get(keyid).map(GenericPublicKey::from)
+        keys.get(keyid).map(GenericPublicKey::from).or_else(|| {
+            self.two_party_key(keyid)
+                .map(|key| GenericPublicKey::Ed(key.public_key().clone()))
+        })
     }
 
-    /// Signs a hash value and returns the signature.
-    pub fn sign_inner(&self, keyid: &KeyID, message: &HashValue) -> Option<GenericSignature> {
-        let keys = self
-            .keys
+    /// Signs a hash value and returns the signature, or None if the key does not exist. Signing
+    /// with a two-party key fails if the co-signer does not complete the protocol.
+    pub fn sign_inner(
+        &self,
+        keyid: &KeyID,
+        message: &HashValue,
+    ) -> Result<Option<GenericSignature>> {
+        {
+            let keys = self
+                .keys
+                .read()
+                .expect("[obtaining signature] acquire keys lock");
+            if let Some(secret_key) = keys.get(keyid) {
+                return Ok(Some(secret_key.sign_message(message)));
+            }
+        }
+        // The lock is not held while talking to the co-signer.
+        match self.two_party_key(keyid) {
+            Some(key) => Ok(Some(GenericSignature::Ed(key.sign_message(message)?))),
+            None => Ok(None),
+        }
+    }
+
+    fn two_party_key(&self, keyid: &KeyID) -> Option<TwoPartyKey> {
+        let two_party_keys = self
+            .two_party_keys
             .read()
-            .expect("[obtaining signature] acquire keys lock");
-        keys.get(keyid)
-            .map(|secret_key| secret_key.sign_message(message))
+            .expect("[getting two-party key] acquire two-party keys lock");
+        two_party_keys.get(keyid).cloned()
     }
 }
 
@@ -122,12 +308,16 @@ impl secret_service_grpc::SecretService for SecretServiceServer {
     ) {
         let mut response = GenerateKeyResponse::new();
         let spec = req.get_spec();
-        let keyid = self.generate_key_inner(spec);
-        if let Ok(key_identity) = keyid {
-            response.set_code(ErrorCode::Success);
-            response.set_key_id(key_identity.to_vec());
-        } else {
-            response.set_code(ErrorCode::Unspecified);
+        let keyid = self.generate_key_with_policy_inner(spec, req.get_policy().into());
+        match keyid {
+            Ok(key_identity) => {
+                response.set_code(ErrorCode::Success);
+                response.set_key_id(key_identity.to_vec());
+            }
+            Err(e) => {
+                error!("Failed to generate {:?} key: {}", spec, e);
+                response.set_code(ErrorCode::Unspecified);
+            }
         }
         provide_grpc_response(Ok(response), ctx, sink);
     }
@@ -176,12 +366,16 @@ impl secret_service_grpc::SecretService for SecretServiceServer {
         } else if let Ok(keyid) = HashValue::from_slice(keyid_raw_bytes) {
             let keyid = KeyID(keyid);
             if let Ok(message) = HashValue::from_slice(message_raw_bytes) {
-                let signature = self.sign_inner(&keyid, &message);
-                if let Some(sig) = signature {
-                    response.set_code(ErrorCode::Success);
-                    response.set_signature(sig.to_bytes().to_vec());
-                } else {
-                    response.set_code(ErrorCode::KeyIdNotFound);
+                match self.sign_inner(&keyid, &message) {
+                    Ok(Some(sig)) => {
+                        response.set_code(ErrorCode::Success);
+                        response.set_signature(sig.to_bytes().to_vec());
+                    }
+                    Ok(None) => response.set_code(ErrorCode::KeyIdNotFound),
+                    Err(e) => {
+                        error!("Failed to sign {}: {}", message, e);
+                        response.set_code(ErrorCode::Unspecified);
+                    }
                 }
             } else {
                 response.set_code(ErrorCode::Unspecified);
@@ -191,4 +385,51 @@ impl secret_service_grpc::SecretService for SecretServiceServer {
         }
         provide_grpc_response(Ok(response), ctx, sink);
     }
+
+    /// Deletes a key answering a DeleteKeyRequest with a DeleteKeyResponse.
+    fn delete_key(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: DeleteKeyRequest,
+        sink: ::grpcio::UnarySink<DeleteKeyResponse>,
+    ) {
+        let mut response = DeleteKeyResponse::new();
+        let keyid_raw_bytes = req.get_key_id();
+        if keyid_raw_bytes.len() != HashValue::LENGTH {
+            response.set_code(ErrorCode::WrongLength);
+        } else if let Ok(keyid) = HashValue::from_slice(keyid_raw_bytes) {
+            match self.delete_key_inner(&KeyID(keyid)) {
+                Ok(()) => response.set_code(ErrorCode::Success),
+                Err(code) => response.set_code(code),
+            }
+        } else {
+            response.set_code(ErrorCode::Unspecified);
+        }
+        provide_grpc_response(Ok(response), ctx, sink);
+    }
+
+    /// Returns a private key answering an ExportKeyRequest with an ExportKeyResponse.
+    fn export_key(
+        &mut self,
+        ctx: ::grpcio::RpcContext,
+        req: ExportKeyRequest,
+        sink: ::grpcio::UnarySink<ExportKeyResponse>,
+    ) {
+        let mut response = ExportKeyResponse::new();
+        let keyid_raw_bytes = req.get_key_id();
+        if keyid_raw_bytes.len() != HashValue::LENGTH {
+            response.set_code(ErrorCode::WrongLength);
+        } else if let Ok(keyid) = HashValue::from_slice(keyid_raw_bytes) {
+            match self.export_key_inner(&KeyID(keyid)) {
+                Ok(private_key) => {
+                    response.set_code(ErrorCode::Success);
+                    response.set_private_key(private_key);
+                }
+                Err(code) => response.set_code(code),
+            }
+        } else {
+            response.set_code(ErrorCode::Unspecified);
+        }
+        provide_grpc_response(Ok(response), ctx, sink);
+    }
 }
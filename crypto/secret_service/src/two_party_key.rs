@@ -0,0 +1,112 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keys split between the secret service and a remote co-signer.
+//!
+//! The secret service holds the client share of a two-party EdDSA key and drives the signing
+//! protocol with the co-signer holding the server share, so that the whole key never exists in
+//! a single process. The aggregated key is a standard Ed25519 key: its public key and signatures
+//! are used exactly as those of the Ed25519 keys held by the secret service.
+
+use co_signer_client::{co_signer::CoSigner, session_log::SessionLog, two_party};
+use co_signer_protocol::{ed25519_public_key, ed25519_signature, KeyAgg, KeyPair};
+use crypto::hash::HashValue;
+use failure::prelude::*;
+use nextgen_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, fmt, sync::Arc};
+
+/// Persistent part of a TwoPartyKey. The co-signer is not part of it: the share is signed with
+/// together with the co-signer configured in the secret service.
+#[derive(Deserialize, Serialize)]
+pub struct TwoPartyKeyShare {
+    key_pair: KeyPair,
+    key_agg: KeyAgg,
+    id: String,
+    epoch: u64,
+}
+
+/// Client share of a two-party Ed25519 key, along with the co-signer holding the server share.
+#[derive(Clone)]
+pub struct TwoPartyKey {
+    co_signer: Arc<dyn CoSigner>,
+    key_pair: KeyPair,
+    key_agg: KeyAgg,
+    id: String,
+    epoch: u64,
+    public_key: Ed25519PublicKey,
+    session_log: Arc<SessionLog>,
+}
+
+impl fmt::Debug for TwoPartyKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TwoPartyKey {{ id: {}, epoch: {} }}",
+            self.id, self.epoch
+        )
+    }
+}
+
+impl TwoPartyKey {
+    /// Generates a new key together with co_signer. The co-signer only lets it sign prehashed
+    /// messages, not transactions.
+    pub fn generate(co_signer: Arc<dyn CoSigner>) -> Result<Self> {
+        let (key_pair, key_agg, id, _) = two_party::generate_message_key(&*co_signer)?;
+        Self::from_share(
+            co_signer,
+            TwoPartyKeyShare {
+                key_pair,
+                key_agg,
+                id,
+                epoch: 0,
+            },
+        )
+    }
+
+    /// Restores a key from its share, co-signing with co_signer.
+    pub fn from_share(co_signer: Arc<dyn CoSigner>, share: TwoPartyKeyShare) -> Result<Self> {
+        let public_key = ed25519_public_key(&share.key_agg.apk)?;
+        let public_key = Ed25519PublicKey::try_from(&public_key.to_slice()[..])?;
+        Ok(Self {
+            co_signer,
+            key_pair: share.key_pair,
+            key_agg: share.key_agg,
+            id: share.id,
+            epoch: share.epoch,
+            public_key,
+            session_log: Arc::new(SessionLog::default()),
+        })
+    }
+
+    /// Exports the share of the key so that it can be persisted.
+    pub fn share(&self) -> TwoPartyKeyShare {
+        TwoPartyKeyShare {
+            key_pair: self.key_pair.clone(),
+            key_agg: self.key_agg.clone(),
+            id: self.id.clone(),
+            epoch: self.epoch,
+        }
+    }
+
+    /// The aggregated public key
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public_key
+    }
+
+    /// Co-signs a prehashed message. The signature is verified against the aggregated public key
+    /// before it is returned.
+    pub fn sign_message(&self, message: &HashValue) -> Result<Ed25519Signature> {
+        let signature = two_party::sign_message(
+            &*self.co_signer,
+            *message,
+            &self.key_pair,
+            &self.key_agg,
+            &self.id,
+            self.epoch,
+            &self.session_log,
+        )?;
+        let signature = ed25519_signature(&signature)?;
+        Ok(Ed25519Signature::try_from(&signature.to_compact()[..])?)
+    }
+}
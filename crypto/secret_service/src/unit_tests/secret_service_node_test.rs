@@ -14,10 +14,11 @@ use crate::{
     proto::secret_service::KeyType, secret_service_client::ConsensusKeyManager,
     secret_service_node::SecretServiceNode,
 };
-use crypto::hash::HashValue;
+use crypto::{hash::HashValue, signing, PublicKey};
 use logger::prelude::*;
 // use crate::crypto_wrappers::GenericSignature;
-use nextgen_crypto::traits::Signature;
+use nextgen_crypto::traits::{Signature, ValidKey};
+use types::account_address::AccountAddress;
 
 /////////////////////////////////////////////////////////////////////////////////////
 // These tests check interoperability of key_generation,                           //
@@ -71,6 +72,34 @@ fn test_generate_consensus_key() {
     );
 }
 
+#[test]
+fn test_validator_signer() {
+    let node_config = NodeConfigHelpers::get_single_node_test_config(true);
+    let client = Arc::new(create_secret_service_node_and_client(node_config.clone()));
+
+    let key_manager = ConsensusKeyManager::new(Arc::clone(&client)).unwrap();
+    let public_key = key_manager.get_consensus_public_key().unwrap();
+    let public_key = PublicKey::from_slice(&public_key.to_bytes()).unwrap();
+    let author = AccountAddress::from(public_key);
+    let signer = key_manager
+        .into_validator_signer(author, public_key)
+        .unwrap();
+
+    let message_hash = HashValue::random();
+    let signature = signer.sign_message(message_hash).unwrap();
+    assert!(
+        signer.verify_message(message_hash, &signature).is_ok(),
+        "Signature from the secret service does not verify"
+    );
+
+    // a key manager whose key is not the expected consensus key is refused
+    let (_, other_public_key) = signing::generate_keypair();
+    assert!(ConsensusKeyManager::new(client)
+        .unwrap()
+        .into_validator_signer(author, other_public_key)
+        .is_err());
+}
+
 #[test]
 fn test_generate_key() {
     let node_config = NodeConfigHelpers::get_single_node_test_config(true);
@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    proto::secret_service::KeyType,
+    key_store::{EncryptedFileKeyStore, KeyPolicy},
+    proto::secret_service::{ErrorCode, KeyType},
     secret_service_server::{KeyID, SecretServiceServer},
 };
 use crypto::hash::HashValue;
 use nextgen_crypto::traits::{Signature, ValidKey};
+use std::sync::Arc;
 
 /////////////////////////////////////////////////////////////////////////////////////
 // These tests check interoperability of key_generation,                           //
@@ -118,7 +120,7 @@ fn test_ed25519_sign() {
         /* signature obtained verifies */
         // let message_hash1 = b"hello".digest(STANDARD_DIGESTER.get());
         let message_hash1 = HashValue::random();
-        let signature11 = ss_service.sign_inner(&keyid1, &message_hash1);
+        let signature11 = ss_service.sign_inner(&keyid1, &message_hash1).unwrap();
         assert!(
             signature11.is_some(),
             "SecretService does not return a signature"
@@ -136,7 +138,7 @@ fn test_ed25519_sign() {
             "Incorrect signature verifies"
         );
 
-        let signature22 = ss_service.sign_inner(&keyid2, &message_hash2);
+        let signature22 = ss_service.sign_inner(&keyid2, &message_hash2).unwrap();
         assert!(
             signature22.is_some(),
             "SecretService does not return a signature"
@@ -160,3 +162,141 @@ fn test_ed25519_sign() {
         );
     }
 }
+
+#[test]
+fn test_two_party_sign() {
+    use co_signer_client::co_signer::CoSignerConfig;
+
+    assert!(
+        SecretServiceServer::new()
+            .generate_key_inner(KeyType::TwoPartyEd25519)
+            .is_err(),
+        "Two-party key generated without a co-signer"
+    );
+
+    let mut ss_service =
+        SecretServiceServer::with_co_signer(&CoSignerConfig::in_process("secret_service_test"))
+            .unwrap();
+    let keyid = ss_service
+        .generate_key_inner(KeyType::TwoPartyEd25519)
+        .unwrap();
+    let public_key = ss_service.get_public_key_inner(&keyid).unwrap();
+
+    let message_hash = HashValue::random();
+    let signature = ss_service
+        .sign_inner(&keyid, &message_hash)
+        .unwrap()
+        .expect("SecretService does not return a signature");
+    assert!(
+        signature.verify(&message_hash, &public_key).is_ok(),
+        "Correct signature does not verify"
+    );
+    assert!(
+        signature.verify(&HashValue::random(), &public_key).is_err(),
+        "Incorrect signature verifies"
+    );
+}
+
+#[test]
+fn test_persistent_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let open = |passphrase: &[u8]| {
+        EncryptedFileKeyStore::new(dir.path(), passphrase)
+            .and_then(|store| SecretServiceServer::with_key_store(Arc::new(store), None))
+    };
+
+    let mut ss_service = open(b"passphrase").unwrap();
+    let keyids: Vec<_> = [KeyType::Ed25519, KeyType::BLS12381]
+        .iter()
+        .map(|key_type| ss_service.generate_key_inner(*key_type).unwrap())
+        .collect();
+
+    let reloaded = open(b"passphrase").unwrap();
+    for keyid in &keyids {
+        let public_key = ss_service.get_public_key_inner(keyid).unwrap();
+        assert_eq!(
+            reloaded.get_public_key_inner(keyid).unwrap().to_bytes(),
+            public_key.to_bytes(),
+            "Reloaded key does not match"
+        );
+        let message_hash = HashValue::random();
+        let signature = reloaded.sign_inner(keyid, &message_hash).unwrap().unwrap();
+        assert!(
+            signature.verify(&message_hash, &public_key).is_ok(),
+            "Reloaded key does not sign"
+        );
+    }
+
+    assert!(
+        open(b"wrong passphrase").is_err(),
+        "Keys decrypted with a wrong passphrase"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_key_file_permissions() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let dir = tempfile::tempdir().unwrap();
+    let store_dir = dir.path().join("keys");
+    let store = EncryptedFileKeyStore::new(&store_dir, b"passphrase").unwrap();
+    let mut ss_service = SecretServiceServer::with_key_store(Arc::new(store), None).unwrap();
+    ss_service.generate_key_inner(KeyType::Ed25519).unwrap();
+
+    let mode = |path: &std::path::Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(&store_dir), 0o700);
+    let files: Vec<_> = fs::read_dir(&store_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1, "Temporary key file left behind");
+    assert_eq!(mode(&files[0]), 0o600);
+}
+
+#[test]
+fn test_key_policies() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(EncryptedFileKeyStore::new(dir.path(), b"passphrase").unwrap());
+    let mut ss_service = SecretServiceServer::with_key_store(store.clone(), None).unwrap();
+
+    let locked = ss_service.generate_key_inner(KeyType::Ed25519).unwrap();
+    assert_eq!(
+        ss_service.export_key_inner(&locked),
+        Err(ErrorCode::OperationNotPermitted)
+    );
+    assert_eq!(
+        ss_service.delete_key_inner(&locked),
+        Err(ErrorCode::OperationNotPermitted)
+    );
+    assert!(ss_service.get_public_key_inner(&locked).is_some());
+
+    let policy = KeyPolicy {
+        deletable: true,
+        exportable: true,
+    };
+    let open = ss_service
+        .generate_key_with_policy_inner(KeyType::Ed25519, policy)
+        .unwrap();
+    let exported = ss_service.export_key_inner(&open).unwrap();
+    assert_eq!(exported.len(), 32);
+    let bls = ss_service
+        .generate_key_with_policy_inner(KeyType::BLS12381, policy)
+        .unwrap();
+    assert_eq!(
+        ss_service.export_key_inner(&bls),
+        Err(ErrorCode::InvalidParameters)
+    );
+
+    assert_eq!(ss_service.delete_key_inner(&open), Ok(()));
+    assert!(ss_service.get_public_key_inner(&open).is_none());
+    assert_eq!(
+        ss_service.delete_key_inner(&open),
+        Err(ErrorCode::KeyIdNotFound)
+    );
+
+    /* deleted keys are gone from the store too */
+    let reloaded = SecretServiceServer::with_key_store(store, None).unwrap();
+    assert!(reloaded.get_public_key_inner(&open).is_none());
+    assert!(reloaded.get_public_key_inner(&locked).is_some());
+}
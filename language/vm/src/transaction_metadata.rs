@@ -1,12 +1,13 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crypto::{signing::generate_genesis_keypair, PublicKey};
+use crypto::signing::generate_genesis_keypair;
 use types::{account_address::AccountAddress, transaction::SignedTransaction};
 
 pub struct TransactionMetadata {
     pub sender: AccountAddress,
-    pub public_key: PublicKey,
+    /// Public key bytes of the sender, which the authentication key of its account is the hash of
+    pub public_key: Vec<u8>,
     pub sequence_number: u64,
     pub max_gas_amount: u64,
     pub gas_unit_price: u64,
@@ -17,7 +18,7 @@ impl TransactionMetadata {
     pub fn new(txn: &SignedTransaction) -> Self {
         Self {
             sender: txn.sender(),
-            public_key: txn.public_key(),
+            public_key: txn.authenticator().public_key_bytes(),
             sequence_number: txn.sequence_number(),
             max_gas_amount: txn.max_gas_amount(),
             gas_unit_price: txn.gas_unit_price(),
@@ -37,7 +38,7 @@ impl TransactionMetadata {
         self.sender.to_owned()
     }
 
-    pub fn public_key(&self) -> &PublicKey {
+    pub fn public_key(&self) -> &[u8] {
         &self.public_key
     }
 
@@ -55,7 +56,7 @@ impl Default for TransactionMetadata {
         let (_, public_key) = generate_genesis_keypair();
         TransactionMetadata {
             sender: AccountAddress::default(),
-            public_key,
+            public_key: public_key.to_slice().to_vec(),
             sequence_number: 0,
             max_gas_amount: 100_000_000,
             gas_unit_price: 0,
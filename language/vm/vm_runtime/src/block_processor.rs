@@ -73,6 +73,40 @@ pub fn execute_block<'alloc>(
     result
 }
 
+/// Execute a single transaction against data_view, returning its output without applying it. The
+/// signature of the transaction is only checked if check_signature is set, so that the cost of a
+/// transaction can be learnt before signing it. The modules it publishes are never added to
+/// code_cache.
+pub fn simulate_transaction<'alloc>(
+    txn: SignedTransaction,
+    code_cache: &VMModuleCache<'alloc>,
+    script_cache: &ScriptCache<'alloc>,
+    data_view: &dyn StateView,
+    publishing_option: &VMPublishingOption,
+    check_signature: bool,
+) -> TransactionOutput {
+    trace!("[VM] Simulate transaction: {:?}", txn);
+    if check_signature {
+        if let Err(err) = txn.verify_signature() {
+            error!("[VM] Verify signature error: {}", err);
+            counters::FAILED_TRANSACTION.inc();
+            return ExecutedTransaction::discard_error_output(VMStatus::Validation(
+                VMValidationStatus::InvalidSignature,
+            ));
+        }
+    }
+    let module_cache = BlockModuleCache::new(code_cache, ModuleFetcherImpl::new(data_view));
+    let data_cache = BlockDataCache::new(data_view);
+    transaction_flow(
+        txn,
+        &module_cache,
+        script_cache,
+        &data_cache,
+        ValidationMode::Simulating,
+        publishing_option,
+    )
+}
+
 /// Process a transaction and emit a TransactionOutput.
 ///
 /// A successful execution will have `TransactionStatus::Keep` in the TransactionOutput and a
@@ -84,8 +118,8 @@ pub fn execute_block<'alloc>(
 ///
 /// Note that this function DO HAVE side effect. If a transaction tries to publish some module,
 /// and this transaction is executed successfully, this function will update `module_cache` to
-/// include those newly published modules. This function will also update the `script_cache` to
-/// cache this `txn`
+/// include those newly published modules, unless it is only simulated. This function will also
+/// update the `script_cache` to cache this `txn`
 fn transaction_flow<'alloc, P>(
     txn: SignedTransaction,
     module_cache: P,
@@ -117,9 +151,15 @@ where
     let executed_txn = verified_txn.execute(script_cache);
 
     // On success, publish the modules into the cache so that future transactions can refer to them
-    // directly.
+    // directly. The modules of a simulated transaction are never published.
     let output = executed_txn.into_output();
     match output.status() {
+        TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+            if mode == ValidationMode::Simulating =>
+        {
+            counters::SUCCESSFUL_TRANSACTION.inc();
+            output
+        }
         TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)) => {
             match module_cache.reclaim_cached_module(arena.into_vec()) {
                 Ok(_) => {
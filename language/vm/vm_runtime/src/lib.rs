@@ -174,3 +174,17 @@ pub trait VMExecutor {
         state_view: &dyn StateView,
     ) -> Vec<TransactionOutput>;
 }
+
+/// This trait describes the VM's simulation interface.
+pub trait VMSimulator {
+    /// Executes a transaction against the state view and returns its output, which is not applied
+    /// anywhere. The signature of the transaction is only checked if `check_signature` is set, so
+    /// that unsigned transactions can be simulated too. The modules a simulated transaction
+    /// publishes never reach the caches of the VM.
+    fn simulate_transaction(
+        &self,
+        transaction: SignedTransaction,
+        state_view: &dyn StateView,
+        check_signature: bool,
+    ) -> TransactionOutput;
+}
@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{loaded_data::loaded_module::LoadedModule, runtime::VMRuntime, VMExecutor, VMVerifier};
+use crate::{
+    loaded_data::loaded_module::LoadedModule, runtime::VMRuntime, VMExecutor, VMSimulator,
+    VMVerifier,
+};
 use state_view::StateView;
 use std::sync::Arc;
 use types::{
@@ -69,6 +72,19 @@ impl VMExecutor for MoveVM {
     }
 }
 
+impl VMSimulator for MoveVM {
+    fn simulate_transaction(
+        &self,
+        transaction: SignedTransaction,
+        state_view: &dyn StateView,
+        check_signature: bool,
+    ) -> TransactionOutput {
+        self.inner.rent(move |runtime| {
+            runtime.simulate_transaction(transaction, state_view, check_signature)
+        })
+    }
+}
+
 #[test]
 fn vm_thread_safe() {
     fn assert_send<T: Send>() {}
@@ -55,6 +55,9 @@ pub enum ValidationMode {
     /// We're executing a transaction. This runs the full suite of checks.
     #[allow(dead_code)]
     Executing,
+    /// We're simulating a transaction that may not be signed yet. This runs the full suite of
+    /// checks but the signature check, and the modules it publishes are not cached.
+    Simulating,
 }
 
 impl<'alloc, 'txn, P> ValidatedTransaction<'alloc, 'txn, P>
@@ -77,9 +80,11 @@ where
             allocator,
             ..
         } = process_txn;
-        if txn.verify_signature().is_err() {
-            error!("[VM] Verify signature error");
-            return Err(VMStatus::Validation(VMValidationStatus::InvalidSignature));
+        if mode != ValidationMode::Simulating {
+            if let Err(err) = txn.verify_signature() {
+                error!("[VM] Verify signature error: {}", err);
+                return Err(VMStatus::Validation(VMValidationStatus::InvalidSignature));
+            }
         }
 
         let txn_state = match txn.payload() {
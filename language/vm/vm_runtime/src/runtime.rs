@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_processor::execute_block,
+    block_processor::{execute_block, simulate_transaction},
     code_cache::{
         module_adapter::ModuleFetcherImpl,
         module_cache::{BlockModuleCache, VMModuleCache},
@@ -117,4 +117,23 @@ impl<'alloc> VMRuntime<'alloc> {
             &self.publishing_option,
         )
     }
+
+    /// Execute a single transaction without applying its output to the data view, e.g. to learn
+    /// the gas it uses and the events it emits before submitting it. The signature of the
+    /// transaction is only checked if `check_signature` is set.
+    pub fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+        data_view: &dyn StateView,
+        check_signature: bool,
+    ) -> TransactionOutput {
+        simulate_transaction(
+            txn,
+            &self.code_cache,
+            &self.script_cache,
+            data_view,
+            &self.publishing_option,
+            check_signature,
+        )
+    }
 }
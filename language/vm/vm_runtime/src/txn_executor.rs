@@ -453,7 +453,7 @@ where
                 }
                 Bytecode::GetTxnPublicKey => {
                     self.execution_stack.push(Local::bytearray(ByteArray::new(
-                        self.txn_data.public_key().to_slice().to_vec(),
+                        self.txn_data.public_key().to_vec(),
                     )));
                 }
                 Bytecode::BorrowGlobal(idx) => {
@@ -695,7 +695,7 @@ fn test_transaction_info() {
         let (_, public_key) = crypto::signing::generate_genesis_keypair();
         TransactionMetadata {
             sender: AccountAddress::default(),
-            public_key,
+            public_key: public_key.to_slice().to_vec(),
             sequence_number: 10,
             max_gas_amount: 100_000_009,
             gas_unit_price: 5,
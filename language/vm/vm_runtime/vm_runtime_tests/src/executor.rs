@@ -18,7 +18,7 @@ use types::{
 use vm_runtime::{
     loaded_data::{struct_def::StructDef, types::Type},
     value::Value,
-    MoveVM, VMExecutor, VMVerifier,
+    MoveVM, VMExecutor, VMSimulator, VMVerifier,
 };
 
 /// Provides an environment to run a VM instance.
@@ -118,6 +118,16 @@ impl FakeExecutor {
             .expect("A block with one transaction should have one output")
     }
 
+    /// Simulates the given transaction, checking its signature only if `check_signature` is set.
+    pub fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+        check_signature: bool,
+    ) -> TransactionOutput {
+        let vm = MoveVM::new(&self.config.vm_config);
+        vm.simulate_transaction(txn, &self.data_store, check_signature)
+    }
+
     /// Get the blob for the associated AccessPath
     pub fn read_from_access_path(&self, path: &AccessPath) -> Option<Vec<u8>> {
         StateView::get(&self.data_store, path).unwrap()
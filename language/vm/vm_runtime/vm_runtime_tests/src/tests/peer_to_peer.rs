@@ -7,11 +7,12 @@ use crate::{
     executor::FakeExecutor,
 };
 use canonical_serialization::SimpleDeserializer;
+use crypto::signing::Signature;
 use std::time::Instant;
 use types::{
     account_config::{account_received_event_path, account_sent_event_path, AccountEvent},
     transaction::{SignedTransaction, TransactionOutput, TransactionStatus},
-    vm_error::{ExecutionStatus, VMStatus},
+    vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
 };
 
 #[test]
@@ -194,6 +195,41 @@ fn zero_amount_peer_to_peer() {
     );
 }
 
+#[test]
+fn simulate_peer_to_peer() {
+    let mut executor = FakeExecutor::from_genesis_file();
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 10, 1_000);
+    let output = executor.execute_transaction(txn.clone());
+    assert_eq!(executor.simulate_transaction(txn.clone(), true), output);
+
+    // An unsigned transaction simulates the same, unless its signature is checked.
+    let unsigned_txn = SignedTransaction::craft_signed_transaction_for_client(
+        txn.into_raw_transaction(),
+        sender.account().pubkey,
+        Signature::from_compact(&[0; 64]).unwrap(),
+    );
+    assert_eq!(
+        executor.simulate_transaction(unsigned_txn.clone(), false),
+        output
+    );
+    assert_eq!(
+        executor.simulate_transaction(unsigned_txn, true).status(),
+        &TransactionStatus::Discard(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+    );
+
+    // Nothing was applied.
+    let sender_resource = executor
+        .read_account_resource(sender.account())
+        .expect("sender must exist");
+    assert_eq!(1_000_000, AccountResource::read_balance(&sender_resource));
+    assert_eq!(10, AccountResource::read_sequence_number(&sender_resource));
+}
+
 #[test]
 fn peer_to_peer_create_account() {
     // create a FakeExecutor with a genesis from file
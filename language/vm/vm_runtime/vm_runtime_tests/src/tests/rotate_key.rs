@@ -3,12 +3,18 @@
 
 use crate::{
     account::{Account, AccountData, AccountResource},
-    common_transactions::{create_account_txn, rotate_key_txn},
+    common_transactions::{create_account_txn, peer_to_peer_txn, rotate_key_txn, PEER_TO_PEER},
     executor::FakeExecutor,
+    gas_costs,
 };
+use crypto::PrivateKey;
+use std::time::Duration;
 use types::{
     account_address::AccountAddress,
-    transaction::TransactionStatus,
+    transaction::{
+        MultiEd25519PublicKey, Program, RawTransaction, SignedTransaction, TransactionArgument,
+        TransactionStatus,
+    },
     vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
 };
 
@@ -63,3 +69,132 @@ fn rotate_key() {
         &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
     );
 }
+
+/// Returns a transfer of amount from sender to receiver, signed by each of private_keys given
+/// with the index of its public key in public_key.
+fn multi_signed_transfer(
+    sender: &AccountAddress,
+    receiver: &Account,
+    seq_num: u64,
+    amount: u64,
+    private_keys: &[(&PrivateKey, u8)],
+    public_key: MultiEd25519PublicKey,
+) -> SignedTransaction {
+    let args = vec![
+        TransactionArgument::Address(*receiver.address()),
+        TransactionArgument::U64(amount),
+    ];
+    RawTransaction::new(
+        *sender,
+        seq_num,
+        Program::new(PEER_TO_PEER.clone(), vec![], args),
+        gas_costs::TXN_RESERVED,
+        1,
+        Duration::from_secs(u64::max_value()),
+    )
+    .multi_sign(private_keys, public_key)
+    .unwrap()
+}
+
+#[test]
+fn rotate_key_to_multi_ed25519() {
+    let mut executor = FakeExecutor::from_genesis_file();
+
+    // create and publish sender and receiver
+    let sender = AccountData::new(1_000_000, 10);
+    let receiver = AccountData::new(100_000, 10);
+    executor.add_account_data(&sender);
+    executor.add_account_data(&receiver);
+
+    // rotate the sender to a 2-of-3 key
+    let keypairs = (0..3)
+        .map(|_| crypto::signing::generate_keypair())
+        .collect::<Vec<_>>();
+    let public_keys = keypairs
+        .iter()
+        .map(|(_, pubkey)| *pubkey)
+        .collect::<Vec<_>>();
+    let multi_key = MultiEd25519PublicKey::new(public_keys.clone(), 2).unwrap();
+    let new_key_hash = AccountAddress::from(&multi_key);
+    let txn = rotate_key_txn(sender.account(), new_key_hash, 10);
+    let output = &executor.execute_block(vec![txn])[0];
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
+    );
+    executor.apply_write_set(output.write_set());
+    let updated_sender = executor
+        .read_account_resource(sender.account())
+        .expect("sender must exist");
+    assert_eq!(
+        new_key_hash,
+        AccountResource::read_auth_key(&updated_sender)
+    );
+    let balance = AccountResource::read_balance(&updated_sender);
+
+    // The old key, a single signature of the new key, and the new keys under another threshold
+    // are all refused.
+    let refused = vec![
+        (
+            peer_to_peer_txn(sender.account(), receiver.account(), 11, 1_000),
+            VMValidationStatus::InvalidAuthKey,
+        ),
+        (
+            multi_signed_transfer(
+                sender.address(),
+                receiver.account(),
+                11,
+                1_000,
+                &[(&keypairs[1].0, 1)],
+                multi_key.clone(),
+            ),
+            VMValidationStatus::InvalidSignature,
+        ),
+        (
+            multi_signed_transfer(
+                sender.address(),
+                receiver.account(),
+                11,
+                1_000,
+                &[(&keypairs[1].0, 1)],
+                MultiEd25519PublicKey::new(public_keys, 1).unwrap(),
+            ),
+            VMValidationStatus::InvalidAuthKey,
+        ),
+    ];
+    for (txn, status) in refused {
+        let output = &executor.execute_block(vec![txn])[0];
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Discard(VMStatus::Validation(status)),
+        );
+    }
+
+    // Any two of the new keys transfer through the prologue.
+    let txn = multi_signed_transfer(
+        sender.address(),
+        receiver.account(),
+        11,
+        1_000,
+        &[(&keypairs[2].0, 2), (&keypairs[0].0, 0)],
+        multi_key,
+    );
+    let output = &executor.execute_block(vec![txn])[0];
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
+    );
+    executor.apply_write_set(output.write_set());
+    let updated_sender = executor
+        .read_account_resource(sender.account())
+        .expect("sender must exist");
+    let updated_receiver = executor
+        .read_account_resource(receiver.account())
+        .expect("receiver must exist");
+    assert_eq!(
+        balance - 1_000 - output.gas_used(),
+        AccountResource::read_balance(&updated_sender)
+    );
+    assert_eq!(12, AccountResource::read_sequence_number(&updated_sender));
+    assert_eq!(101_000, AccountResource::read_balance(&updated_receiver));
+}
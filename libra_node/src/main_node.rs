@@ -4,12 +4,16 @@
 use admission_control_proto::proto::admission_control_grpc::{
     create_admission_control, AdmissionControlClient,
 };
-use admission_control_service::admission_control_service::AdmissionControlService;
+use admission_control_service::{
+    admission_control_service::AdmissionControlService, admission_policy::AdmissionPolicy,
+};
 use config::config::NodeConfig;
 use consensus::consensus_provider::{make_consensus_provider, ConsensusProvider};
+use crypto::HashValue;
 use debug_interface::{node_debug_service::NodeDebugService, proto::node_debug_interface_grpc};
 use execution_proto::proto::execution_grpc;
 use execution_service::ExecutionService;
+use failure::prelude::*;
 use grpc_helpers::ServerHandle;
 use grpcio::{ChannelBuilder, EnvBuilder, ServerBuilder};
 use grpcio_sys;
@@ -24,17 +28,22 @@ use network::{
     },
     NetworkPublicKeys, ProtocolId,
 };
+use secret_service::{
+    crypto_wrappers::KeyID, proto::secret_service_grpc::SecretServiceClient,
+    secret_service_client::ConsensusKeyManager,
+};
 use std::{
     cmp::max,
     convert::{TryFrom, TryInto},
+    process,
     sync::Arc,
     thread,
-    time::Instant,
+    time::{Duration, Instant},
 };
 use storage_client::{StorageRead, StorageReadServiceClient, StorageWriteServiceClient};
 use storage_service::start_storage_service;
 use tokio::runtime::{Builder, Runtime};
-use types::account_address::AccountAddress as PeerId;
+use types::{account_address::AccountAddress as PeerId, validator_signer::ValidatorSigner};
 use vm_validator::vm_validator::VMValidator;
 
 pub struct LibraHandle {
@@ -53,7 +62,10 @@ impl Drop for LibraHandle {
     }
 }
 
-fn setup_ac(config: &NodeConfig) -> (::grpcio::Server, AdmissionControlClient) {
+fn setup_ac(
+    config: &NodeConfig,
+    admission_policy: Arc<AdmissionPolicy>,
+) -> (::grpcio::Server, AdmissionControlClient) {
     let env = Arc::new(
         EnvBuilder::new()
             .name_prefix("grpc-ac-")
@@ -85,6 +97,7 @@ fn setup_ac(config: &NodeConfig) -> (::grpcio::Server, AdmissionControlClient) {
         config
             .admission_control
             .need_to_check_mempool_before_validation,
+        admission_policy,
     );
     let service = create_admission_control(handle);
     let server = ServerBuilder::new(Arc::clone(&env))
@@ -120,11 +133,15 @@ fn setup_executor(config: &NodeConfig) -> ::grpcio::Server {
         .expect("Unable to create grpc server")
 }
 
-fn setup_debug_interface(config: &NodeConfig) -> ::grpcio::Server {
+fn setup_debug_interface(
+    config: &NodeConfig,
+    admission_policy: &AdmissionPolicy,
+) -> ::grpcio::Server {
     let env = Arc::new(EnvBuilder::new().name_prefix("grpc-debug-").build());
     // Start Debug interface
-    let debug_service =
-        node_debug_interface_grpc::create_node_debug_interface(NodeDebugService::new());
+    let debug_service = node_debug_interface_grpc::create_node_debug_interface(
+        NodeDebugService::with_sender_blacklist(admission_policy.sender_blacklist()),
+    );
     ::grpcio::ServerBuilder::new(env)
         .register_service(debug_service)
         .bind(
@@ -135,6 +152,39 @@ fn setup_debug_interface(config: &NodeConfig) -> ::grpcio::Server {
         .expect("Unable to create grpc server")
 }
 
+/// Sets up the signer of consensus messages: with the consensus key held by the secret service if
+/// consensus.secret_service_key_id is configured, with the key from peer_keypairs otherwise.
+fn setup_validator_signer(config: &NodeConfig) -> Result<ValidatorSigner> {
+    let author = PeerId::try_from(config.base.peer_id.clone())
+        .context("Failed to parse peer id of a validator")?;
+    let public_key = config.base.peer_keypairs.get_consensus_public();
+    match config.consensus.secret_service_key_id() {
+        Some(key_id) => {
+            let keyid = hex::decode(key_id)
+                .map_err(Into::into)
+                .and_then(|bytes| HashValue::from_slice(&bytes))
+                .context("Invalid consensus secret_service_key_id")?;
+            let connection_str = format!(
+                "{}:{}",
+                config.secret_service.address, config.secret_service.secret_service_port
+            );
+            let env = Arc::new(EnvBuilder::new().name_prefix("grpc-con-ss-").build());
+            let secret_service =
+                SecretServiceClient::new(ChannelBuilder::new(env).connect(&connection_str));
+            Ok(
+                ConsensusKeyManager::with_key_id(Arc::new(secret_service), KeyID(keyid))
+                    .into_validator_signer(author, public_key)
+                    .context("Failed to set up consensus signing with the secret service")?,
+            )
+        }
+        None => Ok(ValidatorSigner::new(
+            author,
+            public_key,
+            config.base.peer_keypairs.get_consensus_private(),
+        )),
+    }
+}
+
 pub fn setup_network(
     config: &NodeConfig,
 ) -> (
@@ -214,6 +264,21 @@ pub fn setup_network(
 pub fn setup_environment(node_config: &NodeConfig) -> (AdmissionControlClient, LibraHandle) {
     crash_handler::setup_panic_handler();
 
+    // Set up the consensus signer first: a misconfigured or unreachable secret service is reported
+    // before any service is started.
+    let validator_signer = match setup_validator_signer(&node_config) {
+        Ok(validator_signer) => validator_signer,
+        Err(e) => {
+            crit!("Failed to set up the consensus signer: {}", e);
+            for cause in e.iter_causes() {
+                crit!("Caused by: {}", cause);
+            }
+            // allow to save on disk
+            thread::sleep(Duration::from_millis(100));
+            process::exit(1);
+        }
+    };
+
     let mut instant = Instant::now();
     let storage = start_storage_service(&node_config);
     debug!(
@@ -237,7 +302,11 @@ pub fn setup_environment(node_config: &NodeConfig) -> (AdmissionControlClient, L
     debug!("Network started in {} ms", instant.elapsed().as_millis());
 
     instant = Instant::now();
-    let (ac_server, ac_client) = setup_ac(&node_config);
+    let admission_policy = Arc::new(
+        AdmissionPolicy::from_config(&node_config.admission_control)
+            .expect("Invalid admission control config"),
+    );
+    let (ac_server, ac_client) = setup_ac(&node_config, Arc::clone(&admission_policy));
     let ac = ServerHandle::setup(ac_server);
     debug!("AC started in {} ms", instant.elapsed().as_millis());
 
@@ -246,7 +315,7 @@ pub fn setup_environment(node_config: &NodeConfig) -> (AdmissionControlClient, L
         MempoolRuntime::bootstrap(&node_config, mempool_network_sender, mempool_network_events);
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
-    let debug_if = ServerHandle::setup(setup_debug_interface(&node_config));
+    let debug_if = ServerHandle::setup(setup_debug_interface(&node_config, &admission_policy));
 
     let metrics_port = node_config.debug_interface.metrics_server_port;
     let metric_host = node_config.debug_interface.address.clone();
@@ -255,6 +324,7 @@ pub fn setup_environment(node_config: &NodeConfig) -> (AdmissionControlClient, L
     instant = Instant::now();
     let mut consensus_provider = make_consensus_provider(
         &node_config,
+        validator_signer,
         consensus_network_sender,
         consensus_network_events,
     );
@@ -3,6 +3,7 @@
 
 use crate::utils;
 use client_lib::{client_proxy::ClientProxy, commands};
+use libra_wallet::co_signer::CoSignerConfig;
 use std::{
     collections::HashMap,
     io::{self, Write},
@@ -39,11 +40,22 @@ impl Drop for InteractiveClient {
 }
 
 impl InteractiveClient {
+    /// Client command, pointed to co_signer_url if any. Otherwise the client uses the co-signer
+    /// stored in its wallet file, or the default one.
+    fn command(co_signer_url: Option<&str>) -> Command {
+        let mut command = Command::new(utils::get_bin("client"));
+        if let Some(co_signer_url) = co_signer_url {
+            command.arg("--co_signer_url").arg(co_signer_url);
+        }
+        command
+    }
+
     pub fn new_with_inherit_io(
         port: u16,
         faucet_key_file_path: &Path,
         mnemonic_file_path: &Path,
         validator_set_file: String,
+        co_signer_url: Option<&str>,
     ) -> Self {
         // We need to call canonicalize on the path because we are running client from
         // workspace root and the function calling new_with_inherit_io isn't necessarily
@@ -51,7 +63,7 @@ impl InteractiveClient {
         // unless we convert it to an absolute path
         Self {
             client: Some(
-                Command::new(utils::get_bin("client"))
+                Self::command(co_signer_url)
                     .current_dir(utils::workspace_root())
                     .arg("-p")
                     .arg(port.to_string())
@@ -89,13 +101,14 @@ impl InteractiveClient {
         faucet_key_file_path: &Path,
         mnemonic_file_path: &Path,
         validator_set_file: String,
+        co_signer_url: Option<&str>,
     ) -> Self {
         Self {
             /// Note: For easier debugging it's convenient to see the output
             /// from the client CLI. Comment the stdout/stderr lines below
             /// and enjoy pretty Matrix-style output.
             client: Some(
-                Command::new(utils::get_bin("client"))
+                Self::command(co_signer_url)
                     .current_dir(utils::workspace_root())
                     .arg("-p")
                     .arg(port.to_string())
@@ -153,6 +166,7 @@ impl InProcessTestClient {
         faucet_key_file_path: &Path,
         mnemonic_file_path: &str,
         validator_set_file: String,
+        co_signer: Option<CoSignerConfig>,
     ) -> Self {
         let (_, alias_to_cmd) = commands::get_commands();
         Self {
@@ -167,6 +181,8 @@ impl InProcessTestClient {
                     .unwrap(),
                 /* faucet server */ None,
                 Some(mnemonic_file_path.to_string()),
+                /* wallet passphrase */ None,
+                co_signer,
             )
             .unwrap(),
             alias_to_cmd,
@@ -25,8 +25,19 @@ struct Args {
     /// If not passed, a new keypair will be generated for you and placed in a temp directory
     #[structopt(short = "m", long = "faucet_key_file_path")]
     pub faucet_key_file_path: Option<String>,
+    /// Co-signer of the accounts created by the client. In builds with the in-process-co-signer
+    /// feature, the client runs the co-signer in process by default, so that no `server_exec` is
+    /// needed; otherwise it uses its default co-signer.
+    #[structopt(short = "c", long = "co_signer_url")]
+    pub co_signer_url: Option<String>,
 }
 
+/// Co-signer of the client when none is passed.
+#[cfg(feature = "in-process-co-signer")]
+const DEFAULT_CO_SIGNER_URL: Option<&str> = Some("in-process://libra_swarm");
+#[cfg(not(feature = "in-process-co-signer"))]
+const DEFAULT_CO_SIGNER_URL: Option<&str> = None;
+
 fn main() {
     let args = Args::from_args();
     let num_nodes = args.num_nodes.unwrap_or(1);
@@ -50,6 +61,10 @@ fn main() {
             Path::new(&faucet_key_file_path),
             &tmp_mnemonic_file.into_temp_path(),
             swarm.get_trusted_peers_config_path(),
+            args.co_signer_url
+                .as_ref()
+                .map(String::as_str)
+                .or(DEFAULT_CO_SIGNER_URL),
         );
         println!("Loading client...");
         let _output = client.output().expect("Failed to wait on child");
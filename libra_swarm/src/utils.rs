@@ -30,6 +30,22 @@ lazy_static! {
         if !cargo_build.status.success() {
             panic!(WORKSPACE_BUILD_ERROR_MSG);
         }
+        // Features cannot be passed to a workspace build: the client is built again with its
+        // in-process co-signer.
+        if cfg!(feature = "in-process-co-signer") {
+            let mut args = vec!["build", "--bin", "client", "--features", "in-process-co-signer"];
+            if !cfg!(debug_assertions) {
+                args.push("--release");
+            }
+            let cargo_build = Command::new("cargo")
+                .current_dir(workspace_root().join("client"))
+                .args(&args)
+                .output()
+                .expect(WORKSPACE_BUILD_ERROR_MSG);
+            if !cargo_build.status.success() {
+                panic!(WORKSPACE_BUILD_ERROR_MSG);
+            }
+        }
 
         info!("Finished building project binaries");
 
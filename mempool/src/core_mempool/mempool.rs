@@ -8,13 +8,17 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::{
     core_mempool::{
         index::TxnPointer,
-        transaction::{MempoolAddTransactionStatus, MempoolTransaction, TimelineState},
+        transaction::{
+            MempoolAddTransactionStatus, MempoolTransaction, MempoolTransactionStatus,
+            TimelineState,
+        },
         transaction_store::TransactionStore,
     },
     OP_COUNTERS,
 };
 use chrono::Utc;
 use config::config::NodeConfig;
+use crypto::{hash::CryptoHash, HashValue};
 use logger::prelude::*;
 use lru_cache::LruCache;
 use std::{
@@ -24,6 +28,9 @@ use std::{
 use ttl_cache::TtlCache;
 use types::{account_address::AccountAddress, transaction::SignedTransaction};
 
+/// How long mempool remembers why it removed a transaction which was not committed
+const REMOVED_TRANSACTION_STATUS_TTL: Duration = Duration::from_secs(600);
+
 pub struct Mempool {
     // stores metadata of all transactions in mempool (of all states)
     transactions: TransactionStore,
@@ -34,6 +41,10 @@ pub struct Mempool {
     // used to measure e2e latency of transaction in system, as well as time it takes to pick it up
     // by consensus
     metrics_cache: TtlCache<(AccountAddress, u64), i64>,
+    // statuses of recently expired or discarded transactions, so clients can learn what happened
+    // to them
+    removed_transactions: TtlCache<HashValue, MempoolTransactionStatus>,
+    removed_transaction_hashes: TtlCache<TxnPointer, HashValue>,
     pub system_transaction_timeout: Duration,
 }
 
@@ -43,6 +54,8 @@ impl Mempool {
             transactions: TransactionStore::new(&config.mempool),
             sequence_number_cache: LruCache::new(config.mempool.sequence_cache_capacity),
             metrics_cache: TtlCache::new(config.mempool.capacity),
+            removed_transactions: TtlCache::new(config.mempool.capacity),
+            removed_transaction_hashes: TtlCache::new(config.mempool.capacity),
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
@@ -77,10 +90,70 @@ impl Mempool {
         self.sequence_number_cache
             .insert(sender.clone(), new_sequence_number);
 
+        if is_rejected {
+            if let Some(txn) = self.transactions.get(sender, sequence_number) {
+                self.record_removal(&txn, MempoolTransactionStatus::Discarded);
+            }
+        }
         self.transactions
             .commit_transaction(&sender, sequence_number);
     }
 
+    /// Remembers why a transaction which was not committed was removed
+    fn record_removal(&mut self, txn: &SignedTransaction, status: MempoolTransactionStatus) {
+        let hash = txn.hash();
+        self.removed_transactions
+            .insert(hash, status, REMOVED_TRANSACTION_STATUS_TTL);
+        self.removed_transaction_hashes.insert(
+            (txn.sender(), txn.sequence_number()),
+            hash,
+            REMOVED_TRANSACTION_STATUS_TTL,
+        );
+    }
+
+    /// Looks up where the transaction of given account with given sequence number stands in
+    /// Mempool
+    pub(crate) fn get_transaction_status(
+        &mut self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) -> MempoolTransactionStatus {
+        if let Some(timeline_state) = self
+            .transactions
+            .get_timeline_state(sender, sequence_number)
+        {
+            return MempoolTransactionStatus::Pending(timeline_state);
+        }
+        match self
+            .removed_transaction_hashes
+            .get(&(*sender, sequence_number))
+            .cloned()
+        {
+            Some(hash) => self.get_removed_transaction_status(&hash),
+            None => MempoolTransactionStatus::Unknown,
+        }
+    }
+
+    /// Looks up where the transaction with given hash stands in Mempool
+    pub(crate) fn get_transaction_status_by_hash(
+        &mut self,
+        hash: &HashValue,
+    ) -> MempoolTransactionStatus {
+        match self.transactions.get_by_hash(hash) {
+            Some((sender, sequence_number)) => {
+                self.get_transaction_status(&sender, sequence_number)
+            }
+            None => self.get_removed_transaction_status(hash),
+        }
+    }
+
+    fn get_removed_transaction_status(&mut self, hash: &HashValue) -> MempoolTransactionStatus {
+        self.removed_transactions
+            .get(hash)
+            .cloned()
+            .unwrap_or(MempoolTransactionStatus::Unknown)
+    }
+
     fn log_latency(&mut self, account: AccountAddress, sequence_number: u64, metric: &str) {
         if let Some(&creation_time) = self.metrics_cache.get(&(account, sequence_number)) {
             OP_COUNTERS.observe(
@@ -213,12 +286,16 @@ impl Mempool {
 
     /// TTL based garbage collection. Remove all transactions that got expired
     pub(crate) fn gc_by_system_ttl(&mut self) {
-        self.transactions.gc_by_system_ttl();
+        for txn in self.transactions.gc_by_system_ttl() {
+            self.record_removal(&txn, MempoolTransactionStatus::Expired);
+        }
     }
 
     /// Garbage collection based on client-specified expiration time
     pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) {
-        self.transactions.gc_by_expiration_time(block_time);
+        for txn in self.transactions.gc_by_expiration_time(block_time) {
+            self.record_removal(&txn, MempoolTransactionStatus::Expired);
+        }
     }
 
     /// Read `count` transactions from timeline since `timeline_id`
@@ -9,7 +9,7 @@ mod transaction_store;
 pub use self::{
     index::TxnPointer,
     mempool::Mempool as CoreMempool,
-    transaction::{MempoolAddTransactionStatus, TimelineState},
+    transaction::{MempoolAddTransactionStatus, MempoolTransactionStatus, TimelineState},
 };
 
 #[cfg(test)]
@@ -1,7 +1,11 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::proto::shared::mempool_status::MempoolAddTransactionStatus as ProtoMempoolAddTransactionStatus;
+use crate::proto::shared::mempool_status::{
+    MempoolAddTransactionStatus as ProtoMempoolAddTransactionStatus,
+    MempoolTransactionStatus_State as ProtoMempoolTransactionState,
+    TimelineState_Kind as ProtoTimelineStateKind,
+};
 use failure::prelude::*;
 use proto_conv::{FromProto, IntoProto};
 use std::time::Duration;
@@ -41,19 +45,103 @@ impl MempoolTransaction {
     }
 }
 
+/// Broadcast state of a transaction in mempool
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum TimelineState {
-    // transaction is ready for broadcast
-    // Associated integer represents it's position in log of such transactions
+    /// transaction is ready for broadcast
+    /// Associated integer represents it's position in log of such transactions
     Ready(u64),
-    // transaction is not yet ready for broadcast
-    // but it might change in a future
+    /// transaction is not yet ready for broadcast
+    /// but it might change in a future
     NotReady,
-    // transaction will never be qualified for broadcasting
-    // currently we don't broadcast transactions originated on other peers
+    /// transaction will never be qualified for broadcasting
+    /// currently we don't broadcast transactions originated on other peers
     NonQualified,
 }
 
+impl IntoProto for TimelineState {
+    type ProtoType = crate::proto::shared::mempool_status::TimelineState;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        match self {
+            TimelineState::Ready(timeline_id) => {
+                proto.set_kind(ProtoTimelineStateKind::Ready);
+                proto.set_timeline_id(timeline_id);
+            }
+            TimelineState::NotReady => proto.set_kind(ProtoTimelineStateKind::NotReady),
+            TimelineState::NonQualified => proto.set_kind(ProtoTimelineStateKind::NonQualified),
+        }
+        proto
+    }
+}
+
+impl FromProto for TimelineState {
+    type ProtoType = crate::proto::shared::mempool_status::TimelineState;
+
+    fn from_proto(object: Self::ProtoType) -> Result<Self> {
+        let ret = match object.get_kind() {
+            ProtoTimelineStateKind::Ready => TimelineState::Ready(object.get_timeline_id()),
+            ProtoTimelineStateKind::NotReady => TimelineState::NotReady,
+            ProtoTimelineStateKind::NonQualified => TimelineState::NonQualified,
+        };
+        Ok(ret)
+    }
+}
+
+/// Where a transaction stands in mempool
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MempoolTransactionStatus {
+    /// Mempool does not know the transaction
+    Unknown,
+    /// Transaction is in mempool, waiting to be included in a block
+    Pending(TimelineState),
+    /// Transaction was removed from mempool because it expired
+    Expired,
+    /// Transaction was removed from mempool because it was discarded on execution
+    Discarded,
+}
+
+impl IntoProto for MempoolTransactionStatus {
+    type ProtoType = crate::proto::shared::mempool_status::MempoolTransactionStatus;
+
+    fn into_proto(self) -> Self::ProtoType {
+        let mut proto = Self::ProtoType::new();
+        match self {
+            MempoolTransactionStatus::Unknown => {
+                proto.set_state(ProtoMempoolTransactionState::Unknown)
+            }
+            MempoolTransactionStatus::Pending(timeline_state) => {
+                proto.set_state(ProtoMempoolTransactionState::Pending);
+                proto.set_timeline_state(timeline_state.into_proto());
+            }
+            MempoolTransactionStatus::Expired => {
+                proto.set_state(ProtoMempoolTransactionState::Expired)
+            }
+            MempoolTransactionStatus::Discarded => {
+                proto.set_state(ProtoMempoolTransactionState::Discarded)
+            }
+        }
+        proto
+    }
+}
+
+impl FromProto for MempoolTransactionStatus {
+    type ProtoType = crate::proto::shared::mempool_status::MempoolTransactionStatus;
+
+    fn from_proto(mut object: Self::ProtoType) -> Result<Self> {
+        let ret = match object.get_state() {
+            ProtoMempoolTransactionState::Unknown => MempoolTransactionStatus::Unknown,
+            ProtoMempoolTransactionState::Pending => MempoolTransactionStatus::Pending(
+                TimelineState::from_proto(object.take_timeline_state())?,
+            ),
+            ProtoMempoolTransactionState::Expired => MempoolTransactionStatus::Expired,
+            ProtoMempoolTransactionState::Discarded => MempoolTransactionStatus::Discarded,
+        };
+        Ok(ret)
+    }
+}
+
 /// Status of transaction insertion operation
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum MempoolAddTransactionStatus {
@@ -5,13 +5,14 @@ use crate::{
     core_mempool::{
         index::{
             AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
-            TimelineIndex,
+            TimelineIndex, TxnPointer,
         },
         transaction::{MempoolAddTransactionStatus, MempoolTransaction, TimelineState},
     },
     OP_COUNTERS,
 };
 use config::config::MempoolConfig;
+use crypto::{hash::CryptoHash, HashValue};
 use std::{
     collections::HashMap,
     ops::Bound,
@@ -35,6 +36,8 @@ pub struct TransactionStore {
     timeline_index: TimelineIndex,
     // keeps track of "non-ready" txns (transactions that can't be included in next block)
     parking_lot_index: ParkingLotIndex,
+    // signed transaction hash -> transaction
+    hash_index: HashMap<HashValue, TxnPointer>,
 
     // configuration
     capacity: usize,
@@ -55,6 +58,7 @@ impl TransactionStore {
             priority_index: PriorityIndex::new(),
             timeline_index: TimelineIndex::new(),
             parking_lot_index: ParkingLotIndex::new(),
+            hash_index: HashMap::new(),
 
             // configuration
             capacity: config.capacity,
@@ -76,6 +80,23 @@ impl TransactionStore {
         None
     }
 
+    /// fetch timeline state of transaction by account address + sequence_number
+    pub(crate) fn get_timeline_state(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<TimelineState> {
+        self.transactions
+            .get(&address)
+            .and_then(|txns| txns.get(&sequence_number))
+            .map(|txn| txn.timeline_state)
+    }
+
+    /// fetch account address + sequence_number of transaction by its hash
+    pub(crate) fn get_by_hash(&self, hash: &HashValue) -> Option<TxnPointer> {
+        self.hash_index.get(hash).cloned()
+    }
+
     /// insert transaction into TransactionStore
     /// performs validation checks and updates indexes
     pub(crate) fn insert(
@@ -107,6 +128,8 @@ impl TransactionStore {
             // insert into storage and other indexes
             self.system_ttl_index.insert(&txn);
             self.expiration_time_index.insert(&txn);
+            self.hash_index
+                .insert(txn.txn.hash(), (address, sequence_number));
             txns.insert(sequence_number, txn);
             OP_COUNTERS.set("txn.system_ttl_index", self.system_ttl_index.size());
         }
@@ -153,8 +176,11 @@ impl TransactionStore {
                     status = MempoolAddTransactionStatus::InvalidUpdate;
                 } else {
                     self.priority_index.remove(&current_version);
+                    self.hash_index.remove(&current_version.txn.hash());
                     current_version.txn = txn.txn.clone();
                     self.priority_index.insert(&current_version);
+                    self.hash_index
+                        .insert(txn.txn.hash(), TxnPointer::from(txn));
                 }
             }
         }
@@ -220,6 +246,7 @@ impl TransactionStore {
         self.priority_index.remove(&txn);
         self.timeline_index.remove(&txn);
         self.parking_lot_index.remove(&txn);
+        self.hash_index.remove(&txn.txn.hash());
         OP_COUNTERS.set("txn.system_ttl_index", self.system_ttl_index.size());
     }
 
@@ -256,20 +283,22 @@ impl TransactionStore {
     }
 
     /// GC old transactions
-    pub(crate) fn gc_by_system_ttl(&mut self) {
+    /// Returns removed transactions
+    pub(crate) fn gc_by_system_ttl(&mut self) -> Vec<SignedTransaction> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("init timestamp failure");
 
-        self.gc(now, true);
+        self.gc(now, true)
     }
 
     /// GC old transactions based on client-specified expiration time
-    pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) {
-        self.gc(block_time, false);
+    /// Returns removed transactions
+    pub(crate) fn gc_by_expiration_time(&mut self, block_time: Duration) -> Vec<SignedTransaction> {
+        self.gc(block_time, false)
     }
 
-    fn gc(&mut self, now: Duration, by_system_ttl: bool) {
+    fn gc(&mut self, now: Duration, by_system_ttl: bool) -> Vec<SignedTransaction> {
         let (index_name, index) = if by_system_ttl {
             ("gc.system_ttl_index", &mut self.system_ttl_index)
         } else {
@@ -277,6 +306,7 @@ impl TransactionStore {
         };
         OP_COUNTERS.inc(index_name);
 
+        let mut removed = vec![];
         for key in index.gc(now) {
             if let Some(txns) = self.transactions.get_mut(&key.address) {
                 // mark all following transactions as non-ready
@@ -290,10 +320,12 @@ impl TransactionStore {
                     let status = if is_active { "active" } else { "parked" };
                     OP_COUNTERS.inc(&format!("{}.{}", index_name, status));
                     self.index_remove(&txn);
+                    removed.push(txn.txn);
                 }
             }
         }
         OP_COUNTERS.set("txn.system_ttl_index", self.system_ttl_index.size());
+        removed
     }
 
     pub(crate) fn iter_queue(&self) -> PriorityQueueIter {
@@ -3,9 +3,10 @@
 
 use crate::core_mempool::{
     unit_tests::common::{add_txn, add_txns_to_mempool, setup_mempool, TestTransaction},
-    CoreMempool, MempoolAddTransactionStatus, TimelineState,
+    CoreMempool, MempoolAddTransactionStatus, MempoolTransactionStatus, TimelineState,
 };
 use config::config::NodeConfigHelpers;
+use crypto::{hash::CryptoHash, HashValue};
 use std::{collections::HashSet, time::Duration};
 use types::transaction::SignedTransaction;
 
@@ -319,3 +320,72 @@ fn test_gc_ready_transaction() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline[0].sequence_number(), 0);
 }
+
+#[test]
+fn test_transaction_status() {
+    let mut pool = setup_mempool().0;
+    let sender = TestTransaction::get_address(0);
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 0, 1), TestTransaction::new(0, 1, 1)],
+    );
+    for txn in &txns {
+        let status = pool.get_transaction_status(&sender, txn.sequence_number());
+        match status {
+            MempoolTransactionStatus::Pending(_) => {}
+            _ => panic!("Unexpected status {:?}", status),
+        }
+        assert_eq!(pool.get_transaction_status_by_hash(&txn.hash()), status);
+    }
+
+    // an update replaces the pending transaction
+    let updated = add_txns_to_mempool(&mut pool, vec![TestTransaction::new(0, 0, 5)]);
+    assert_eq!(
+        pool.get_transaction_status_by_hash(&txns[0].hash()),
+        MempoolTransactionStatus::Unknown
+    );
+    assert_eq!(
+        pool.get_transaction_status_by_hash(&updated[0].hash()),
+        pool.get_transaction_status(&sender, 0)
+    );
+
+    // rejected transactions are remembered as discarded
+    pool.remove_transaction(&sender, 0, true);
+    assert_eq!(
+        pool.get_transaction_status(&sender, 0),
+        MempoolTransactionStatus::Discarded
+    );
+    assert_eq!(
+        pool.get_transaction_status_by_hash(&updated[0].hash()),
+        MempoolTransactionStatus::Discarded
+    );
+
+    // committed transactions are left to storage
+    pool.remove_transaction(&sender, 1, false);
+    assert_eq!(
+        pool.get_transaction_status_by_hash(&txns[1].hash()),
+        MempoolTransactionStatus::Unknown
+    );
+    assert_eq!(
+        pool.get_transaction_status_by_hash(&HashValue::zero()),
+        MempoolTransactionStatus::Unknown
+    );
+}
+
+#[test]
+fn test_expired_transaction_status() {
+    let mut pool = setup_mempool().0;
+    let txn = TestTransaction::new(1, 0, 1)
+        .make_signed_transaction_with_expiration_time(Duration::from_secs(0));
+    pool.add_txn(txn.clone(), 0, 0, 100, TimelineState::NotReady);
+
+    pool.gc_by_expiration_time(Duration::from_secs(1));
+    assert_eq!(
+        pool.get_transaction_status(&txn.sender(), 0),
+        MempoolTransactionStatus::Expired
+    );
+    assert_eq!(
+        pool.get_transaction_status_by_hash(&txn.hash()),
+        MempoolTransactionStatus::Expired
+    );
+}
@@ -66,7 +66,9 @@ use metrics::OpMetrics;
 lazy_static! {
     static ref OP_COUNTERS: OpMetrics = OpMetrics::new_and_registered("mempool");
 }
-pub use crate::core_mempool::MempoolAddTransactionStatus;
+pub use crate::core_mempool::{
+    MempoolAddTransactionStatus, MempoolTransactionStatus, TimelineState,
+};
 
 #[cfg(test)]
 mod unit_tests;
@@ -6,6 +6,7 @@ use crate::{
     proto::mempool_grpc::Mempool,
     OP_COUNTERS,
 };
+use crypto::HashValue;
 use futures::Future;
 use grpc_helpers::{create_grpc_invalid_arg_status, default_reply_error_logger};
 use logger::prelude::*;
@@ -72,6 +73,69 @@ impl Mempool for MempoolService {
         SVC_COUNTERS.resp(&ctx, success);
     }
 
+    fn add_transactions_with_validation(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        mut req: crate::proto::mempool::AddTransactionsWithValidationRequest,
+        sink: ::grpcio::UnarySink<crate::proto::mempool::AddTransactionsWithValidationResponse>,
+    ) {
+        trace!("[GRPC] Mempool::add_transactions_with_validation");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        OP_COUNTERS.inc_by("add_transactions.requested", req.get_transactions().len());
+        let transactions = req
+            .take_transactions()
+            .into_iter()
+            .map(|mut txn_req| {
+                SignedTransaction::from_proto(txn_req.take_signed_txn()).map(|transaction| {
+                    (
+                        transaction,
+                        txn_req.max_gas_cost,
+                        txn_req.latest_sequence_number,
+                        txn_req.account_balance,
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let success = transactions.is_ok();
+        match transactions {
+            Ok(transactions) => {
+                // The transactions are added one after the other, under the same lock, so that a
+                // transaction can follow one from the same sender earlier in the request.
+                let mut pool = self
+                    .core_mempool
+                    .lock()
+                    .expect("[add txns] acquire mempool lock");
+                let statuses = transactions
+                    .into_iter()
+                    .map(|(transaction, gas_amount, sequence_number, balance)| {
+                        pool.add_txn(
+                            transaction,
+                            gas_amount,
+                            sequence_number,
+                            balance,
+                            TimelineState::NotReady,
+                        )
+                        .into_proto()
+                    })
+                    .collect();
+                drop(pool);
+
+                let mut response =
+                    crate::proto::mempool::AddTransactionsWithValidationResponse::new();
+                response.set_statuses(statuses);
+                ctx.spawn(sink.success(response).map_err(default_reply_error_logger))
+            }
+            Err(e) => ctx.spawn(
+                sink.fail(create_grpc_invalid_arg_status(
+                    "add_transactions_with_validation",
+                    e,
+                ))
+                .map_err(default_reply_error_logger),
+            ),
+        }
+        SVC_COUNTERS.resp(&ctx, success);
+    }
+
     fn get_block(
         &mut self,
         ctx: ::grpcio::RpcContext<'_>,
@@ -154,4 +218,42 @@ impl Mempool for MempoolService {
         response.set_is_healthy(pool.health_check());
         ctx.spawn(sink.success(response).map_err(default_reply_error_logger));
     }
+
+    fn get_transaction_status(
+        &mut self,
+        ctx: ::grpcio::RpcContext<'_>,
+        req: crate::proto::mempool::GetTransactionStatusRequest,
+        sink: ::grpcio::UnarySink<crate::proto::mempool::GetTransactionStatusResponse>,
+    ) {
+        trace!("[GRPC] Mempool::get_transaction_status");
+        let _timer = SVC_COUNTERS.req(&ctx);
+        let status = if req.get_hash().is_empty() {
+            AccountAddress::try_from(req.get_sender()).map(|sender| {
+                self.core_mempool
+                    .lock()
+                    .expect("[get_transaction_status] acquire mempool lock")
+                    .get_transaction_status(&sender, req.get_sequence_number())
+            })
+        } else {
+            HashValue::from_slice(req.get_hash()).map(|hash| {
+                self.core_mempool
+                    .lock()
+                    .expect("[get_transaction_status] acquire mempool lock")
+                    .get_transaction_status_by_hash(&hash)
+            })
+        };
+        let success = status.is_ok();
+        match status {
+            Ok(status) => {
+                let mut response = crate::proto::mempool::GetTransactionStatusResponse::new();
+                response.set_status(status.into_proto());
+                ctx.spawn(sink.success(response).map_err(default_reply_error_logger));
+            }
+            Err(e) => ctx.spawn(
+                sink.fail(create_grpc_invalid_arg_status("get_transaction_status", e))
+                    .map_err(default_reply_error_logger),
+            ),
+        }
+        SVC_COUNTERS.resp(&ctx, success);
+    }
 }
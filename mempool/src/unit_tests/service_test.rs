@@ -11,7 +11,7 @@ use crate::{
     },
 };
 use config::config::NodeConfigHelpers;
-use crypto::signing::generate_keypair;
+use crypto::{hash::CryptoHash, signing::generate_keypair};
 use grpc_helpers::ServerHandle;
 use grpcio::{ChannelBuilder, EnvBuilder};
 use proto_conv::FromProto;
@@ -153,3 +153,103 @@ fn test_gc_by_expiration_time() {
     let response = client.get_block(&GetBlockRequest::new()).unwrap();
     assert_eq!(response.get_block().get_transactions().len(), 1);
 }
+
+#[test]
+fn test_get_transaction_status() {
+    let (server, client) = setup_mempool();
+    let _handle = ServerHandle::setup(server);
+
+    let add_req = create_add_transaction_request(0);
+    client.add_transaction_with_validation(&add_req).unwrap();
+    let signed_txn = SignedTransaction::from_proto(add_req.get_signed_txn().clone()).unwrap();
+    let sender = signed_txn.sender().as_ref().to_vec();
+
+    // look the transaction up by sender and sequence number, then by hash
+    let mut by_seq_req = GetTransactionStatusRequest::new();
+    by_seq_req.set_sender(sender.clone());
+    by_seq_req.set_sequence_number(0);
+    let response = client.get_transaction_status(&by_seq_req).unwrap();
+    assert_eq!(
+        response.get_status().get_state(),
+        MempoolTransactionStatus_State::Pending
+    );
+    let mut by_hash_req = GetTransactionStatusRequest::new();
+    by_hash_req.set_hash(signed_txn.hash().to_vec());
+    let response = client.get_transaction_status(&by_hash_req).unwrap();
+    assert_eq!(
+        response.get_status().get_state(),
+        MempoolTransactionStatus_State::Pending
+    );
+
+    // reject the transaction
+    let mut transaction = CommittedTransaction::new();
+    transaction.set_sender(sender);
+    transaction.set_sequence_number(0);
+    transaction.set_is_rejected(true);
+    let mut req = CommitTransactionsRequest::new();
+    req.set_transactions(::protobuf::RepeatedField::from_vec(vec![transaction]));
+    client.commit_transactions(&req).unwrap();
+    let response = client.get_transaction_status(&by_hash_req).unwrap();
+    assert_eq!(
+        response.get_status().get_state(),
+        MempoolTransactionStatus_State::Discarded
+    );
+
+    // malformed hash
+    let mut req = GetTransactionStatusRequest::new();
+    req.set_hash(vec![1, 2, 3]);
+    assert!(client.get_transaction_status(&req).is_err());
+}
+
+#[test]
+fn test_add_transactions() {
+    let (server, client) = setup_mempool();
+    let _handle = ServerHandle::setup(server);
+
+    // three transactions with consecutive sequence numbers, from a sender who can only pay the
+    // gas of two of them
+    let sender = AccountAddress::random();
+    let (private_key, public_key) = generate_keypair();
+    let transactions = (0..3)
+        .map(|sequence_number| {
+            let mut req = AddTransactionWithValidationRequest::new();
+            req.set_signed_txn(get_test_signed_transaction(
+                sender,
+                sequence_number,
+                private_key.clone(),
+                public_key,
+                None,
+                0,
+                1,
+                None,
+            ));
+            req.set_max_gas_cost(10);
+            req.set_account_balance(25);
+            req
+        })
+        .collect();
+    let mut req = AddTransactionsWithValidationRequest::new();
+    req.set_transactions(transactions);
+    let response = client.add_transactions_with_validation(&req).unwrap();
+    assert_eq!(
+        response.get_statuses(),
+        &[
+            MempoolAddTransactionStatus::Valid,
+            MempoolAddTransactionStatus::Valid,
+            MempoolAddTransactionStatus::InsufficientBalance,
+        ]
+    );
+
+    let mut block_req = GetBlockRequest::new();
+    block_req.set_max_block_size(10);
+    let response = client.get_block(&block_req).unwrap();
+    assert_eq!(response.get_block().get_transactions().len(), 2);
+
+    // nothing is added if a transaction is malformed
+    let mut req = AddTransactionsWithValidationRequest::new();
+    req.mut_transactions()
+        .push(create_add_transaction_request(0));
+    req.mut_transactions()
+        .push(AddTransactionWithValidationRequest::new());
+    assert!(client.add_transactions_with_validation(&req).is_err());
+}
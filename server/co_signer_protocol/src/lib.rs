@@ -0,0 +1,506 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![deny(missing_docs)]
+//! Messages exchanged between the libra_wallet client and the co-signer (`server_exec`) while
+//! running the two-party EdDSA protocol, together with the conversions from the protocol types
+//! to the ed25519 encodings used by Libra.
+//!
+//! Key generation is a single round trip: both parties exchange their public keys and aggregate
+//! them, the server always sitting at SERVER_INDEX and the client at CLIENT_INDEX. Signing runs
+//! in two round trips: the client sends the raw transaction together with a commitment to its
+//! ephemeral key, the server answers with its own commitment, the client reveals its ephemeral
+//! key and finally the server reveals its ephemeral key along with its partial signature.
+//!
+//! Shares can be refreshed without changing the aggregated public key. With a_s and a_c the
+//! aggregation coefficients of the server and client shares x_s and x_c, the aggregated private
+//! key is a_s * x_s + a_c * x_c. Both parties contribute to a random delta, then the server
+//! replaces its weighted share a_s * x_s with a_s * x_s + delta and the client a_c * x_c with
+//! a_c * x_c - delta. A KeyPair cannot be built from a given private key, so a shifted share is
+//! held by a fresh key pair x', along with the coefficient a' = (a * x +/- delta) / x'. Every
+//! refresh bumps the epoch of the key, and the co-signer only signs with the share of the current
+//! epoch.
+//!
+//! The authentication key of an account can be rotated to a new key, generated to replace the
+//! key of the account. The co-signer binds the new key to the account once the client confirms
+//! that the rotation was committed, and never binds a key generated for anything else.
+//!
+//! Any key can be used as a master key, from which child keys are derived without interaction.
+//! The derivation is non-hardened: the aggregated public key of child i is apk + t_i * G, t_i
+//! being a hash of apk, of the chain code returned by the co-signer at key generation and of i.
+//! The client absorbs the whole tweak into its share, the server signs with its master share.
+//!
+//! Keys shared among more than two parties, any t of which sign, are covered by the threshold
+//! module.
+
+use crypto::{hash::CryptoHash, signing, HashValue};
+use curv::arithmetic::traits::Modulo;
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use types::{account_address::AccountAddress, transaction::RawTransactionBytes};
+
+pub use curv::{
+    arithmetic::traits::Converter,
+    elliptic::curves::{
+        ed25519::{FE, GE},
+        traits::{ECPoint, ECScalar},
+    },
+    BigInt,
+};
+pub use multi_party_eddsa::protocols::aggsig::{
+    test_com, verify, EphemeralKey, KeyAgg, KeyPair, SignFirstMsg, SignSecondMsg, Signature,
+};
+
+pub mod threshold;
+
+/// Position of the server public key in the key aggregation.
+pub const SERVER_INDEX: usize = 0;
+/// Position of the client public key in the key aggregation.
+pub const CLIENT_INDEX: usize = 1;
+/// Length of the chain code child keys are derived with.
+pub const CHAIN_CODE_LENGTH: usize = 32;
+
+/// Domain separator of the child key tweaks.
+const CHILD_TWEAK_SALT: &[u8] = b"LIBRA_TWO_PARTY_CHILD";
+
+/// Aggregates the public keys of both parties, as seen from party_index.
+pub fn aggregate_public_keys(
+    server_public_key: &GE,
+    client_public_key: &GE,
+    party_index: usize,
+) -> KeyAgg {
+    let public_keys = vec![server_public_key.clone(), client_public_key.clone()];
+    KeyPair::key_aggregation_n(&public_keys, &party_index)
+}
+
+/// The message both parties sign for a raw transaction, i.e. the bytes of its RawTransactionBytes
+/// hash.
+pub fn signing_message(raw_txn_bytes: &[u8]) -> HashValue {
+    RawTransactionBytes(raw_txn_bytes).hash()
+}
+
+/// Shifts the weighted share a * x of key_pair by delta, a being the aggregation coefficient of
+/// the share, adding delta if add is set and subtracting it otherwise. Returns a fresh key pair
+/// holding the shifted share, along with its aggregation coefficient. The aggregated public key
+/// is preserved when one party adds and the other one subtracts the same delta.
+pub fn refresh_key_pair(key_pair: &KeyPair, a: &FE, delta: &FE, add: bool) -> (KeyPair, FE) {
+    let q = FE::q();
+    let share = weighted_share(key_pair, a).to_big_int();
+    let share: FE = ECScalar::from(&if add {
+        BigInt::mod_add(&share, &delta.to_big_int(), &q)
+    } else {
+        BigInt::mod_sub(&share, &delta.to_big_int(), &q)
+    });
+    key_pair_from_weighted_share(&share)
+}
+
+/// Derives the child at child_number of a master key. Both parties derive the same aggregated
+/// public key; the share of key_pair is tweaked only if tweak_share is set, which is the case for
+/// the client and not for the server.
+pub fn derive_child(
+    key_pair: &KeyPair,
+    key_agg: &KeyAgg,
+    chain_code: &[u8],
+    child_number: u64,
+    tweak_share: bool,
+) -> Result<(KeyPair, KeyAgg)> {
+    ensure!(
+        chain_code.len() == CHAIN_CODE_LENGTH,
+        "Chain code is {} bytes long, expected {}",
+        chain_code.len(),
+        CHAIN_CODE_LENGTH
+    );
+    let apk_bytes = ed25519_public_key(&key_agg.apk)?.to_slice();
+    let hash = HashValue::from_iter_sha3(vec![
+        CHILD_TWEAK_SALT,
+        &apk_bytes[..],
+        chain_code,
+        &child_number.to_le_bytes()[..],
+    ]);
+    // The tweak is the hash reduced modulo the order q of the group.
+    let tweak: FE = ECScalar::from(&BigInt::from(hash.as_ref()).modulus(&FE::q()));
+
+    let (child_key_pair, hash) = if tweak_share {
+        refresh_key_pair(key_pair, &key_agg.hash, &tweak, true)
+    } else {
+        (key_pair.clone(), key_agg.hash.clone())
+    };
+    let child_key_agg = KeyAgg {
+        apk: key_agg
+            .apk
+            .add_point(&(GE::generator() * &tweak).get_element()),
+        hash,
+    };
+    Ok((child_key_pair, child_key_agg))
+}
+
+/// Returns the share of key_pair weighted by its aggregation coefficient a, i.e. a * x. It is
+/// all a party signs, refreshes and derives children with, so that a share can be restored
+/// without the public keys its coefficient was computed from.
+pub fn weighted_share(key_pair: &KeyPair, a: &FE) -> FE {
+    private_key(key_pair).mul(&a.get_element())
+}
+
+/// Generates a key pair x holding a weighted share, returned along with its aggregation
+/// coefficient share / x.
+pub fn key_pair_from_weighted_share(share: &FE) -> (KeyPair, FE) {
+    let key_pair = KeyPair::create();
+    let q = FE::q();
+    let coefficient = BigInt::mod_mul(
+        &share.to_big_int(),
+        &BigInt::mod_inv(&private_key(&key_pair).to_big_int(), &q),
+        &q,
+    );
+    (key_pair, ECScalar::from(&coefficient))
+}
+
+/// Signs message with key_pair alone, as a standard ed25519 signature under its public key.
+pub fn sign_single(key_pair: &KeyPair, message: &[u8]) -> Signature {
+    let (ephemeral_key, _, reveal) = Signature::create_ephemeral_key_and_commit(key_pair, message);
+    let k = Signature::k(&reveal.R, &key_pair.public_key, message);
+    Signature::partial_sign(
+        &ephemeral_key.r,
+        key_pair,
+        &k,
+        &unit_coefficient(),
+        &reveal.R,
+    )
+}
+
+/// The aggregation coefficient of one.
+fn unit_coefficient() -> FE {
+    ECScalar::from(&BigInt::from(1))
+}
+
+/// Encodes a scalar as 32 big endian bytes.
+pub fn scalar_to_bytes(scalar: &FE) -> Result<Vec<u8>> {
+    let bytes = hex::decode(format!("{:0>64}", scalar.to_big_int().to_hex()))?;
+    ensure!(
+        bytes.len() == 32,
+        "Malformed scalar of {} bytes",
+        bytes.len()
+    );
+    Ok(bytes)
+}
+
+/// Decodes a scalar from 32 big endian bytes.
+pub fn scalar_from_bytes(bytes: &[u8]) -> Result<FE> {
+    ensure!(
+        bytes.len() == 32,
+        "Malformed scalar of {} bytes",
+        bytes.len()
+    );
+    Ok(ECScalar::from(&BigInt::from(bytes)))
+}
+
+/// Decodes an aggregated public key from its ed25519 encoding.
+pub fn aggregated_public_key_from_bytes(bytes: &[u8]) -> Result<GE> {
+    GE::from_bytes(bytes).map_err(|e| format_err!("Malformed public key: {:?}", e))
+}
+
+/// Returns the private key x of key_pair, which KeyPair does not expose: its partial signature
+/// r + k * a * x with a zero nonce r and unit k and a is x itself.
+fn private_key(key_pair: &KeyPair) -> FE {
+    let zero: FE = ECScalar::from(&BigInt::from(0));
+    let unit = unit_coefficient();
+    Signature::partial_sign(&zero, key_pair, &unit, &unit, &GE::generator()).s
+}
+
+/// Encodes an aggregated public key as an ed25519 public key.
+pub fn ed25519_public_key(apk: &GE) -> Result<signing::PublicKey> {
+    let bytes = hex::decode(format!(
+        "{:0>64}",
+        apk.bytes_compressed_to_big_int().to_hex()
+    ))?;
+    signing::PublicKey::from_slice(&bytes)
+}
+
+/// Encodes an aggregated signature as an ed25519 signature: the compressed R followed by s in
+/// little endian.
+#[allow(non_snake_case)]
+pub fn ed25519_signature(signature: &Signature) -> Result<signing::Signature> {
+    let R = hex::decode(format!(
+        "{:0>64}",
+        signature.R.bytes_compressed_to_big_int().to_hex()
+    ))?;
+    let mut s = hex::decode(format!("{:0>64}", signature.s.to_big_int().to_hex()))?;
+    ensure!(
+        R.len() == 32 && s.len() == 32,
+        "Malformed signature: R is {} bytes long and s is {} bytes long",
+        R.len(),
+        s.len()
+    );
+    // to little endian
+    s.reverse();
+    let mut bytes = R;
+    bytes.extend_from_slice(&s);
+    signing::Signature::from_compact(&bytes)
+}
+
+/// First message of key generation, sent by the client.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KeyGenRequest {
+    /// Public key of the client share
+    pub client_public_key: GE,
+    /// Whether the key signs prehashed messages, e.g. consensus votes, instead of transactions.
+    /// Such messages cannot be checked against a spending policy, so a key signs either kind
+    /// but never both.
+    #[serde(default)]
+    pub message_signing: bool,
+    /// Id of the key whose account the new key is generated to take over, by a rotation of the
+    /// account authentication key
+    #[serde(default)]
+    pub replaces: Option<String>,
+}
+
+/// Answer of the co-signer to a KeyGenRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KeyGenResponse {
+    /// Id under which the co-signer stores its share
+    pub id: String,
+    /// Public key of the server share
+    pub server_public_key: GE,
+    /// Chain code child keys are derived with
+    #[serde(with = "hex_bytes", default)]
+    pub chain_code: Vec<u8>,
+}
+
+/// First message of signing, sent by the client.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignFirstRequest {
+    /// Protobuf encoded RawTransaction to sign. The co-signer decodes it, checks it against its
+    /// policy and computes the message to sign by itself. Empty for message signing keys.
+    #[serde(with = "hex_bytes")]
+    pub raw_txn_bytes: Vec<u8>,
+    /// Prehashed message to sign, for message signing keys only
+    #[serde(default)]
+    pub message_hash: Option<HashValue>,
+    /// Commitment to the client ephemeral key
+    pub client_commitment: SignFirstMsg,
+    /// Epoch of the client share. Signing fails if it is not the current epoch of the key.
+    #[serde(default)]
+    pub epoch: u64,
+    /// Child of the key to sign with, if the key is used as a master key
+    #[serde(default)]
+    pub child_number: Option<u64>,
+}
+
+/// Answer of the co-signer to a SignFirstRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignFirstResponse {
+    /// Id of the signing session, valid for a single SignSecondRequest
+    pub session_id: String,
+    /// Commitment to the server ephemeral key
+    pub server_commitment: SignFirstMsg,
+}
+
+/// Second message of signing, sent by the client.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignSecondRequest {
+    /// Id returned in the SignFirstResponse
+    pub session_id: String,
+    /// Opening of the client commitment
+    pub client_reveal: SignSecondMsg,
+}
+
+/// Answer of the co-signer to a SignSecondRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignSecondResponse {
+    /// Opening of the server commitment
+    pub server_reveal: SignSecondMsg,
+    /// Partial signature of the server share
+    pub server_partial_signature: Signature,
+}
+
+/// Request to refresh the shares of a key, sent by the client.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RefreshRequest {
+    /// Epoch of the client share
+    pub epoch: u64,
+    /// Contribution of the client to the refresh delta
+    pub client_delta: FE,
+}
+
+/// Answer of the co-signer to a RefreshRequest. The refreshed server share is pending until the
+/// client confirms it, or uses it to sign.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RefreshResponse {
+    /// Epoch of the refreshed shares
+    pub epoch: u64,
+    /// Contribution of the server to the refresh delta
+    pub server_delta: FE,
+}
+
+/// Confirmation that the client persisted its refreshed share, after which the co-signer drops
+/// the share of the previous epoch.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RefreshConfirmRequest {
+    /// Epoch of the refreshed shares
+    pub epoch: u64,
+}
+
+/// Answer of the co-signer to a RefreshConfirmRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RefreshConfirmResponse {
+    /// Current epoch of the key
+    pub epoch: u64,
+}
+
+/// Confirmation that the authentication key of an account was rotated on chain to the key the
+/// request is sent for, after which the co-signer binds the key to the account.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RotationConfirmRequest {
+    /// Account whose authentication key was rotated
+    pub address: AccountAddress,
+}
+
+/// Answer of the co-signer to a RotationConfirmRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RotationConfirmResponse {
+    /// Account the key is bound to
+    pub address: AccountAddress,
+}
+
+/// Body returned by the co-signer along with any non-successful status.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ErrorResponse {
+    /// Human readable reason, e.g. the policy rule a transaction was rejected by
+    pub reason: String,
+}
+
+/// Serializes byte vectors as hex strings.
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signs message with the key pair of every party, along with its aggregation coefficient,
+    /// and checks the signature against apk.
+    fn sign_and_verify(parties: &[(&KeyPair, &FE)], apk: &GE, message: &[u8]) -> bool {
+        let ephemeral_keys: Vec<_> = parties
+            .iter()
+            .map(|(key_pair, _)| Signature::create_ephemeral_key_and_commit(key_pair, message))
+            .collect();
+        let r_tot = Signature::get_R_tot(
+            ephemeral_keys
+                .iter()
+                .map(|(_, _, reveal)| reveal.R.clone())
+                .collect(),
+        );
+        let k = Signature::k(&r_tot, apk, message);
+        let partial_signatures = parties
+            .iter()
+            .zip(&ephemeral_keys)
+            .map(|((key_pair, a), (ephemeral_key, _, _))| {
+                Signature::partial_sign(&ephemeral_key.r, key_pair, &k, a, &r_tot)
+            })
+            .collect();
+        verify(
+            &Signature::add_signature_parts(partial_signatures),
+            message,
+            apk,
+        )
+        .is_ok()
+    }
+
+    #[test]
+    fn test_weighted_share() {
+        let key_pair = KeyPair::create();
+        let a = FE::new_random();
+        let share = weighted_share(&key_pair, &a);
+        assert!(GE::generator() * &share == key_pair.public_key.scalar_mul(&a.get_element()));
+
+        let (restored, coefficient) = key_pair_from_weighted_share(&share);
+        assert!(weighted_share(&restored, &coefficient) == share);
+    }
+
+    #[test]
+    fn test_derive_child() {
+        let server = KeyPair::create();
+        let client = KeyPair::create();
+        let server_agg =
+            aggregate_public_keys(&server.public_key, &client.public_key, SERVER_INDEX);
+        let client_agg =
+            aggregate_public_keys(&server.public_key, &client.public_key, CLIENT_INDEX);
+        let chain_code = HashValue::random().to_vec();
+
+        let (server_child, server_child_agg) =
+            derive_child(&server, &server_agg, &chain_code, 7, false).unwrap();
+        let (client_child, client_child_agg) =
+            derive_child(&client, &client_agg, &chain_code, 7, true).unwrap();
+        // Both parties derive the same child public key, and only the client share is tweaked.
+        assert!(server_child_agg.apk == client_child_agg.apk);
+        assert!(server_child_agg.apk != server_agg.apk);
+        assert!(server_child.public_key == server.public_key);
+        let apk = client_child_agg.apk.clone();
+        let private_key = weighted_share(&server_child, &server_child_agg.hash)
+            .add(&weighted_share(&client_child, &client_child_agg.hash).get_element());
+        assert!(GE::generator() * &private_key == apk);
+        ed25519_public_key(&apk).unwrap();
+        assert!(sign_and_verify(
+            &[
+                (&server_child, &server_child_agg.hash),
+                (&client_child, &client_child_agg.hash),
+            ],
+            &apk,
+            b"child",
+        ));
+
+        // The derivation only depends on the master key, chain code and child number.
+        let (again, again_agg) = derive_child(&client, &client_agg, &chain_code, 7, true).unwrap();
+        assert!(again_agg.apk == apk);
+        assert!(
+            weighted_share(&again, &again_agg.hash)
+                == weighted_share(&client_child, &client_child_agg.hash)
+        );
+        let (_, other_agg) = derive_child(&client, &client_agg, &chain_code, 8, true).unwrap();
+        assert!(other_agg.apk != apk);
+        assert!(derive_child(&client, &client_agg, &chain_code[1..], 7, true).is_err());
+    }
+
+    #[test]
+    fn test_refresh_preserves_aggregated_public_key() {
+        let server = KeyPair::create();
+        let client = KeyPair::create();
+        let server_agg =
+            aggregate_public_keys(&server.public_key, &client.public_key, SERVER_INDEX);
+        let client_agg =
+            aggregate_public_keys(&server.public_key, &client.public_key, CLIENT_INDEX);
+        let apk = server_agg.apk.clone();
+        assert!(client_agg.apk == apk);
+
+        let delta = FE::new_random();
+        let (new_server, server_a) = refresh_key_pair(&server, &server_agg.hash, &delta, true);
+        let (new_client, client_a) = refresh_key_pair(&client, &client_agg.hash, &delta, false);
+        assert!(new_server.public_key != server.public_key);
+        assert!(new_client.public_key != client.public_key);
+        let private_key = weighted_share(&new_server, &server_a)
+            .add(&weighted_share(&new_client, &client_a).get_element());
+        assert!(GE::generator() * &private_key == apk);
+
+        let message = b"refresh";
+        assert!(sign_and_verify(
+            &[(&new_server, &server_a), (&new_client, &client_a)],
+            &apk,
+            message,
+        ));
+        // Shares of different epochs do not sign together.
+        assert!(!sign_and_verify(
+            &[(&new_server, &server_a), (&client, &client_agg.hash)],
+            &apk,
+            message,
+        ));
+    }
+}
@@ -0,0 +1,1051 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Threshold keys, split among n participants so that any t of them can sign.
+//!
+//! The client is always participant CLIENT_PARTICIPANT, the co-signers follow from 2 on. Key
+//! generation is a Pedersen distributed key generation relayed by the client, in three rounds:
+//! every participant draws a random polynomial f_j of degree t - 1 and an ephemeral encryption
+//! key, and publishes Feldman commitments to the coefficients of f_j along with the public
+//! encryption key; then every participant j sends f_j(i) to every other participant i, padded
+//! with a hash of their Diffie-Hellman secret; finally every participant checks the sub-shares
+//! it received against the commitments of their senders and sums them. The share of participant
+//! i is x_i = sum_j f_j(i) and the key is x = sum_j f_j(0), with public key X = sum_j C_j0.
+//!
+//! Signing with a set S of at least t participants reuses the aggregated signature of the
+//! two-party protocol: every signer signs with its share weighted by its Lagrange coefficient,
+//! y_i = l_i * x_i, so that sum_i y_i = x and the sum of the partial signatures is a standard
+//! ed25519 signature under X. The rounds are the same, commitments to the ephemeral keys are
+//! exchanged before they are revealed. The client relays every message, it sees no secret of the
+//! co-signers but must be trusted to send the same commitments to all of them.
+//!
+//! Every co-signer signs its key generation commitment with a long-term identity key, which the
+//! client and the other co-signers have pinned. The commitments of co-signers are refused unless
+//! signed by distinct pinned identity keys: otherwise the client relaying them could swap the
+//! encryption key of a co-signer for one of its own, and decrypt the sub-shares sent to it.
+
+use crate::{
+    aggregated_public_key_from_bytes, ed25519_public_key, key_pair_from_weighted_share,
+    sign_single, test_com, verify, ECPoint, ECScalar, EphemeralKey, KeyPair, SignFirstMsg,
+    SignSecondMsg, Signature, FE, GE,
+};
+use crypto::HashValue;
+use curv::{arithmetic::traits::Modulo, BigInt};
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Index of the client among the participants of a threshold key.
+pub const CLIENT_PARTICIPANT: u64 = 1;
+
+/// Domain separator of the pads sub-shares are encrypted with.
+const SUB_SHARE_PAD_SALT: &[u8] = b"LIBRA_THRESHOLD_SUB_SHARE";
+/// Domain separator of the signatures of commitments by identity keys.
+const COMMITMENT_IDENTITY_SALT: &[u8] = b"LIBRA_THRESHOLD_COMMITMENT_IDENTITY";
+
+/// Number of participants of a threshold key, and how many of them are needed to sign.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ThresholdParameters {
+    /// Number of participants needed to sign
+    pub threshold: u64,
+    /// Number of participants holding a share
+    pub participants: u64,
+}
+
+impl ThresholdParameters {
+    /// Checks that a key can be generated with these parameters.
+    pub fn validate(&self) -> Result<()> {
+        ensure!(
+            self.participants >= 2,
+            "A threshold key needs at least 2 participants, got {}",
+            self.participants
+        );
+        ensure!(
+            self.threshold >= 1 && self.threshold <= self.participants,
+            "Threshold {} is out of range for {} participants",
+            self.threshold,
+            self.participants
+        );
+        Ok(())
+    }
+
+    /// Checks that index designates a participant.
+    pub fn check_index(&self, index: u64) -> Result<()> {
+        ensure!(
+            index >= 1 && index <= self.participants,
+            "Participant {} is out of range 1..={}",
+            index,
+            self.participants
+        );
+        Ok(())
+    }
+
+    /// Checks that signers are distinct participants, sorted by index, and enough to sign.
+    pub fn check_signers(&self, signers: &[u64]) -> Result<()> {
+        ensure!(
+            signers.len() as u64 >= self.threshold,
+            "{} signers are not enough, {} are needed",
+            signers.len(),
+            self.threshold
+        );
+        for index in signers {
+            self.check_index(*index)?;
+        }
+        ensure!(
+            signers.windows(2).all(|pair| pair[0] < pair[1]),
+            "Signers must be distinct and sorted by index"
+        );
+        Ok(())
+    }
+}
+
+/// Public contribution of a participant to key generation.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ParticipantCommitment {
+    /// Index of the participant
+    pub index: u64,
+    /// Commitments to the coefficients of the polynomial of the participant, constant first
+    pub commitments: Vec<GE>,
+    /// Public key the sub-shares sent to the participant are encrypted to
+    pub encryption_key: GE,
+}
+
+impl ParticipantCommitment {
+    /// Signs the commitment of a co-signer with its identity key, for a key with parameters.
+    pub fn sign_identity(
+        &self,
+        parameters: ThresholdParameters,
+        identity: &KeyPair,
+    ) -> CommitmentIdentity {
+        CommitmentIdentity {
+            index: self.index,
+            identity_key: identity.public_key.clone(),
+            signature: sign_single(identity, self.identity_message(parameters).as_ref()),
+        }
+    }
+
+    /// Message identity keys sign, binding the commitment, and in particular its encryption key,
+    /// to the parameters of the key.
+    fn identity_message(&self, parameters: ThresholdParameters) -> HashValue {
+        let header = [
+            parameters.threshold.to_le_bytes(),
+            parameters.participants.to_le_bytes(),
+            self.index.to_le_bytes(),
+        ];
+        let points = self
+            .commitments
+            .iter()
+            .chain(std::iter::once(&self.encryption_key))
+            .map(|point| point.pk_to_key_slice())
+            .collect::<Vec<_>>();
+        let mut parts = vec![COMMITMENT_IDENTITY_SALT];
+        parts.extend(header.iter().map(|bytes| &bytes[..]));
+        parts.extend(points.iter().map(|bytes| &bytes[..]));
+        HashValue::from_iter_sha3(parts)
+    }
+}
+
+/// Signature of the commitment of a co-signer by its long-term identity key.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CommitmentIdentity {
+    /// Index of the co-signer
+    pub index: u64,
+    /// Identity key of the co-signer, as pinned by the other participants
+    pub identity_key: GE,
+    /// Signature of the commitment of the co-signer by its identity key
+    pub signature: Signature,
+}
+
+/// Checks that the commitment of every co-signer, i.e. of every participant but the client, is
+/// signed by its own identity key among trusted_keys, identities being sorted by index.
+pub fn authenticate_participants(
+    parameters: ThresholdParameters,
+    participants: &[ParticipantCommitment],
+    identities: &[CommitmentIdentity],
+    trusted_keys: &[GE],
+) -> Result<()> {
+    let co_signers = participants
+        .iter()
+        .filter(|participant| participant.index != CLIENT_PARTICIPANT)
+        .collect::<Vec<_>>();
+    ensure!(
+        identities.len() == co_signers.len(),
+        "Expected the identities of {} co-signers, got {}",
+        co_signers.len(),
+        identities.len()
+    );
+    for (position, (participant, identity)) in co_signers.iter().zip(identities).enumerate() {
+        ensure!(
+            identity.index == participant.index,
+            "Identities must be sorted by index"
+        );
+        ensure!(
+            trusted_keys.contains(&identity.identity_key),
+            "Commitment of participant {} is signed by an identity key that is not pinned",
+            participant.index
+        );
+        if let Some(other) = identities[..position]
+            .iter()
+            .find(|other| other.identity_key == identity.identity_key)
+        {
+            bail!(
+                "Participants {} and {} share the same identity key",
+                other.index,
+                participant.index
+            );
+        }
+        verify(
+            &identity.signature,
+            participant.identity_message(parameters).as_ref(),
+            &identity.identity_key,
+        )
+        .map_err(|_| {
+            format_err!(
+                "Commitment of participant {} is not signed by its identity key",
+                participant.index
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Encodes an identity key as hex, the way it is pinned in configs.
+pub fn encode_identity_key(identity_key: &GE) -> Result<String> {
+    Ok(hex::encode(
+        &ed25519_public_key(identity_key)?.to_slice()[..],
+    ))
+}
+
+/// Decodes an identity key pinned in a config.
+pub fn decode_identity_key(identity_key: &str) -> Result<GE> {
+    aggregated_public_key_from_bytes(&hex::decode(identity_key)?)
+}
+
+/// Evaluation of the polynomial of sender at the index of recipient, padded so that only
+/// recipient learns it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EncryptedSubShare {
+    /// Index of the participant the sub-share comes from
+    pub sender: u64,
+    /// Index of the participant the sub-share is for
+    pub recipient: u64,
+    /// Padded sub-share
+    pub value: FE,
+}
+
+/// Secret state of a participant during key generation.
+pub struct KeyGenState {
+    parameters: ThresholdParameters,
+    index: u64,
+    coefficients: Vec<FE>,
+    encryption_secret: FE,
+}
+
+impl KeyGenState {
+    /// Draws the polynomial and the encryption key of participant index.
+    pub fn new(parameters: ThresholdParameters, index: u64) -> Result<Self> {
+        parameters.validate()?;
+        parameters.check_index(index)?;
+        Ok(Self {
+            parameters,
+            index,
+            coefficients: (0..parameters.threshold)
+                .map(|_| FE::new_random())
+                .collect(),
+            encryption_secret: FE::new_random(),
+        })
+    }
+
+    /// Parameters of the key being generated.
+    pub fn parameters(&self) -> ThresholdParameters {
+        self.parameters
+    }
+
+    /// Index of the participant.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Public contribution of the participant, sent to all the others.
+    pub fn commitment(&self) -> ParticipantCommitment {
+        ParticipantCommitment {
+            index: self.index,
+            commitments: self
+                .coefficients
+                .iter()
+                .map(|coefficient| GE::generator() * coefficient)
+                .collect(),
+            encryption_key: GE::generator() * &self.encryption_secret,
+        }
+    }
+
+    /// Computes the sub-shares of all the other participants, given the commitments of all the
+    /// participants ordered by index.
+    pub fn sub_shares(
+        &self,
+        participants: &[ParticipantCommitment],
+    ) -> Result<Vec<EncryptedSubShare>> {
+        self.check_participants(participants)?;
+        let q = FE::q();
+        Ok(participants
+            .iter()
+            .filter(|participant| participant.index != self.index)
+            .map(|participant| {
+                let pad = self.pad(&participant.encryption_key, self.index, participant.index);
+                EncryptedSubShare {
+                    sender: self.index,
+                    recipient: participant.index,
+                    value: ECScalar::from(&BigInt::mod_add(
+                        &self.evaluate(participant.index),
+                        &pad.to_big_int(),
+                        &q,
+                    )),
+                }
+            })
+            .collect())
+    }
+
+    /// Decrypts the sub-shares sent to the participant, checks them against the commitments of
+    /// their senders and returns the share of the participant.
+    pub fn complete(
+        &self,
+        participants: &[ParticipantCommitment],
+        sub_shares: &[EncryptedSubShare],
+    ) -> Result<ThresholdShare> {
+        self.check_participants(participants)?;
+        let q = FE::q();
+        let mut share = self.evaluate(self.index);
+        for participant in participants {
+            if participant.index == self.index {
+                continue;
+            }
+            let sub_share = sub_shares
+                .iter()
+                .find(|sub_share| {
+                    sub_share.sender == participant.index && sub_share.recipient == self.index
+                })
+                .ok_or_else(|| {
+                    format_err!("Missing sub-share of participant {}", participant.index)
+                })?;
+            let pad = self.pad(&participant.encryption_key, participant.index, self.index);
+            let value: FE = ECScalar::from(&BigInt::mod_sub(
+                &sub_share.value.to_big_int(),
+                &pad.to_big_int(),
+                &q,
+            ));
+            ensure!(
+                GE::generator() * &value
+                    == evaluate_commitments(&participant.commitments, self.index),
+                "Sub-share of participant {} does not match its commitments",
+                participant.index
+            );
+            share = BigInt::mod_add(&share, &value.to_big_int(), &q);
+        }
+
+        let commitments = (0..self.parameters.threshold as usize)
+            .map(|k| {
+                participants[1..].iter().fold(
+                    participants[0].commitments[k].clone(),
+                    |sum, participant| sum.add_point(&participant.commitments[k].get_element()),
+                )
+            })
+            .collect();
+        Ok(ThresholdShare {
+            parameters: self.parameters,
+            index: self.index,
+            share: ECScalar::from(&share),
+            commitments,
+        })
+    }
+
+    fn check_participants(&self, participants: &[ParticipantCommitment]) -> Result<()> {
+        ensure!(
+            participants.len() as u64 == self.parameters.participants,
+            "Expected the commitments of {} participants, got {}",
+            self.parameters.participants,
+            participants.len()
+        );
+        for (position, participant) in participants.iter().enumerate() {
+            ensure!(
+                participant.index == position as u64 + 1,
+                "Participant commitments must be sorted by index"
+            );
+            ensure!(
+                participant.commitments.len() as u64 == self.parameters.threshold,
+                "Participant {} committed to {} coefficients, expected {}",
+                participant.index,
+                participant.commitments.len(),
+                self.parameters.threshold
+            );
+        }
+        ensure!(
+            participants[self.index as usize - 1] == self.commitment(),
+            "Commitments of participant {} were altered",
+            self.index
+        );
+        Ok(())
+    }
+
+    /// Evaluates the polynomial of the participant at x.
+    fn evaluate(&self, x: u64) -> BigInt {
+        let q = FE::q();
+        let x = BigInt::from(x);
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigInt::from(0), |value, coefficient| {
+                BigInt::mod_add(
+                    &BigInt::mod_mul(&value, &x, &q),
+                    &coefficient.to_big_int(),
+                    &q,
+                )
+            })
+    }
+
+    fn pad(&self, encryption_key: &GE, sender: u64, recipient: u64) -> FE {
+        let secret = encryption_key.scalar_mul(&self.encryption_secret.get_element());
+        hash_to_scalar(vec![
+            SUB_SHARE_PAD_SALT,
+            &secret.pk_to_key_slice()[..],
+            &sender.to_le_bytes()[..],
+            &recipient.to_le_bytes()[..],
+        ])
+    }
+}
+
+/// Share of a threshold key held by a participant.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdShare {
+    /// Parameters of the key
+    pub parameters: ThresholdParameters,
+    /// Index of the participant
+    pub index: u64,
+    /// Secret share of the participant
+    pub share: FE,
+    /// Commitments to the coefficients of the sum of the polynomials of all the participants,
+    /// the first one being the public key
+    pub commitments: Vec<GE>,
+}
+
+impl ThresholdShare {
+    /// Public key of the threshold key.
+    pub fn public_key(&self) -> &GE {
+        &self.commitments[0]
+    }
+
+    /// Public key of the share of participant index.
+    pub fn participant_public_key(&self, index: u64) -> GE {
+        evaluate_commitments(&self.commitments, index)
+    }
+
+    /// Key pair holding the share weighted by its Lagrange coefficient among signers, along with
+    /// the aggregation coefficient it signs with.
+    pub fn signing_key_pair(&self, signers: &[u64]) -> Result<(KeyPair, FE)> {
+        self.parameters.check_signers(signers)?;
+        let coefficient = lagrange_coefficient(self.index, signers)?;
+        Ok(key_pair_from_weighted_share(
+            &self.share.mul(&coefficient.get_element()),
+        ))
+    }
+
+    /// Checks the partial signature of signer index, s * G = R + k * l * X_i, R being the nonce
+    /// it revealed, l its Lagrange coefficient and X_i the public key of its share.
+    pub fn verify_partial_signature(
+        &self,
+        index: u64,
+        signers: &[u64],
+        partial_signature: &Signature,
+        nonce: &GE,
+        r_tot: &GE,
+        k: &FE,
+    ) -> Result<()> {
+        let coefficient = lagrange_coefficient(index, signers)?;
+        let contribution = self
+            .participant_public_key(index)
+            .scalar_mul(&coefficient.get_element());
+        let expected = nonce.add_point(&contribution.scalar_mul(&k.get_element()).get_element());
+        let actual = GE::generator().scalar_mul(&partial_signature.s.get_element());
+        ensure!(
+            partial_signature.R == *r_tot && actual == expected,
+            "Signer {} returned an invalid partial signature",
+            index
+        );
+        Ok(())
+    }
+}
+
+/// Lagrange coefficient of participant index at zero, among signers.
+pub fn lagrange_coefficient(index: u64, signers: &[u64]) -> Result<FE> {
+    ensure!(
+        signers.contains(&index),
+        "Participant {} is not among the signers",
+        index
+    );
+    let q = FE::q();
+    let mut numerator = BigInt::from(1);
+    let mut denominator = BigInt::from(1);
+    for signer in signers.iter().filter(|signer| **signer != index) {
+        numerator = BigInt::mod_mul(&numerator, &BigInt::from(*signer), &q);
+        denominator = BigInt::mod_mul(
+            &denominator,
+            &BigInt::mod_sub(&BigInt::from(*signer), &BigInt::from(index), &q),
+            &q,
+        );
+    }
+    Ok(ECScalar::from(&BigInt::mod_mul(
+        &numerator,
+        &BigInt::mod_inv(&denominator, &q),
+        &q,
+    )))
+}
+
+/// Commitment of a signer to its ephemeral key.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignerCommitment {
+    /// Index of the signer
+    pub index: u64,
+    /// Commitment to the ephemeral key
+    pub commitment: SignFirstMsg,
+}
+
+/// Opening of the commitment of a signer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignerReveal {
+    /// Index of the signer
+    pub index: u64,
+    /// Ephemeral public key and blinding factor of the commitment
+    pub reveal: SignSecondMsg,
+}
+
+/// Checks that every reveal opens the commitment of its signer, both being sorted by index, and
+/// returns the aggregated nonce.
+pub fn aggregate_nonce(commitments: &[SignerCommitment], reveals: &[SignerReveal]) -> Result<GE> {
+    ensure!(
+        commitments.len() == reveals.len(),
+        "Got {} reveals for {} commitments",
+        reveals.len(),
+        commitments.len()
+    );
+    for (commitment, reveal) in commitments.iter().zip(reveals) {
+        ensure!(
+            commitment.index == reveal.index
+                && test_com(
+                    &reveal.reveal.R,
+                    &reveal.reveal.blind_factor,
+                    &commitment.commitment.commitment
+                ),
+            "Reveal of signer {} does not open its commitment",
+            reveal.index
+        );
+    }
+    Ok(Signature::get_R_tot(
+        reveals
+            .iter()
+            .map(|reveal| reveal.reveal.R.clone())
+            .collect(),
+    ))
+}
+
+/// Signing session of one signer of a threshold key.
+pub struct SignerSession {
+    index: u64,
+    signers: Vec<u64>,
+    message: HashValue,
+    public_key: GE,
+    key_pair: KeyPair,
+    coefficient: FE,
+    ephemeral_key: EphemeralKey,
+    commitment: SignFirstMsg,
+    reveal: SignSecondMsg,
+    commitments: Option<Vec<SignerCommitment>>,
+}
+
+impl SignerSession {
+    /// Starts signing message with share, together with signers.
+    pub fn new(share: &ThresholdShare, signers: Vec<u64>, message: HashValue) -> Result<Self> {
+        let (key_pair, coefficient) = share.signing_key_pair(&signers)?;
+        let (ephemeral_key, commitment, reveal) =
+            Signature::create_ephemeral_key_and_commit(&key_pair, message.as_ref());
+        Ok(Self {
+            index: share.index,
+            signers,
+            message,
+            public_key: share.public_key().clone(),
+            key_pair,
+            coefficient,
+            ephemeral_key,
+            commitment,
+            reveal,
+            commitments: None,
+        })
+    }
+
+    /// Participants signing in this session.
+    pub fn signers(&self) -> &[u64] {
+        &self.signers
+    }
+
+    /// Message signed in this session.
+    pub fn message(&self) -> HashValue {
+        self.message
+    }
+
+    /// Commitment of the signer to its ephemeral key.
+    pub fn commitment(&self) -> SignerCommitment {
+        SignerCommitment {
+            index: self.index,
+            commitment: self.commitment.clone(),
+        }
+    }
+
+    /// Records the commitments of all the signers, sorted by index, and reveals the ephemeral key
+    /// of the signer.
+    pub fn reveal(&mut self, commitments: Vec<SignerCommitment>) -> Result<SignerReveal> {
+        ensure!(
+            self.commitments.is_none(),
+            "The ephemeral key of this session is already revealed"
+        );
+        ensure!(
+            commitments
+                .iter()
+                .map(|commitment| commitment.index)
+                .eq(self.signers.iter().cloned()),
+            "Commitments do not match the signers of the session"
+        );
+        let own = &commitments[self
+            .signers
+            .iter()
+            .position(|index| *index == self.index)
+            .ok_or_else(|| format_err!("Participant {} is not a signer", self.index))?];
+        ensure!(
+            own.commitment.commitment == self.commitment.commitment,
+            "Commitment of signer {} was altered",
+            self.index
+        );
+        self.commitments = Some(commitments);
+        Ok(SignerReveal {
+            index: self.index,
+            reveal: self.reveal.clone(),
+        })
+    }
+
+    /// Checks the reveals of all the signers against their commitments and returns the partial
+    /// signature of the signer.
+    pub fn sign(&self, reveals: &[SignerReveal]) -> Result<Signature> {
+        let commitments = self
+            .commitments
+            .as_ref()
+            .ok_or_else(|| format_err!("The ephemeral key of this session is not revealed"))?;
+        let own = reveals
+            .iter()
+            .find(|reveal| reveal.index == self.index)
+            .ok_or_else(|| format_err!("Missing reveal of signer {}", self.index))?;
+        ensure!(
+            own.reveal.R == self.reveal.R,
+            "Reveal of signer {} was altered",
+            self.index
+        );
+        let r_tot = aggregate_nonce(commitments, reveals)?;
+        let k = Signature::k(&r_tot, &self.public_key, self.message.as_ref());
+        Ok(Signature::partial_sign(
+            &self.ephemeral_key.r,
+            &self.key_pair,
+            &k,
+            &self.coefficient,
+            &r_tot,
+        ))
+    }
+}
+
+/// First message of threshold key generation, sent by the client to every co-signer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdKeyGenCommitRequest {
+    /// Parameters of the key
+    pub parameters: ThresholdParameters,
+    /// Index of the co-signer among the participants
+    pub index: u64,
+}
+
+/// Answer of a co-signer to a ThresholdKeyGenCommitRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdKeyGenCommitResponse {
+    /// Id of the key generation session
+    pub session_id: String,
+    /// Public contribution of the co-signer
+    pub commitment: ParticipantCommitment,
+    /// Signature of the commitment by the identity key of the co-signer
+    pub identity: CommitmentIdentity,
+}
+
+/// Second message of threshold key generation, relaying the commitments of all the participants.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdKeyGenShareRequest {
+    /// Id returned in the ThresholdKeyGenCommitResponse
+    pub session_id: String,
+    /// Commitments of all the participants, sorted by index
+    pub participants: Vec<ParticipantCommitment>,
+    /// Signatures of the commitments of all the co-signers by their identity keys, sorted by
+    /// index
+    pub identities: Vec<CommitmentIdentity>,
+}
+
+/// Answer of a co-signer to a ThresholdKeyGenShareRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdKeyGenShareResponse {
+    /// Sub-shares of the co-signer for all the other participants
+    pub sub_shares: Vec<EncryptedSubShare>,
+}
+
+/// Last message of threshold key generation, relaying the sub-shares sent to the co-signer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdKeyGenCompleteRequest {
+    /// Id returned in the ThresholdKeyGenCommitResponse
+    pub session_id: String,
+    /// Sub-shares of all the other participants for the co-signer
+    pub sub_shares: Vec<EncryptedSubShare>,
+}
+
+/// Answer of a co-signer to a ThresholdKeyGenCompleteRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdKeyGenCompleteResponse {
+    /// Id under which the co-signer stores its share
+    pub id: String,
+    /// Public key of the threshold key
+    pub public_key: GE,
+    /// Public key of the share of the co-signer
+    pub share_public_key: GE,
+}
+
+/// First message of threshold signing, sent by the client to every co-signer taking part.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdSignCommitRequest {
+    /// Protobuf encoded RawTransaction to sign, checked by the co-signer against its policy
+    #[serde(with = "crate::hex_bytes")]
+    pub raw_txn_bytes: Vec<u8>,
+    /// Participants signing, sorted by index
+    pub signers: Vec<u64>,
+}
+
+/// Answer of a co-signer to a ThresholdSignCommitRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdSignCommitResponse {
+    /// Id of the signing session
+    pub session_id: String,
+    /// Commitment of the co-signer to its ephemeral key
+    pub commitment: SignerCommitment,
+}
+
+/// Second message of threshold signing, relaying the commitments of all the signers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdSignRevealRequest {
+    /// Id returned in the ThresholdSignCommitResponse
+    pub session_id: String,
+    /// Commitments of all the signers, sorted by index
+    pub commitments: Vec<SignerCommitment>,
+}
+
+/// Answer of a co-signer to a ThresholdSignRevealRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdSignRevealResponse {
+    /// Opening of the commitment of the co-signer
+    pub reveal: SignerReveal,
+}
+
+/// Last message of threshold signing, relaying the reveals of all the signers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdSignRequest {
+    /// Id returned in the ThresholdSignCommitResponse
+    pub session_id: String,
+    /// Reveals of all the signers, sorted by index
+    pub reveals: Vec<SignerReveal>,
+}
+
+/// Answer of a co-signer to a ThresholdSignRequest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThresholdSignResponse {
+    /// Partial signature of the co-signer
+    pub partial_signature: Signature,
+}
+
+/// Evaluates committed coefficients at x, i.e. sum_k C_k * x^k.
+fn evaluate_commitments(commitments: &[GE], x: u64) -> GE {
+    let q = FE::q();
+    let x = BigInt::from(x);
+    let mut power = BigInt::from(1);
+    let mut value = commitments[0].clone();
+    for commitment in &commitments[1..] {
+        power = BigInt::mod_mul(&power, &x, &q);
+        let scalar: FE = ECScalar::from(&power);
+        value = value.add_point(&commitment.scalar_mul(&scalar.get_element()).get_element());
+    }
+    value
+}
+
+fn hash_to_scalar(parts: Vec<&[u8]>) -> FE {
+    let hash = HashValue::from_iter_sha3(parts);
+    ECScalar::from(&BigInt::mod_mul(
+        &BigInt::from(hash.as_ref()),
+        &BigInt::from(1),
+        &FE::q(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMETERS: ThresholdParameters = ThresholdParameters {
+        threshold: 2,
+        participants: 3,
+    };
+
+    /// Runs key generation among all the participants and returns their shares.
+    fn generate_shares(parameters: ThresholdParameters) -> Vec<ThresholdShare> {
+        let states = (1..=parameters.participants)
+            .map(|index| KeyGenState::new(parameters, index).unwrap())
+            .collect::<Vec<_>>();
+        let participants = states
+            .iter()
+            .map(KeyGenState::commitment)
+            .collect::<Vec<_>>();
+        let sub_shares = states
+            .iter()
+            .flat_map(|state| state.sub_shares(&participants).unwrap())
+            .collect::<Vec<_>>();
+        states
+            .iter()
+            .map(|state| state.complete(&participants, &sub_shares).unwrap())
+            .collect()
+    }
+
+    /// Signs message with the shares of signers and checks the signature against the public key.
+    fn sign_and_verify(shares: &[ThresholdShare], signers: &[u64], message: HashValue) -> bool {
+        let mut sessions = signers
+            .iter()
+            .map(|index| {
+                SignerSession::new(&shares[*index as usize - 1], signers.to_vec(), message).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let commitments = sessions
+            .iter()
+            .map(SignerSession::commitment)
+            .collect::<Vec<_>>();
+        let reveals = sessions
+            .iter_mut()
+            .map(|session| session.reveal(commitments.clone()).unwrap())
+            .collect::<Vec<_>>();
+        let partial_signatures = sessions
+            .iter()
+            .map(|session| session.sign(&reveals).unwrap())
+            .collect();
+        verify(
+            &Signature::add_signature_parts(partial_signatures),
+            message.as_ref(),
+            shares[0].public_key(),
+        )
+        .is_ok()
+    }
+
+    #[test]
+    fn test_keygen_and_sign() {
+        let shares = generate_shares(PARAMETERS);
+        for share in &shares[1..] {
+            assert!(share.public_key() == shares[0].public_key());
+        }
+        for share in &shares {
+            assert!(GE::generator() * &share.share == share.participant_public_key(share.index));
+        }
+        let message = HashValue::random();
+        for signers in &[vec![1, 2], vec![1, 3], vec![2, 3], vec![1, 2, 3]] {
+            assert!(sign_and_verify(&shares, signers, message));
+        }
+    }
+
+    #[test]
+    fn test_lagrange_coefficient() {
+        // The weighted shares of any signer set sum to the key, f(0).
+        let state = KeyGenState::new(
+            ThresholdParameters {
+                threshold: 3,
+                participants: 5,
+            },
+            1,
+        )
+        .unwrap();
+        let q = FE::q();
+        for signers in &[vec![1, 2, 3], vec![2, 4, 5], vec![1, 3, 4, 5]] {
+            let sum = signers.iter().fold(BigInt::from(0), |sum, index| {
+                let coefficient = lagrange_coefficient(*index, signers).unwrap();
+                BigInt::mod_add(
+                    &sum,
+                    &BigInt::mod_mul(&coefficient.to_big_int(), &state.evaluate(*index), &q),
+                    &q,
+                )
+            });
+            assert_eq!(sum, state.evaluate(0));
+        }
+
+        // Among {1, 2}: l_1 = 2 / (2 - 1) = 2 and l_2 = 1 / (1 - 2) = -1.
+        let l_1 = lagrange_coefficient(1, &[1, 2]).unwrap();
+        let l_2 = lagrange_coefficient(2, &[1, 2]).unwrap();
+        assert_eq!(l_1.to_big_int(), BigInt::from(2));
+        assert_eq!(l_2.to_big_int(), BigInt::mod_sub(&q, &BigInt::from(1), &q));
+
+        assert!(lagrange_coefficient(3, &[1, 2]).is_err());
+    }
+
+    #[test]
+    fn test_pad() {
+        let sender = KeyGenState::new(PARAMETERS, 1).unwrap();
+        let recipient = KeyGenState::new(PARAMETERS, 2).unwrap();
+        let other = KeyGenState::new(PARAMETERS, 3).unwrap();
+        let sender_key = sender.commitment().encryption_key;
+        let recipient_key = recipient.commitment().encryption_key;
+
+        // Both ends of the Diffie-Hellman exchange derive the same pad.
+        let pad = sender.pad(&recipient_key, 1, 2);
+        assert!(recipient.pad(&sender_key, 1, 2) == pad);
+        // The pad depends on the direction and on both keys.
+        assert!(sender.pad(&recipient_key, 2, 1) != pad);
+        assert!(other.pad(&sender_key, 1, 2) != pad);
+        assert!(sender.pad(&other.commitment().encryption_key, 1, 2) != pad);
+    }
+
+    #[test]
+    fn test_check_participants() {
+        let states = (1..=PARAMETERS.participants)
+            .map(|index| KeyGenState::new(PARAMETERS, index).unwrap())
+            .collect::<Vec<_>>();
+        let participants = states
+            .iter()
+            .map(KeyGenState::commitment)
+            .collect::<Vec<_>>();
+        let state = &states[1];
+        state.check_participants(&participants).unwrap();
+
+        // Missing participant
+        assert!(state.check_participants(&participants[..2]).is_err());
+        // Participants out of order
+        let mut unsorted = participants.clone();
+        unsorted.swap(0, 2);
+        assert!(state.check_participants(&unsorted).is_err());
+        // Commitments to too few coefficients
+        let mut truncated = participants.clone();
+        truncated[0].commitments.pop();
+        assert!(state.check_participants(&truncated).is_err());
+        // Own commitment replaced, e.g. its encryption key
+        let mut altered = participants.clone();
+        altered[1].encryption_key = states[0].commitment().encryption_key;
+        assert!(state.check_participants(&altered).is_err());
+    }
+
+    #[test]
+    fn test_authenticate_participants() {
+        let states = (1..=PARAMETERS.participants)
+            .map(|index| KeyGenState::new(PARAMETERS, index).unwrap())
+            .collect::<Vec<_>>();
+        let participants = states
+            .iter()
+            .map(KeyGenState::commitment)
+            .collect::<Vec<_>>();
+        let identity_keys = vec![KeyPair::create(), KeyPair::create()];
+        let trusted_keys = identity_keys
+            .iter()
+            .map(|key_pair| key_pair.public_key.clone())
+            .collect::<Vec<_>>();
+        let identities = participants[1..]
+            .iter()
+            .zip(&identity_keys)
+            .map(|(participant, identity)| participant.sign_identity(PARAMETERS, identity))
+            .collect::<Vec<_>>();
+        authenticate_participants(PARAMETERS, &participants, &identities, &trusted_keys).unwrap();
+
+        // Encryption key of a co-signer swapped by the client
+        let mut swapped = participants.clone();
+        swapped[2].encryption_key = participants[0].encryption_key.clone();
+        assert!(
+            authenticate_participants(PARAMETERS, &swapped, &identities, &trusted_keys).is_err()
+        );
+        // Commitment signed for other parameters
+        let other_parameters = ThresholdParameters {
+            threshold: 3,
+            participants: 3,
+        };
+        let mut resigned = identities.clone();
+        resigned[1] = participants[2].sign_identity(other_parameters, &identity_keys[1]);
+        assert!(
+            authenticate_participants(PARAMETERS, &participants, &resigned, &trusted_keys).is_err()
+        );
+        // Identity key that is not pinned
+        assert!(authenticate_participants(
+            PARAMETERS,
+            &participants,
+            &identities,
+            &trusted_keys[..1]
+        )
+        .is_err());
+        let mut impostor = identities.clone();
+        impostor[1] = participants[2].sign_identity(PARAMETERS, &KeyPair::create());
+        assert!(
+            authenticate_participants(PARAMETERS, &participants, &impostor, &trusted_keys).is_err()
+        );
+        // Both co-signers vouched for by the same identity key
+        let mut shared = identities.clone();
+        shared[1] = participants[2].sign_identity(PARAMETERS, &identity_keys[0]);
+        assert!(
+            authenticate_participants(PARAMETERS, &participants, &shared, &trusted_keys).is_err()
+        );
+        // Missing or unsorted identities
+        assert!(authenticate_participants(
+            PARAMETERS,
+            &participants,
+            &identities[..1],
+            &trusted_keys
+        )
+        .is_err());
+        let mut unsorted = identities.clone();
+        unsorted.swap(0, 1);
+        assert!(
+            authenticate_participants(PARAMETERS, &participants, &unsorted, &trusted_keys).is_err()
+        );
+
+        let encoded = encode_identity_key(&trusted_keys[0]).unwrap();
+        assert!(decode_identity_key(&encoded).unwrap() == trusted_keys[0]);
+        assert!(decode_identity_key("00").is_err());
+    }
+
+    #[test]
+    fn test_check_signers() {
+        PARAMETERS.check_signers(&[1, 2]).unwrap();
+        PARAMETERS.check_signers(&[2, 3]).unwrap();
+        PARAMETERS.check_signers(&[1, 2, 3]).unwrap();
+
+        // Too few signers
+        assert!(PARAMETERS.check_signers(&[2]).is_err());
+        assert!(PARAMETERS.check_signers(&[]).is_err());
+        // Not participants
+        assert!(PARAMETERS.check_signers(&[0, 1]).is_err());
+        assert!(PARAMETERS.check_signers(&[2, 4]).is_err());
+        // Repeated or unsorted
+        assert!(PARAMETERS.check_signers(&[2, 2]).is_err());
+        assert!(PARAMETERS.check_signers(&[3, 1]).is_err());
+
+        assert!(ThresholdParameters {
+            threshold: 1,
+            participants: 1,
+        }
+        .validate()
+        .is_err());
+        assert!(ThresholdParameters {
+            threshold: 0,
+            participants: 3,
+        }
+        .validate()
+        .is_err());
+        assert!(ThresholdParameters {
+            threshold: 4,
+            participants: 3,
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,382 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    audit::ClientIdentity, config::ServerConfig, error::CoSignerError, service::CoSignerService,
+};
+use co_signer_protocol::{
+    threshold::{
+        ThresholdKeyGenCommitRequest, ThresholdKeyGenCommitResponse,
+        ThresholdKeyGenCompleteRequest, ThresholdKeyGenCompleteResponse,
+        ThresholdKeyGenShareRequest, ThresholdKeyGenShareResponse, ThresholdSignCommitRequest,
+        ThresholdSignCommitResponse, ThresholdSignRequest, ThresholdSignResponse,
+        ThresholdSignRevealRequest, ThresholdSignRevealResponse,
+    },
+    KeyGenRequest, KeyGenResponse, RefreshConfirmRequest, RefreshConfirmResponse, RefreshRequest,
+    RefreshResponse, RotationConfirmRequest, RotationConfirmResponse, SignFirstRequest,
+    SignFirstResponse, SignSecondRequest, SignSecondResponse,
+};
+use crypto::HashValue;
+use failure::prelude::*;
+use logger::prelude::*;
+use rocket::{
+    config::Environment,
+    http::Status,
+    post,
+    request::{self, FromRequest, Request},
+    routes, Outcome, Rocket, State,
+};
+use rocket_contrib::json::Json;
+use std::{net::IpAddr, path::Path};
+
+type ApiResult<T> = std::result::Result<Json<T>, CoSignerError>;
+
+/// Header in which a trusted proxy forwards the fingerprint of the client certificate it checked.
+const CLIENT_CERT_FINGERPRINT_HEADER: &str = "X-Client-Cert-Fingerprint";
+
+/// Reverse proxies whose X-Real-IP and X-Client-Cert-Fingerprint headers are trusted, see
+/// ServerConfig::trusted_proxies.
+struct TrustedProxies(Vec<IpAddr>);
+
+/// Hashes of the credentials clients authenticate with, see ServerConfig::bearer_tokens and
+/// ServerConfig::client_certificates.
+struct Credentials(Vec<HashValue>);
+
+impl Credentials {
+    fn new(config: &ServerConfig) -> Self {
+        let tokens = config.bearer_tokens.iter().map(String::as_str);
+        let certificates = config.client_certificates.iter().map(String::as_str);
+        Credentials(
+            tokens
+                .map(bearer_credentials)
+                .chain(certificates.map(certificate_credentials))
+                .collect(),
+        )
+    }
+
+    /// Hash of the credentials of a request carrying the given Authorization header, and the
+    /// given client certificate fingerprint forwarded by a trusted proxy, if either is known.
+    fn authenticate(
+        &self,
+        authorization: Option<&str>,
+        fingerprint: Option<&str>,
+    ) -> Option<HashValue> {
+        let token =
+            authorization.map(|authorization| HashValue::from_sha3(authorization.as_bytes()));
+        let certificate = fingerprint.map(certificate_credentials);
+        token
+            .into_iter()
+            .chain(certificate)
+            .find(|credentials| self.0.contains(credentials))
+    }
+}
+
+fn bearer_credentials(token: &str) -> HashValue {
+    HashValue::from_sha3(format!("Bearer {}", token).as_bytes())
+}
+
+/// Fingerprints are compared regardless of case and of the colons separating their bytes.
+fn certificate_credentials(fingerprint: &str) -> HashValue {
+    let fingerprint: String = fingerprint
+        .chars()
+        .filter(|c| *c != ':')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    HashValue::from_sha3(format!("Certificate {}", fingerprint).as_bytes())
+}
+
+/// Authenticates the client by a configured bearer token or client certificate, refusing the
+/// request otherwise, and identifies it by the address it connects from, or the one forwarded
+/// by a trusted proxy in front of the co-signer.
+impl<'a, 'r> FromRequest<'a, 'r> for ClientIdentity {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let trusted_proxies = request.guard::<State<TrustedProxies>>().succeeded();
+        let trusted_proxies = trusted_proxies
+            .as_ref()
+            .map_or(&[][..], |proxies| &proxies.0[..]);
+        let remote = request.remote().map(|remote| remote.ip());
+        let address = client_address(remote, request.real_ip(), trusted_proxies);
+        let from_proxy = remote.map_or(false, |remote| trusted_proxies.contains(&remote));
+        let fingerprint = if from_proxy {
+            request.headers().get_one(CLIENT_CERT_FINGERPRINT_HEADER)
+        } else {
+            None
+        };
+        let authorization = request.headers().get_one("Authorization");
+        let credentials = request
+            .guard::<State<Credentials>>()
+            .succeeded()
+            .and_then(|credentials| credentials.authenticate(authorization, fingerprint));
+        match credentials {
+            Some(credentials) => Outcome::Success(ClientIdentity {
+                address: address.map(|address| address.to_string()),
+                credentials: Some(credentials),
+            }),
+            None => {
+                warn!("Refused unauthenticated request from {:?}", address);
+                Outcome::Failure((Status::Unauthorized, ()))
+            }
+        }
+    }
+}
+
+/// Address of the client of a request coming from remote and carrying real_ip in its X-Real-IP
+/// header. The header is ignored unless remote is one of trusted_proxies: anyone else could name
+/// any client.
+fn client_address(
+    remote: Option<IpAddr>,
+    real_ip: Option<IpAddr>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    match remote {
+        Some(proxy) if trusted_proxies.contains(&proxy) => real_ip.or(remote),
+        _ => remote,
+    }
+}
+
+#[post("/eddsa/keygen", format = "json", data = "<request>")]
+fn keygen(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    request: Json<KeyGenRequest>,
+) -> ApiResult<KeyGenResponse> {
+    service.keygen(&client, request.into_inner()).map(Json)
+}
+
+#[post("/eddsa/sign/<id>/first", format = "json", data = "<request>")]
+fn sign_first(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<SignFirstRequest>,
+) -> ApiResult<SignFirstResponse> {
+    service
+        .sign_first(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+#[post("/eddsa/sign/<id>/second", format = "json", data = "<request>")]
+fn sign_second(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<SignSecondRequest>,
+) -> ApiResult<SignSecondResponse> {
+    service
+        .sign_second(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+#[post("/eddsa/refresh/<id>", format = "json", data = "<request>")]
+fn refresh(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<RefreshRequest>,
+) -> ApiResult<RefreshResponse> {
+    service
+        .refresh(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+#[post("/eddsa/refresh/<id>/confirm", format = "json", data = "<request>")]
+fn confirm_refresh(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<RefreshConfirmRequest>,
+) -> ApiResult<RefreshConfirmResponse> {
+    service
+        .confirm_refresh(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+#[post("/eddsa/rotate/<id>/confirm", format = "json", data = "<request>")]
+fn confirm_rotation(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<RotationConfirmRequest>,
+) -> ApiResult<RotationConfirmResponse> {
+    service
+        .confirm_rotation(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+#[post("/eddsa/threshold/keygen/commit", format = "json", data = "<request>")]
+fn threshold_keygen_commit(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    request: Json<ThresholdKeyGenCommitRequest>,
+) -> ApiResult<ThresholdKeyGenCommitResponse> {
+    service
+        .threshold_keygen_commit(&client, request.into_inner())
+        .map(Json)
+}
+
+#[post("/eddsa/threshold/keygen/share", format = "json", data = "<request>")]
+fn threshold_keygen_share(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    request: Json<ThresholdKeyGenShareRequest>,
+) -> ApiResult<ThresholdKeyGenShareResponse> {
+    service
+        .threshold_keygen_share(&client, request.into_inner())
+        .map(Json)
+}
+
+#[post(
+    "/eddsa/threshold/keygen/complete",
+    format = "json",
+    data = "<request>"
+)]
+fn threshold_keygen_complete(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    request: Json<ThresholdKeyGenCompleteRequest>,
+) -> ApiResult<ThresholdKeyGenCompleteResponse> {
+    service
+        .threshold_keygen_complete(&client, request.into_inner())
+        .map(Json)
+}
+
+#[post(
+    "/eddsa/threshold/sign/<id>/commit",
+    format = "json",
+    data = "<request>"
+)]
+fn threshold_sign_commit(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<ThresholdSignCommitRequest>,
+) -> ApiResult<ThresholdSignCommitResponse> {
+    service
+        .threshold_sign_commit(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+#[post(
+    "/eddsa/threshold/sign/<id>/reveal",
+    format = "json",
+    data = "<request>"
+)]
+fn threshold_sign_reveal(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<ThresholdSignRevealRequest>,
+) -> ApiResult<ThresholdSignRevealResponse> {
+    service
+        .threshold_sign_reveal(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+#[post("/eddsa/threshold/sign/<id>", format = "json", data = "<request>")]
+fn threshold_sign(
+    service: State<CoSignerService>,
+    client: ClientIdentity,
+    id: String,
+    request: Json<ThresholdSignRequest>,
+) -> ApiResult<ThresholdSignResponse> {
+    service
+        .threshold_sign(&client, &id, request.into_inner())
+        .map(Json)
+}
+
+/// Builds the co-signer described by config, ready to be launched.
+pub fn build_server(config: &ServerConfig) -> Result<Rocket> {
+    let mut rocket_config = rocket::Config::build(Environment::active()?)
+        .address(config.address.clone())
+        .port(config.port);
+    if let Some(tls) = &config.tls {
+        let path = |path: &Path| {
+            path.to_str()
+                .map(str::to_string)
+                .ok_or_else(|| format_err!("Invalid TLS file path {:?}", path))
+        };
+        rocket_config = rocket_config.tls(path(&tls.certs)?, path(&tls.key)?);
+    }
+    let rocket_config = rocket_config.finalize()?;
+    let service = CoSignerService::new(config)?;
+    if config.bearer_tokens.is_empty() && config.client_certificates.is_empty() {
+        warn!("No bearer token nor client certificate is configured: every request is refused");
+    }
+    Ok(rocket::custom(rocket_config)
+        .manage(service)
+        .manage(TrustedProxies(config.trusted_proxies.clone()))
+        .manage(Credentials::new(config))
+        .mount(
+            "/",
+            routes![
+                keygen,
+                sign_first,
+                sign_second,
+                refresh,
+                confirm_refresh,
+                confirm_rotation,
+                threshold_keygen_commit,
+                threshold_keygen_share,
+                threshold_keygen_complete,
+                threshold_sign_commit,
+                threshold_sign_reveal,
+                threshold_sign
+            ],
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_address() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let client: IpAddr = "192.0.2.7".parse().unwrap();
+        let forged: IpAddr = "192.0.2.8".parse().unwrap();
+        assert_eq!(
+            client_address(Some(proxy), Some(client), &[proxy]),
+            Some(client)
+        );
+        assert_eq!(client_address(Some(proxy), None, &[proxy]), Some(proxy));
+        // Clients connecting directly cannot pick the address they are recorded under.
+        assert_eq!(
+            client_address(Some(client), Some(forged), &[proxy]),
+            Some(client)
+        );
+        assert_eq!(
+            client_address(Some(client), Some(forged), &[]),
+            Some(client)
+        );
+        assert_eq!(client_address(None, Some(forged), &[proxy]), None);
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let credentials = Credentials::new(&ServerConfig {
+            bearer_tokens: vec!["secret".to_string()],
+            client_certificates: vec!["AB:CD:EF".to_string()],
+            ..ServerConfig::default()
+        });
+        let token = credentials.authenticate(Some("Bearer secret"), None);
+        assert_eq!(token, Some(bearer_credentials("secret")));
+        assert_eq!(credentials.authenticate(Some("Bearer other"), None), None);
+        assert_eq!(credentials.authenticate(Some("secret"), None), None);
+        assert_eq!(credentials.authenticate(None, None), None);
+
+        let certificate = credentials.authenticate(None, Some("abcdef"));
+        assert!(certificate.is_some());
+        assert_ne!(certificate, token);
+        assert_eq!(
+            credentials.authenticate(Some("Bearer other"), Some("ab:cd:ef")),
+            certificate
+        );
+        assert_eq!(credentials.authenticate(None, Some("abcd")), None);
+
+        // Nothing is accepted without credentials configured.
+        let credentials = Credentials::new(&ServerConfig::default());
+        assert_eq!(credentials.authenticate(Some("Bearer "), Some("")), None);
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Append-only log of the decisions of the co-signer.
+//!
+//! Every key generation, refresh and signing session is appended to the log as one JSON entry
+//! per line. Each entry carries the hash of the previous one, so that removing, reordering or
+//! editing an entry breaks the chain from that entry on. Dropping entries at the end of the log
+//! cannot be detected from the log alone: the hash of the last entry, as printed by the
+//! verification tool, should be kept elsewhere from time to time.
+
+use crate::transaction::TransactionSummary;
+use crypto::{
+    hash::{CoSignerAuditHasher, CryptoHash, CryptoHasher},
+    HashValue,
+};
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Who sent a request to the co-signer.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ClientIdentity {
+    /// Address the request came from
+    pub address: Option<String>,
+    /// Hash of the credentials the client authenticated with, i.e. of its bearer token or client
+    /// certificate fingerprint. The credentials themselves are never logged.
+    pub credentials: Option<HashValue>,
+}
+
+/// Operation recorded in the log.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// Generation of a two-party key
+    KeyGen,
+    /// Two-party signing session
+    Sign,
+    /// Refresh of the server share of a two-party key
+    Refresh,
+    /// Confirmation that the client persisted its refreshed share of a two-party key
+    RefreshConfirmation,
+    /// Opening of a threshold key generation session, with the commitment of the server
+    ThresholdKeyGenCommit,
+    /// Sub-shares of the server handed to the other participants of a threshold key generation
+    ThresholdKeyGenShare,
+    /// Generation of a threshold key
+    ThresholdKeyGen,
+    /// Reveal of the ephemeral key of the server in a threshold signing session
+    ThresholdSignReveal,
+    /// Threshold signing session
+    ThresholdSign,
+    /// Binding of a two-party key to the account whose authentication key was rotated to it
+    Rotation,
+}
+
+impl AuditAction {
+    /// Whether the action produces a signature
+    pub fn is_signing(self) -> bool {
+        match self {
+            AuditAction::Sign | AuditAction::ThresholdSign => true,
+            _ => false,
+        }
+    }
+}
+
+/// What the co-signer decided.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", content = "reason", rename_all = "snake_case")]
+pub enum AuditDecision {
+    /// The request was served, e.g. the partial signature was returned
+    Approved,
+    /// The request was refused, by the spending policy or for another reason
+    Rejected(String),
+}
+
+/// An operation of the co-signer, as recorded in the log.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuditEvent {
+    /// Operation requested
+    pub action: AuditAction,
+    /// Key the operation applies to, unless it failed before a key was known
+    pub key_id: Option<String>,
+    /// Message signed, for signing sessions
+    pub message_hash: Option<HashValue>,
+    /// Decoded transaction signed, for transaction signing sessions
+    pub transaction: Option<TransactionSummary>,
+    /// Who requested the operation
+    pub client: ClientIdentity,
+    /// Outcome of the request
+    pub decision: AuditDecision,
+}
+
+impl AuditEvent {
+    /// An approved action requested by client on key_id.
+    pub fn new(action: AuditAction, client: &ClientIdentity, key_id: Option<&str>) -> Self {
+        Self {
+            action,
+            key_id: key_id.map(str::to_string),
+            message_hash: None,
+            transaction: None,
+            client: client.clone(),
+            decision: AuditDecision::Approved,
+        }
+    }
+
+    /// Sets the transaction signed, and the message hash along with it.
+    pub fn with_transaction(mut self, txn: &TransactionSummary) -> Self {
+        self.message_hash = Some(txn.hash);
+        self.transaction = Some(txn.clone());
+        self
+    }
+
+    /// Sets the prehashed message signed.
+    pub fn with_message(mut self, message_hash: HashValue) -> Self {
+        self.message_hash = Some(message_hash);
+        self
+    }
+
+    /// Sets the decision of the co-signer.
+    pub fn with_decision(mut self, decision: AuditDecision) -> Self {
+        self.decision = decision;
+        self
+    }
+}
+
+/// The hashed part of a log entry.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuditRecord {
+    /// Position of the entry in the log, starting from 0
+    pub sequence_number: u64,
+    /// Time of the decision, in seconds since the unix epoch
+    pub timestamp: u64,
+    /// Hash of the previous entry, zero for the first one
+    pub previous_hash: HashValue,
+    /// What happened
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+impl CryptoHash for AuditRecord {
+    type Hasher = CoSignerAuditHasher;
+
+    fn hash(&self) -> HashValue {
+        let mut state = Self::Hasher::default();
+        state.write(&serde_json::to_vec(self).expect("Audit records can always be serialized"));
+        state.finish()
+    }
+}
+
+/// A line of the log.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AuditEntry {
+    /// The hashed record
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    /// Hash of the record, which the next entry refers to
+    pub hash: HashValue,
+}
+
+/// The audit log of a co-signer, opened for appending.
+pub struct AuditLog {
+    path: PathBuf,
+    /// Sequence number and hash of the last entry, if any
+    head: Mutex<Option<(u64, HashValue)>>,
+}
+
+impl AuditLog {
+    /// Opens the log at path, creating it if needed. The existing entries are verified first:
+    /// the co-signer does not extend a chain that was tampered with.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let head = if path.exists() {
+            let entries = read_entries(&path)?;
+            verify_chain(&entries)
+                .with_context(|_| format!("Audit log {:?} is corrupted", path))?;
+            entries
+                .last()
+                .map(|entry| (entry.record.sequence_number, entry.hash))
+        } else {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            None
+        };
+        Ok(Self {
+            path,
+            head: Mutex::new(head),
+        })
+    }
+
+    /// Appends event to the log, as decided at time now. The entry is synced to disk before
+    /// returning, so that the co-signer never hands out a signature it did not record.
+    pub fn append(&self, event: AuditEvent, now: Duration) -> Result<AuditEntry> {
+        let mut head = self.head.lock().unwrap();
+        let (sequence_number, previous_hash) = match *head {
+            Some((sequence_number, hash)) => (sequence_number + 1, hash),
+            None => (0, HashValue::zero()),
+        };
+        let record = AuditRecord {
+            sequence_number,
+            timestamp: now.as_secs(),
+            previous_hash,
+            event,
+        };
+        let entry = AuditEntry {
+            hash: record.hash(),
+            record,
+        };
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|_| format!("Cannot open audit log {:?}", self.path))?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        *head = Some((sequence_number, entry.hash));
+        Ok(entry)
+    }
+}
+
+/// Reads all the entries of the log at path, without verifying them.
+pub fn read_entries<P: AsRef<Path>>(path: P) -> Result<Vec<AuditEntry>> {
+    let path = path.as_ref();
+    let contents =
+        fs::read_to_string(path).with_context(|_| format!("Cannot read audit log {:?}", path))?;
+    let mut entries = vec![];
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(serde_json::from_str(line).with_context(|_| {
+            format!("Malformed entry at line {} of {:?}", line_number + 1, path)
+        })?);
+    }
+    Ok(entries)
+}
+
+/// Checks that every entry is numbered after, and refers to the hash of, the previous one, and
+/// that its hash matches its record.
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<()> {
+    let mut previous_hash = HashValue::zero();
+    for (position, entry) in entries.iter().enumerate() {
+        let record = &entry.record;
+        ensure!(
+            record.sequence_number == position as u64,
+            "Entry {} has sequence number {}",
+            position,
+            record.sequence_number
+        );
+        ensure!(
+            record.previous_hash == previous_hash,
+            "Entry {} does not follow the previous entry",
+            position
+        );
+        ensure!(
+            record.hash() == entry.hash,
+            "Entry {} does not match its hash",
+            position
+        );
+        previous_hash = entry.hash;
+    }
+    Ok(())
+}
+
+/// Returns the entries of the signatures key_id contributed to between from and to, both
+/// inclusive and in seconds since the unix epoch.
+pub fn signed_by<'a>(
+    entries: &'a [AuditEntry],
+    key_id: &'a str,
+    from: u64,
+    to: u64,
+) -> impl Iterator<Item = &'a AuditEntry> {
+    entries.iter().filter(move |entry| {
+        let record = &entry.record;
+        record.event.action.is_signing()
+            && record.event.decision == AuditDecision::Approved
+            && record.event.key_id.as_ref().map(String::as_str) == Some(key_id)
+            && record.timestamp >= from
+            && record.timestamp <= to
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::ProgramKind;
+    use types::account_address::AccountAddress;
+
+    fn signed(key_id: &str) -> AuditEvent {
+        let txn = TransactionSummary {
+            hash: HashValue::random(),
+            sender: AccountAddress::random(),
+            sequence_number: 0,
+            max_gas_amount: 10_000,
+            gas_unit_price: 1,
+            expiration_time: Duration::from_secs(100),
+            program: ProgramKind::PeerToPeerTransfer {
+                payee: AccountAddress::random(),
+                amount: 10,
+            },
+        };
+        AuditEvent::new(AuditAction::Sign, &ClientIdentity::default(), Some(key_id))
+            .with_transaction(&txn)
+    }
+
+    #[test]
+    fn test_audit_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.log");
+        let log = AuditLog::open(&path).unwrap();
+        log.append(signed("a"), Duration::from_secs(10)).unwrap();
+        log.append(signed("b"), Duration::from_secs(20)).unwrap();
+        let rejected = signed("a").with_decision(AuditDecision::Rejected("policy".to_string()));
+        log.append(rejected, Duration::from_secs(30)).unwrap();
+
+        // Reopening the log extends the same chain.
+        let log = AuditLog::open(&path).unwrap();
+        log.append(signed("a"), Duration::from_secs(40)).unwrap();
+        let entries = read_entries(&path).unwrap();
+        verify_chain(&entries).unwrap();
+        let timestamps = signed_by(&entries, "a", 0, 35)
+            .map(|entry| entry.record.timestamp)
+            .collect::<Vec<_>>();
+        assert_eq!(timestamps, vec![10]);
+        assert_eq!(signed_by(&entries, "a", 0, 40).count(), 2);
+
+        let mut tampered = entries.clone();
+        tampered[1].record.timestamp = 15;
+        assert!(verify_chain(&tampered).is_err());
+        let mut removed = entries.clone();
+        removed.remove(1);
+        assert!(verify_chain(&removed).is_err());
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(&path, contents.replacen("\"b\"", "\"c\"", 1)).unwrap();
+        assert!(AuditLog::open(&path).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use server::audit::{read_entries, signed_by, verify_chain};
+use std::{path::PathBuf, process};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "audit_verify",
+    about = "Verifies the audit log of a co-signer and lists what a key signed"
+)]
+struct Args {
+    /// Path to the audit log
+    #[structopt(parse(from_os_str))]
+    log: PathBuf,
+    /// List the signatures this key contributed to, one JSON entry per line
+    #[structopt(short = "k", long = "key")]
+    key_id: Option<String>,
+    /// Start of the listed period, in seconds since the unix epoch
+    #[structopt(long = "from", default_value = "0")]
+    from: u64,
+    /// End of the listed period, in seconds since the unix epoch. Defaults to no end.
+    #[structopt(long = "to")]
+    to: Option<u64>,
+}
+
+fn main() {
+    let args = Args::from_args();
+    let entries = match read_entries(&args.log) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Unable to read audit log: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = verify_chain(&entries) {
+        eprintln!("Audit log {:?} was tampered with: {}", args.log, e);
+        process::exit(1);
+    }
+    match entries.last() {
+        Some(last) => eprintln!(
+            "Audit log is consistent: {} entries, last entry hash {}",
+            entries.len(),
+            last.hash
+        ),
+        None => eprintln!("Audit log is empty"),
+    }
+
+    if let Some(key_id) = &args.key_id {
+        let to = args.to.unwrap_or(u64::max_value());
+        for entry in signed_by(&entries, key_id, args.from, to) {
+            println!(
+                "{}",
+                serde_json::to_string(entry).expect("Audit entries can always be serialized")
+            );
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+/// Configuration of the co-signer, loaded from a TOML file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ServerConfig {
+    /// Address to listen on
+    pub address: String,
+    /// Port to listen on
+    pub port: u16,
+    /// Directory holding the server key shares and the spending history
+    pub data_dir: PathBuf,
+    /// TOML file describing the spending policies. Without it, every key follows the default
+    /// policy: no amount or payee limits, but programs other than the standard transaction
+    /// scripts are refused, and so are rotations to keys the co-signer does not hold.
+    #[serde(default)]
+    pub policy_file: Option<PathBuf>,
+    /// File the audit log is appended to. Defaults to audit.log in data_dir.
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+    /// Identity keys of the other co-signers threshold keys are generated with, hex encoded.
+    /// Their key generation commitments are refused unless signed by one of these keys.
+    #[serde(default)]
+    pub trusted_co_signers: Vec<String>,
+    /// Addresses of the reverse proxies in front of the co-signer. The X-Real-IP header names
+    /// the client only in requests coming from one of them, any other request is recorded under
+    /// the address it comes from.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Bearer tokens clients authenticate with, sent as `Authorization: Bearer <token>`.
+    /// Requests presenting none of them, nor one of client_certificates, are refused.
+    #[serde(default)]
+    pub bearer_tokens: Vec<String>,
+    /// Hex encoded fingerprints of the certificates clients authenticate with over mutual TLS.
+    /// The co-signer does not check client certificates itself: one of trusted_proxies checks
+    /// them and forwards the fingerprint of the certificate in the X-Client-Cert-Fingerprint
+    /// header, which is ignored in requests coming from anyone else.
+    #[serde(default)]
+    pub client_certificates: Vec<String>,
+    /// Certificate and private key to serve over TLS with. Without them, the co-signer serves
+    /// plain HTTP, which is only fit behind a proxy terminating TLS.
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+}
+
+/// Files the co-signer serves over TLS with.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain of the co-signer
+    pub certs: PathBuf,
+    /// PEM file with the private key of the co-signer certificate
+    pub key: PathBuf,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: "localhost".to_string(),
+            port: 8000,
+            data_dir: PathBuf::from("co_signer_data"),
+            policy_file: None,
+            audit_log: None,
+            trusted_co_signers: vec![],
+            trusted_proxies: vec![],
+            bearer_tokens: vec![],
+            client_certificates: vec![],
+            tls: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads the config file. Relative paths used in the config are relative to the config
+    /// location.
+    pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let config_string = fs::read_to_string(path)
+            .with_context(|_| format!("Cannot read ServerConfig file {:?}", path))?;
+        let mut config = Self::parse(&config_string)
+            .with_context(|_| format!("Cannot parse ServerConfig file {:?}", path))?;
+
+        config.data_dir = path.with_file_name(&config.data_dir);
+        config.policy_file = config
+            .policy_file
+            .map(|policy_file| path.with_file_name(policy_file));
+        config.audit_log = config
+            .audit_log
+            .map(|audit_log| path.with_file_name(audit_log));
+        if let Some(tls) = &mut config.tls {
+            tls.certs = path.with_file_name(&tls.certs);
+            tls.key = path.with_file_name(&tls.key);
+        }
+        Ok(config)
+    }
+
+    /// Returns the path of the audit log.
+    pub fn audit_log_path(&self) -> PathBuf {
+        self.audit_log
+            .clone()
+            .unwrap_or_else(|| self.data_dir.join("audit.log"))
+    }
+
+    /// Parses the config file into a ServerConfig object
+    pub fn parse(config_string: &str) -> Result<Self> {
+        Ok(toml::from_str(config_string)?)
+    }
+}
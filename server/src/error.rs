@@ -0,0 +1,64 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use co_signer_protocol::ErrorResponse;
+use failure::prelude::*;
+use rocket::{
+    http::Status,
+    request::Request,
+    response::{self, Responder},
+};
+use rocket_contrib::json::Json;
+
+/// Errors returned by the co-signer to the wallet. Every variant carries a human readable reason
+/// that is sent back in an ErrorResponse.
+#[derive(Clone, Debug, Fail, PartialEq)]
+pub enum CoSignerError {
+    /// The transaction is rejected by the spending policy of the key
+    #[fail(display = "Rejected by spending policy: {}", _0)]
+    PolicyViolation(String),
+    /// No share is stored under the requested key id
+    #[fail(display = "Unknown key: {}", _0)]
+    UnknownKey(String),
+    /// The signing session does not exist, expired or was already used
+    #[fail(display = "Unknown signing session: {}", _0)]
+    UnknownSession(String),
+    /// The key belongs to another client
+    #[fail(display = "Unauthorized: {}", _0)]
+    Unauthorized(String),
+    /// The request is malformed or breaks the protocol
+    #[fail(display = "Invalid request: {}", _0)]
+    InvalidRequest(String),
+    /// The co-signer failed to process a valid request
+    #[fail(display = "Internal error: {}", _0)]
+    Internal(String),
+}
+
+impl CoSignerError {
+    fn status(&self) -> Status {
+        match self {
+            CoSignerError::PolicyViolation(_) | CoSignerError::Unauthorized(_) => Status::Forbidden,
+            CoSignerError::UnknownKey(_) | CoSignerError::UnknownSession(_) => Status::NotFound,
+            CoSignerError::InvalidRequest(_) => Status::BadRequest,
+            CoSignerError::Internal(_) => Status::InternalServerError,
+        }
+    }
+}
+
+impl From<Error> for CoSignerError {
+    fn from(error: Error) -> Self {
+        CoSignerError::Internal(error.to_string())
+    }
+}
+
+impl<'r> Responder<'r> for CoSignerError {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let status = self.status();
+        let body = Json(ErrorResponse {
+            reason: self.to_string(),
+        });
+        response::Response::build_from(body.respond_to(request)?)
+            .status(status)
+            .ok()
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![feature(proc_macro_hygiene, decl_macro)]
+#![deny(missing_docs)]
+//! Co-signer holding the server share of two-party wallet keys.
+//!
+//! Before contributing its partial signature, the co-signer decodes the transaction it is asked
+//! to sign and checks it against the spending policy configured for the key, so that a stolen
+//! client share alone cannot drain an account. Every decision is recorded in a hash-chained audit
+//! log, which the `audit_verify` tool checks and queries.
+
+/// REST endpoints of the co-signer
+pub mod api;
+/// Tamper-evident log of the co-signer decisions
+pub mod audit;
+/// Configuration of the co-signer
+pub mod config;
+/// Errors returned to the wallet
+pub mod error;
+/// Spending policies and the engine enforcing them
+pub mod policy;
+/// Two-party key generation and signing
+pub mod service;
+/// Persistence of the server shares
+pub mod storage;
+/// Decoding of the transactions submitted for co-signing
+pub mod transaction;
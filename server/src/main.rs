@@ -1,5 +1,28 @@
-extern crate two_party_eddsa_server;
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use server::{api, config::ServerConfig};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "server_exec", about = "Libra wallet co-signer")]
+struct Args {
+    /// Path to the co-signer config file. Defaults are used if omitted.
+    #[structopt(short = "f", long = "config", parse(from_os_str))]
+    config: Option<PathBuf>,
+}
 
 fn main() {
-    two_party_eddsa_server::api::get_server().launch();
-}
\ No newline at end of file
+    let args = Args::from_args();
+    let _logger = logger::set_default_global_logger(false /* async */, None);
+
+    let config = match &args.config {
+        Some(path) => ServerConfig::load_config(path).expect("Unable to load co-signer config"),
+        None => ServerConfig::default(),
+    };
+    let error = api::build_server(&config)
+        .expect("Unable to start co-signer")
+        .launch();
+    panic!("Co-signer stopped: {}", error);
+}
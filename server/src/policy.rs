@@ -0,0 +1,424 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    error::CoSignerError,
+    storage::write_file,
+    transaction::{parse_address, ProgramKind, TransactionSummary},
+};
+use crypto::HashValue;
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use types::account_address::AccountAddress;
+
+/// Length of the window the daily limit applies to.
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Rules a transaction has to follow to be co-signed. Every rule left out of the policy file is
+/// not enforced, except that unknown programs and rotations to keys the co-signer does not hold
+/// are refused unless allowed. Amounts include the maximal gas cost of the transaction, which is
+/// charged to the sender whatever the program.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct SpendingPolicy {
+    /// Maximal amount of microlibras a single transaction can move, gas included
+    pub max_amount_per_transaction: Option<u64>,
+    /// Maximal amount of microlibras moved over any rolling 24 hours window, gas included
+    pub max_amount_per_day: Option<u64>,
+    /// If set, the only accounts coins can be sent to
+    pub allowed_payees: Option<Vec<String>>,
+    /// Accounts coins can never be sent to
+    pub denied_payees: Vec<String>,
+    /// Maximal gas unit price
+    pub max_gas_unit_price: Option<u64>,
+    /// Maximal number of seconds between now and the transaction expiration time
+    pub max_expiration_secs: Option<u64>,
+    /// Whether programs other than the standard transaction scripts (and write sets) are signed.
+    /// The amounts and payees of such programs are unknown, so they are denied unless explicitly
+    /// allowed.
+    pub allow_unknown_programs: bool,
+    /// Whether the authentication key can be rotated to another key than the one this co-signer
+    /// generated to replace it. Such a rotation hands the account over for good, past every other
+    /// rule, so it is denied unless explicitly allowed.
+    pub allow_key_rotation: bool,
+}
+
+impl Default for SpendingPolicy {
+    fn default() -> Self {
+        Self {
+            max_amount_per_transaction: None,
+            max_amount_per_day: None,
+            allowed_payees: None,
+            denied_payees: vec![],
+            max_gas_unit_price: None,
+            max_expiration_secs: None,
+            allow_unknown_programs: false,
+            allow_key_rotation: false,
+        }
+    }
+}
+
+impl SpendingPolicy {
+    fn validate(&self) -> Result<()> {
+        let payees = self.allowed_payees.iter().flatten();
+        for address in payees.chain(self.denied_payees.iter()) {
+            parse_address(address)?;
+        }
+        Ok(())
+    }
+
+    fn lists(list: &[String], address: &AccountAddress) -> bool {
+        list.iter()
+            .filter_map(|entry| parse_address(entry).ok())
+            .any(|entry| entry == *address)
+    }
+
+    /// Checks a single transaction against the policy. spent_today is the amount already moved
+    /// in the last 24 hours and now the current time since the unix epoch.
+    pub fn check(
+        &self,
+        txn: &TransactionSummary,
+        spent_today: u64,
+        now: Duration,
+    ) -> std::result::Result<(), CoSignerError> {
+        let violation = |reason: String| Err(CoSignerError::PolicyViolation(reason));
+
+        match txn.program {
+            ProgramKind::Unknown | ProgramKind::WriteSet if !self.allow_unknown_programs => {
+                return violation("only standard transaction scripts can be signed".to_string());
+            }
+            ProgramKind::RotateAuthenticationKey { held_key: None, .. }
+                if !self.allow_key_rotation =>
+            {
+                return violation(
+                    "the authentication key can only be rotated to the key generated to replace it"
+                        .to_string(),
+                );
+            }
+            _ => (),
+        }
+        if let Some(max) = self.max_amount_per_transaction {
+            if txn.max_cost() > max {
+                return violation(format!(
+                    "amount {} (gas included) exceeds the per transaction limit of {}",
+                    txn.max_cost(),
+                    max
+                ));
+            }
+        }
+        if let Some(max) = self.max_amount_per_day {
+            if spent_today.saturating_add(txn.max_cost()) > max {
+                return violation(format!(
+                    "amount {} (gas included) exceeds the remaining daily limit of {}",
+                    txn.max_cost(),
+                    max.saturating_sub(spent_today)
+                ));
+            }
+        }
+        if let Some(payee) = txn.payee() {
+            if Self::lists(&self.denied_payees, &payee) {
+                return violation(format!("payee {} is denied", payee));
+            }
+            if let Some(allowed_payees) = &self.allowed_payees {
+                if !Self::lists(allowed_payees, &payee) {
+                    return violation(format!("payee {} is not allowed", payee));
+                }
+            }
+        }
+        if let Some(max) = self.max_gas_unit_price {
+            if txn.gas_unit_price > max {
+                return violation(format!(
+                    "gas unit price {} exceeds {}",
+                    txn.gas_unit_price, max
+                ));
+            }
+        }
+        if let Some(max) = self.max_expiration_secs {
+            if txn.expiration_time > now + Duration::from_secs(max) {
+                return violation(format!(
+                    "expiration time {}s is more than {}s in the future",
+                    txn.expiration_time.as_secs(),
+                    max
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Spending policies of the co-signer, as read from the policy file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PolicyConfig {
+    /// Policy of the keys that have no dedicated entry
+    #[serde(default)]
+    pub default: SpendingPolicy,
+    /// Dedicated policies, indexed by key id or by hex encoded account address
+    #[serde(default)]
+    pub keys: HashMap<String, SpendingPolicy>,
+}
+
+impl PolicyConfig {
+    /// Reads and validates a policy file
+    pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let config_string = fs::read_to_string(path)
+            .with_context(|_| format!("Cannot read policy file {:?}", path))?;
+        Self::parse(&config_string).with_context(|_| format!("Invalid policy file {:?}", path))
+    }
+
+    /// Parses and validates a policy file
+    pub fn parse(config_string: &str) -> Result<Self> {
+        let config: Self = toml::from_str(config_string)?;
+        config.default.validate()?;
+        for policy in config.keys.values() {
+            policy.validate()?;
+        }
+        Ok(config)
+    }
+
+    /// Returns the policy of a key
+    pub fn policy_for(&self, key_id: &str, address: &AccountAddress) -> &SpendingPolicy {
+        self.keys
+            .get(key_id)
+            .or_else(|| {
+                self.keys
+                    .iter()
+                    .find(|(entry, _)| parse_address(entry).ok() == Some(*address))
+                    .map(|(_, policy)| policy)
+            })
+            .unwrap_or(&self.default)
+    }
+}
+
+/// A co-signed transaction counted against the daily limit.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct SpendingRecord {
+    /// Time of signing, in seconds since the unix epoch
+    time: u64,
+    /// Amount moved, gas included
+    amount: u64,
+    hash: HashValue,
+}
+
+/// Enforces the spending policies and keeps track of the amounts co-signed for every account. The
+/// history is indexed by account rather than by key, so that rotating the authentication key of
+/// an account does not reset its daily limit, and is persisted so that restarting the co-signer
+/// does not either.
+pub struct PolicyEngine {
+    config: PolicyConfig,
+    history_path: PathBuf,
+    history: HashMap<String, Vec<SpendingRecord>>,
+}
+
+impl PolicyEngine {
+    /// Creates an engine, reloading the spending history from history_path if it exists.
+    pub fn new(config: PolicyConfig, history_path: PathBuf) -> Result<Self> {
+        let history = if history_path.exists() {
+            serde_json::from_slice(&fs::read(&history_path)?)?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            config,
+            history_path,
+            history,
+        })
+    }
+
+    /// Amount co-signed for account over the last 24 hours, gas included.
+    fn spent_today(&self, account: &AccountAddress, now: Duration) -> u64 {
+        let since = now.checked_sub(DAY).unwrap_or_default().as_secs();
+        self.history
+            .get(&account.to_string())
+            .into_iter()
+            .flatten()
+            .filter(|record| record.time > since)
+            .fold(0u64, |total, record| total.saturating_add(record.amount))
+    }
+
+    /// Checks that key_id is allowed to sign txn.
+    pub fn check(
+        &self,
+        key_id: &str,
+        txn: &TransactionSummary,
+        now: Duration,
+    ) -> std::result::Result<(), CoSignerError> {
+        self.config.policy_for(key_id, &txn.sender).check(
+            txn,
+            self.spent_today(&txn.sender, now),
+            now,
+        )
+    }
+
+    /// Counts txn against the daily limit of its sender. Recording the same transaction twice, e.g.
+    /// when the wallet retries a signature, only counts it once.
+    pub fn record(&mut self, txn: &TransactionSummary, now: Duration) -> Result<()> {
+        let since = now.checked_sub(DAY).unwrap_or_default().as_secs();
+        let records = self.history.entry(txn.sender.to_string()).or_default();
+        records.retain(|record| record.time > since);
+        if records.iter().any(|record| record.hash == txn.hash) {
+            return Ok(());
+        }
+        records.push(SpendingRecord {
+            time: now.as_secs(),
+            amount: txn.max_cost(),
+            hash: txn.hash,
+        });
+        write_file(&self.history_path, &serde_json::to_vec(&self.history)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(sender: AccountAddress, payee: AccountAddress, amount: u64) -> TransactionSummary {
+        TransactionSummary {
+            hash: HashValue::random(),
+            sender,
+            sequence_number: 0,
+            max_gas_amount: 10,
+            gas_unit_price: 1,
+            expiration_time: Duration::from_secs(100),
+            program: ProgramKind::PeerToPeerTransfer { payee, amount },
+        }
+    }
+
+    #[test]
+    fn test_daily_limit() {
+        let config = PolicyConfig::parse(
+            "[default]\nmax_amount_per_transaction = 70\nmax_amount_per_day = 100\n",
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = PolicyEngine::new(config, dir.path().join("spending.json")).unwrap();
+        let now = Duration::from_secs(DAY.as_secs() * 10);
+        let sender = AccountAddress::random();
+        let other = AccountAddress::random();
+
+        // Every transaction may also spend up to 10 in gas.
+        let first = transfer(sender, AccountAddress::random(), 50);
+        engine.check("key", &first, now).unwrap();
+        engine.record(&first, now).unwrap();
+        engine.record(&first, now).unwrap();
+        assert!(engine
+            .check("key", &transfer(sender, AccountAddress::random(), 61), now)
+            .is_err());
+        assert!(engine
+            .check("key", &transfer(sender, AccountAddress::random(), 31), now)
+            .is_err());
+        engine
+            .check("key", &transfer(sender, AccountAddress::random(), 30), now)
+            .unwrap();
+        engine
+            .check("key", &transfer(other, AccountAddress::random(), 50), now)
+            .unwrap();
+        engine
+            .check(
+                "key",
+                &transfer(sender, AccountAddress::random(), 50),
+                now + DAY,
+            )
+            .unwrap();
+
+        // The history survives a restart.
+        let config = PolicyConfig::parse("[default]\nmax_amount_per_day = 100\n").unwrap();
+        let engine = PolicyEngine::new(config, dir.path().join("spending.json")).unwrap();
+        assert!(engine
+            .check("key", &transfer(sender, AccountAddress::random(), 31), now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_unknown_programs() {
+        let config = PolicyConfig::parse(
+            "[default]\nmax_amount_per_transaction = 100\n\n\
+             [keys.scripting]\nallow_unknown_programs = true\n",
+        )
+        .unwrap();
+        let now = Duration::from_secs(0);
+        let mut script = transfer(AccountAddress::random(), AccountAddress::random(), 0);
+        script.program = ProgramKind::Unknown;
+
+        // A custom script may move any amount, whatever the amount limits say.
+        assert_eq!(
+            config
+                .policy_for("key", &script.sender)
+                .check(&script, 0, now),
+            Err(CoSignerError::PolicyViolation(
+                "only standard transaction scripts can be signed".to_string()
+            ))
+        );
+        config
+            .policy_for("scripting", &script.sender)
+            .check(&script, 0, now)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_payee_lists() {
+        let allowed = AccountAddress::random();
+        let config =
+            PolicyConfig::parse(&format!("[keys.key]\nallowed_payees = [\"{}\"]\n", allowed))
+                .unwrap();
+        let policy = config.policy_for("key", &AccountAddress::random());
+        let now = Duration::from_secs(0);
+        let denied = AccountAddress::random();
+        let sender = AccountAddress::random();
+        policy
+            .check(&transfer(sender, allowed, 10), 0, now)
+            .unwrap();
+        assert_eq!(
+            policy.check(&transfer(sender, denied, 10), 0, now),
+            Err(CoSignerError::PolicyViolation(format!(
+                "payee {} is not allowed",
+                denied
+            )))
+        );
+        assert!(PolicyConfig::parse("[default]\ndenied_payees = [\"not an address\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let config = PolicyConfig::parse(
+            "[default]\nmax_amount_per_transaction = 0\nallowed_payees = []\n\n\
+             [keys.rotating]\nallow_key_rotation = true\n",
+        )
+        .unwrap();
+        let now = Duration::from_secs(0);
+        let sender = AccountAddress::random();
+        let mut rotation = transfer(sender, AccountAddress::random(), 0);
+        rotation.gas_unit_price = 0;
+        rotation.program = ProgramKind::RotateAuthenticationKey {
+            new_key: AccountAddress::random().to_vec(),
+            held_key: None,
+        };
+
+        // A rotation moves no coins and has no payee, yet the most restrictive policy refuses it.
+        let policy = config.policy_for("key", &sender);
+        assert_eq!(
+            policy.check(&rotation, 0, now),
+            Err(CoSignerError::PolicyViolation(
+                "the authentication key can only be rotated to the key generated to replace it"
+                    .to_string()
+            ))
+        );
+        config
+            .policy_for("rotating", &sender)
+            .check(&rotation, 0, now)
+            .unwrap();
+
+        rotation.program = ProgramKind::RotateAuthenticationKey {
+            new_key: AccountAddress::random().to_vec(),
+            held_key: Some("new_key".to_string()),
+        };
+        policy.check(&rotation, 0, now).unwrap();
+    }
+}
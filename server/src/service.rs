@@ -0,0 +1,1515 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    audit::{AuditAction, AuditDecision, AuditEvent, AuditLog, ClientIdentity},
+    config::ServerConfig,
+    error::CoSignerError,
+    policy::{PolicyConfig, PolicyEngine},
+    storage::{load_or_create_identity, ServerShare, ShareStore, ThresholdServerShare},
+    transaction::{ProgramKind, TransactionSummary},
+};
+use co_signer_protocol::{
+    aggregate_public_keys, ed25519_public_key, refresh_key_pair, test_com,
+    threshold::{
+        authenticate_participants, decode_identity_key, encode_identity_key, KeyGenState,
+        ParticipantCommitment, SignerSession, ThresholdKeyGenCommitRequest,
+        ThresholdKeyGenCommitResponse, ThresholdKeyGenCompleteRequest,
+        ThresholdKeyGenCompleteResponse, ThresholdKeyGenShareRequest, ThresholdKeyGenShareResponse,
+        ThresholdSignCommitRequest, ThresholdSignCommitResponse, ThresholdSignRequest,
+        ThresholdSignResponse, ThresholdSignRevealRequest, ThresholdSignRevealResponse,
+        CLIENT_PARTICIPANT,
+    },
+    ECScalar, EphemeralKey, KeyGenRequest, KeyGenResponse, KeyPair, RefreshConfirmRequest,
+    RefreshConfirmResponse, RefreshRequest, RefreshResponse, RotationConfirmRequest,
+    RotationConfirmResponse, SignFirstMsg, SignFirstRequest, SignFirstResponse, SignSecondMsg,
+    SignSecondRequest, SignSecondResponse, Signature, CHAIN_CODE_LENGTH, FE, GE, SERVER_INDEX,
+};
+use crypto::HashValue;
+use failure::prelude::*;
+use logger::prelude::*;
+use rand::{rngs::EntropyRng, RngCore};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use types::account_address::AccountAddress;
+
+/// Time the wallet has to complete a signing session.
+const SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+type CoSignerResult<T> = std::result::Result<T, CoSignerError>;
+
+/// What a signing session signs.
+enum SignPayload {
+    /// A transaction, checked against the spending policy of the key
+    Transaction(TransactionSummary),
+    /// A prehashed message, for message signing keys
+    Message(HashValue),
+}
+
+impl SignPayload {
+    fn message(&self) -> HashValue {
+        match self {
+            SignPayload::Transaction(txn) => txn.hash,
+            SignPayload::Message(message) => *message,
+        }
+    }
+}
+
+/// State kept by the server between the two signing round trips.
+struct SignSession {
+    key_id: String,
+    epoch: u64,
+    child_number: Option<u64>,
+    payload: SignPayload,
+    client_commitment: SignFirstMsg,
+    ephemeral_key: EphemeralKey,
+    server_reveal: SignSecondMsg,
+    created: Instant,
+}
+
+/// State kept by the server between the threshold key generation round trips.
+struct ThresholdKeyGenSession {
+    /// Credentials of the client that opened the session, which owns the generated key
+    client: Option<HashValue>,
+    state: KeyGenState,
+    participants: Option<Vec<ParticipantCommitment>>,
+    created: Instant,
+}
+
+/// State kept by the server between the threshold signing round trips.
+struct ThresholdSignSession {
+    key_id: String,
+    txn: TransactionSummary,
+    session: SignerSession,
+    created: Instant,
+}
+
+/// Runs the server side of two-party key generation and signing, checking every transaction
+/// against the spending policy of its key before contributing a partial signature. The server
+/// may also hold shares of threshold keys, as one of their participants, and applies the same
+/// policy to the transactions of their accounts. Every key generation, refresh and signing
+/// decision is recorded in the audit log before the response is returned. A key is only used on
+/// behalf of the client that generated it.
+pub struct CoSignerService {
+    store: ShareStore,
+    threshold_store: ShareStore,
+    /// Long-term key the server signs its threshold key generation commitments with
+    identity: KeyPair,
+    /// Identity keys threshold key generation commitments of co-signers must be signed with: the
+    /// pinned keys of the other co-signers, and the key of the server itself
+    trusted_identity_keys: Vec<GE>,
+    policy: Mutex<PolicyEngine>,
+    audit: AuditLog,
+    sessions: Mutex<HashMap<String, SignSession>>,
+    threshold_keygen_sessions: Mutex<HashMap<String, ThresholdKeyGenSession>>,
+    threshold_sessions: Mutex<HashMap<String, ThresholdSignSession>>,
+    /// Ids of the two-party keys, indexed by the authentication key derived from their
+    /// aggregated public key
+    authentication_keys: Mutex<HashMap<AccountAddress, String>>,
+    /// Serializes the updates of stored shares
+    updates: Mutex<()>,
+}
+
+impl CoSignerService {
+    /// Creates the service described by config.
+    pub fn new(config: &ServerConfig) -> Result<Self> {
+        let policy_config = match &config.policy_file {
+            Some(policy_file) => PolicyConfig::load_config(policy_file)?,
+            None => PolicyConfig::default(),
+        };
+        let store = ShareStore::new(config.data_dir.join("keys"))?;
+        let threshold_store = ShareStore::new(config.data_dir.join("threshold_keys"))?;
+        let policy = PolicyEngine::new(policy_config, config.data_dir.join("spending.json"))?;
+        let audit = AuditLog::open(config.audit_log_path())?;
+        let identity = load_or_create_identity(&config.data_dir.join("identity.json"))?;
+        info!(
+            "Identity key of the co-signer: {}",
+            encode_identity_key(&identity.public_key)?
+        );
+        let mut trusted_identity_keys = config
+            .trusted_co_signers
+            .iter()
+            .map(|identity_key| {
+                decode_identity_key(identity_key).with_context(|_| {
+                    format!("Invalid identity key of trusted co-signer {}", identity_key)
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        trusted_identity_keys.push(identity.public_key.clone());
+        let mut authentication_keys = HashMap::new();
+        for id in store.ids()? {
+            if let Some(share) = store.get::<ServerShare>(&id)? {
+                authentication_keys.insert(share.authentication_key()?, id);
+            }
+        }
+        Ok(Self {
+            store,
+            threshold_store,
+            identity,
+            trusted_identity_keys,
+            policy: Mutex::new(policy),
+            audit,
+            sessions: Mutex::new(HashMap::new()),
+            threshold_keygen_sessions: Mutex::new(HashMap::new()),
+            threshold_sessions: Mutex::new(HashMap::new()),
+            authentication_keys: Mutex::new(authentication_keys),
+            updates: Mutex::new(()),
+        })
+    }
+
+    /// Generates the server share of a new key.
+    pub fn keygen(
+        &self,
+        client: &ClientIdentity,
+        request: KeyGenRequest,
+    ) -> CoSignerResult<KeyGenResponse> {
+        let result = self.generate_key(client, request);
+        let key_id = result.as_ref().ok().map(|response| response.id.as_str());
+        let event = AuditEvent::new(AuditAction::KeyGen, client, key_id);
+        self.audited(event, result)
+    }
+
+    fn generate_key(
+        &self,
+        client: &ClientIdentity,
+        request: KeyGenRequest,
+    ) -> CoSignerResult<KeyGenResponse> {
+        if let Some(replaced) = &request.replaces {
+            self.authorize(client, replaced)?;
+        }
+        let key_pair = KeyPair::create();
+        let key_agg = aggregate_public_keys(
+            &key_pair.public_key,
+            &request.client_public_key,
+            SERVER_INDEX,
+        );
+        let address = AccountAddress::from(ed25519_public_key(&key_agg.apk)?);
+        let server_public_key = key_pair.public_key.clone();
+        let mut chain_code = vec![0u8; CHAIN_CODE_LENGTH];
+        EntropyRng::new().fill_bytes(&mut chain_code);
+        let share = ServerShare {
+            key_pair,
+            key_agg,
+            client_public_key: request.client_public_key,
+            address,
+            epoch: 0,
+            pending_refresh: None,
+            pending_coefficient: None,
+            chain_code: chain_code.clone(),
+            message_signing: request.message_signing,
+            replaces: request.replaces,
+            pending_rotation: None,
+            owner: client.credentials,
+        };
+
+        let id = random_id();
+        self.store.insert(&id, &share)?;
+        self.authentication_keys
+            .lock()
+            .unwrap()
+            .insert(address, id.clone());
+        if request.message_signing {
+            info!("Generated message signing key {}", id);
+        } else {
+            info!("Generated key {} for account {}", id, address);
+        }
+        Ok(KeyGenResponse {
+            id,
+            server_public_key,
+            chain_code,
+        })
+    }
+
+    /// Checks the transaction against the policy of the key and opens a signing session. Message
+    /// signing keys only sign prehashed messages, and transaction keys only transactions.
+    /// Rejections are recorded in the audit log, approvals once the session is closed.
+    pub fn sign_first(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: SignFirstRequest,
+    ) -> CoSignerResult<SignFirstResponse> {
+        let mut event = AuditEvent::new(AuditAction::Sign, client, Some(key_id));
+        event = match request.message_hash {
+            Some(message) => event.with_message(message),
+            None => with_decoded_transaction(event, &request.raw_txn_bytes),
+        };
+        let result = self
+            .authorize(client, key_id)
+            .and_then(|_| self.open_sign_session(key_id, request));
+        if result.is_err() {
+            return self.audited(event, result);
+        }
+        result
+    }
+
+    fn open_sign_session(
+        &self,
+        key_id: &str,
+        request: SignFirstRequest,
+    ) -> CoSignerResult<SignFirstResponse> {
+        let share = self.share_at_epoch(key_id, request.epoch)?;
+        let (key_pair, _, address) = share.signing_key(request.child_number)?;
+        let payload = match (request.message_hash, share.message_signing) {
+            (Some(message), true) => SignPayload::Message(message),
+            (None, false) => {
+                let txn = self.decode_transaction(key_id, &request.raw_txn_bytes, address)?;
+                self.policy.lock().unwrap().check(key_id, &txn, now())?;
+                SignPayload::Transaction(txn)
+            }
+            (Some(_), false) => {
+                return Err(CoSignerError::InvalidRequest(format!(
+                    "Key {} only signs transactions",
+                    key_id
+                )));
+            }
+            (None, true) => {
+                return Err(CoSignerError::InvalidRequest(format!(
+                    "Key {} only signs prehashed messages",
+                    key_id
+                )));
+            }
+        };
+
+        let (ephemeral_key, server_commitment, server_reveal) =
+            Signature::create_ephemeral_key_and_commit(&key_pair, payload.message().as_ref());
+        let session_id = random_id();
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, session| session.created.elapsed() < SESSION_TIMEOUT);
+        sessions.insert(
+            session_id.clone(),
+            SignSession {
+                key_id: key_id.to_string(),
+                epoch: request.epoch,
+                child_number: request.child_number,
+                payload,
+                client_commitment: request.client_commitment,
+                ephemeral_key,
+                server_reveal,
+                created: Instant::now(),
+            },
+        );
+        Ok(SignFirstResponse {
+            session_id,
+            server_commitment,
+        })
+    }
+
+    /// Closes a signing session, returning the partial signature of the server. The policy is
+    /// checked again since other transactions may have been signed since the session opened.
+    pub fn sign_second(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: SignSecondRequest,
+    ) -> CoSignerResult<SignSecondResponse> {
+        let event = AuditEvent::new(AuditAction::Sign, client, Some(key_id));
+        // Sessions of the key are left alone unless client may use it.
+        if let Err(error) = self.authorize(client, key_id) {
+            return self.audited(event, Err(error));
+        }
+        let session = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(&request.session_id)
+            .filter(|session| {
+                session.key_id == key_id && session.created.elapsed() < SESSION_TIMEOUT
+            });
+        let session = match session {
+            Some(session) => session,
+            None => {
+                let error = CoSignerError::UnknownSession(request.session_id);
+                return self.audited(event, Err(error));
+            }
+        };
+        let event = match &session.payload {
+            SignPayload::Transaction(txn) => event.with_transaction(txn),
+            SignPayload::Message(message) => event.with_message(*message),
+        };
+        let result = self.co_sign(key_id, session, request.client_reveal);
+        self.audited(event, result)
+    }
+
+    fn co_sign(
+        &self,
+        key_id: &str,
+        session: SignSession,
+        client_reveal: SignSecondMsg,
+    ) -> CoSignerResult<SignSecondResponse> {
+        if !test_com(
+            &client_reveal.R,
+            &client_reveal.blind_factor,
+            &session.client_commitment.commitment,
+        ) {
+            return Err(CoSignerError::InvalidRequest(
+                "Client reveal does not open its commitment".to_string(),
+            ));
+        }
+        let share = self.share_at_epoch(key_id, session.epoch)?;
+        let (key_pair, key_agg, _) = share.signing_key(session.child_number)?;
+
+        let mut policy = self.policy.lock().unwrap();
+        let now = now();
+        if let SignPayload::Transaction(txn) = &session.payload {
+            policy.check(key_id, txn, now)?;
+        }
+        let message = session.payload.message();
+        let r_tot = Signature::get_R_tot(vec![
+            session.server_reveal.R.clone(),
+            client_reveal.R.clone(),
+        ]);
+        let k = Signature::k(&r_tot, &key_agg.apk, message.as_ref());
+        let server_partial_signature = Signature::partial_sign(
+            &session.ephemeral_key.r,
+            &key_pair,
+            &k,
+            &key_agg.hash,
+            &r_tot,
+        );
+        match &session.payload {
+            SignPayload::Transaction(txn) => {
+                policy.record(txn, now)?;
+                info!(
+                    "Key {} co-signed transaction {} of {} (sequence number {})",
+                    key_id, message, txn.sender, txn.sequence_number
+                );
+                if let ProgramKind::RotateAuthenticationKey {
+                    held_key: Some(held_key),
+                    ..
+                } = &txn.program
+                {
+                    self.start_rotation(held_key, txn.sender)?;
+                }
+            }
+            SignPayload::Message(_) => info!("Key {} co-signed message {}", key_id, message),
+        }
+
+        Ok(SignSecondResponse {
+            server_reveal: session.server_reveal,
+            server_partial_signature,
+        })
+    }
+
+    /// Refreshes the server share of a key. The refreshed share is kept pending, next to the
+    /// current one, until the client confirms it or signs with it: the client may fail to persist
+    /// its own refreshed share, in which case it retries from the current epoch.
+    pub fn refresh(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: RefreshRequest,
+    ) -> CoSignerResult<RefreshResponse> {
+        let result = self
+            .authorize(client, key_id)
+            .and_then(|_| self.refresh_share(key_id, request));
+        let event = AuditEvent::new(AuditAction::Refresh, client, Some(key_id));
+        self.audited(event, result)
+    }
+
+    fn refresh_share(
+        &self,
+        key_id: &str,
+        request: RefreshRequest,
+    ) -> CoSignerResult<RefreshResponse> {
+        let _updates = self.updates.lock().unwrap();
+        let mut share = self.share_at_epoch_locked(key_id, request.epoch)?;
+        let server_delta = FE::new_random();
+        let delta = request.client_delta.add(&server_delta.get_element());
+        let (key_pair, coefficient) =
+            refresh_key_pair(&share.key_pair, &share.key_agg.hash, &delta, true);
+        share.pending_refresh = Some(key_pair);
+        share.pending_coefficient = Some(coefficient);
+        self.store.update(key_id, &share)?;
+        info!("Key {} refreshed to epoch {}", key_id, share.epoch + 1);
+        Ok(RefreshResponse {
+            epoch: share.epoch + 1,
+            server_delta,
+        })
+    }
+
+    /// Makes the pending refreshed share of a key the current one, dropping the share of the
+    /// previous epoch for good.
+    pub fn confirm_refresh(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: RefreshConfirmRequest,
+    ) -> CoSignerResult<RefreshConfirmResponse> {
+        let result = self
+            .authorize(client, key_id)
+            .and_then(|_| self.confirm_refreshed_share(key_id, request));
+        let event = AuditEvent::new(AuditAction::RefreshConfirmation, client, Some(key_id));
+        self.audited(event, result)
+    }
+
+    fn confirm_refreshed_share(
+        &self,
+        key_id: &str,
+        request: RefreshConfirmRequest,
+    ) -> CoSignerResult<RefreshConfirmResponse> {
+        let _updates = self.updates.lock().unwrap();
+        let share = self.share_at_epoch_locked(key_id, request.epoch)?;
+        Ok(RefreshConfirmResponse { epoch: share.epoch })
+    }
+
+    /// Opens a threshold key generation session, as participant request.index.
+    pub fn threshold_keygen_commit(
+        &self,
+        client: &ClientIdentity,
+        request: ThresholdKeyGenCommitRequest,
+    ) -> CoSignerResult<ThresholdKeyGenCommitResponse> {
+        let result = self.open_threshold_keygen_session(client, request);
+        let event = AuditEvent::new(AuditAction::ThresholdKeyGenCommit, client, None);
+        self.audited(event, result)
+    }
+
+    fn open_threshold_keygen_session(
+        &self,
+        client: &ClientIdentity,
+        request: ThresholdKeyGenCommitRequest,
+    ) -> CoSignerResult<ThresholdKeyGenCommitResponse> {
+        if request.index == CLIENT_PARTICIPANT {
+            return Err(CoSignerError::InvalidRequest(
+                "A co-signer cannot take the index of the client".to_string(),
+            ));
+        }
+        let state = KeyGenState::new(request.parameters, request.index).map_err(invalid_request)?;
+        let commitment = state.commitment();
+        let identity = commitment.sign_identity(request.parameters, &self.identity);
+        let session_id = random_id();
+        let mut sessions = self.threshold_keygen_sessions.lock().unwrap();
+        sessions.retain(|_, session| session.created.elapsed() < SESSION_TIMEOUT);
+        sessions.insert(
+            session_id.clone(),
+            ThresholdKeyGenSession {
+                client: client.credentials,
+                state,
+                participants: None,
+                created: Instant::now(),
+            },
+        );
+        Ok(ThresholdKeyGenCommitResponse {
+            session_id,
+            commitment,
+            identity,
+        })
+    }
+
+    /// Returns the sub-shares of the server for the other participants of a key generation, once
+    /// the commitments of the other co-signers are checked to be signed by their pinned identity
+    /// keys.
+    pub fn threshold_keygen_share(
+        &self,
+        client: &ClientIdentity,
+        request: ThresholdKeyGenShareRequest,
+    ) -> CoSignerResult<ThresholdKeyGenShareResponse> {
+        let result = self.share_threshold_keygen(client, request);
+        let event = AuditEvent::new(AuditAction::ThresholdKeyGenShare, client, None);
+        self.audited(event, result)
+    }
+
+    fn share_threshold_keygen(
+        &self,
+        client: &ClientIdentity,
+        request: ThresholdKeyGenShareRequest,
+    ) -> CoSignerResult<ThresholdKeyGenShareResponse> {
+        let mut sessions = self.threshold_keygen_sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(&request.session_id)
+            .filter(|session| {
+                session.client == client.credentials
+                    && session.participants.is_none()
+                    && session.created.elapsed() < SESSION_TIMEOUT
+            })
+            .ok_or_else(|| CoSignerError::UnknownSession(request.session_id.clone()))?;
+        authenticate_participants(
+            session.state.parameters(),
+            &request.participants,
+            &request.identities,
+            &self.trusted_identity_keys,
+        )
+        .map_err(invalid_request)?;
+        let sub_shares = session
+            .state
+            .sub_shares(&request.participants)
+            .map_err(invalid_request)?;
+        session.participants = Some(request.participants);
+        Ok(ThresholdKeyGenShareResponse { sub_shares })
+    }
+
+    /// Closes a threshold key generation session, storing the share of the server.
+    pub fn threshold_keygen_complete(
+        &self,
+        client: &ClientIdentity,
+        request: ThresholdKeyGenCompleteRequest,
+    ) -> CoSignerResult<ThresholdKeyGenCompleteResponse> {
+        let result = self.complete_threshold_keygen(client, request);
+        let key_id = result.as_ref().ok().map(|response| response.id.as_str());
+        let event = AuditEvent::new(AuditAction::ThresholdKeyGen, client, key_id);
+        self.audited(event, result)
+    }
+
+    fn complete_threshold_keygen(
+        &self,
+        client: &ClientIdentity,
+        request: ThresholdKeyGenCompleteRequest,
+    ) -> CoSignerResult<ThresholdKeyGenCompleteResponse> {
+        let session = {
+            let mut sessions = self.threshold_keygen_sessions.lock().unwrap();
+            let owned = sessions
+                .get(&request.session_id)
+                .map_or(false, |session| session.client == client.credentials);
+            // Sessions of other clients are left for their client to complete.
+            if owned {
+                sessions.remove(&request.session_id)
+            } else {
+                None
+            }
+        };
+        let (state, participants) = session
+            .filter(|session| session.created.elapsed() < SESSION_TIMEOUT)
+            .and_then(|session| Some((session.state, session.participants?)))
+            .ok_or_else(|| CoSignerError::UnknownSession(request.session_id.clone()))?;
+        let share = state
+            .complete(&participants, &request.sub_shares)
+            .map_err(invalid_request)?;
+        let address = AccountAddress::from(ed25519_public_key(share.public_key())?);
+        let response = ThresholdKeyGenCompleteResponse {
+            id: random_id(),
+            public_key: share.public_key().clone(),
+            share_public_key: share.participant_public_key(share.index),
+        };
+        info!(
+            "Generated threshold key {} for account {}, as participant {} of {} (threshold {})",
+            response.id,
+            address,
+            share.index,
+            share.parameters.participants,
+            share.parameters.threshold
+        );
+        self.threshold_store.insert(
+            &response.id,
+            &ThresholdServerShare {
+                share,
+                address,
+                owner: client.credentials,
+            },
+        )?;
+        Ok(response)
+    }
+
+    /// Checks the transaction against the policy of the threshold key and opens a signing
+    /// session with the given signers. Rejections are recorded in the audit log, approvals once
+    /// the session is closed.
+    pub fn threshold_sign_commit(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: ThresholdSignCommitRequest,
+    ) -> CoSignerResult<ThresholdSignCommitResponse> {
+        let event = with_decoded_transaction(
+            AuditEvent::new(AuditAction::ThresholdSign, client, Some(key_id)),
+            &request.raw_txn_bytes,
+        );
+        let result = self
+            .authorize_threshold(client, key_id)
+            .and_then(|_| self.open_threshold_sign_session(key_id, request));
+        if result.is_err() {
+            return self.audited(event, result);
+        }
+        result
+    }
+
+    fn open_threshold_sign_session(
+        &self,
+        key_id: &str,
+        request: ThresholdSignCommitRequest,
+    ) -> CoSignerResult<ThresholdSignCommitResponse> {
+        let share = self.threshold_share(key_id)?;
+        let txn = self.decode_transaction(key_id, &request.raw_txn_bytes, share.address)?;
+        self.policy.lock().unwrap().check(key_id, &txn, now())?;
+        let session =
+            SignerSession::new(&share.share, request.signers, txn.hash).map_err(invalid_request)?;
+        let commitment = session.commitment();
+        let session_id = random_id();
+        let mut sessions = self.threshold_sessions.lock().unwrap();
+        sessions.retain(|_, session| session.created.elapsed() < SESSION_TIMEOUT);
+        sessions.insert(
+            session_id.clone(),
+            ThresholdSignSession {
+                key_id: key_id.to_string(),
+                txn,
+                session,
+                created: Instant::now(),
+            },
+        );
+        Ok(ThresholdSignCommitResponse {
+            session_id,
+            commitment,
+        })
+    }
+
+    /// Reveals the ephemeral key of the server, given the commitments of all the signers.
+    pub fn threshold_sign_reveal(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: ThresholdSignRevealRequest,
+    ) -> CoSignerResult<ThresholdSignRevealResponse> {
+        let result = self
+            .authorize_threshold(client, key_id)
+            .and_then(|_| self.reveal_threshold_sign(key_id, request));
+        let event = AuditEvent::new(AuditAction::ThresholdSignReveal, client, Some(key_id));
+        self.audited(event, result)
+    }
+
+    fn reveal_threshold_sign(
+        &self,
+        key_id: &str,
+        request: ThresholdSignRevealRequest,
+    ) -> CoSignerResult<ThresholdSignRevealResponse> {
+        let mut sessions = self.threshold_sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(&request.session_id)
+            .filter(|session| {
+                session.key_id == key_id && session.created.elapsed() < SESSION_TIMEOUT
+            })
+            .ok_or_else(|| CoSignerError::UnknownSession(request.session_id.clone()))?;
+        let reveal = session
+            .session
+            .reveal(request.commitments)
+            .map_err(invalid_request)?;
+        Ok(ThresholdSignRevealResponse { reveal })
+    }
+
+    /// Closes a threshold signing session, returning the partial signature of the server. The
+    /// policy is checked again since other transactions may have been signed since the session
+    /// opened.
+    pub fn threshold_sign(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: ThresholdSignRequest,
+    ) -> CoSignerResult<ThresholdSignResponse> {
+        let event = AuditEvent::new(AuditAction::ThresholdSign, client, Some(key_id));
+        if let Err(error) = self.authorize_threshold(client, key_id) {
+            return self.audited(event, Err(error));
+        }
+        let session = self
+            .threshold_sessions
+            .lock()
+            .unwrap()
+            .remove(&request.session_id)
+            .filter(|session| {
+                session.key_id == key_id && session.created.elapsed() < SESSION_TIMEOUT
+            });
+        let session = match session {
+            Some(session) => session,
+            None => {
+                let error = CoSignerError::UnknownSession(request.session_id);
+                return self.audited(event, Err(error));
+            }
+        };
+        let event = event.with_transaction(&session.txn);
+        let result = self.threshold_co_sign(key_id, session, request);
+        self.audited(event, result)
+    }
+
+    fn threshold_co_sign(
+        &self,
+        key_id: &str,
+        session: ThresholdSignSession,
+        request: ThresholdSignRequest,
+    ) -> CoSignerResult<ThresholdSignResponse> {
+        let mut policy = self.policy.lock().unwrap();
+        let now = now();
+        policy.check(key_id, &session.txn, now)?;
+        let partial_signature = session
+            .session
+            .sign(&request.reveals)
+            .map_err(invalid_request)?;
+        policy.record(&session.txn, now)?;
+        info!(
+            "Threshold key {} co-signed transaction {} of {} (sequence number {}) with signers \
+             {:?}",
+            key_id,
+            session.txn.hash,
+            session.txn.sender,
+            session.txn.sequence_number,
+            session.session.signers()
+        );
+        if let ProgramKind::RotateAuthenticationKey {
+            held_key: Some(held_key),
+            ..
+        } = &session.txn.program
+        {
+            self.start_rotation(held_key, session.txn.sender)?;
+        }
+        Ok(ThresholdSignResponse { partial_signature })
+    }
+
+    /// Returns the share of a key at the given epoch. Reaching the epoch of a pending refresh
+    /// confirms it, any other epoch is stale.
+    fn share_at_epoch(&self, key_id: &str, epoch: u64) -> CoSignerResult<ServerShare> {
+        let _updates = self.updates.lock().unwrap();
+        self.share_at_epoch_locked(key_id, epoch)
+    }
+
+    fn share_at_epoch_locked(&self, key_id: &str, epoch: u64) -> CoSignerResult<ServerShare> {
+        let mut share = self.share(key_id)?;
+        if epoch == share.epoch + 1 {
+            if let Some(key_pair) = share.pending_refresh.take() {
+                share.key_pair = key_pair;
+                if let Some(coefficient) = share.pending_coefficient.take() {
+                    share.key_agg.hash = coefficient;
+                }
+                share.epoch = epoch;
+                self.store.update(key_id, &share)?;
+                info!("Key {} confirmed epoch {}", key_id, epoch);
+            }
+        }
+        if epoch != share.epoch {
+            return Err(CoSignerError::InvalidRequest(format!(
+                "Share of key {} at epoch {} is stale, current epoch is {}",
+                key_id, epoch, share.epoch
+            )));
+        }
+        Ok(share)
+    }
+
+    /// Decodes a transaction key_id is asked to sign for address. The key a rotation hands the
+    /// account over to is looked up among the keys generated to replace key_id, which the policy
+    /// tells apart from any other key.
+    fn decode_transaction(
+        &self,
+        key_id: &str,
+        raw_txn_bytes: &[u8],
+        address: AccountAddress,
+    ) -> CoSignerResult<TransactionSummary> {
+        let mut txn = TransactionSummary::decode(raw_txn_bytes)?;
+        if txn.sender != address {
+            return Err(CoSignerError::InvalidRequest(format!(
+                "Key {} cannot sign for sender {}",
+                key_id, txn.sender
+            )));
+        }
+        if let ProgramKind::RotateAuthenticationKey { new_key, held_key } = &mut txn.program {
+            *held_key = self.replacement_key(key_id, new_key)?;
+        }
+        Ok(txn)
+    }
+
+    /// Returns the id of the key whose authentication key is new_key, if it was generated to
+    /// replace key_id. The keys of other accounts, whoever holds them, cannot be taken over by
+    /// rotating to their authentication key.
+    fn replacement_key(&self, key_id: &str, new_key: &[u8]) -> CoSignerResult<Option<String>> {
+        let new_key = AccountAddress::try_from(new_key).map_err(invalid_request)?;
+        let id = match self.authentication_keys.lock().unwrap().get(&new_key) {
+            Some(id) => id.clone(),
+            None => return Ok(None),
+        };
+        let share = self.share(&id)?;
+        if share.replaces.as_ref().map(String::as_str) != Some(key_id) {
+            return Ok(None);
+        }
+        Ok(Some(id))
+    }
+
+    /// Records that the account at address rotates its authentication key to held_key. The key
+    /// is only bound to the account once the wallet confirms that the rotation was committed: a
+    /// rotation that never commits leaves it as it is.
+    fn start_rotation(&self, held_key: &str, address: AccountAddress) -> CoSignerResult<()> {
+        let _updates = self.updates.lock().unwrap();
+        let mut share = self.share(held_key)?;
+        share.pending_rotation = Some(address);
+        self.store.update(held_key, &share)?;
+        info!(
+            "Key {} is pending rotation of account {}",
+            held_key, address
+        );
+        Ok(())
+    }
+
+    /// Binds a key to the account whose authentication key was rotated to it, once the wallet
+    /// saw the rotation committed, so that the key co-signs the transactions of that account.
+    /// Confirming again the same rotation is harmless, which lets the wallet retry.
+    pub fn confirm_rotation(
+        &self,
+        client: &ClientIdentity,
+        key_id: &str,
+        request: RotationConfirmRequest,
+    ) -> CoSignerResult<RotationConfirmResponse> {
+        let result = self
+            .authorize(client, key_id)
+            .and_then(|_| self.bind_rotated_key(key_id, request.address));
+        let event = AuditEvent::new(AuditAction::Rotation, client, Some(key_id));
+        self.audited(event, result)
+    }
+
+    fn bind_rotated_key(
+        &self,
+        key_id: &str,
+        address: AccountAddress,
+    ) -> CoSignerResult<RotationConfirmResponse> {
+        let _updates = self.updates.lock().unwrap();
+        let mut share = self.share(key_id)?;
+        if share.address != address {
+            if share.pending_rotation != Some(address) {
+                return Err(CoSignerError::InvalidRequest(format!(
+                    "No rotation of account {} to key {} was co-signed",
+                    address, key_id
+                )));
+            }
+            share.address = address;
+            share.pending_rotation = None;
+            self.store.update(key_id, &share)?;
+            info!("Key {} is bound to account {}", key_id, address);
+        }
+        Ok(RotationConfirmResponse { address })
+    }
+
+    /// Checks that client may use the two-party key key_id, i.e. that it generated it. Keys
+    /// generated before clients were authenticated are bound to the first client using them.
+    fn authorize(&self, client: &ClientIdentity, key_id: &str) -> CoSignerResult<()> {
+        let _updates = self.updates.lock().unwrap();
+        let mut share = self.share(key_id)?;
+        if bind_owner(&mut share.owner, client, key_id)? {
+            self.store.update(key_id, &share)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that client may use the threshold key key_id, see authorize.
+    fn authorize_threshold(&self, client: &ClientIdentity, key_id: &str) -> CoSignerResult<()> {
+        let _updates = self.updates.lock().unwrap();
+        let mut share = self.threshold_share(key_id)?;
+        if bind_owner(&mut share.owner, client, key_id)? {
+            self.threshold_store.update(key_id, &share)?;
+        }
+        Ok(())
+    }
+
+    /// Records event with the decision result stands for. The result is only returned once
+    /// recorded: a request that cannot be recorded fails. Rejections are only recorded for keys
+    /// the co-signer holds, so that requests naming made up keys cannot flood the log.
+    fn audited<T>(&self, event: AuditEvent, result: CoSignerResult<T>) -> CoSignerResult<T> {
+        let decision = match &result {
+            Ok(_) => AuditDecision::Approved,
+            Err(error) => {
+                if !self.holds_key(&event) {
+                    warn!(
+                        "Rejected {:?} request from {:?} for no known key: {}",
+                        event.action, event.client.address, error
+                    );
+                    return result;
+                }
+                AuditDecision::Rejected(error.to_string())
+            }
+        };
+        self.audit.append(event.with_decision(decision), now())?;
+        result
+    }
+
+    /// Whether the co-signer holds the key event applies to.
+    fn holds_key(&self, event: &AuditEvent) -> bool {
+        let store = match event.action {
+            AuditAction::ThresholdKeyGenCommit
+            | AuditAction::ThresholdKeyGenShare
+            | AuditAction::ThresholdKeyGen
+            | AuditAction::ThresholdSignReveal
+            | AuditAction::ThresholdSign => &self.threshold_store,
+            _ => &self.store,
+        };
+        event
+            .key_id
+            .as_ref()
+            .map_or(false, |key_id| store.contains(key_id))
+    }
+
+    fn share(&self, key_id: &str) -> CoSignerResult<ServerShare> {
+        self.store
+            .get(key_id)?
+            .ok_or_else(|| CoSignerError::UnknownKey(key_id.to_string()))
+    }
+
+    fn threshold_share(&self, key_id: &str) -> CoSignerResult<ThresholdServerShare> {
+        self.threshold_store
+            .get(key_id)?
+            .ok_or_else(|| CoSignerError::UnknownKey(key_id.to_string()))
+    }
+}
+
+/// Checks that client is the owner of key_id, making it the owner if the key has none yet.
+/// Returns whether the owner was set.
+fn bind_owner(
+    owner: &mut Option<HashValue>,
+    client: &ClientIdentity,
+    key_id: &str,
+) -> CoSignerResult<bool> {
+    match *owner {
+        Some(_) if *owner == client.credentials => Ok(false),
+        Some(_) => Err(CoSignerError::Unauthorized(format!(
+            "Key {} belongs to another client",
+            key_id
+        ))),
+        None => {
+            *owner = client.credentials;
+            if owner.is_some() {
+                info!("Key {} is bound to the credentials of its client", key_id);
+            }
+            Ok(owner.is_some())
+        }
+    }
+}
+
+/// Adds the transaction to event, if raw_txn_bytes decode to one.
+fn with_decoded_transaction(event: AuditEvent, raw_txn_bytes: &[u8]) -> AuditEvent {
+    match TransactionSummary::decode(raw_txn_bytes) {
+        Ok(txn) => event.with_transaction(&txn),
+        Err(_) => event,
+    }
+}
+
+fn invalid_request(error: Error) -> CoSignerError {
+    CoSignerError::InvalidRequest(error.to_string())
+}
+
+fn random_id() -> String {
+    let mut bytes = [0u8; 16];
+    EntropyRng::new().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before the unix epoch")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use co_signer_protocol::{
+        signing_message,
+        threshold::{CommitmentIdentity, ThresholdParameters},
+        verify, KeyAgg, CLIENT_INDEX,
+    };
+    use proto_conv::IntoProtoBytes;
+    use std::{fs, path::Path};
+    use types::transaction::{Program, RawTransaction};
+    use vm_genesis::{encode_transfer_program, rotate_authentication_key_program};
+
+    /// Client side of a two-party key.
+    struct ClientKey {
+        id: String,
+        key_pair: KeyPair,
+        key_agg: KeyAgg,
+        epoch: u64,
+    }
+
+    impl ClientKey {
+        fn authentication_key(&self) -> AccountAddress {
+            AccountAddress::from(ed25519_public_key(&self.key_agg.apk).unwrap())
+        }
+    }
+
+    fn client(address: &str) -> ClientIdentity {
+        ClientIdentity {
+            address: Some(address.to_string()),
+            credentials: Some(HashValue::from_sha3(address.as_bytes())),
+        }
+    }
+
+    fn start_service(dir: &Path, policy: &str) -> CoSignerService {
+        let policy_file = dir.join("policy.toml");
+        fs::write(&policy_file, policy).unwrap();
+        CoSignerService::new(&ServerConfig {
+            data_dir: dir.to_path_buf(),
+            policy_file: Some(policy_file),
+            ..ServerConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn keygen(
+        service: &CoSignerService,
+        client: &ClientIdentity,
+        replaces: Option<&str>,
+    ) -> ClientKey {
+        let key_pair = KeyPair::create();
+        let response = service
+            .keygen(
+                client,
+                KeyGenRequest {
+                    client_public_key: key_pair.public_key.clone(),
+                    message_signing: false,
+                    replaces: replaces.map(str::to_string),
+                },
+            )
+            .unwrap();
+        let key_agg = aggregate_public_keys(
+            &response.server_public_key,
+            &key_pair.public_key,
+            CLIENT_INDEX,
+        );
+        ClientKey {
+            id: response.id,
+            key_pair,
+            key_agg,
+            epoch: 0,
+        }
+    }
+
+    fn transfer() -> Program {
+        encode_transfer_program(&AccountAddress::random(), 1_000)
+    }
+
+    /// Runs both signing round trips for a transaction of sender, checking the signature.
+    fn sign(
+        service: &CoSignerService,
+        client: &ClientIdentity,
+        key: &ClientKey,
+        sender: AccountAddress,
+        program: Program,
+    ) -> CoSignerResult<()> {
+        let expiration_time = Duration::from_secs(now().as_secs() + 60);
+        let raw_txn_bytes = RawTransaction::new(sender, 0, program, 10_000, 1, expiration_time)
+            .into_proto_bytes()
+            .unwrap();
+        let message = signing_message(&raw_txn_bytes);
+        let (ephemeral_key, client_commitment, client_reveal) =
+            Signature::create_ephemeral_key_and_commit(&key.key_pair, message.as_ref());
+        let first = service.sign_first(
+            client,
+            &key.id,
+            SignFirstRequest {
+                raw_txn_bytes,
+                message_hash: None,
+                client_commitment,
+                epoch: key.epoch,
+                child_number: None,
+            },
+        )?;
+        let second = service.sign_second(
+            client,
+            &key.id,
+            SignSecondRequest {
+                session_id: first.session_id,
+                client_reveal: client_reveal.clone(),
+            },
+        )?;
+        let r_tot = Signature::get_R_tot(vec![second.server_reveal.R.clone(), client_reveal.R]);
+        let k = Signature::k(&r_tot, &key.key_agg.apk, message.as_ref());
+        let client_partial_signature = Signature::partial_sign(
+            &ephemeral_key.r,
+            &key.key_pair,
+            &k,
+            &key.key_agg.hash,
+            &r_tot,
+        );
+        let signature = Signature::add_signature_parts(vec![
+            second.server_partial_signature,
+            client_partial_signature,
+        ]);
+        verify(&signature, message.as_ref(), &key.key_agg.apk).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_rotation_to_key_of_other_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = start_service(dir.path(), "[default]\nallow_key_rotation = true\n");
+        let alice = client("10.0.0.1");
+        let mallory = client("10.0.0.2");
+        let victim = keygen(&service, &alice, None);
+        let victim_address = victim.authentication_key();
+        let attacker = keygen(&service, &mallory, None);
+        let attacker_address = attacker.authentication_key();
+
+        // The policy lets the account rotate to any key, but the key of another account is not
+        // handed over to it.
+        let rotation = rotate_authentication_key_program(victim_address);
+        sign(&service, &mallory, &attacker, attacker_address, rotation).unwrap();
+        assert!(service
+            .confirm_rotation(
+                &mallory,
+                &victim.id,
+                RotationConfirmRequest {
+                    address: attacker_address
+                },
+            )
+            .is_err());
+        sign(&service, &alice, &victim, victim_address, transfer()).unwrap();
+        assert!(sign(&service, &mallory, &victim, attacker_address, transfer()).is_err());
+
+        // Under the default policy, such a rotation is not even co-signed.
+        drop(service);
+        let service = start_service(dir.path(), "");
+        let rotation = rotate_authentication_key_program(victim_address);
+        match sign(&service, &mallory, &attacker, attacker_address, rotation) {
+            Err(CoSignerError::PolicyViolation(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_key_of_other_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = start_service(dir.path(), "");
+        let alice = client("10.0.0.1");
+        let mallory = client("10.0.0.2");
+        let key = keygen(&service, &alice, None);
+        let address = key.authentication_key();
+        let is_unauthorized = |result: CoSignerResult<_>| match result {
+            Err(CoSignerError::Unauthorized(_)) => true,
+            _ => false,
+        };
+
+        // Knowing the id of a key is not enough to use it.
+        assert!(is_unauthorized(sign(
+            &service,
+            &mallory,
+            &key,
+            address,
+            transfer()
+        )));
+        let refresh = RefreshRequest {
+            epoch: key.epoch,
+            client_delta: FE::new_random(),
+        };
+        assert!(is_unauthorized(
+            service
+                .refresh(&mallory, &key.id, refresh.clone())
+                .map(|_| ())
+        ));
+        assert!(is_unauthorized(
+            service
+                .confirm_refresh(&mallory, &key.id, RefreshConfirmRequest { epoch: 1 })
+                .map(|_| ())
+        ));
+        assert!(is_unauthorized(
+            service
+                .confirm_rotation(&mallory, &key.id, RotationConfirmRequest { address })
+                .map(|_| ())
+        ));
+        let replacement = service.keygen(
+            &mallory,
+            KeyGenRequest {
+                client_public_key: KeyPair::create().public_key,
+                message_signing: false,
+                replaces: Some(key.id.clone()),
+            },
+        );
+        assert!(is_unauthorized(replacement.map(|_| ())));
+
+        sign(&service, &alice, &key, address, transfer()).unwrap();
+        service.refresh(&alice, &key.id, refresh).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_never_committed() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = start_service(dir.path(), "");
+        let client = client("10.0.0.1");
+        let key = keygen(&service, &client, None);
+        let address = key.authentication_key();
+        let new_key = keygen(&service, &client, Some(&key.id));
+
+        let rotation = rotate_authentication_key_program(new_key.authentication_key());
+        sign(&service, &client, &key, address, rotation).unwrap();
+        // Until the wallet confirms the rotation, the current key signs for the account and the
+        // new one does not, even after a restart of the co-signer.
+        drop(service);
+        let service = start_service(dir.path(), "");
+        assert!(sign(&service, &client, &new_key, address, transfer()).is_err());
+        sign(&service, &client, &key, address, transfer()).unwrap();
+
+        service
+            .confirm_rotation(&client, &new_key.id, RotationConfirmRequest { address })
+            .unwrap();
+        sign(&service, &client, &new_key, address, transfer()).unwrap();
+        service
+            .confirm_rotation(&client, &new_key.id, RotationConfirmRequest { address })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = start_service(dir.path(), "");
+        let client = client("10.0.0.1");
+        let key = keygen(&service, &client, None);
+        let address = key.authentication_key();
+
+        let client_delta = FE::new_random();
+        let response = service
+            .refresh(
+                &client,
+                &key.id,
+                RefreshRequest {
+                    epoch: key.epoch,
+                    client_delta: client_delta.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(response.epoch, 1);
+        let delta = client_delta.add(&response.server_delta.get_element());
+        let (key_pair, hash) = refresh_key_pair(&key.key_pair, &key.key_agg.hash, &delta, false);
+        let refreshed = ClientKey {
+            id: key.id.clone(),
+            key_pair,
+            key_agg: KeyAgg {
+                apk: key.key_agg.apk.clone(),
+                hash,
+            },
+            epoch: response.epoch,
+        };
+        assert_eq!(refreshed.authentication_key(), address);
+
+        // The co-signer signs with its share of the previous epoch until the refreshed share is
+        // used, after which the share of the previous epoch is stale.
+        sign(&service, &client, &key, address, transfer()).unwrap();
+        sign(&service, &client, &refreshed, address, transfer()).unwrap();
+        match sign(&service, &client, &key, address, transfer()) {
+            Err(CoSignerError::InvalidRequest(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        drop(service);
+        let service = start_service(dir.path(), "");
+        assert!(sign(&service, &client, &key, address, transfer()).is_err());
+        sign(&service, &client, &refreshed, address, transfer()).unwrap();
+    }
+
+    #[test]
+    fn test_audit_log() {
+        use crate::audit::{read_entries, signed_by, verify_chain};
+
+        let dir = tempfile::tempdir().unwrap();
+        let service = start_service(dir.path(), "");
+        let client = client("10.0.0.1");
+        let key = keygen(&service, &client, None);
+        let address = key.authentication_key();
+        sign(&service, &client, &key, address, transfer()).unwrap();
+        service
+            .refresh(
+                &client,
+                &key.id,
+                RefreshRequest {
+                    epoch: key.epoch,
+                    client_delta: FE::new_random(),
+                },
+            )
+            .unwrap();
+        // Rotations are denied by the default policy.
+        let rotation = rotate_authentication_key_program(AccountAddress::random());
+        match sign(&service, &client, &key, address, rotation) {
+            Err(CoSignerError::PolicyViolation(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        // Requests naming keys the co-signer does not hold are not recorded.
+        let unknown = ClientKey {
+            id: random_id(),
+            key_pair: KeyPair::create(),
+            key_agg: key.key_agg.clone(),
+            epoch: 0,
+        };
+        match sign(&service, &client, &unknown, address, transfer()) {
+            Err(CoSignerError::UnknownKey(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert!(service
+            .sign_second(
+                &client,
+                &unknown.id,
+                SignSecondRequest {
+                    session_id: random_id(),
+                    client_reveal: Signature::create_ephemeral_key_and_commit(
+                        &unknown.key_pair,
+                        &[0u8; 32]
+                    )
+                    .2,
+                },
+            )
+            .is_err());
+        // Confirming the refresh is recorded as well.
+        service
+            .confirm_refresh(&client, &key.id, RefreshConfirmRequest { epoch: 1 })
+            .unwrap();
+
+        let entries = read_entries(dir.path().join("audit.log")).unwrap();
+        verify_chain(&entries).unwrap();
+        let events = entries
+            .iter()
+            .map(|entry| {
+                let event = &entry.record.event;
+                let approved = event.decision == AuditDecision::Approved;
+                (event.action, event.key_id.clone(), approved)
+            })
+            .collect::<Vec<_>>();
+        let key_id = Some(key.id.clone());
+        assert_eq!(
+            events,
+            vec![
+                (AuditAction::KeyGen, key_id.clone(), true),
+                (AuditAction::Sign, key_id.clone(), true),
+                (AuditAction::Refresh, key_id.clone(), true),
+                (AuditAction::Sign, key_id.clone(), false),
+                (AuditAction::RefreshConfirmation, key_id, true),
+            ]
+        );
+        assert!(entries
+            .iter()
+            .all(|entry| entry.record.event.client == client));
+        let signed = signed_by(&entries, &key.id, 0, u64::max_value()).collect::<Vec<_>>();
+        assert_eq!(signed.len(), 1);
+        assert!(signed[0].record.event.transaction.is_some());
+    }
+
+    /// Starts a co-signer pinning the identity keys of the co-signers whose data lives in
+    /// trusted_dirs.
+    fn start_threshold_service(dir: &Path, trusted_dirs: &[&Path]) -> CoSignerService {
+        let trusted_co_signers = trusted_dirs
+            .iter()
+            .map(|trusted_dir| {
+                let identity = load_or_create_identity(&trusted_dir.join("identity.json")).unwrap();
+                encode_identity_key(&identity.public_key).unwrap()
+            })
+            .collect();
+        CoSignerService::new(&ServerConfig {
+            data_dir: dir.to_path_buf(),
+            trusted_co_signers,
+            ..ServerConfig::default()
+        })
+        .unwrap()
+    }
+
+    /// Opens a key generation session with every co-signer, co-signer i taking index i + 2, and
+    /// returns the session ids, the commitments of all the participants and the identities of
+    /// the co-signers.
+    fn threshold_keygen_commit(
+        services: &[&CoSignerService],
+        client_state: &KeyGenState,
+    ) -> (
+        Vec<String>,
+        Vec<ParticipantCommitment>,
+        Vec<CommitmentIdentity>,
+    ) {
+        let mut session_ids = vec![];
+        let mut participants = vec![client_state.commitment()];
+        let mut identities = vec![];
+        for (position, service) in services.iter().enumerate() {
+            let response = service
+                .threshold_keygen_commit(
+                    &client("127.0.0.1"),
+                    ThresholdKeyGenCommitRequest {
+                        parameters: client_state.parameters(),
+                        index: CLIENT_PARTICIPANT + 1 + position as u64,
+                    },
+                )
+                .unwrap();
+            session_ids.push(response.session_id);
+            participants.push(response.commitment);
+            identities.push(response.identity);
+        }
+        (session_ids, participants, identities)
+    }
+
+    #[test]
+    fn test_threshold_keygen_identities() {
+        let parameters = ThresholdParameters {
+            threshold: 2,
+            participants: 3,
+        };
+        let dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+        let client_state = KeyGenState::new(parameters, CLIENT_PARTICIPANT).unwrap();
+        let is_invalid = |result: CoSignerResult<ThresholdKeyGenShareResponse>| match result {
+            Err(CoSignerError::InvalidRequest(_)) => true,
+            _ => false,
+        };
+
+        // Co-signers that did not pin each other refuse each other's commitments.
+        let first = start_threshold_service(dirs[0].path(), &[]);
+        let second = start_threshold_service(dirs[1].path(), &[]);
+        let (session_ids, participants, identities) =
+            threshold_keygen_commit(&[&first, &second], &client_state);
+        for (service, session_id) in [&first, &second].iter().zip(&session_ids) {
+            assert!(is_invalid(service.threshold_keygen_share(
+                &client("127.0.0.1"),
+                ThresholdKeyGenShareRequest {
+                    session_id: session_id.clone(),
+                    participants: participants.clone(),
+                    identities: identities.clone(),
+                }
+            )));
+        }
+
+        let first = start_threshold_service(dirs[0].path(), &[dirs[1].path()]);
+        let second = start_threshold_service(dirs[1].path(), &[dirs[0].path()]);
+
+        // The encryption key of a co-signer cannot be swapped by the client, nor can the
+        // identities be left out.
+        let (session_ids, participants, identities) =
+            threshold_keygen_commit(&[&first, &second], &client_state);
+        let mut swapped = participants.clone();
+        swapped[2].encryption_key = client_state.commitment().encryption_key;
+        assert!(is_invalid(first.threshold_keygen_share(
+            &client("127.0.0.1"),
+            ThresholdKeyGenShareRequest {
+                session_id: session_ids[0].clone(),
+                participants: swapped,
+                identities: identities.clone(),
+            }
+        )));
+        assert!(is_invalid(second.threshold_keygen_share(
+            &client("127.0.0.1"),
+            ThresholdKeyGenShareRequest {
+                session_id: session_ids[1].clone(),
+                participants: participants.clone(),
+                identities: vec![],
+            }
+        )));
+
+        // Co-signers pinning each other complete key generation.
+        let services = [&first, &second];
+        let (session_ids, participants, identities) =
+            threshold_keygen_commit(&services, &client_state);
+        let mut sub_shares = client_state.sub_shares(&participants).unwrap();
+        for (service, session_id) in services.iter().zip(&session_ids) {
+            let response = service
+                .threshold_keygen_share(
+                    &client("127.0.0.1"),
+                    ThresholdKeyGenShareRequest {
+                        session_id: session_id.clone(),
+                        participants: participants.clone(),
+                        identities: identities.clone(),
+                    },
+                )
+                .unwrap();
+            sub_shares.extend(response.sub_shares);
+        }
+        let share = client_state.complete(&participants, &sub_shares).unwrap();
+        for (position, (service, session_id)) in services.iter().zip(&session_ids).enumerate() {
+            let index = CLIENT_PARTICIPANT + 1 + position as u64;
+            let response = service
+                .threshold_keygen_complete(
+                    &client("127.0.0.1"),
+                    ThresholdKeyGenCompleteRequest {
+                        session_id: session_id.clone(),
+                        sub_shares: sub_shares
+                            .iter()
+                            .filter(|sub_share| sub_share.recipient == index)
+                            .cloned()
+                            .collect(),
+                    },
+                )
+                .unwrap();
+            assert!(response.public_key == *share.public_key());
+            assert!(response.share_public_key == share.participant_public_key(index));
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use co_signer_protocol::{
+    derive_child, ed25519_public_key, threshold::ThresholdShare, KeyAgg, KeyPair, FE, GE,
+};
+use crypto::HashValue;
+use failure::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use types::account_address::AccountAddress;
+
+/// Server side of a two-party key.
+#[derive(Deserialize, Serialize)]
+pub struct ServerShare {
+    /// Key pair of the server share
+    pub key_pair: KeyPair,
+    /// Aggregation of both public keys, as seen from the server
+    pub key_agg: KeyAgg,
+    /// Public key of the client share at key generation
+    pub client_public_key: GE,
+    /// Account the share signs for. It is derived from the aggregated public key, until the
+    /// authentication key of another account is rotated to this key.
+    pub address: AccountAddress,
+    /// Number of refreshes the shares went through
+    #[serde(default)]
+    pub epoch: u64,
+    /// Refreshed key pair of the next epoch, until the client confirms it
+    #[serde(default)]
+    pub pending_refresh: Option<KeyPair>,
+    /// Aggregation coefficient of the refreshed key pair. Refreshes left pending by earlier
+    /// versions kept the coefficient of the current key pair.
+    #[serde(default)]
+    pub pending_coefficient: Option<FE>,
+    /// Chain code child keys are derived with
+    #[serde(default)]
+    pub chain_code: Vec<u8>,
+    /// Whether the key signs prehashed messages instead of transactions
+    #[serde(default)]
+    pub message_signing: bool,
+    /// Key whose account this key was generated to take over, by an authentication key rotation
+    #[serde(default)]
+    pub replaces: Option<String>,
+    /// Account whose authentication key rotation to this key was co-signed, until the wallet
+    /// confirms that the rotation was committed
+    #[serde(default)]
+    pub pending_rotation: Option<AccountAddress>,
+    /// Hash of the credentials of the client that generated the key, the only client allowed to
+    /// use it. Keys generated before clients were authenticated have none until a client uses
+    /// them.
+    #[serde(default)]
+    pub owner: Option<HashValue>,
+}
+
+impl ServerShare {
+    /// Returns the authentication key of the key itself, i.e. the address derived from its
+    /// aggregated public key.
+    pub fn authentication_key(&self) -> Result<AccountAddress> {
+        Ok(AccountAddress::from(ed25519_public_key(&self.key_agg.apk)?))
+    }
+
+    /// Returns the key pair and key aggregation to sign with for the given child, and the
+    /// account they sign for. Without a child, the key itself is used.
+    pub fn signing_key(
+        &self,
+        child_number: Option<u64>,
+    ) -> Result<(KeyPair, KeyAgg, AccountAddress)> {
+        match child_number {
+            Some(child_number) => {
+                let (key_pair, key_agg) = derive_child(
+                    &self.key_pair,
+                    &self.key_agg,
+                    &self.chain_code,
+                    child_number,
+                    false,
+                )?;
+                let address = AccountAddress::from(ed25519_public_key(&key_agg.apk)?);
+                Ok((key_pair, key_agg, address))
+            }
+            None => Ok((self.key_pair.clone(), self.key_agg.clone(), self.address)),
+        }
+    }
+}
+
+/// Share of a threshold key held by the server, as one of its participants.
+#[derive(Deserialize, Serialize)]
+pub struct ThresholdServerShare {
+    /// Share of the server
+    pub share: ThresholdShare,
+    /// Account the share signs for, derived from the public key of the threshold key
+    pub address: AccountAddress,
+    /// Hash of the credentials of the client that generated the key, see ServerShare::owner
+    #[serde(default)]
+    pub owner: Option<HashValue>,
+}
+
+/// Loads the long-term identity key of the server from path, generating it on first start. The
+/// server signs its threshold key generation commitments with it.
+pub fn load_or_create_identity(path: &Path) -> Result<KeyPair> {
+    if path.exists() {
+        return Ok(serde_json::from_slice(&fs::read(path)?)
+            .with_context(|_| format!("Cannot parse identity key {:?}", path))?);
+    }
+    let identity = KeyPair::create();
+    write_file(path, &serde_json::to_vec(&identity)?)
+        .with_context(|_| format!("Cannot write identity key {:?}", path))?;
+    Ok(identity)
+}
+
+/// Writes contents to path through a temporary file renamed over it, so that an interrupted
+/// write never leaves a truncated file behind. Only the owner can read the file.
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp_file_name = path
+        .file_name()
+        .map(OsString::from)
+        .ok_or_else(|| format_err!("Invalid file path {:?}", path))?;
+    tmp_file_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_file_name);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Stores every server share as a JSON file named after its key id.
+pub struct ShareStore {
+    dir: PathBuf,
+}
+
+impl ShareStore {
+    /// Opens the store, creating its directory if needed.
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|_| format!("Cannot create share directory {:?}", dir))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        // Key ids come from the network, make sure they cannot escape the store directory.
+        ensure!(
+            !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'),
+            "Malformed key id {}",
+            id
+        );
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    /// Whether a share is stored under id.
+    pub fn contains(&self, id: &str) -> bool {
+        self.path(id).map_or(false, |path| path.exists())
+    }
+
+    /// Returns the share stored under id, if any.
+    pub fn get<T: DeserializeOwned>(&self, id: &str) -> Result<Option<T>> {
+        let path = self.path(id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    /// Stores a share under id, failing if id is already taken.
+    pub fn insert<T: Serialize>(&self, id: &str, share: &T) -> Result<()> {
+        let path = self.path(id)?;
+        ensure!(!path.exists(), "Key id {} is already taken", id);
+        write_file(&path, &serde_json::to_vec(share)?)?;
+        Ok(())
+    }
+
+    /// Replaces the share stored under id.
+    pub fn update<T: Serialize>(&self, id: &str, share: &T) -> Result<()> {
+        let path = self.path(id)?;
+        ensure!(path.exists(), "Unknown key id {}", id);
+        write_file(&path, &serde_json::to_vec(share)?)?;
+        Ok(())
+    }
+
+    /// Returns the ids of all the stored shares.
+    pub fn ids(&self) -> Result<Vec<String>> {
+        let mut ids = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(false, |extension| extension == "json")
+            {
+                if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
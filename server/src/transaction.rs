@@ -0,0 +1,200 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::CoSignerError;
+use co_signer_protocol::signing_message;
+use crypto::HashValue;
+use proto_conv::FromProtoBytes;
+use serde::{Deserialize, Serialize};
+use std::{convert::TryFrom, time::Duration};
+use types::{
+    account_address::AccountAddress,
+    transaction::{RawTransaction, TransactionArgument, TransactionPayload},
+};
+use vm_genesis::get_transaction_name;
+
+/// The program run by a transaction, as far as the co-signer understands it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ProgramKind {
+    /// Transfer of amount microlibras to payee
+    PeerToPeerTransfer {
+        /// Receiver of the coins
+        payee: AccountAddress,
+        /// Amount in microlibras
+        amount: u64,
+    },
+    /// Creation of a new account, funded from the sender account
+    CreateAccount {
+        /// Address of the new account
+        address: AccountAddress,
+        /// Amount in microlibras moved to the new account
+        initial_balance: u64,
+    },
+    /// Rotation of the sender authentication key
+    RotateAuthenticationKey {
+        /// New authentication key
+        new_key: Vec<u8>,
+        /// Id of the key whose authentication key is new_key, if the co-signer generated it to
+        /// replace the signing key. It is not part of the transaction: the co-signer fills it in
+        /// after decoding.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        held_key: Option<String>,
+    },
+    /// Minting of coins, only valid from the association account
+    Mint {
+        /// Receiver of the coins
+        receiver: AccountAddress,
+        /// Amount in microlibras
+        amount: u64,
+    },
+    /// Direct write set
+    WriteSet,
+    /// Any program that is not one of the standard transaction scripts
+    Unknown,
+}
+
+/// What the co-signer knows about a transaction it is asked to sign.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TransactionSummary {
+    /// The message both parties sign
+    pub hash: HashValue,
+    /// Sender of the transaction
+    pub sender: AccountAddress,
+    /// Sequence number of the transaction
+    pub sequence_number: u64,
+    /// Maximal amount of gas units the transaction can use
+    pub max_gas_amount: u64,
+    /// Price of a gas unit in microlibras
+    pub gas_unit_price: u64,
+    /// Expiration time of the transaction, in seconds since the unix epoch
+    pub expiration_time: Duration,
+    /// Program run by the transaction
+    pub program: ProgramKind,
+}
+
+impl TransactionSummary {
+    /// Decodes a protobuf encoded RawTransaction.
+    pub fn decode(raw_txn_bytes: &[u8]) -> Result<Self, CoSignerError> {
+        let raw_txn = RawTransaction::from_proto_bytes(raw_txn_bytes).map_err(|e| {
+            CoSignerError::InvalidRequest(format!("Cannot decode raw transaction: {}", e))
+        })?;
+        let program = match raw_txn.payload() {
+            TransactionPayload::Program(program) => {
+                decode_program(&get_transaction_name(program.code()), program.args())
+            }
+            TransactionPayload::WriteSet(_) => ProgramKind::WriteSet,
+        };
+
+        Ok(Self {
+            hash: signing_message(raw_txn_bytes),
+            sender: raw_txn.sender(),
+            sequence_number: raw_txn.sequence_number(),
+            max_gas_amount: raw_txn.max_gas_amount(),
+            gas_unit_price: raw_txn.gas_unit_price(),
+            expiration_time: raw_txn.expiration_time(),
+            program,
+        })
+    }
+
+    /// Amount of microlibras leaving the sender account, gas excluded.
+    pub fn amount(&self) -> u64 {
+        match &self.program {
+            ProgramKind::PeerToPeerTransfer { amount, .. } => *amount,
+            ProgramKind::CreateAccount {
+                initial_balance, ..
+            } => *initial_balance,
+            _ => 0,
+        }
+    }
+
+    /// Account receiving coins from the sender, if any.
+    pub fn payee(&self) -> Option<AccountAddress> {
+        match &self.program {
+            ProgramKind::PeerToPeerTransfer { payee, .. } => Some(*payee),
+            ProgramKind::CreateAccount { address, .. } => Some(*address),
+            _ => None,
+        }
+    }
+
+    /// Maximal fee the sender pays for the transaction.
+    pub fn max_gas_cost(&self) -> u64 {
+        self.max_gas_amount.saturating_mul(self.gas_unit_price)
+    }
+
+    /// Maximal amount the transaction takes from the sender: the amount moved and the gas.
+    pub fn max_cost(&self) -> u64 {
+        self.amount().saturating_add(self.max_gas_cost())
+    }
+}
+
+/// Recognizes the standard transaction scripts along with their arguments. A standard script
+/// called with unexpected arguments is treated as Unknown.
+fn decode_program(name: &str, args: &[TransactionArgument]) -> ProgramKind {
+    match (name, args) {
+        (
+            "peer_to_peer_transaction",
+            [TransactionArgument::Address(payee), TransactionArgument::U64(amount)],
+        ) => ProgramKind::PeerToPeerTransfer {
+            payee: *payee,
+            amount: *amount,
+        },
+        (
+            "create_account_transaction",
+            [TransactionArgument::Address(address), TransactionArgument::U64(initial_balance)],
+        ) => ProgramKind::CreateAccount {
+            address: *address,
+            initial_balance: *initial_balance,
+        },
+        ("rotate_authentication_key_transaction", [TransactionArgument::ByteArray(new_key)]) => {
+            ProgramKind::RotateAuthenticationKey {
+                new_key: new_key.as_bytes().to_vec(),
+                held_key: None,
+            }
+        }
+        (
+            "mint_transaction",
+            [TransactionArgument::Address(receiver), TransactionArgument::U64(amount)],
+        ) => ProgramKind::Mint {
+            receiver: *receiver,
+            amount: *amount,
+        },
+        _ => ProgramKind::Unknown,
+    }
+}
+
+/// Parses a hex encoded account address as found in the policy files.
+pub fn parse_address(address: &str) -> Result<AccountAddress, CoSignerError> {
+    AccountAddress::try_from(address.trim_start_matches("0x").to_string())
+        .map_err(|e| CoSignerError::InvalidRequest(format!("Invalid address {}: {}", address, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto_conv::IntoProtoBytes;
+    use vm_genesis::encode_transfer_program;
+
+    #[test]
+    fn test_decode_transfer() {
+        let sender = AccountAddress::random();
+        let payee = AccountAddress::random();
+        let raw_txn = RawTransaction::new(
+            sender,
+            3,
+            encode_transfer_program(&payee, 1_000),
+            10_000,
+            1,
+            Duration::from_secs(1_000),
+        );
+        let bytes = raw_txn.into_proto_bytes().unwrap();
+        let summary = TransactionSummary::decode(&bytes).unwrap();
+
+        assert_eq!(summary.sender, sender);
+        assert_eq!(summary.sequence_number, 3);
+        assert_eq!(summary.amount(), 1_000);
+        assert_eq!(summary.payee(), Some(payee));
+        assert_eq!(summary.max_gas_cost(), 10_000);
+        assert_eq!(summary.max_cost(), 11_000);
+        assert_eq!(summary.hash, signing_message(&bytes));
+    }
+}
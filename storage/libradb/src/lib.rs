@@ -98,6 +98,7 @@ impl LibraDB {
                 TRANSACTION_ACCUMULATOR_CF_NAME,
                 ColumnFamilyOptions::default(),
             ),
+            (TRANSACTION_BY_HASH_CF_NAME, ColumnFamilyOptions::default()),
             (TRANSACTION_INFO_CF_NAME, ColumnFamilyOptions::default()),
             (VALIDATOR_CF_NAME, ColumnFamilyOptions::default()),
         ]
@@ -118,12 +119,20 @@ impl LibraDB {
             instant.elapsed().as_millis()
         );
 
+        let transaction_store = TransactionStore::new(Arc::clone(&db));
+        let backfilled = transaction_store
+            .backfill_transaction_by_hash()
+            .unwrap_or_else(|e| panic!("Indexing transactions by hash failed: {:?}", e));
+        if backfilled > 0 {
+            info!("Indexed {} earlier transactions by hash", backfilled);
+        }
+
         LibraDB {
             db: Arc::clone(&db),
             event_store: EventStore::new(Arc::clone(&db)),
             ledger_store: LedgerStore::new(Arc::clone(&db)),
             state_store: StateStore::new(Arc::clone(&db)),
-            transaction_store: TransactionStore::new(Arc::clone(&db)),
+            transaction_store,
         }
     }
 
@@ -300,6 +309,22 @@ impl LibraDB {
             .map(Some)
     }
 
+    /// Returns the signed transaction with the given hash if it is committed at or before
+    /// `ledger_version`, otherwise returns `None`.
+    fn get_txn_by_hash(
+        &self,
+        hash: &HashValue,
+        ledger_version: Version,
+        fetch_events: bool,
+    ) -> Result<Option<SignedTransactionWithProof>> {
+        match self.transaction_store.get_version_by_hash(hash)? {
+            Some(version) if version <= ledger_version => self
+                .get_transaction_with_proof(version, ledger_version, fetch_events)
+                .map(Some),
+            _ => Ok(None),
+        }
+    }
+
     /// Gets the latest version number available in the ledger.
     fn get_latest_version(&self) -> Result<Version> {
         Ok(self
@@ -524,6 +549,14 @@ impl LibraDB {
                         txn_list_with_proof,
                     })
                 }
+                RequestItem::GetTransactionByHash { hash, fetch_events } => {
+                    let signed_transaction_with_proof =
+                        self.get_txn_by_hash(&hash, ledger_version, fetch_events)?;
+
+                    Ok(ResponseItem::GetTransactionByHash {
+                        signed_transaction_with_proof,
+                    })
+                }
             })
             .collect::<Result<Vec<_>>>()?;
 
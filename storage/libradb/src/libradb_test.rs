@@ -285,6 +285,22 @@ fn verify_committed_transactions(
             db.get_transactions(cur_ver, 1, ledger_version, true /* fetch_events */)?;
         txn_list_with_proof.verify(ledger_info, Some(cur_ver))?;
 
+        // Look the transaction up by hash.
+        let signed_txn = txn_to_commit.signed_txn();
+        let txn_with_proof = db
+            .get_txn_by_hash(
+                &signed_txn.hash(),
+                ledger_version,
+                true, /* fetch_events */
+            )?
+            .expect("Committed transaction should be found by hash.");
+        txn_with_proof.verify(
+            ledger_info,
+            cur_ver,
+            signed_txn.sender(),
+            signed_txn.sequence_number(),
+        )?;
+
         // Fetch and verify account states.
         for (addr, expected_blob) in txn_to_commit.account_states() {
             let account_state_with_proof =
@@ -14,6 +14,7 @@ pub(crate) mod ledger_info;
 pub(crate) mod signed_transaction;
 pub(crate) mod state_merkle_node;
 pub(crate) mod transaction_accumulator;
+pub(crate) mod transaction_by_hash;
 pub(crate) mod transaction_info;
 pub(crate) mod validator;
 
@@ -28,6 +29,7 @@ pub(super) const SIGNATURE_CF_NAME: ColumnFamilyName = "signature";
 pub(super) const SIGNED_TRANSACTION_CF_NAME: ColumnFamilyName = "signed_transaction";
 pub(super) const STATE_MERKLE_NODE_CF_NAME: ColumnFamilyName = "state_merkle_node";
 pub(super) const TRANSACTION_ACCUMULATOR_CF_NAME: ColumnFamilyName = "transaction_accumulator";
+pub(super) const TRANSACTION_BY_HASH_CF_NAME: ColumnFamilyName = "transaction_by_hash";
 pub(super) const TRANSACTION_INFO_CF_NAME: ColumnFamilyName = "transaction_info";
 pub(super) const VALIDATOR_CF_NAME: ColumnFamilyName = "validator";
 
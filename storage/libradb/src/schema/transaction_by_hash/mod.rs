@@ -0,0 +1,51 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an index from the hash of a signed transaction
+//! to the version at which it was committed.
+//! ```text
+//! |<--key-->|<--value-->|
+//! |  hash   |  version  |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, TRANSACTION_BY_HASH_CF_NAME};
+use byteorder::{BigEndian, ReadBytesExt};
+use crypto::HashValue;
+use failure::prelude::*;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+use types::transaction::Version;
+
+define_schema!(
+    TransactionByHashSchema,
+    HashValue,
+    Version,
+    TRANSACTION_BY_HASH_CF_NAME
+);
+
+impl KeyCodec<TransactionByHashSchema> for HashValue {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(HashValue::from_slice(data)?)
+    }
+}
+
+impl ValueCodec<TransactionByHashSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Version>())?;
+        Ok((&data[..]).read_u64::<BigEndian>()?)
+    }
+}
+
+#[cfg(test)]
+mod test;
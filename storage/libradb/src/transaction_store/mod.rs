@@ -3,13 +3,17 @@
 
 //! This file defines transaction store APIs that are related to committed signed transactions.
 
-use super::schema::signed_transaction::*;
+use super::schema::{signed_transaction::*, transaction_by_hash::*};
 use crate::errors::LibraDbError;
+use crypto::{hash::CryptoHash, HashValue};
 use failure::prelude::*;
-use schemadb::{SchemaBatch, DB};
+use schemadb::{ReadOptions, SchemaBatch, DB};
 use std::sync::Arc;
 use types::transaction::{SignedTransaction, Version};
 
+/// Number of transactions indexed by hash in each batch when backfilling the index.
+const BACKFILL_BATCH_SIZE: usize = 10_000;
+
 pub(crate) struct TransactionStore {
     db: Arc<DB>,
 }
@@ -26,15 +30,53 @@ impl TransactionStore {
             .ok_or_else(|| LibraDbError::NotFound(format!("Txn {}", version)).into())
     }
 
-    /// Save signed transaction at `version`
+    /// Get the version of the signed transaction with hash `hash`, if it is committed.
+    pub fn get_version_by_hash(&self, hash: &HashValue) -> Result<Option<Version>> {
+        self.db.get::<TransactionByHashSchema>(hash)
+    }
+
+    /// Save signed transaction at `version`, indexed by its hash.
     pub fn put_transaction(
         &self,
         version: Version,
         signed_transaction: &SignedTransaction,
         batch: &mut SchemaBatch,
     ) -> Result<()> {
+        batch.put::<TransactionByHashSchema>(&signed_transaction.hash(), &version)?;
         batch.put::<SignedTransactionSchema>(&version, signed_transaction)
     }
+
+    /// Index by hash the transactions committed before the index existed. Transactions are
+    /// indexed in order, so the index is complete once the latest one is indexed, and an
+    /// interrupted backfill starts over. Returns the number of transactions indexed.
+    pub fn backfill_transaction_by_hash(&self) -> Result<usize> {
+        let mut iter = self
+            .db
+            .iter::<SignedTransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_last();
+        match iter.next().transpose()? {
+            Some((_, latest)) if self.get_version_by_hash(&latest.hash())?.is_none() => (),
+            _ => return Ok(0),
+        }
+
+        let mut iter = self
+            .db
+            .iter::<SignedTransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let mut batch = SchemaBatch::new();
+        let mut indexed = 0;
+        for kv in iter {
+            let (version, signed_transaction) = kv?;
+            batch.put::<TransactionByHashSchema>(&signed_transaction.hash(), &version)?;
+            indexed += 1;
+            if indexed % BACKFILL_BATCH_SIZE == 0 {
+                self.db
+                    .write_schemas(std::mem::replace(&mut batch, SchemaBatch::new()))?;
+            }
+        }
+        self.db.write_schemas(batch)?;
+        Ok(indexed)
+    }
 }
 
 #[cfg(test)]
@@ -25,8 +25,33 @@ proptest! {
 
         for (i, txn) in txns.iter().enumerate() {
             prop_assert_eq!(store.get_transaction(i as u64).unwrap(), txn.clone());
+            prop_assert_eq!(store.get_version_by_hash(&txn.hash()).unwrap(), Some(i as u64));
         }
 
         prop_assert!(store.get_transaction(txns.len() as u64).is_err());
+        prop_assert_eq!(store.get_version_by_hash(&HashValue::zero()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_backfill_transaction_by_hash(txns in vec(any::<SignedTransaction>(), 1..10)) {
+        let tmp_dir = tempdir().unwrap();
+        let db = LibraDB::new(&tmp_dir);
+        let store = &db.transaction_store;
+
+        prop_assert_eq!(store.backfill_transaction_by_hash().unwrap(), 0);
+
+        // Transactions committed before the index by hash existed.
+        let mut batch = SchemaBatch::new();
+        for (i, txn) in txns.iter().enumerate() {
+            batch.put::<SignedTransactionSchema>(&(i as u64), txn).unwrap();
+        }
+        db.commit(batch).unwrap();
+        prop_assert_eq!(store.get_version_by_hash(&txns[0].hash()).unwrap(), None);
+
+        prop_assert_eq!(store.backfill_transaction_by_hash().unwrap(), txns.len());
+        for (i, txn) in txns.iter().enumerate() {
+            prop_assert_eq!(store.get_version_by_hash(&txn.hash()).unwrap(), Some(i as u64));
+        }
+        prop_assert_eq!(store.backfill_transaction_by_hash().unwrap(), 0);
     }
 }
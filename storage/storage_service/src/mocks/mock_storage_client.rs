@@ -20,7 +20,8 @@ use types::{
     proto::{
         account_state_blob::AccountStateWithProof,
         get_with_proof::{
-            GetAccountStateResponse, GetTransactionsResponse, RequestItem as ProtoRequestItem,
+            GetAccountStateResponse, GetEventsByEventAccessPathResponse,
+            GetTransactionByHashResponse, GetTransactionsResponse, RequestItem as ProtoRequestItem,
             RequestItem_oneof_requested_items, ResponseItem as ProtoResponseItem,
             UpdateToLatestLedgerRequest, UpdateToLatestLedgerResponse,
         },
@@ -203,7 +204,10 @@ fn get_mock_response_item(request_item: &ProtoRequestItem) -> Result<ProtoRespon
                 unimplemented!();
             }
             RequestItem_oneof_requested_items::get_events_by_event_access_path_request(_request) => {
-                unimplemented!();
+                // No event is emitted in the mock ledger.
+                response_item.set_get_events_by_event_access_path_response(
+                    GetEventsByEventAccessPathResponse::new(),
+                );
             }
             RequestItem_oneof_requested_items::get_transactions_request(request) => {
                 let mut ret = TransactionListWithProof::new();
@@ -225,6 +229,11 @@ fn get_mock_response_item(request_item: &ProtoRequestItem) -> Result<ProtoRespon
 
                 response_item.set_get_transactions_response(resp);
             }
+            RequestItem_oneof_requested_items::get_transaction_by_hash_request(_request) => {
+                // No transaction is committed in the mock ledger.
+                response_item
+                    .set_get_transaction_by_hash_response(GetTransactionByHashResponse::new());
+            }
         }
     }
     Ok(response_item)
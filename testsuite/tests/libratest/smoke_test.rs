@@ -3,6 +3,7 @@
 #![allow(unused_mut)]
 use cli::client_proxy::ClientProxy;
 use libra_swarm::swarm::LibraSwarm;
+use libra_wallet::co_signer::CoSignerConfig;
 use num_traits::cast::FromPrimitive;
 use rust_decimal::Decimal;
 
@@ -36,6 +37,8 @@ fn setup_swarm_and_client_proxy(
                 .unwrap()
                 .to_string(),
         ),
+        /* wallet passphrase */ None,
+        Some(CoSignerConfig::in_process("smoke_test")),
     )
     .unwrap();
     (swarm, client_proxy)
@@ -229,6 +232,8 @@ fn test_basic_state_synchronization() {
                 .unwrap()
                 .to_string(),
         ),
+        /* wallet passphrase */ None,
+        Some(CoSignerConfig::in_process("smoke_test")),
     )
     .unwrap();
     client_proxy2.set_accounts(client_proxy.copy_all_accounts());
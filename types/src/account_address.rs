@@ -3,6 +3,7 @@
 
 #![allow(clippy::unit_arg)]
 
+use crate::transaction::MultiEd25519PublicKey;
 use bech32::{Bech32, FromBase32, ToBase32};
 use bytes::Bytes;
 use canonical_serialization::{
@@ -180,6 +181,17 @@ impl From<PublicKey> for AccountAddress {
     }
 }
 
+impl From<&MultiEd25519PublicKey> for AccountAddress {
+    fn from(public_key: &MultiEd25519PublicKey) -> AccountAddress {
+        // The same hash as for a single public key, over the keys followed by the threshold.
+        let mut keccak = Keccak::new_sha3_256();
+        let mut hash = [0u8; ADDRESS_LENGTH];
+        keccak.update(&public_key.to_bytes());
+        keccak.finalize(&mut hash);
+        AccountAddress::new(hash)
+    }
+}
+
 impl From<&AccountAddress> for String {
     fn from(addr: &AccountAddress) -> String {
         ::hex::encode(addr.as_ref())
@@ -16,13 +16,14 @@ use crate::{
         GetAccountStateRequest, GetAccountStateResponse,
         GetAccountTransactionBySequenceNumberRequest,
         GetAccountTransactionBySequenceNumberResponse, GetEventsByEventAccessPathRequest,
-        GetEventsByEventAccessPathResponse, GetTransactionsRequest, GetTransactionsResponse,
+        GetEventsByEventAccessPathResponse, GetTransactionByHashRequest,
+        GetTransactionByHashResponse, GetTransactionsRequest, GetTransactionsResponse,
     },
     transaction::{SignedTransactionWithProof, TransactionListWithProof, Version},
     validator_change::ValidatorChangeEventWithProof,
     validator_verifier::ValidatorVerifier,
 };
-use crypto::hash::CryptoHash;
+use crypto::{hash::CryptoHash, HashValue};
 use failure::prelude::*;
 use proptest_derive::Arbitrary;
 use proto_conv::{FromProto, IntoProto};
@@ -197,6 +198,18 @@ fn verify_response_item(
             *fetch_events,
             txn_list_with_proof,
         ),
+        // GetTransactionByHash
+        (
+            RequestItem::GetTransactionByHash { hash, fetch_events },
+            ResponseItem::GetTransactionByHash {
+                signed_transaction_with_proof,
+            },
+        ) => verify_get_txn_by_hash_resp(
+            ledger_info,
+            *hash,
+            *fetch_events,
+            signed_transaction_with_proof.as_ref(),
+        ),
         // Request-response item types mismatch.
         _ => bail!(
             "ResquestItem/ResponseItem types mismatch. request: {:?}, response: {:?}",
@@ -249,6 +262,38 @@ fn verify_get_txn_by_seq_num_resp(
     }
 }
 
+fn verify_get_txn_by_hash_resp(
+    ledger_info: &LedgerInfo,
+    req_hash: HashValue,
+    req_fetch_events: bool,
+    signed_transaction_with_proof: Option<&SignedTransactionWithProof>,
+) -> Result<()> {
+    // Nothing proves that a transaction which is not returned is not in the ledger.
+    let signed_transaction_with_proof = match signed_transaction_with_proof {
+        Some(signed_transaction_with_proof) => signed_transaction_with_proof,
+        None => return Ok(()),
+    };
+    ensure!(
+        req_fetch_events == signed_transaction_with_proof.events.is_some(),
+        "Bad GetTransactionByHash response. Events requested: {}, events returned: {}.",
+        req_fetch_events,
+        signed_transaction_with_proof.events.is_some(),
+    );
+    let signed_transaction = &signed_transaction_with_proof.signed_transaction;
+    ensure!(
+        signed_transaction.hash() == req_hash,
+        "Bad GetTransactionByHash response. Hash requested: {:x}, hash returned: {:x}.",
+        req_hash,
+        signed_transaction.hash(),
+    );
+    signed_transaction_with_proof.verify(
+        ledger_info,
+        signed_transaction_with_proof.version,
+        signed_transaction.sender(),
+        signed_transaction.sequence_number(),
+    )
+}
+
 fn verify_get_events_by_access_path_resp(
     ledger_info: &LedgerInfo,
     req_access_path: &AccessPath,
@@ -373,6 +418,10 @@ pub enum RequestItem {
         limit: u64,
         fetch_events: bool,
     },
+    GetTransactionByHash {
+        hash: HashValue,
+        fetch_events: bool,
+    },
 }
 
 impl FromProto for RequestItem {
@@ -419,6 +468,12 @@ impl FromProto for RequestItem {
                 limit,
                 fetch_events,
             }
+        } else if object.has_get_transaction_by_hash_request() {
+            let req = object.get_get_transaction_by_hash_request();
+            let hash = HashValue::from_slice(req.get_hash())?;
+            let fetch_events = req.get_fetch_events();
+
+            RequestItem::GetTransactionByHash { hash, fetch_events }
         } else {
             unreachable!("Unknown RequestItem type.")
         })
@@ -474,6 +529,13 @@ impl IntoProto for RequestItem {
 
                 out.set_get_transactions_request(req);
             }
+            RequestItem::GetTransactionByHash { hash, fetch_events } => {
+                let mut req = GetTransactionByHashRequest::new();
+                req.set_hash(hash.to_vec());
+                req.set_fetch_events(fetch_events);
+
+                out.set_get_transaction_by_hash_request(req);
+            }
         }
         out
     }
@@ -497,6 +559,9 @@ pub enum ResponseItem {
     GetTransactions {
         txn_list_with_proof: TransactionListWithProof,
     },
+    GetTransactionByHash {
+        signed_transaction_with_proof: Option<SignedTransactionWithProof>,
+    },
 }
 
 impl ResponseItem {
@@ -547,6 +612,17 @@ impl ResponseItem {
             _ => bail!("Not ResponseItem::GetTransactions."),
         }
     }
+
+    pub fn into_get_transaction_by_hash_response(
+        self,
+    ) -> Result<Option<SignedTransactionWithProof>> {
+        match self {
+            ResponseItem::GetTransactionByHash {
+                signed_transaction_with_proof,
+            } => Ok(signed_transaction_with_proof),
+            _ => bail!("Not ResponseItem::GetTransactionByHash."),
+        }
+    }
 }
 
 impl FromProto for ResponseItem {
@@ -607,6 +683,17 @@ impl FromProto for ResponseItem {
             ResponseItem::GetTransactions {
                 txn_list_with_proof,
             }
+        } else if object.has_get_transaction_by_hash_response() {
+            let signed_transaction_with_proof = object
+                .take_get_transaction_by_hash_response()
+                .signed_transaction_with_proof
+                .take()
+                .map(SignedTransactionWithProof::from_proto)
+                .transpose()?;
+
+            ResponseItem::GetTransactionByHash {
+                signed_transaction_with_proof,
+            }
         } else {
             unreachable!("Unknown ResponseItem type.")
         })
@@ -667,6 +754,16 @@ impl IntoProto for ResponseItem {
 
                 out.set_get_transactions_response(res)
             }
+            ResponseItem::GetTransactionByHash {
+                signed_transaction_with_proof,
+            } => {
+                let mut res = GetTransactionByHashResponse::new();
+                if let Some(t) = signed_transaction_with_proof {
+                    res.set_signed_transaction_with_proof(t.into_proto())
+                }
+
+                out.set_get_transaction_by_hash_response(res)
+            }
         }
         out
     }
@@ -7,14 +7,16 @@ use crate::{
         RawTransaction as ProtoRawTransaction, SignedTransaction as ProtoSignedTransaction,
         SignedTransactionsBlock,
     },
-    transaction::{Program, RawTransaction, RawTransactionBytes, SignedTransaction},
+    transaction::{
+        MultiEd25519PublicKey, Program, RawTransaction, RawTransactionBytes, SignedTransaction,
+    },
     transaction_helpers::get_signed_transactions_digest,
     write_set::WriteSet,
 };
 use crypto::{hash::CryptoHash, signing::sign_message, PrivateKey, PublicKey};
 use proto_conv::{FromProto, IntoProto};
 use protobuf::Message;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 static PLACEHOLDER_SCRIPT: &[u8] = include_bytes!("fixtures/scripts/placeholder_script.mvbin");
 
@@ -132,6 +134,32 @@ pub fn get_unverified_test_signed_txn(
     )
 }
 
+// Test helper for transactions of MultiEd25519 accounts, signed by each of private_keys given with
+// the index of its public key. Like get_unverified_test_signed_txn, the signatures are not checked.
+pub fn get_test_multi_signed_txn(
+    sender: AccountAddress,
+    sequence_number: u64,
+    private_keys: &[(&PrivateKey, u8)],
+    public_key: MultiEd25519PublicKey,
+    program: Option<Program>,
+) -> SignedTransaction {
+    let expiration_time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 10; // 10 seconds from now.
+    RawTransaction::new(
+        sender,
+        sequence_number,
+        program.unwrap_or_else(placeholder_script),
+        MAX_GAS_AMOUNT,
+        MAX_GAS_PRICE,
+        Duration::from_secs(expiration_time),
+    )
+    .multi_sign(private_keys, public_key)
+    .unwrap()
+}
+
 pub fn placeholder_script() -> Program {
     Program::new(PLACEHOLDER_SCRIPT.to_vec(), vec![], vec![])
 }
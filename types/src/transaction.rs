@@ -33,8 +33,12 @@ use proto_conv::{FromProto, IntoProto, IntoProtoBytes};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, convert::TryFrom, fmt, time::Duration};
 
+mod authenticator;
 mod program;
 
+pub use authenticator::{
+    MultiEd25519PublicKey, MultiEd25519Signature, TransactionAuthenticator, MAX_NUM_OF_KEYS,
+};
 pub use program::{Program, TransactionArgument, SCRIPT_HASH_LENGTH};
 use protobuf::well_known_types::UInt64Value;
 
@@ -117,8 +121,33 @@ impl RawTransaction {
         let signature = signing::sign_message(hash, private_key)?;
         Ok(SignedTransaction {
             raw_txn: self,
-            public_key,
-            signature,
+            authenticator: TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            },
+            raw_txn_bytes,
+        })
+    }
+
+    /// Signs the given `RawTransaction` with each of private_keys, given with the index of their
+    /// public key in public_key, for an account whose authentication key is a MultiEd25519 key.
+    pub fn multi_sign(
+        self,
+        private_keys: &[(&PrivateKey, u8)],
+        public_key: MultiEd25519PublicKey,
+    ) -> Result<SignedTransaction> {
+        let raw_txn_bytes = self.clone().into_proto_bytes()?;
+        let hash = RawTransactionBytes(&raw_txn_bytes).hash();
+        let signatures = private_keys
+            .iter()
+            .map(|(private_key, index)| Ok((signing::sign_message(hash, private_key)?, *index)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(SignedTransaction {
+            raw_txn: self,
+            authenticator: TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature: MultiEd25519Signature::new(signatures)?,
+            },
             raw_txn_bytes,
         })
     }
@@ -127,6 +156,30 @@ impl RawTransaction {
         self.payload
     }
 
+    pub fn sender(&self) -> AccountAddress {
+        self.sender
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    pub fn payload(&self) -> &TransactionPayload {
+        &self.payload
+    }
+
+    pub fn max_gas_amount(&self) -> u64 {
+        self.max_gas_amount
+    }
+
+    pub fn gas_unit_price(&self) -> u64 {
+        self.gas_unit_price
+    }
+
+    pub fn expiration_time(&self) -> Duration {
+        self.expiration_time
+    }
+
     pub fn format_for_client(&self, get_transaction_name: impl Fn(&[u8]) -> String) -> String {
         let empty_vec = vec![];
         let (code, args) = match &self.payload {
@@ -229,12 +282,10 @@ pub struct SignedTransaction {
     /// The raw transaction
     raw_txn: RawTransaction,
 
-    /// Sender's public key. When checking the signature, we first need to check whether this key
-    /// is indeed the pre-image of the pubkey hash stored under sender's account.
-    public_key: PublicKey,
-
-    /// Signature of the transaction that correspond to the public key
-    signature: Signature,
+    /// Sender's public key(s) and signature(s) of the transaction. When checking the signature,
+    /// we first need to check whether the public key is indeed the pre-image of the pubkey hash
+    /// stored under sender's account.
+    authenticator: TransactionAuthenticator,
 
     // The original raw bytes from the protobuf are also stored here so that we use
     // these bytes when generating the canonical serialization of the SignedTransaction struct
@@ -250,11 +301,10 @@ impl fmt::Debug for SignedTransaction {
             f,
             "SignedTransaction {{ \n \
              {{ raw_txn: {:#?}, \n \
-             public_key: {:#?}, \n \
-             signature: {:#?}, \n \
+             authenticator: {:#?}, \n \
              }} \n \
              }}",
-            self.raw_txn, self.public_key, self.signature,
+            self.raw_txn, self.authenticator,
         )
     }
 }
@@ -264,22 +314,86 @@ impl SignedTransaction {
         raw_txn: RawTransaction,
         public_key: PublicKey,
         signature: Signature,
+    ) -> SignedTransaction {
+        Self::craft_authenticated_transaction_for_client(
+            raw_txn,
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            },
+        )
+    }
+
+    pub fn craft_authenticated_transaction_for_client(
+        raw_txn: RawTransaction,
+        authenticator: TransactionAuthenticator,
     ) -> SignedTransaction {
         SignedTransaction {
             raw_txn: raw_txn.clone(),
-            public_key,
-            signature,
+            authenticator,
             // In real world raw_txn should be derived from raw_txn_bytes, not the opposite.
             raw_txn_bytes: raw_txn.into_proto_bytes().expect("Should convert."),
         }
     }
 
-    pub fn public_key(&self) -> PublicKey {
-        self.public_key
+    pub fn authenticator(&self) -> &TransactionAuthenticator {
+        &self.authenticator
+    }
+
+    /// Assembles the transaction out of the protobuf bytes of the RawTransaction its sender
+    /// signed, checking the signature(s) like `from_proto` does.
+    pub fn from_raw_txn_bytes(
+        raw_txn_bytes: Vec<u8>,
+        authenticator: TransactionAuthenticator,
+    ) -> Result<Self> {
+        let t = Self::from_raw_txn_bytes_unverified(raw_txn_bytes, authenticator)?;
+
+        // Please do not remove this check. It may appear redundant, as it is also performed by VM,
+        // but its goal is to ensure that:
+        // - transactions parsed from a GRPC request are validated before being processed by other
+        // portions of code;
+        // - Moxie Marlinspike's Cryptographic Doom Principle is mitigated;
+        // - resources are committed only for valid data.
+        match t.verify_signature() {
+            Ok(_) => Ok(t),
+            Err(e) => Err(e),
+        }
     }
 
-    pub fn signature(&self) -> Signature {
-        self.signature
+    /// Like `from_proto`, but without checking the signature, which may even be missing. Only
+    /// meant for transactions being simulated: such a transaction must never reach mempool or
+    /// storage.
+    pub fn from_proto_unverified(
+        mut txn: crate::proto::transaction::SignedTransaction,
+    ) -> Result<Self> {
+        let authenticator = authenticator_from_proto(&mut txn, true)?;
+        Self::from_raw_txn_bytes_unverified(txn.raw_txn_bytes, authenticator)
+    }
+
+    fn from_raw_txn_bytes_unverified(
+        raw_txn_bytes: Vec<u8>,
+        authenticator: TransactionAuthenticator,
+    ) -> Result<Self> {
+        let proto_raw_transaction = protobuf::parse_from_bytes::<
+            crate::proto::transaction::RawTransaction,
+        >(raw_txn_bytes.as_ref())?;
+
+        // First check if extra data is being sent in the proto.  Note that this is a temporary
+        // measure to prevent extraneous data from being packaged.  Longer-term, we will likely
+        // need to allow this for compatibility reasons.  Note that we only need to do this
+        // for raw bytes under the signed transaction.  We do this because we actually store this
+        // field in the DB.
+        // TODO: Remove prevention of unknown fields
+        ensure!(
+            proto_raw_transaction.unknown_fields.fields.is_none(),
+            "Unknown fields not allowed in testnet proto for raw transaction"
+        );
+
+        Ok(SignedTransaction {
+            raw_txn: RawTransaction::from_proto(proto_raw_transaction)?,
+            authenticator,
+            raw_txn_bytes,
+        })
     }
 
     pub fn sender(&self) -> AccountAddress {
@@ -314,23 +428,25 @@ impl SignedTransaction {
         self.raw_txn_bytes.len()
     }
 
-    /// Verifies the signature of given transaction. Returns `Ok()` if the signature is valid.
+    /// The hash the sender signed.
+    pub fn raw_txn_hash(&self) -> HashValue {
+        RawTransactionBytes(&self.raw_txn_bytes).hash()
+    }
+
+    /// Verifies the signature of given transaction. Returns `Ok()` if the signature is valid, or
+    /// for MultiEd25519 senders if at least the threshold of the signatures are there and valid.
     pub fn verify_signature(&self) -> Result<()> {
-        let hash = RawTransactionBytes(&self.raw_txn_bytes).hash();
-        signing::verify_message(hash, &self.signature, &self.public_key)?;
-        Ok(())
+        self.authenticator.verify(self.raw_txn_hash())
     }
 
     pub fn format_for_client(&self, get_transaction_name: impl Fn(&[u8]) -> String) -> String {
         format!(
             "SignedTransaction {{ \n \
              raw_txn: {}, \n \
-             public_key: {:#?}, \n \
-             signature: {:#?}, \n \
+             authenticator: {}, \n \
              }}",
             self.raw_txn.format_for_client(get_transaction_name),
-            self.public_key,
-            self.signature,
+            self.authenticator,
         )
     }
 }
@@ -348,40 +464,34 @@ impl CryptoHash for SignedTransaction {
 impl FromProto for SignedTransaction {
     type ProtoType = crate::proto::transaction::SignedTransaction;
 
-    fn from_proto(txn: Self::ProtoType) -> Result<Self> {
-        let proto_raw_transaction = protobuf::parse_from_bytes::<
-            crate::proto::transaction::RawTransaction,
-        >(txn.raw_txn_bytes.as_ref())?;
+    fn from_proto(mut txn: Self::ProtoType) -> Result<Self> {
+        let authenticator = authenticator_from_proto(&mut txn, false)?;
+        SignedTransaction::from_raw_txn_bytes(txn.raw_txn_bytes, authenticator)
+    }
+}
 
-        // First check if extra data is being sent in the proto.  Note that this is a temporary
-        // measure to prevent extraneous data from being packaged.  Longer-term, we will likely
-        // need to allow this for compatibility reasons.  Note that we only need to do this
-        // for raw bytes under the signed transaction.  We do this because we actually store this
-        // field in the DB.
-        // TODO: Remove prevention of unknown fields
+/// Reads the authenticator of txn. When allow_unsigned is set, a missing ed25519 signature reads
+/// as all zeros, which never verifies.
+fn authenticator_from_proto(
+    txn: &mut crate::proto::transaction::SignedTransaction,
+    allow_unsigned: bool,
+) -> Result<TransactionAuthenticator> {
+    if txn.has_multi_ed25519() {
         ensure!(
-            proto_raw_transaction.unknown_fields.fields.is_none(),
-            "Unknown fields not allowed in testnet proto for raw transaction"
+            txn.get_sender_public_key().is_empty() && txn.get_sender_signature().is_empty(),
+            "Both a public key and MultiEd25519 keys for the sender"
         );
-
-        let t = SignedTransaction {
-            raw_txn: RawTransaction::from_proto(proto_raw_transaction)?,
-            public_key: PublicKey::from_slice(txn.get_sender_public_key())?,
-            signature: Signature::from_compact(txn.get_sender_signature())?,
-            raw_txn_bytes: txn.raw_txn_bytes,
-        };
-
-        // Please do not remove this check. It may appear redundant, as it is also performed by VM,
-        // but its goal is to ensure that:
-        // - transactions parsed from a GRPC request are validated before being processed by other
-        // portions of code;
-        // - Moxie Marlinspike's Cryptographic Doom Principle is mitigated;
-        // - resources are committed only for valid data.
-        match t.verify_signature() {
-            Ok(_) => Ok(t),
-            Err(e) => Err(e),
-        }
+        return authenticator::multi_ed25519_from_proto(txn.take_multi_ed25519());
     }
+    let signature = if allow_unsigned && txn.get_sender_signature().is_empty() {
+        Signature::from_compact(&[0u8; 64])?
+    } else {
+        Signature::from_compact(txn.get_sender_signature())?
+    };
+    Ok(TransactionAuthenticator::Ed25519 {
+        public_key: PublicKey::from_slice(txn.get_sender_public_key())?,
+        signature,
+    })
 }
 
 impl IntoProto for SignedTransaction {
@@ -390,8 +500,22 @@ impl IntoProto for SignedTransaction {
     fn into_proto(self) -> Self::ProtoType {
         let mut transaction = Self::ProtoType::new();
         transaction.set_raw_txn_bytes(self.raw_txn_bytes);
-        transaction.set_sender_public_key(self.public_key.to_slice().to_vec());
-        transaction.set_sender_signature(self.signature.to_compact().to_vec());
+        match self.authenticator {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => {
+                transaction.set_sender_public_key(public_key.to_slice().to_vec());
+                transaction.set_sender_signature(signature.to_compact().to_vec());
+            }
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => transaction.set_multi_ed25519(authenticator::multi_ed25519_into_proto(
+                &public_key,
+                &signature,
+            )),
+        }
         transaction
     }
 }
@@ -504,8 +628,8 @@ impl CanonicalSerialize for SignedTransaction {
     fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
         serializer
             .encode_variable_length_bytes(&self.raw_txn_bytes)?
-            .encode_variable_length_bytes(&self.public_key.to_slice())?
-            .encode_variable_length_bytes(&self.signature.to_compact())?;
+            .encode_variable_length_bytes(&self.authenticator.public_key_bytes())?
+            .encode_variable_length_bytes(&self.authenticator.signature_bytes())?;
         Ok(())
     }
 }
@@ -524,8 +648,10 @@ impl CanonicalDeserialize for SignedTransaction {
 
         Ok(SignedTransaction {
             raw_txn: RawTransaction::from_proto(proto_raw_transaction)?,
-            public_key: PublicKey::from_slice(&public_key_bytes)?,
-            signature: Signature::from_compact(&signature_bytes)?,
+            authenticator: TransactionAuthenticator::from_bytes(
+                &public_key_bytes,
+                &signature_bytes,
+            )?,
             raw_txn_bytes,
         })
     }
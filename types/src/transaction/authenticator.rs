@@ -0,0 +1,381 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account_address::AccountAddress,
+    proto::transaction::MultiEd25519Authenticator as ProtoMultiEd25519Authenticator,
+};
+use crypto::{signing, HashValue, PublicKey, Signature};
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Maximum number of keys of a MultiEd25519 key, one per bit of the bitmap of its signatures.
+pub const MAX_NUM_OF_KEYS: usize = 32;
+
+const BITMAP_NUM_OF_BYTES: usize = MAX_NUM_OF_KEYS / 8;
+
+const SIGNATURE_LENGTH: usize = 64;
+
+/// The k-of-n key of an account: any threshold of the public_keys may sign its transactions.
+///
+/// The authentication key of the account is the sha3 of `to_bytes`, just as the authentication
+/// key of a single key account is the sha3 of its public key. The transaction prologue hashes
+/// the key bytes carried by the transaction, whichever kind they are.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct MultiEd25519PublicKey {
+    public_keys: Vec<PublicKey>,
+    threshold: u8,
+}
+
+impl MultiEd25519PublicKey {
+    /// Creates a key out of between 1 and `MAX_NUM_OF_KEYS` distinct public keys, of which
+    /// threshold have to sign.
+    pub fn new(public_keys: Vec<PublicKey>, threshold: u8) -> Result<Self> {
+        ensure!(
+            !public_keys.is_empty() && public_keys.len() <= MAX_NUM_OF_KEYS,
+            "A MultiEd25519 key has between 1 and {} keys, got {}",
+            MAX_NUM_OF_KEYS,
+            public_keys.len()
+        );
+        ensure!(
+            threshold > 0 && threshold as usize <= public_keys.len(),
+            "Threshold {} is not between 1 and the number of keys {}",
+            threshold,
+            public_keys.len()
+        );
+        for (index, public_key) in public_keys.iter().enumerate() {
+            ensure!(
+                !public_keys[..index].contains(public_key),
+                "Key {} appears more than once",
+                public_key
+            );
+        }
+        Ok(Self {
+            public_keys,
+            threshold,
+        })
+    }
+
+    pub fn public_keys(&self) -> &[PublicKey] {
+        &self.public_keys
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The keys followed by the threshold, i.e. the preimage of the authentication key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.public_keys.len() * PublicKey::LENGTH + 1);
+        for public_key in &self.public_keys {
+            bytes.extend_from_slice(&public_key.to_slice());
+        }
+        bytes.push(self.threshold);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() % PublicKey::LENGTH == 1,
+            "Wrong length {} for a MultiEd25519 key",
+            bytes.len()
+        );
+        let (keys, threshold) = bytes.split_at(bytes.len() - 1);
+        let public_keys = keys
+            .chunks(PublicKey::LENGTH)
+            .map(PublicKey::from_slice)
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(public_keys, threshold[0])
+    }
+
+    /// Position of public_key among the keys, if it is one of them.
+    pub fn index_of(&self, public_key: &PublicKey) -> Option<u8> {
+        self.public_keys
+            .iter()
+            .position(|key| key == public_key)
+            .map(|index| index as u8)
+    }
+
+    /// Checks that signature holds at least threshold valid signatures of hash, by distinct keys.
+    pub fn verify(&self, hash: HashValue, signature: &MultiEd25519Signature) -> Result<()> {
+        ensure!(
+            signature.signatures.len() >= self.threshold as usize,
+            "{} signatures, {} required",
+            signature.signatures.len(),
+            self.threshold
+        );
+        for (index, signature) in signature.indexes().zip(signature.signatures.iter()) {
+            let public_key = self
+                .public_keys
+                .get(index as usize)
+                .ok_or_else(|| format_err!("No key at index {}", index))?;
+            signing::verify_message(hash, signature, public_key)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for MultiEd25519PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-of-{} [", self.threshold, self.public_keys.len())?;
+        for (index, public_key) in self.public_keys.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", public_key)?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Signatures by some of the keys of a `MultiEd25519PublicKey`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct MultiEd25519Signature {
+    /// Signatures, in the order of the keys that produced them
+    signatures: Vec<Signature>,
+    /// Bit i, counting from the most significant bit of the first byte, is set if key i signed
+    bitmap: [u8; BITMAP_NUM_OF_BYTES],
+}
+
+impl MultiEd25519Signature {
+    /// Gathers signatures, each given with the index of the key that produced it.
+    pub fn new(mut signatures: Vec<(Signature, u8)>) -> Result<Self> {
+        signatures.sort_by_key(|(_, index)| *index);
+        let mut bitmap = [0u8; BITMAP_NUM_OF_BYTES];
+        for (_, index) in &signatures {
+            let index = *index as usize;
+            ensure!(index < MAX_NUM_OF_KEYS, "Key index {} out of range", index);
+            ensure!(
+                !bit_is_set(&bitmap, index),
+                "Key {} signed more than once",
+                index
+            );
+            bitmap[index / 8] |= 0b1000_0000 >> (index % 8);
+        }
+        Ok(Self {
+            signatures: signatures
+                .into_iter()
+                .map(|(signature, _)| signature)
+                .collect(),
+            bitmap,
+        })
+    }
+
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    pub fn bitmap(&self) -> [u8; BITMAP_NUM_OF_BYTES] {
+        self.bitmap
+    }
+
+    /// Indexes of the keys that signed, in increasing order.
+    pub fn indexes(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..MAX_NUM_OF_KEYS)
+            .filter(move |index| bit_is_set(&self.bitmap, *index))
+            .map(|index| index as u8)
+    }
+
+    /// The signatures followed by the bitmap.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(self.signatures.len() * SIGNATURE_LENGTH + BITMAP_NUM_OF_BYTES);
+        for signature in &self.signatures {
+            bytes.extend_from_slice(&signature.to_compact());
+        }
+        bytes.extend_from_slice(&self.bitmap);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() >= BITMAP_NUM_OF_BYTES
+                && (bytes.len() - BITMAP_NUM_OF_BYTES) % SIGNATURE_LENGTH == 0,
+            "Wrong length {} for a MultiEd25519 signature",
+            bytes.len()
+        );
+        let (signatures, bitmap) = bytes.split_at(bytes.len() - BITMAP_NUM_OF_BYTES);
+        Self::from_parts(
+            signatures
+                .chunks(SIGNATURE_LENGTH)
+                .map(Signature::from_compact)
+                .collect::<Result<Vec<_>>>()?,
+            bitmap,
+        )
+    }
+
+    fn from_parts(signatures: Vec<Signature>, bitmap: &[u8]) -> Result<Self> {
+        ensure!(
+            bitmap.len() == BITMAP_NUM_OF_BYTES,
+            "Wrong length {} for a bitmap",
+            bitmap.len()
+        );
+        let mut signature = Self {
+            signatures,
+            bitmap: [0u8; BITMAP_NUM_OF_BYTES],
+        };
+        signature.bitmap.copy_from_slice(bitmap);
+        ensure!(
+            signature.indexes().count() == signature.signatures.len(),
+            "{} signatures for {} keys in the bitmap",
+            signature.signatures.len(),
+            signature.indexes().count()
+        );
+        Ok(signature)
+    }
+}
+
+fn bit_is_set(bitmap: &[u8; BITMAP_NUM_OF_BYTES], index: usize) -> bool {
+    bitmap[index / 8] & (0b1000_0000 >> (index % 8)) != 0
+}
+
+/// Proof that the sender of a transaction authorized it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum TransactionAuthenticator {
+    /// A signature by the single key of the sender
+    Ed25519 {
+        public_key: PublicKey,
+        signature: Signature,
+    },
+    /// Signatures by at least the threshold of the keys of the sender
+    MultiEd25519 {
+        public_key: MultiEd25519PublicKey,
+        signature: MultiEd25519Signature,
+    },
+}
+
+impl TransactionAuthenticator {
+    /// Rebuilds an authenticator from its canonical bytes. Ed25519 public keys are the only keys
+    /// of `PublicKey::LENGTH` bytes, MultiEd25519 keys always have an extra threshold byte.
+    pub fn from_bytes(public_key_bytes: &[u8], signature_bytes: &[u8]) -> Result<Self> {
+        Ok(if public_key_bytes.len() == PublicKey::LENGTH {
+            TransactionAuthenticator::Ed25519 {
+                public_key: PublicKey::from_slice(public_key_bytes)?,
+                signature: Signature::from_compact(signature_bytes)?,
+            }
+        } else {
+            TransactionAuthenticator::MultiEd25519 {
+                public_key: MultiEd25519PublicKey::from_bytes(public_key_bytes)?,
+                signature: MultiEd25519Signature::from_bytes(signature_bytes)?,
+            }
+        })
+    }
+
+    /// Bytes of the public key, which the authentication key of the sender is the sha3 of.
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        match self {
+            TransactionAuthenticator::Ed25519 { public_key, .. } => public_key.to_slice().to_vec(),
+            TransactionAuthenticator::MultiEd25519 { public_key, .. } => public_key.to_bytes(),
+        }
+    }
+
+    pub fn signature_bytes(&self) -> Vec<u8> {
+        match self {
+            TransactionAuthenticator::Ed25519 { signature, .. } => signature.to_compact().to_vec(),
+            TransactionAuthenticator::MultiEd25519 { signature, .. } => signature.to_bytes(),
+        }
+    }
+
+    /// The authentication key the sender account has to hold for this authenticator.
+    pub fn authentication_key(&self) -> AccountAddress {
+        match self {
+            TransactionAuthenticator::Ed25519 { public_key, .. } => {
+                AccountAddress::from(*public_key)
+            }
+            TransactionAuthenticator::MultiEd25519 { public_key, .. } => {
+                AccountAddress::from(public_key)
+            }
+        }
+    }
+
+    /// Verifies the signature(s) of hash.
+    pub fn verify(&self, hash: HashValue) -> Result<()> {
+        match self {
+            TransactionAuthenticator::Ed25519 {
+                public_key,
+                signature,
+            } => signing::verify_message(hash, signature, public_key),
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => public_key.verify(hash, signature),
+        }
+    }
+}
+
+impl fmt::Display for TransactionAuthenticator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionAuthenticator::Ed25519 { public_key, .. } => {
+                write!(f, "Ed25519 {}", public_key)
+            }
+            TransactionAuthenticator::MultiEd25519 {
+                public_key,
+                signature,
+            } => write!(
+                f,
+                "MultiEd25519 {}, signed by {:?}",
+                public_key,
+                signature.indexes().collect::<Vec<_>>()
+            ),
+        }
+    }
+}
+
+/// Reads the MultiEd25519 authenticator of a signed transaction. Ed25519 authenticators do not
+/// have a message of their own, they are carried by the sender_public_key and sender_signature
+/// fields of the signed transaction.
+pub(super) fn multi_ed25519_from_proto(
+    mut object: ProtoMultiEd25519Authenticator,
+) -> Result<TransactionAuthenticator> {
+    ensure!(
+        object.get_threshold() <= u32::from(u8::max_value()),
+        "Threshold {} out of range",
+        object.get_threshold()
+    );
+    let public_key = MultiEd25519PublicKey::new(
+        object
+            .get_public_keys()
+            .iter()
+            .map(|key| PublicKey::from_slice(key))
+            .collect::<Result<Vec<_>>>()?,
+        object.get_threshold() as u8,
+    )?;
+    let signature = MultiEd25519Signature::from_parts(
+        object
+            .get_signatures()
+            .iter()
+            .map(|signature| Signature::from_compact(signature))
+            .collect::<Result<Vec<_>>>()?,
+        &object.take_bitmap(),
+    )?;
+    Ok(TransactionAuthenticator::MultiEd25519 {
+        public_key,
+        signature,
+    })
+}
+
+pub(super) fn multi_ed25519_into_proto(
+    public_key: &MultiEd25519PublicKey,
+    signature: &MultiEd25519Signature,
+) -> ProtoMultiEd25519Authenticator {
+    let mut object = ProtoMultiEd25519Authenticator::new();
+    object.set_public_keys(protobuf::RepeatedField::from_vec(
+        public_key
+            .public_keys
+            .iter()
+            .map(|key| key.to_slice().to_vec())
+            .collect(),
+    ));
+    object.set_threshold(u32::from(public_key.threshold));
+    object.set_signatures(protobuf::RepeatedField::from_vec(
+        signature
+            .signatures
+            .iter()
+            .map(|signature| signature.to_compact().to_vec())
+            .collect(),
+    ));
+    object.set_bitmap(signature.bitmap.to_vec());
+    object
+}
@@ -10,6 +10,9 @@ pub fn get_signed_transactions_digest(signed_txns: &[SignedTransaction]) -> Hash
     let mut signatures = vec![];
     for transaction in signed_txns {
         signatures.extend_from_slice(&transaction.sender_signature);
+        for signature in transaction.get_multi_ed25519().get_signatures() {
+            signatures.extend_from_slice(signature);
+        }
     }
     signatures.test_only_hash()
 }
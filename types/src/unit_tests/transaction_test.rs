@@ -3,15 +3,30 @@
 
 use crate::{
     account_address::AccountAddress,
-    transaction::{Program, RawTransaction, SignedTransaction},
+    transaction::{
+        MultiEd25519PublicKey, MultiEd25519Signature, Program, RawTransaction, SignedTransaction,
+        TransactionAuthenticator,
+    },
 };
+use canonical_serialization::test_helper::assert_canonical_encode_decode;
 use crypto::{
-    signing::{generate_keypair, Signature},
+    signing::{generate_keypair, sign_message, Signature},
     utils::keypair_strategy,
 };
 use proptest::prelude::*;
 use proto_conv::{FromProto, IntoProto};
 
+fn raw_txn(sender: AccountAddress) -> RawTransaction {
+    RawTransaction::new(
+        sender,
+        0,
+        Program::new(vec![], vec![], vec![]),
+        0,
+        0,
+        std::time::Duration::new(0, 0),
+    )
+}
+
 #[test]
 fn test_signed_transaction_from_proto_invalid_signature() {
     let keypair = generate_keypair();
@@ -33,6 +48,115 @@ fn test_signed_transaction_from_proto_invalid_signature() {
     .is_err());
 }
 
+#[test]
+fn test_signed_transaction_from_proto_unverified() {
+    let keypair = generate_keypair();
+    let mut proto = SignedTransaction::craft_signed_transaction_for_client(
+        raw_txn(AccountAddress::from(keypair.1)),
+        keypair.1,
+        Signature::from_compact(&[0; 64]).unwrap(),
+    )
+    .into_proto();
+    proto.clear_sender_signature();
+    assert!(SignedTransaction::from_proto(proto.clone()).is_err());
+    let txn = SignedTransaction::from_proto_unverified(proto).unwrap();
+    assert_eq!(txn.sender(), AccountAddress::from(keypair.1));
+    assert!(txn.verify_signature().is_err());
+}
+
+#[test]
+fn test_multi_ed25519_signed_transaction() {
+    let keypairs = vec![generate_keypair(), generate_keypair(), generate_keypair()];
+    let public_key = MultiEd25519PublicKey::new(
+        keypairs.iter().map(|(_, public_key)| *public_key).collect(),
+        2,
+    )
+    .unwrap();
+    let sender = AccountAddress::from(&public_key);
+
+    let signed_txn = raw_txn(sender)
+        .multi_sign(
+            &[(&keypairs[2].0, 2), (&keypairs[0].0, 0)],
+            public_key.clone(),
+        )
+        .unwrap();
+    assert!(signed_txn.verify_signature().is_ok());
+    assert_eq!(signed_txn.authenticator().authentication_key(), sender);
+    assert_eq!(
+        SignedTransaction::from_proto(signed_txn.clone().into_proto()).unwrap(),
+        signed_txn
+    );
+    assert_canonical_encode_decode(&signed_txn);
+
+    // One signature short of the threshold.
+    let signed_txn = raw_txn(sender)
+        .multi_sign(&[(&keypairs[1].0, 1)], public_key.clone())
+        .unwrap();
+    assert!(signed_txn.verify_signature().is_err());
+    assert!(SignedTransaction::from_proto(signed_txn.into_proto()).is_err());
+
+    // A signature attributed to the wrong key.
+    let signed_txn = raw_txn(sender)
+        .multi_sign(
+            &[(&keypairs[0].0, 1), (&keypairs[2].0, 2)],
+            public_key.clone(),
+        )
+        .unwrap();
+    assert!(signed_txn.verify_signature().is_err());
+
+    // The same key twice.
+    let hash = signed_txn.raw_txn_hash();
+    let signature = sign_message(hash, &keypairs[0].0).unwrap();
+    assert!(MultiEd25519Signature::new(vec![(signature, 0), (signature, 0)]).is_err());
+    assert!(MultiEd25519PublicKey::new(vec![keypairs[0].1, keypairs[0].1], 1).is_err());
+    assert!(MultiEd25519PublicKey::new(vec![keypairs[0].1], 2).is_err());
+}
+
+#[test]
+fn test_multi_ed25519_authenticator_bytes() {
+    let keypairs = vec![generate_keypair(), generate_keypair()];
+    let public_key = MultiEd25519PublicKey::new(
+        keypairs.iter().map(|(_, public_key)| *public_key).collect(),
+        1,
+    )
+    .unwrap();
+    let signature = Signature::from_compact(&[0; 64]).unwrap();
+    let authenticator = TransactionAuthenticator::MultiEd25519 {
+        public_key: public_key.clone(),
+        signature: MultiEd25519Signature::new(vec![(signature, 1)]).unwrap(),
+    };
+    assert_eq!(authenticator.public_key_bytes().len(), 65);
+    assert_eq!(
+        &authenticator.signature_bytes()[64..],
+        &[0b0100_0000, 0, 0, 0]
+    );
+    assert_eq!(
+        TransactionAuthenticator::from_bytes(
+            &authenticator.public_key_bytes(),
+            &authenticator.signature_bytes()
+        )
+        .unwrap(),
+        authenticator
+    );
+
+    let authenticator = TransactionAuthenticator::Ed25519 {
+        public_key: keypairs[0].1,
+        signature,
+    };
+    assert_eq!(
+        TransactionAuthenticator::from_bytes(
+            &authenticator.public_key_bytes(),
+            &authenticator.signature_bytes()
+        )
+        .unwrap(),
+        authenticator
+    );
+    assert_eq!(
+        authenticator.authentication_key(),
+        AccountAddress::from(keypairs[0].1)
+    );
+}
+
 proptest! {
     #[test]
     fn test_sig(raw_txn in any::<RawTransaction>(), (sk1, pk1) in keypair_strategy()) {
@@ -5,7 +5,32 @@ use crate::account_address::{AccountAddress, ADDRESS_LENGTH};
 use crypto::{signing, HashValue, PrivateKey, PublicKey, Signature};
 use failure::Error;
 use proptest::{prelude::*, sample, strategy::LazyJust};
-use std::convert::TryFrom;
+use std::{convert::TryFrom, fmt::Debug, sync::Arc};
+
+/// Holder of the private key of a ValidatorSigner: either the key itself, in memory, or a handle
+/// to a key held by a separate process, such as the secret service.
+pub trait MessageSigner: Debug + Send + Sync {
+    /// Constructs a signature for `message` with the private key.
+    fn sign_message(&self, message: HashValue) -> Result<Signature, Error>;
+}
+
+/// MessageSigner holding the private key in memory.
+#[derive(Debug)]
+pub struct InMemorySigner {
+    private_key: PrivateKey,
+}
+
+impl InMemorySigner {
+    pub fn new(private_key: PrivateKey) -> Self {
+        InMemorySigner { private_key }
+    }
+}
+
+impl MessageSigner for InMemorySigner {
+    fn sign_message(&self, message: HashValue) -> Result<Signature, Error> {
+        signing::sign_message(message, &self.private_key)
+    }
+}
 
 /// ValidatorSigner associates an author with public and private keys with helpers for signing and
 /// validating. This struct can be used for all signing operations including block and network
@@ -14,7 +39,7 @@ use std::convert::TryFrom;
 pub struct ValidatorSigner {
     author: AccountAddress,
     public_key: PublicKey,
-    private_key: PrivateKey,
+    signer: Arc<dyn MessageSigner>,
 }
 
 impl ValidatorSigner {
@@ -22,11 +47,25 @@ impl ValidatorSigner {
         account_address: AccountAddress,
         public_key: PublicKey,
         private_key: PrivateKey,
+    ) -> Self {
+        Self::with_signer(
+            account_address,
+            public_key,
+            Arc::new(InMemorySigner::new(private_key)),
+        )
+    }
+
+    /// Makes a signer whose private key is held by `signer`, which must sign with the private key
+    /// of `public_key`.
+    pub fn with_signer(
+        account_address: AccountAddress,
+        public_key: PublicKey,
+        signer: Arc<dyn MessageSigner>,
     ) -> Self {
         ValidatorSigner {
             author: account_address,
             public_key,
-            private_key,
+            signer,
         }
     }
 
@@ -39,11 +78,7 @@ impl ValidatorSigner {
     /// Generate a random set of public and private keys and author information.
     pub fn random() -> Self {
         let (private_key, public_key) = signing::generate_keypair();
-        ValidatorSigner {
-            author: AccountAddress::from(public_key),
-            public_key,
-            private_key,
-        }
+        Self::new(AccountAddress::from(public_key), public_key, private_key)
     }
 
     /// For test only - makes signer with nicely looking account address that has specified integer
@@ -52,16 +87,16 @@ impl ValidatorSigner {
         let mut address = [0; ADDRESS_LENGTH];
         address[0] = num;
         let (private_key, public_key) = signing::generate_keypair();
-        ValidatorSigner {
-            author: AccountAddress::try_from(&address[..]).unwrap(),
+        Self::new(
+            AccountAddress::try_from(&address[..]).unwrap(),
             public_key,
             private_key,
-        }
+        )
     }
 
-    /// Constructs a signature for `message` using `private_key`.
+    /// Constructs a signature for `message` using the private key.
     pub fn sign_message(&self, message: HashValue) -> Result<Signature, Error> {
-        signing::sign_message(message, &self.private_key)
+        self.signer.sign_message(message)
     }
 
     /// Checks that `signature` is valid for `message` using `public_key`.
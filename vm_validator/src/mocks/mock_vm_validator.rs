@@ -1,17 +1,21 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::vm_validator::TransactionValidation;
+use crate::vm_validator::{TransactionSimulation, TransactionValidation};
 use futures::future::{ok, Future};
 use state_view::StateView;
 use std::convert::TryFrom;
 use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
-    transaction::SignedTransaction,
-    vm_error::{VMStatus, VMValidationStatus},
+    transaction::{SignedTransaction, TransactionOutput, TransactionStatus, Version},
+    vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
+    write_set::WriteSet,
 };
 use vm_runtime::VMVerifier;
 
+/// Gas used by the transactions the mock simulates successfully.
+pub const MOCK_GAS_USED: u64 = 100;
+
 #[derive(Clone)]
 pub struct MockVMValidator;
 
@@ -72,3 +76,32 @@ impl TransactionValidation for MockVMValidator {
         Box::new(ok(ret))
     }
 }
+
+impl TransactionSimulation for MockVMValidator {
+    fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+        _check_signature: bool,
+    ) -> Box<dyn Future<Item = (TransactionOutput, Version), Error = failure::Error> + Send> {
+        // The transactions the mock rejects fail validation, the others run successfully.
+        Box::new(
+            TransactionValidation::validate_transaction(self, txn).map(|status| {
+                let output = match status {
+                    Some(vm_status) => TransactionOutput::new(
+                        WriteSet::default(),
+                        vec![],
+                        0,
+                        TransactionStatus::from(vm_status),
+                    ),
+                    None => TransactionOutput::new(
+                        WriteSet::default(),
+                        vec![],
+                        MOCK_GAS_USED,
+                        TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)),
+                    ),
+                };
+                (output, 0)
+            }),
+        )
+    }
+}
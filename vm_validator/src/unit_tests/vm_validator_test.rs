@@ -1,7 +1,9 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::vm_validator::{TransactionValidation, VMValidator};
+use crate::vm_validator::{
+    TransactionSimulation, TransactionValidation, VMValidator, MAX_SIMULATIONS_PER_VM,
+};
 use assert_matches::assert_matches;
 use config::config::NodeConfig;
 use config_builder::util::get_test_config;
@@ -18,8 +20,13 @@ use storage_service::start_storage_service;
 use types::{
     account_address, account_config,
     test_helpers::transaction_test_helpers,
-    transaction::{Program, SignedTransaction, TransactionArgument, MAX_TRANSACTION_SIZE_IN_BYTES},
-    vm_error::{VMStatus, VMValidationStatus, VMVerificationError, VMVerificationStatus},
+    transaction::{
+        MultiEd25519PublicKey, Program, SignedTransaction, TransactionArgument, TransactionStatus,
+        MAX_TRANSACTION_SIZE_IN_BYTES,
+    },
+    vm_error::{
+        ExecutionStatus, VMStatus, VMValidationStatus, VMVerificationError, VMVerificationStatus,
+    },
 };
 use vm_genesis::encode_transfer_program;
 
@@ -113,6 +120,100 @@ fn test_validate_transaction() {
     assert_eq!(ret, None);
 }
 
+#[test]
+fn test_validate_transactions() {
+    let (config, keypair) = get_test_config();
+    let vm_validator = TestValidator::new(&config);
+
+    // Consecutive sequence numbers from the same sender are all valid.
+    let address = account_config::association_address();
+    let mut txns: Vec<_> = (0..2)
+        .map(|sequence_number| {
+            let signed_txn = transaction_test_helpers::get_test_signed_txn(
+                address,
+                sequence_number,
+                keypair.private_key().clone(),
+                keypair.public_key(),
+                Some(encode_transfer_program(&address, 100)),
+            );
+            SignedTransaction::from_proto(signed_txn).unwrap()
+        })
+        .collect();
+    let (other_private_key, _) = ::crypto::signing::generate_keypair();
+    txns.push(transaction_test_helpers::get_unverified_test_signed_txn(
+        address,
+        2,
+        other_private_key,
+        keypair.public_key(),
+        Some(encode_transfer_program(&address, 100)),
+    ));
+    let ret = vm_validator.validate_transactions(txns).wait().unwrap();
+    assert_eq!(
+        ret,
+        vec![
+            None,
+            None,
+            Some(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+        ]
+    );
+}
+
+#[test]
+fn test_simulate_transaction() {
+    let (config, keypair) = get_test_config();
+    let vm_validator = TestValidator::new(&config);
+
+    // Not signed by the key of the sender: only runs when the signature is not checked.
+    let (other_private_key, _) = ::crypto::signing::generate_keypair();
+    let address = account_config::association_address();
+    let program = encode_transfer_program(&address, 100);
+    let signed_txn = transaction_test_helpers::get_unverified_test_signed_txn(
+        address,
+        0,
+        other_private_key,
+        keypair.public_key(),
+        Some(program),
+    );
+    let (output, version) = vm_validator
+        .simulate_transaction(signed_txn.clone(), false)
+        .wait()
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+    );
+    assert!(output.gas_used() > 0);
+    assert!(!output.write_set().is_empty());
+    assert_eq!(version, 0);
+
+    let (output, _) = vm_validator
+        .simulate_transaction(signed_txn, true)
+        .wait()
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Discard(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+    );
+    assert_eq!(vm_validator.simulation_vm.lock().unwrap().simulations, 2);
+}
+
+#[test]
+fn test_simulation_vm_replaced() {
+    let (config, _) = get_test_config();
+    let vm_validator = TestValidator::new(&config);
+
+    for _ in 0..MAX_SIMULATIONS_PER_VM {
+        vm_validator.simulation_vm();
+    }
+    assert_eq!(
+        vm_validator.simulation_vm.lock().unwrap().simulations,
+        MAX_SIMULATIONS_PER_VM
+    );
+    // The next simulation runs on a fresh VM.
+    vm_validator.simulation_vm();
+    assert_eq!(vm_validator.simulation_vm.lock().unwrap().simulations, 1);
+}
+
 #[test]
 fn test_validate_invalid_signature() {
     let (config, keypair) = get_test_config();
@@ -141,6 +242,61 @@ fn test_validate_invalid_signature() {
     );
 }
 
+#[test]
+fn test_validate_multi_ed25519_missing_signature() {
+    let (config, keypair) = get_test_config();
+    let vm_validator = TestValidator::new(&config);
+
+    let (other_private_key, other_public_key) = ::crypto::signing::generate_keypair();
+    let public_key =
+        MultiEd25519PublicKey::new(vec![keypair.public_key(), other_public_key], 2).unwrap();
+    let address = account_config::association_address();
+    let program = encode_transfer_program(&address, 100);
+    let signed_txn = transaction_test_helpers::get_test_multi_signed_txn(
+        address,
+        0,
+        &[(&other_private_key, 1)],
+        public_key,
+        Some(program),
+    );
+    let ret = vm_validator
+        .validate_transaction(signed_txn)
+        .wait()
+        .unwrap();
+    assert_eq!(
+        ret,
+        Some(VMStatus::Validation(VMValidationStatus::InvalidSignature))
+    );
+}
+
+#[test]
+fn test_validate_multi_ed25519_invalid_auth_key() {
+    let (config, keypair) = get_test_config();
+    let vm_validator = TestValidator::new(&config);
+
+    // Valid signatures, but the association account is authenticated by its single key alone.
+    let (other_private_key, other_public_key) = ::crypto::signing::generate_keypair();
+    let public_key =
+        MultiEd25519PublicKey::new(vec![keypair.public_key(), other_public_key], 2).unwrap();
+    let address = account_config::association_address();
+    let program = encode_transfer_program(&address, 100);
+    let signed_txn = transaction_test_helpers::get_test_multi_signed_txn(
+        address,
+        0,
+        &[(keypair.private_key(), 0), (&other_private_key, 1)],
+        public_key,
+        Some(program),
+    );
+    let ret = vm_validator
+        .validate_transaction(signed_txn)
+        .wait()
+        .unwrap();
+    assert_eq!(
+        ret,
+        Some(VMStatus::Validation(VMValidationStatus::InvalidAuthKey))
+    );
+}
+
 #[test]
 fn test_validate_known_script_too_large_args() {
     let (config, keypair) = get_test_config();
@@ -1,20 +1,21 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use config::config::NodeConfig;
+use config::config::{NodeConfig, VMConfig};
+use crypto::HashValue;
 use failure::prelude::*;
-use futures::future::{err, ok, Future};
+use futures::future::{err, join_all, ok, Future};
 use scratchpad::SparseMerkleTree;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 use storage_client::{StorageRead, VerifiedStateView};
 use types::{
     account_address::{AccountAddress, ADDRESS_LENGTH},
     account_config::get_account_resource_or_default,
     get_with_proof::{RequestItem, ResponseItem},
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, TransactionOutput, Version},
     vm_error::VMStatus,
 };
-use vm_runtime::{MoveVM, VMVerifier};
+use vm_runtime::{MoveVM, VMSimulator, VMVerifier};
 
 #[cfg(test)]
 #[path = "unit_tests/vm_validator_test.rs"]
@@ -27,12 +28,48 @@ pub trait TransactionValidation: Send + Sync {
         &self,
         _txn: SignedTransaction,
     ) -> Box<dyn Future<Item = Option<VMStatus>, Error = failure::Error> + Send>;
+
+    /// Validate many txns from client, returning the status of each in order. By default they are
+    /// validated one by one.
+    fn validate_transactions(
+        &self,
+        txns: Vec<SignedTransaction>,
+    ) -> Box<dyn Future<Item = Vec<Option<VMStatus>>, Error = failure::Error> + Send> {
+        Box::new(join_all(
+            txns.into_iter()
+                .map(|txn| self.validate_transaction(txn))
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+pub trait TransactionSimulation: Send + Sync {
+    /// Execute a txn from client against the latest state without applying it, checking its
+    /// signature only if check_signature is set. Returns the output along with the version of the
+    /// state the txn ran against.
+    fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+        check_signature: bool,
+    ) -> Box<dyn Future<Item = (TransactionOutput, Version), Error = failure::Error> + Send>;
+}
+
+/// The number of txns simulated by a VM before it is replaced by a fresh one. Every simulated
+/// script stays in the script cache of the VM, so this bounds the memory simulations use.
+pub const MAX_SIMULATIONS_PER_VM: u64 = 1_000;
+
+/// The VM simulations run on, along with the number of txns it simulated so far.
+struct SimulationVM {
+    vm: MoveVM,
+    simulations: u64,
 }
 
 #[derive(Clone)]
 pub struct VMValidator {
     storage_read_client: Arc<dyn StorageRead>,
     vm: MoveVM,
+    vm_config: VMConfig,
+    simulation_vm: Arc<Mutex<SimulationVM>>,
 }
 
 impl VMValidator {
@@ -40,17 +77,18 @@ impl VMValidator {
         VMValidator {
             storage_read_client,
             vm: MoveVM::new(&config.vm_config),
+            vm_config: config.vm_config.clone(),
+            simulation_vm: Arc::new(Mutex::new(SimulationVM {
+                vm: MoveVM::new(&config.vm_config),
+                simulations: 0,
+            })),
         }
     }
 }
 
-impl TransactionValidation for VMValidator {
-    type ValidationInstance = MoveVM;
-
-    fn validate_transaction(
-        &self,
-        txn: SignedTransaction,
-    ) -> Box<dyn Future<Item = Option<VMStatus>, Error = failure::Error> + Send> {
+impl VMValidator {
+    /// Root hash of the latest state in storage, along with the version of that state.
+    fn latest_state_root(&self) -> Result<(HashValue, Version)> {
         // TODO: For transaction validation, there are two options to go:
         // 1. Trust storage: there is no need to get root hash from storage here. We will
         // create another struct similar to `VerifiedStateView` that implements `StateView`
@@ -67,37 +105,101 @@ impl TransactionValidation for VMValidator {
         let address = AccountAddress::new([0xff; ADDRESS_LENGTH]);
         let item = RequestItem::GetAccountState { address };
 
-        match self
+        let (mut items, ledger_info_with_sigs, _) = self
             .storage_read_client
-            .update_to_latest_ledger(/* client_known_version = */ 0, vec![item])
-        {
-            Ok((mut items, _, _)) => {
-                if items.len() != 1 {
-                    return Box::new(err(format_err!(
-                        "Unexpected number of items ({}).",
-                        items.len()
-                    )
-                    .into()));
-                }
-
-                match items.remove(0) {
-                    ResponseItem::GetAccountState {
-                        account_state_with_proof,
-                    } => {
-                        let transaction_info = account_state_with_proof.proof.transaction_info();
-                        let state_root = transaction_info.state_root_hash();
-                        let smt = SparseMerkleTree::new(state_root);
-                        let state_view = VerifiedStateView::new(
-                            Arc::clone(&self.storage_read_client),
-                            state_root,
-                            &smt,
-                        );
-                        Box::new(ok(self.vm.validate_transaction(txn, &state_view)))
-                    }
-                    _ => panic!("Unexpected item in response."),
-                }
+            .update_to_latest_ledger(/* client_known_version = */ 0, vec![item])?;
+        if items.len() != 1 {
+            bail!("Unexpected number of items ({}).", items.len());
+        }
+
+        match items.remove(0) {
+            ResponseItem::GetAccountState {
+                account_state_with_proof,
+            } => {
+                let transaction_info = account_state_with_proof.proof.transaction_info();
+                Ok((
+                    transaction_info.state_root_hash(),
+                    ledger_info_with_sigs.ledger_info().version(),
+                ))
+            }
+            _ => panic!("Unexpected item in response."),
+        }
+    }
+}
+
+impl VMValidator {
+    /// The VM to simulate the next txn on. It is replaced by a fresh one every
+    /// MAX_SIMULATIONS_PER_VM simulations.
+    fn simulation_vm(&self) -> MoveVM {
+        let mut simulation_vm = self
+            .simulation_vm
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if simulation_vm.simulations >= MAX_SIMULATIONS_PER_VM {
+            simulation_vm.vm = MoveVM::new(&self.vm_config);
+            simulation_vm.simulations = 0;
+        }
+        simulation_vm.simulations += 1;
+        simulation_vm.vm.clone()
+    }
+}
+
+impl TransactionValidation for VMValidator {
+    type ValidationInstance = MoveVM;
+
+    fn validate_transaction(
+        &self,
+        txn: SignedTransaction,
+    ) -> Box<dyn Future<Item = Option<VMStatus>, Error = failure::Error> + Send> {
+        match self.latest_state_root() {
+            Ok((state_root, _)) => {
+                let smt = SparseMerkleTree::new(state_root);
+                let state_view =
+                    VerifiedStateView::new(Arc::clone(&self.storage_read_client), state_root, &smt);
+                Box::new(ok(self.vm.validate_transaction(txn, &state_view)))
+            }
+            Err(e) => Box::new(err(e)),
+        }
+    }
+
+    /// Validates all the txns against the same state, so that the state root is read once and
+    /// the state of an account is read once for all its txns.
+    fn validate_transactions(
+        &self,
+        txns: Vec<SignedTransaction>,
+    ) -> Box<dyn Future<Item = Vec<Option<VMStatus>>, Error = failure::Error> + Send> {
+        match self.latest_state_root() {
+            Ok((state_root, _)) => {
+                let smt = SparseMerkleTree::new(state_root);
+                let state_view =
+                    VerifiedStateView::new(Arc::clone(&self.storage_read_client), state_root, &smt);
+                Box::new(ok(txns
+                    .into_iter()
+                    .map(|txn| self.vm.validate_transaction(txn, &state_view))
+                    .collect()))
+            }
+            Err(e) => Box::new(err(e)),
+        }
+    }
+}
+
+impl TransactionSimulation for VMValidator {
+    fn simulate_transaction(
+        &self,
+        txn: SignedTransaction,
+        check_signature: bool,
+    ) -> Box<dyn Future<Item = (TransactionOutput, Version), Error = failure::Error> + Send> {
+        match self.latest_state_root() {
+            Ok((state_root, version)) => {
+                let smt = SparseMerkleTree::new(state_root);
+                let state_view =
+                    VerifiedStateView::new(Arc::clone(&self.storage_read_client), state_root, &smt);
+                let output =
+                    self.simulation_vm()
+                        .simulate_transaction(txn, &state_view, check_signature);
+                Box::new(ok((output, version)))
             }
-            Err(e) => Box::new(err(e.into())),
+            Err(e) => Box::new(err(e)),
         }
     }
 }